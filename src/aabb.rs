@@ -2,24 +2,103 @@
 
 use std::f32;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
 use std::ops::Index;
 
 // use ultraviolet::{Vec3, Vec3};
 // use ultraviolet::Vec3;
 use ultraviolet::vec::Vec3;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use crate::axis::Axis;
+use crate::ray::Ray;
+#[cfg(feature = "glam")]
+use crate::utils::{vec3_from_glam, vec3_to_glam};
+#[cfg(feature = "mint")]
+use crate::utils::{vec3_from_mint, vec3_to_mint};
+
+pub use crate::utils::joint_aabb_of_shapes;
+#[cfg(feature = "rayon")]
+pub use crate::utils::{par_joint_aabb_of_shapes, PARALLEL_JOINT_AABB_THRESHOLD};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 /// AABB struct.
+///
+/// Two [`AABB`]s are equal if the bit patterns of all their components are equal, and
+/// [`Hash`] hashes those bit patterns, so that [`AABB`]s can be used as keys of a
+/// `HashMap` to deduplicate exactly equal boxes. This differs from comparing the `f32`
+/// values: a component of `0.0` is not equal to `-0.0`, and `NaN` components with the
+/// same bit pattern are equal. Use [`relative_eq`] to compare [`AABB`]s numerically.
+///
+/// The layout is `#[repr(C)]`: six `f32`s without padding, `min` before `max`, each in
+/// the order x, y, z. With the `bytemuck` feature, [`AABB`] implements `Pod`, so slices
+/// of [`AABB`]s can be cast to bytes and back without copying, e.g. with
+/// `bytemuck::cast_slice`.
+///
+/// [`AABB`]: struct.AABB.html
+/// [`Hash`]: https://doc.rust-lang.org/std/hash/trait.Hash.html
+/// [`relative_eq`]: struct.AABB.html#method.relative_eq
+///
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(C)]
 pub struct AABB {
     /// Minimum coordinates
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_vec3"))]
     pub min: Vec3,
 
     /// Maximum coordinates
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_vec3"))]
     pub max: Vec3,
 }
 
+impl AABB {
+    /// Returns the bit patterns of the components of `min` and `max`.
+    fn component_bits(&self) -> [u32; 6] {
+        [
+            self.min.x.to_bits(),
+            self.min.y.to_bits(),
+            self.min.z.to_bits(),
+            self.max.x.to_bits(),
+            self.max.y.to_bits(),
+            self.max.z.to_bits(),
+        ]
+    }
+}
+
+/// Compares the bit patterns of the components, see [`AABB`].
+///
+/// [`AABB`]: struct.AABB.html
+///
+impl PartialEq for AABB {
+    fn eq(&self, other: &AABB) -> bool {
+        self.component_bits() == other.component_bits()
+    }
+}
+
+impl Eq for AABB {}
+
+/// Hashes the bit patterns of the components, see [`AABB`].
+///
+/// [`AABB`]: struct.AABB.html
+///
+impl Hash for AABB {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.component_bits().hash(state);
+    }
+}
+
+// Safety: `AABB` is `#[repr(C)]` and consists of two `Vec3`s, which are `Pod` themselves,
+// so it has no padding and every bit pattern is valid.
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for AABB {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for AABB {}
+
 // TODO: ultraviolet::vec::Vec3 doesn't implement fmt::Display
 impl fmt::Display for AABB {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -28,8 +107,10 @@ impl fmt::Display for AABB {
 }
 
 /// A trait implemented by things which can be bounded by an [`AABB`].
+/// The joint [`AABB`] of a collection of `Bounded` items is computed by [`AABB::of_bounded`].
 ///
 /// [`AABB`]: struct.AABB.html
+/// [`AABB::of_bounded`]: struct.AABB.html#method.of_bounded
 ///
 pub trait Bounded {
     /// Returns the geometric bounds of this object in the form of an [`AABB`].
@@ -59,6 +140,36 @@ pub trait Bounded {
     /// [`AABB`]: struct.AABB.html
     ///
     fn aabb(&self) -> AABB;
+
+    /// Returns the [`AABB`] of the part of this object which lies inside of `bounds`, or an
+    /// empty [`AABB`] if no part does. Spatial splits (see [`BuildConfig::spatial_splits`])
+    /// use it to bound the pieces of a shape on both sides of a split plane.
+    ///
+    /// The default intersects [`aabb`] with `bounds`, which is correct for any shape, but
+    /// only tightens the axis of the split plane. Shapes which can clip their geometry,
+    /// such as [`Triangle`], should override it to get the full benefit of spatial splits.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::{AABB, Bounded};
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let point = Vec3::new(1.0, 2.0, 3.0);
+    /// let bounds = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 2.0, 2.0));
+    /// assert!(point.clipped_aabb(&bounds).is_empty());
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    /// assert_eq!(aabb.clipped_aabb(&bounds).min, Vec3::new(0.0, 0.0, 0.0));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`BuildConfig::spatial_splits`]: ../bvh/struct.BuildConfig.html#structfield.spatial_splits
+    /// [`Triangle`]: ../triangle/struct.Triangle.html
+    /// [`aabb`]: trait.Bounded.html#tymethod.aabb
+    ///
+    fn clipped_aabb(&self, bounds: &AABB) -> AABB {
+        self.aabb().intersection(bounds)
+    }
 }
 
 impl AABB {
@@ -80,6 +191,146 @@ impl AABB {
         AABB { min, max }
     }
 
+    /// Creates a new [`AABB`] from its `center` and its `half_extents`, i.e. half of its
+    /// size along every axis. See [`half_extents`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::from_center_half_extents(Vec3::new(1.0, 2.0, 3.0), Vec3::new(0.5, 1.0, 2.0));
+    /// assert_eq!(aabb.min, Vec3::new(0.5, 1.0, 1.0));
+    /// assert_eq!(aabb.max, Vec3::new(1.5, 3.0, 5.0));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`half_extents`]: struct.AABB.html#method.half_extents
+    ///
+    pub fn from_center_half_extents(center: Vec3, half_extents: Vec3) -> AABB {
+        AABB::with_bounds(center - half_extents, center + half_extents)
+    }
+
+    /// Creates a new [`AABB`] from its `center` and its `size` along every axis.
+    /// See [`size`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::from_center_size(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 4.0, 6.0));
+    /// assert_eq!(aabb.min, Vec3::new(-1.0, -2.0, -3.0));
+    /// assert_eq!(aabb.max, Vec3::new(1.0, 2.0, 3.0));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`size`]: struct.AABB.html#method.size
+    ///
+    pub fn from_center_size(center: Vec3, size: Vec3) -> AABB {
+        AABB::from_center_half_extents(center, size * 0.5)
+    }
+
+    /// Creates the tightest [`AABB`] around an oriented bounding box, which spans
+    /// `half_extents[i]` along `axes[i]` in both directions from its `center`. The
+    /// `axes` are expected to be unit vectors.
+    ///
+    /// The [`AABB`] extends from the `center` by the sum of the absolute projections of
+    /// the scaled `axes` onto every world axis, which is cheaper than joining all eight
+    /// corners.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// // A 2x2x2 cube, rotated by 45 degrees around the z axis.
+    /// let diagonal = std::f32::consts::FRAC_1_SQRT_2;
+    /// let axes = [
+    ///     Vec3::new(diagonal, diagonal, 0.0),
+    ///     Vec3::new(-diagonal, diagonal, 0.0),
+    ///     Vec3::new(0.0, 0.0, 1.0),
+    /// ];
+    /// let aabb = AABB::from_obb(Vec3::new(0.0, 0.0, 5.0), axes, Vec3::new(1.0, 1.0, 1.0));
+    ///
+    /// let reach = 2.0 * diagonal;
+    /// assert!((aabb.max - Vec3::new(reach, reach, 6.0)).mag() < 1e-6);
+    /// assert!((aabb.min - Vec3::new(-reach, -reach, 4.0)).mag() < 1e-6);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn from_obb(center: Vec3, axes: [Vec3; 3], half_extents: Vec3) -> AABB {
+        let reach = (axes[0] * half_extents.x).abs()
+            + (axes[1] * half_extents.y).abs()
+            + (axes[2] * half_extents.z).abs();
+        AABB::from_center_half_extents(center, reach)
+    }
+
+    /// Sets the bounds of this [`AABB`] to `min` and `max` if `min <= max` holds for
+    /// every component. Otherwise returns `Err(())` and leaves the [`AABB`] unchanged.
+    ///
+    /// Unlike assigning the public fields, this cannot produce an [`AABB`] whose `min`
+    /// exceeds its `max`, which silently breaks queries. Components which are `NaN` are
+    /// rejected as well, and so are the bounds of [`empty`]. Use [`normalized`] for two
+    /// arbitrary corners.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let mut aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+    ///
+    /// assert!(aabb.try_set_bounds(Vec3::new(-1.0, 0.0, 0.0), Vec3::new(1.0, 2.0, 0.0)).is_ok());
+    /// assert_eq!(aabb.min, Vec3::new(-1.0, 0.0, 0.0));
+    ///
+    /// assert!(aabb.try_set_bounds(Vec3::new(2.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0)).is_err());
+    /// assert_eq!(aabb.min, Vec3::new(-1.0, 0.0, 0.0));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`empty`]: struct.AABB.html#method.empty
+    /// [`normalized`]: struct.AABB.html#method.normalized
+    ///
+    #[allow(clippy::result_unit_err)]
+    pub fn try_set_bounds(&mut self, min: Vec3, max: Vec3) -> Result<(), ()> {
+        if min.x <= max.x && min.y <= max.y && min.z <= max.z {
+            self.min = min;
+            self.max = max;
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Returns a copy of this [`AABB`] whose `min` holds the smaller and whose `max` holds
+    /// the larger value of each component of `min` and `max`. This turns two arbitrary
+    /// opposite corners into a valid [`AABB`]. Note that this also turns an [`empty`]
+    /// [`AABB`] into an infinite one.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let corners = AABB::with_bounds(Vec3::new(1.0, -1.0, 3.0), Vec3::new(-1.0, 2.0, 0.0));
+    /// let aabb = corners.normalized();
+    ///
+    /// assert_eq!(aabb.min, Vec3::new(-1.0, -1.0, 0.0));
+    /// assert_eq!(aabb.max, Vec3::new(1.0, 2.0, 3.0));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`empty`]: struct.AABB.html#method.empty
+    ///
+    pub fn normalized(&self) -> AABB {
+        AABB::with_bounds(
+            self.min.min_by_component(self.max),
+            self.min.max_by_component(self.max),
+        )
+    }
+
     /// Creates a new empty [`AABB`].
     ///
     /// # Examples
@@ -192,6 +443,32 @@ impl AABB {
             && self.approx_contains_eps(&other.max, epsilon)
     }
 
+    /// Returns true if the `other` [`AABB`] is inside this [`AABB`], i.e. if
+    /// `other.min >= self.min` and `other.max <= self.max` hold component-wise.
+    /// Unlike [`approx_contains_aabb_eps`] there is no tolerance, which makes this the
+    /// right predicate for strict checks of parent bounds.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    /// let inner_aabb = AABB::with_bounds(aabb.center(), aabb.max);
+    /// let point_barely_outside = Vec3::new(1.000_000_1, 1.0, 1.0);
+    /// let outer_aabb = AABB::with_bounds(aabb.center(), point_barely_outside);
+    ///
+    /// assert!(aabb.contains_aabb(&inner_aabb));
+    /// assert!(!aabb.contains_aabb(&outer_aabb));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`approx_contains_aabb_eps`]: struct.AABB.html#method.approx_contains_aabb_eps
+    ///
+    pub fn contains_aabb(&self, other: &AABB) -> bool {
+        self.contains(&other.min) && self.contains(&other.max)
+    }
+
     /// Returns true if the `other` [`AABB`] is approximately equal to this [`AABB`]
     /// with respect to some `epsilon`.
     ///
@@ -327,236 +604,1212 @@ impl AABB {
         );
     }
 
-    /// Returns a new minimal [`AABB`] which contains both
-    /// this [`AABB`] and the [`Vec3`] `other`.
+    /// Returns a new [`AABB`] which covers the region shared by this [`AABB`] and `other`.
+    /// If the two [`AABB`]s do not overlap, the result [`is_empty()`].
     ///
     /// # Examples
     /// ```
     /// use bvh_ultraviolet::aabb::AABB;
     /// use bvh_ultraviolet::ultraviolet::Vec3;
     ///
-    /// let point1 = Vec3::new(0.0, 0.0, 0.0);
-    /// let point2 = Vec3::new(1.0, 1.0, 1.0);
-    /// let Vec3 = Vec3::new(2.0, 2.0, 2.0);
-    ///
-    /// let aabb = AABB::empty();
-    /// assert!(!aabb.contains(&point1));
+    /// let aabb1 = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 2.0, 2.0));
+    /// let aabb2 = AABB::with_bounds(Vec3::new(1.0, 1.0, 1.0), Vec3::new(3.0, 3.0, 3.0));
+    /// let overlap = aabb1.intersection(&aabb2);
     ///
-    /// let aabb1 = aabb.grow(&point1);
-    /// assert!(aabb1.contains(&point1));
+    /// assert_eq!(overlap.min.x, 1.0);
+    /// assert_eq!(overlap.max.x, 2.0);
     ///
-    /// let aabb2 = aabb.grow(&point2);
-    /// assert!(aabb2.contains(&point2));
-    /// assert!(!aabb2.contains(&Vec3));
+    /// let far_away = AABB::with_bounds(Vec3::new(10.0, 10.0, 10.0), Vec3::new(11.0, 11.0, 11.0));
+    /// assert!(aabb1.intersection(&far_away).is_empty());
     /// ```
     ///
     /// [`AABB`]: struct.AABB.html
-    /// [`Vec3`]: http://nalgebra.org/doc/nalgebra/struct.Vec3.html
+    /// [`is_empty()`]: #method.is_empty
     ///
-    pub fn grow(&self, other: &Vec3) -> AABB {
+    pub fn intersection(&self, other: &AABB) -> AABB {
         AABB::with_bounds(
             Vec3::new(
-                self.min.x.min(other.x),
-                self.min.y.min(other.y),
-                self.min.z.min(other.z),
+                self.min.x.max(other.min.x),
+                self.min.y.max(other.min.y),
+                self.min.z.max(other.min.z),
             ),
             Vec3::new(
-                self.max.x.max(other.x),
-                self.max.y.max(other.y),
-                self.max.z.max(other.z),
+                self.max.x.min(other.max.x),
+                self.max.y.min(other.max.y),
+                self.max.z.min(other.max.z),
             ),
         )
     }
 
-    /// Mutable version of [`AABB::grow`].
+    /// Mutable version of [`AABB::intersection`].
     ///
     /// # Examples
     /// ```
     /// use bvh_ultraviolet::aabb::AABB;
     /// use bvh_ultraviolet::ultraviolet::Vec3;
     ///
-    /// let point1 = Vec3::new(0.0, 0.0, 0.0);
-    /// let point2 = Vec3::new(1.0, 1.0, 1.0);
-    /// let Vec3 = Vec3::new(2.0, 2.0, 2.0);
-    ///
-    /// let mut aabb = AABB::empty();
-    /// assert!(!aabb.contains(&point1));
+    /// let mut aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 2.0, 2.0));
+    /// let other = AABB::with_bounds(Vec3::new(1.0, 1.0, 1.0), Vec3::new(3.0, 3.0, 3.0));
     ///
-    /// aabb.grow_mut(&point1);
-    /// assert!(aabb.contains(&point1));
-    /// assert!(!aabb.contains(&point2));
+    /// aabb.intersection_mut(&other);
     ///
-    /// aabb.grow_mut(&point2);
-    /// assert!(aabb.contains(&point2));
-    /// assert!(!aabb.contains(&Vec3));
+    /// assert!(aabb.contains(&Vec3::new(1.5, 1.5, 1.5)));
+    /// assert!(!aabb.contains(&Vec3::new(0.5, 0.5, 0.5)));
+    /// assert!(!aabb.contains(&Vec3::new(2.5, 2.5, 2.5)));
     /// ```
     ///
-    /// [`AABB::grow`]: struct.AABB.html
-    /// [`Vec3`]: http://nalgebra.org/doc/nalgebra/struct.Vec3.html
+    /// [`AABB::intersection`]: struct.AABB.html
     ///
-    pub fn grow_mut(&mut self, other: &Vec3) {
+    pub fn intersection_mut(&mut self, other: &AABB) {
         self.min = Vec3::new(
-            self.min.x.min(other.x),
-            self.min.y.min(other.y),
-            self.min.z.min(other.z),
+            self.min.x.max(other.min.x),
+            self.min.y.max(other.min.y),
+            self.min.z.max(other.min.z),
         );
         self.max = Vec3::new(
-            self.max.x.max(other.x),
-            self.max.y.max(other.y),
-            self.max.z.max(other.z),
+            self.max.x.min(other.max.x),
+            self.max.y.min(other.max.y),
+            self.max.z.min(other.max.z),
         );
     }
 
-    /// Returns a new minimal [`AABB`] which contains both this [`AABB`] and the [`Bounded`]
-    /// `other`.
+    /// Returns true if this [`AABB`] and `other` share any volume.
+    /// [`AABB`]s which merely touch on a face, edge or corner count as intersecting.
     ///
     /// # Examples
     /// ```
-    /// use bvh_ultraviolet::aabb::{AABB, Bounded};
+    /// use bvh_ultraviolet::aabb::AABB;
     /// use bvh_ultraviolet::ultraviolet::Vec3;
     ///
-    /// struct Something;
-    ///
-    /// impl Bounded for Something {
-    ///     fn aabb(&self) -> AABB {
-    ///         let point1 = Vec3::new(0.0,0.0,0.0);
-    ///         let point2 = Vec3::new(1.0,1.0,1.0);
-    ///         AABB::with_bounds(point1, point2)
-    ///     }
-    /// }
-    ///
-    /// let aabb = AABB::empty();
-    /// let something = Something;
-    /// let aabb1 = aabb.join_bounded(&something);
+    /// let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+    /// let overlapping = AABB::with_bounds(Vec3::new(0.5, 0.5, 0.5), Vec3::new(2.0, 2.0, 2.0));
+    /// let touching = AABB::with_bounds(Vec3::new(1.0, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0));
+    /// let disjoint = AABB::with_bounds(Vec3::new(1.5, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0));
     ///
-    /// let center = something.aabb().center();
-    /// assert!(aabb1.contains(&center));
+    /// assert!(aabb.intersects_aabb(&overlapping));
+    /// assert!(aabb.intersects_aabb(&touching));
+    /// assert!(!aabb.intersects_aabb(&disjoint));
     /// ```
     ///
     /// [`AABB`]: struct.AABB.html
-    /// [`Bounded`]: trait.Bounded.html
     ///
-    pub fn join_bounded<T: Bounded>(&self, other: &T) -> AABB {
-        self.join(&other.aabb())
+    pub fn intersects_aabb(&self, other: &AABB) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+            && self.min.z <= other.max.z
+            && self.max.z >= other.min.z
     }
 
-    /// Returns the size of this [`AABB`] in all three dimensions.
+    /// Returns true if this [`AABB`] and `other` approximately share any volume
+    /// with respect to some `epsilon`.
     ///
     /// # Examples
     /// ```
+    /// use bvh_ultraviolet::EPSILON;
     /// use bvh_ultraviolet::aabb::AABB;
     /// use bvh_ultraviolet::ultraviolet::Vec3;
     ///
-    /// let aabb = AABB::with_bounds(Vec3::new(-1.0,-1.0,-1.0), Vec3::new(1.0,1.0,1.0));
-    /// let size = aabb.size();
-    /// assert!(size.x == 2.0 && size.y == 2.0 && size.z == 2.0);
+    /// let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+    /// let barely_apart = AABB::with_bounds(Vec3::new(1.000_001, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0));
+    ///
+    /// assert!(!aabb.intersects_aabb(&barely_apart));
+    /// assert!(aabb.intersects_aabb_eps(&barely_apart, EPSILON));
     /// ```
     ///
     /// [`AABB`]: struct.AABB.html
     ///
-    pub fn size(&self) -> Vec3 {
-        self.max - self.min
+    pub fn intersects_aabb_eps(&self, other: &AABB, epsilon: f32) -> bool {
+        (self.min.x - other.max.x) < epsilon
+            && (self.max.x - other.min.x) > -epsilon
+            && (self.min.y - other.max.y) < epsilon
+            && (self.max.y - other.min.y) > -epsilon
+            && (self.min.z - other.max.z) < epsilon
+            && (self.max.z - other.min.z) > -epsilon
     }
 
-    /// Returns the center [`Vec3`] of the [`AABB`].
+    /// Returns true if the [`Ray`] intersects this [`AABB`].
     ///
     /// # Examples
     /// ```
     /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ray::Ray;
     /// use bvh_ultraviolet::ultraviolet::Vec3;
     ///
-    /// let min = Vec3::new(41.0,41.0,41.0);
-    /// let max = Vec3::new(43.0,43.0,43.0);
+    /// let aabb = AABB::with_bounds(Vec3::new(9.0, -1.0, -1.0), Vec3::new(11.0, 1.0, 1.0));
+    /// let ray_hit = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// let ray_miss = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
     ///
-    /// let aabb = AABB::with_bounds(min, max);
-    /// let center = aabb.center();
-    /// assert!(center.x == 42.0 && center.y == 42.0 && center.z == 42.0);
+    /// assert!(aabb.intersects_ray(&ray_hit));
+    /// assert!(!aabb.intersects_ray(&ray_miss));
     /// ```
     ///
     /// [`AABB`]: struct.AABB.html
-    /// [`Vec3`]: http://nalgebra.org/doc/nalgebra/struct.Vec3.html
+    /// [`Ray`]: ../ray/struct.Ray.html
     ///
-    pub fn center(&self) -> Vec3 {
-        self.min + (self.size() / 2.0)
+    pub fn intersects_ray(&self, ray: &Ray) -> bool {
+        ray.intersects_aabb(self)
     }
 
-    /// An empty [`AABB`] is an [`AABB`] where the lower bound is greater than
-    /// the upper bound in at least one component
+    /// Returns the distance along the [`Ray`] at which it enters this [`AABB`],
+    /// or `None` if the [`Ray`] misses it. Uses the slab method with the
+    /// [`Ray`]'s precomputed inverse direction.
+    /// Only the interval `[t_min, t_max]` of the [`Ray`] is considered. If the [`Ray`]
+    /// starts inside the [`AABB`], the distance is `t_min`, i.e. `0.0` by default.
     ///
     /// # Examples
     /// ```
     /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ray::Ray;
     /// use bvh_ultraviolet::ultraviolet::Vec3;
     ///
-    /// let empty_aabb = AABB::empty();
-    /// assert!(empty_aabb.is_empty());
+    /// let aabb = AABB::with_bounds(Vec3::new(9.0, -1.0, -1.0), Vec3::new(11.0, 1.0, 1.0));
     ///
-    /// let min = Vec3::new(41.0,41.0,41.0);
-    /// let max = Vec3::new(43.0,43.0,43.0);
+    /// let ray_outside = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(aabb.intersect_ray_distance(&ray_outside), Some(9.0));
     ///
-    /// let aabb = AABB::with_bounds(min, max);
-    /// assert!(!aabb.is_empty());
+    /// let ray_inside = Ray::new(Vec3::new(10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(aabb.intersect_ray_distance(&ray_inside), Some(0.0));
+    ///
+    /// let ray_away = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+    /// assert_eq!(aabb.intersect_ray_distance(&ray_away), None);
     /// ```
     ///
     /// [`AABB`]: struct.AABB.html
+    /// [`Ray`]: ../ray/struct.Ray.html
     ///
-    pub fn is_empty(&self) -> bool {
-        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    pub fn intersect_ray_distance(&self, ray: &Ray) -> Option<f32> {
+        let tx1 = (self.min.x - ray.origin.x) * ray.inv_direction.x;
+        let tx2 = (self.max.x - ray.origin.x) * ray.inv_direction.x;
+
+        let mut t_near = tx1.min(tx2);
+        let mut t_far = tx1.max(tx2);
+
+        let ty1 = (self.min.y - ray.origin.y) * ray.inv_direction.y;
+        let ty2 = (self.max.y - ray.origin.y) * ray.inv_direction.y;
+
+        t_near = t_near.max(ty1.min(ty2));
+        t_far = t_far.min(ty1.max(ty2));
+
+        let tz1 = (self.min.z - ray.origin.z) * ray.inv_direction.z;
+        let tz2 = (self.max.z - ray.origin.z) * ray.inv_direction.z;
+
+        t_near = t_near.max(tz1.min(tz2));
+        t_far = t_far.min(tz1.max(tz2));
+
+        let t_near = t_near.max(ray.t_min);
+        if t_far >= t_near && t_near <= ray.t_max {
+            Some(t_near)
+        } else {
+            None
+        }
     }
 
-    /// Returns the total surface area of this [`AABB`].
+    /// Returns the distances `(t_near, t_far)` at which the [`Ray`] enters and exits the
+    /// [`AABB`], measured along the [`Ray`]'s direction from its origin. Unlike
+    /// [`intersect_ray_distance`], the distances are not clamped to `[t_min, t_max]` of
+    /// the [`Ray`], so `t_near` is negative if the origin lies inside the [`AABB`].
+    ///
+    /// Returns `None` if the [`Ray`] misses the [`AABB`], or if the interval lies
+    /// completely outside of `[t_min, t_max]`, e.g. behind the origin. A [`Ray`] which
+    /// runs within the plane of a face counts as a hit.
     ///
     /// # Examples
     /// ```
     /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ray::Ray;
     /// use bvh_ultraviolet::ultraviolet::Vec3;
     ///
-    /// let min = Vec3::new(41.0,41.0,41.0);
-    /// let max = Vec3::new(43.0,43.0,43.0);
+    /// let aabb = AABB::with_bounds(Vec3::new(9.0, -1.0, -1.0), Vec3::new(11.0, 1.0, 1.0));
     ///
-    /// let aabb = AABB::with_bounds(min, max);
-    /// let surface_area = aabb.surface_area();
-    /// assert!(surface_area == 24.0);
+    /// let ray_outside = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(aabb.ray_interval(&ray_outside), Some((9.0, 11.0)));
+    ///
+    /// let ray_inside = Ray::new(Vec3::new(10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(aabb.ray_interval(&ray_inside), Some((-1.0, 1.0)));
+    ///
+    /// let ray_away = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+    /// assert_eq!(aabb.ray_interval(&ray_away), None);
     /// ```
     ///
     /// [`AABB`]: struct.AABB.html
-    ///
-    pub fn surface_area(&self) -> f32 {
-        let size = self.size();
-        2.0 * (size.x * size.y + size.x * size.z + size.y * size.z)
+    /// [`Ray`]: ../ray/struct.Ray.html
+    /// [`intersect_ray_distance`]: struct.AABB.html#method.intersect_ray_distance
+    ///
+    pub fn ray_interval(&self, ray: &Ray) -> Option<(f32, f32)> {
+        let mut t_near = f32::NEG_INFINITY;
+        let mut t_far = f32::INFINITY;
+        for axis in Axis::iter() {
+            let origin = ray.origin[axis];
+            if ray.direction[axis] == 0.0 {
+                // The slab test would compute `0.0 * inf` for a ray within a face.
+                if origin < self.min[axis] || origin > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (self.min[axis] - origin) * ray.inv_direction[axis];
+            let t2 = (self.max[axis] - origin) * ray.inv_direction[axis];
+            t_near = t_near.max(t1.min(t2));
+            t_far = t_far.min(t1.max(t2));
+        }
+
+        if t_near <= t_far && t_far >= ray.t_min && t_near <= ray.t_max {
+            Some((t_near, t_far))
+        } else {
+            None
+        }
     }
 
-    /// Returns the volume of this [`AABB`].
+    /// Returns the distance along the [`Ray`] at which it enters this [`AABB`] from
+    /// outside, or `None` if it does not enter it within `[t_min, t_max]`.
+    ///
+    /// Unlike [`intersect_ray_distance`], which returns `t_min` for a [`Ray`] whose origin
+    /// lies inside the [`AABB`], this returns `None` in that case, because the [`Ray`] only
+    /// leaves the [`AABB`]. This tells a [`Ray`] entering the [`AABB`] apart from one
+    /// starting inside of it, e.g. to cull portals seen from behind. An origin on the
+    /// boundary enters the [`AABB`] at `0.0` if the [`Ray`] points inwards.
     ///
     /// # Examples
     /// ```
     /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ray::Ray;
     /// use bvh_ultraviolet::ultraviolet::Vec3;
     ///
-    /// let min = Vec3::new(41.0,41.0,41.0);
-    /// let max = Vec3::new(43.0,43.0,43.0);
+    /// let aabb = AABB::with_bounds(Vec3::new(9.0, -1.0, -1.0), Vec3::new(11.0, 1.0, 1.0));
     ///
-    /// let aabb = AABB::with_bounds(min, max);
-    /// let volume = aabb.volume();
-    /// assert!(volume == 8.0);
+    /// let ray_outside = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(aabb.intersect_ray_entering(&ray_outside), Some(9.0));
+    ///
+    /// let ray_inside = Ray::new(Vec3::new(10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(aabb.intersect_ray_distance(&ray_inside), Some(0.0));
+    /// assert_eq!(aabb.intersect_ray_entering(&ray_inside), None);
     /// ```
     ///
     /// [`AABB`]: struct.AABB.html
+    /// [`Ray`]: ../ray/struct.Ray.html
+    /// [`intersect_ray_distance`]: struct.AABB.html#method.intersect_ray_distance
     ///
-    pub fn volume(&self) -> f32 {
-        let size = self.size();
-        size.x * size.y * size.z
+    pub fn intersect_ray_entering(&self, ray: &Ray) -> Option<f32> {
+        match self.ray_interval(ray) {
+            Some((t_near, _)) if t_near >= ray.t_min => Some(t_near),
+            _ => None,
+        }
     }
 
-    /// Returns the axis along which the [`AABB`] is stretched the most.
+    /// Returns the face of the [`AABB`] through which the [`Ray`] enters it, as the entry
+    /// distance along the [`Ray`], the [`Axis`] the face is orthogonal to, and whether it
+    /// is the face at `max` (`true`) or at `min` (`false`) along that [`Axis`]. The face is
+    /// the one of the slab which is entered last by the slab test.
+    ///
+    /// Returns `None` if the [`Ray`] misses the [`AABB`], if the entry lies outside of
+    /// `[t_min, t_max]`, or if the origin lies inside the [`AABB`], as no face is entered.
+    /// If the [`Ray`] enters exactly through an edge or a corner, where the slabs of several
+    /// axes are entered at the same distance, the first of these axes in the order x, y, z
+    /// is reported.
     ///
     /// # Examples
     /// ```
     /// use bvh_ultraviolet::aabb::AABB;
     /// use bvh_ultraviolet::axis::Axis;
+    /// use bvh_ultraviolet::ray::Ray;
     /// use bvh_ultraviolet::ultraviolet::Vec3;
     ///
-    /// let min = Vec3::new(-100.0,0.0,0.0);
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    ///
+    /// let from_above = Ray::new(Vec3::new(0.5, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+    /// assert_eq!(aabb.ray_hit_face(&from_above), Some((9.0, Axis::Y, true)));
+    ///
+    /// let from_left = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(aabb.ray_hit_face(&from_left), Some((4.0, Axis::X, false)));
+    ///
+    /// let from_inside = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(aabb.ray_hit_face(&from_inside), None);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`Axis`]: ../axis/enum.Axis.html
+    /// [`Ray`]: ../ray/struct.Ray.html
+    ///
+    pub fn ray_hit_face(&self, ray: &Ray) -> Option<(f32, Axis, bool)> {
+        let mut t_near = f32::NEG_INFINITY;
+        let mut t_far = f32::INFINITY;
+        let mut face = None;
+        for axis in Axis::iter() {
+            let origin = ray.origin[axis];
+            if ray.direction[axis] == 0.0 {
+                // The slab test would compute `0.0 * inf` for a ray within a face.
+                if origin < self.min[axis] || origin > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (self.min[axis] - origin) * ray.inv_direction[axis];
+            let t2 = (self.max[axis] - origin) * ray.inv_direction[axis];
+            // A ray along the negative direction enters the slab at `max`.
+            let enters_max = ray.direction[axis] < 0.0;
+            let t_enter = t1.min(t2);
+            if t_enter > t_near {
+                t_near = t_enter;
+                face = Some((axis, enters_max));
+            }
+            t_far = t_far.min(t1.max(t2));
+        }
+
+        match face {
+            Some((axis, is_max))
+                if t_near <= t_far && t_near >= ray.t_min && t_near <= ray.t_max =>
+            {
+                Some((t_near, axis, is_max))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns a new minimal [`AABB`] which contains both
+    /// this [`AABB`] and the [`Vec3`] `other`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let point1 = Vec3::new(0.0, 0.0, 0.0);
+    /// let point2 = Vec3::new(1.0, 1.0, 1.0);
+    /// let Vec3 = Vec3::new(2.0, 2.0, 2.0);
+    ///
+    /// let aabb = AABB::empty();
+    /// assert!(!aabb.contains(&point1));
+    ///
+    /// let aabb1 = aabb.grow(&point1);
+    /// assert!(aabb1.contains(&point1));
+    ///
+    /// let aabb2 = aabb.grow(&point2);
+    /// assert!(aabb2.contains(&point2));
+    /// assert!(!aabb2.contains(&Vec3));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`Vec3`]: http://nalgebra.org/doc/nalgebra/struct.Vec3.html
+    ///
+    pub fn grow(&self, other: &Vec3) -> AABB {
+        AABB::with_bounds(
+            Vec3::new(
+                self.min.x.min(other.x),
+                self.min.y.min(other.y),
+                self.min.z.min(other.z),
+            ),
+            Vec3::new(
+                self.max.x.max(other.x),
+                self.max.y.max(other.y),
+                self.max.z.max(other.z),
+            ),
+        )
+    }
+
+    /// Mutable version of [`AABB::grow`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let point1 = Vec3::new(0.0, 0.0, 0.0);
+    /// let point2 = Vec3::new(1.0, 1.0, 1.0);
+    /// let Vec3 = Vec3::new(2.0, 2.0, 2.0);
+    ///
+    /// let mut aabb = AABB::empty();
+    /// assert!(!aabb.contains(&point1));
+    ///
+    /// aabb.grow_mut(&point1);
+    /// assert!(aabb.contains(&point1));
+    /// assert!(!aabb.contains(&point2));
+    ///
+    /// aabb.grow_mut(&point2);
+    /// assert!(aabb.contains(&point2));
+    /// assert!(!aabb.contains(&Vec3));
+    /// ```
+    ///
+    /// [`AABB::grow`]: struct.AABB.html
+    /// [`Vec3`]: http://nalgebra.org/doc/nalgebra/struct.Vec3.html
+    ///
+    pub fn grow_mut(&mut self, other: &Vec3) {
+        self.min = Vec3::new(
+            self.min.x.min(other.x),
+            self.min.y.min(other.y),
+            self.min.z.min(other.z),
+        );
+        self.max = Vec3::new(
+            self.max.x.max(other.x),
+            self.max.y.max(other.y),
+            self.max.z.max(other.z),
+        );
+    }
+
+    /// Returns a new minimal [`AABB`] which contains both this [`AABB`] and the sphere
+    /// around `center` with `radius`, i.e. `center ± radius` on each axis. This saves
+    /// computing the [`AABB`] of the sphere before joining it.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+    /// let grown = aabb.grow_sphere(Vec3::new(3.0, 0.5, 0.5), 1.0);
+    ///
+    /// assert_eq!(grown.min, Vec3::new(0.0, -0.5, -0.5));
+    /// assert_eq!(grown.max, Vec3::new(4.0, 1.5, 1.5));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn grow_sphere(&self, center: Vec3, radius: f32) -> AABB {
+        let mut aabb = *self;
+        aabb.grow_sphere_mut(center, radius);
+        aabb
+    }
+
+    /// Mutable version of [`AABB::grow_sphere`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let mut aabb = AABB::empty();
+    /// aabb.grow_sphere_mut(Vec3::new(1.0, 2.0, 3.0), 0.5);
+    ///
+    /// assert_eq!(aabb.min, Vec3::new(0.5, 1.5, 2.5));
+    /// assert_eq!(aabb.max, Vec3::new(1.5, 2.5, 3.5));
+    /// ```
+    ///
+    /// [`AABB::grow_sphere`]: struct.AABB.html#method.grow_sphere
+    ///
+    pub fn grow_sphere_mut(&mut self, center: Vec3, radius: f32) {
+        let half_size = Vec3::new(radius, radius, radius);
+        self.min = self.min.min_by_component(center - half_size);
+        self.max = self.max.max_by_component(center + half_size);
+    }
+
+    /// Returns a new [`AABB`] which is grown by `margin` in every direction.
+    /// A negative `margin` shrinks the [`AABB`] and may produce an empty one,
+    /// which can be detected with [`is_empty()`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    ///
+    /// let fat = aabb.expand(0.5);
+    /// assert_eq!(fat.min, Vec3::new(-1.5, -1.5, -1.5));
+    /// assert_eq!(fat.max, Vec3::new(1.5, 1.5, 1.5));
+    ///
+    /// assert!(aabb.expand(-2.0).is_empty());
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`is_empty()`]: #method.is_empty
+    ///
+    pub fn expand(&self, margin: f32) -> AABB {
+        self.expand_by(Vec3::new(margin, margin, margin))
+    }
+
+    /// Mutable version of [`AABB::expand`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let mut aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    /// aabb.expand_mut(1.0);
+    ///
+    /// assert_eq!(aabb.min, Vec3::new(-2.0, -2.0, -2.0));
+    /// assert_eq!(aabb.max, Vec3::new(2.0, 2.0, 2.0));
+    /// ```
+    ///
+    /// [`AABB::expand`]: struct.AABB.html#method.expand
+    ///
+    pub fn expand_mut(&mut self, margin: f32) {
+        *self = self.expand(margin);
+    }
+
+    /// Returns a new [`AABB`] which is grown by the per-axis `margins`.
+    /// Negative margins shrink the [`AABB`] along the respective axis.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    /// let expanded = aabb.expand_by(Vec3::new(1.0, 0.0, -0.5));
+    ///
+    /// assert_eq!(expanded.min, Vec3::new(-2.0, -1.0, -0.5));
+    /// assert_eq!(expanded.max, Vec3::new(2.0, 1.0, 0.5));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn expand_by(&self, margins: Vec3) -> AABB {
+        AABB::with_bounds(self.min - margins, self.max + margins)
+    }
+
+    /// Returns a new [`AABB`] with the same center, whose half extents are scaled by
+    /// `factor`. A `factor` of `1.1` grows the [`AABB`] by 10% of its size, unlike
+    /// [`expand`], which grows it by an absolute margin. A negative `factor` produces an
+    /// empty [`AABB`], and an empty [`AABB`] stays empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(4.0, 2.0, 2.0));
+    /// let scaled = aabb.scaled(1.5);
+    ///
+    /// assert_eq!(scaled.min, Vec3::new(-1.0, -0.5, -0.5));
+    /// assert_eq!(scaled.max, Vec3::new(5.0, 2.5, 2.5));
+    /// assert_eq!(scaled.center(), aabb.center());
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`expand`]: struct.AABB.html#method.expand
+    ///
+    pub fn scaled(&self, factor: f32) -> AABB {
+        self.scaled_by(Vec3::new(factor, factor, factor))
+    }
+
+    /// Mutable version of [`AABB::scaled`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let mut aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    /// aabb.scaled_mut(0.5);
+    ///
+    /// assert_eq!(aabb.min, Vec3::new(-0.5, -0.5, -0.5));
+    /// assert_eq!(aabb.max, Vec3::new(0.5, 0.5, 0.5));
+    /// ```
+    ///
+    /// [`AABB::scaled`]: struct.AABB.html#method.scaled
+    ///
+    pub fn scaled_mut(&mut self, factor: f32) {
+        *self = self.scaled(factor);
+    }
+
+    /// Returns a new [`AABB`] with the same center, whose half extents are scaled by the
+    /// per-axis `factors`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    /// let scaled = aabb.scaled_by(Vec3::new(2.0, 1.0, 0.5));
+    ///
+    /// assert_eq!(scaled.min, Vec3::new(-2.0, -1.0, -0.5));
+    /// assert_eq!(scaled.max, Vec3::new(2.0, 1.0, 0.5));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn scaled_by(&self, factors: Vec3) -> AABB {
+        if self.is_empty() {
+            return *self;
+        }
+        let center = self.center();
+        let half_extents = self.half_extents() * factors;
+        AABB::with_bounds(center - half_extents, center + half_extents)
+    }
+
+    /// Returns the [`AABB`] swept along `velocity`, which is the joint [`AABB`] of this
+    /// [`AABB`] and this [`AABB`] translated by `velocity`. It contains every position
+    /// the moving [`AABB`] passes through, so it is a conservative bound for continuous
+    /// collision detection.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+    /// let swept = aabb.swept(Vec3::new(10.0, -2.0, 0.0));
+    ///
+    /// assert_eq!(swept.min, Vec3::new(0.0, -2.0, 0.0));
+    /// assert_eq!(swept.max, Vec3::new(11.0, 1.0, 1.0));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn swept(&self, velocity: Vec3) -> AABB {
+        let moved = AABB::with_bounds(self.min + velocity, self.max + velocity);
+        self.join(&moved)
+    }
+
+    /// Returns a copy of this [`AABB`] moved by `offset`, which is added to both `min`
+    /// and `max`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 2.0, 3.0));
+    /// let moved = aabb.translate(Vec3::new(10.0, -2.0, 0.5));
+    ///
+    /// assert_eq!(moved.min, Vec3::new(10.0, -2.0, 0.5));
+    /// assert_eq!(moved.max, Vec3::new(11.0, 0.0, 3.5));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn translate(&self, offset: Vec3) -> AABB {
+        AABB::with_bounds(self.min + offset, self.max + offset)
+    }
+
+    /// Mutable version of [`AABB::translate`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let mut aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 2.0, 3.0));
+    /// aabb.translate_mut(Vec3::new(10.0, -2.0, 0.5));
+    ///
+    /// assert_eq!(aabb.min, Vec3::new(10.0, -2.0, 0.5));
+    /// assert_eq!(aabb.max, Vec3::new(11.0, 0.0, 3.5));
+    /// ```
+    ///
+    /// [`AABB::translate`]: struct.AABB.html#method.translate
+    ///
+    pub fn translate_mut(&mut self, offset: Vec3) {
+        self.min += offset;
+        self.max += offset;
+    }
+
+    /// Moves this [`AABB`] so that its [`center()`] lies at `center`, keeping its size.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let mut aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 4.0, 6.0));
+    /// aabb.set_center(Vec3::new(10.0, 10.0, 10.0));
+    ///
+    /// assert_eq!(aabb.min, Vec3::new(9.0, 8.0, 7.0));
+    /// assert_eq!(aabb.max, Vec3::new(11.0, 12.0, 13.0));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`center()`]: #method.center
+    ///
+    pub fn set_center(&mut self, center: Vec3) {
+        let half_extents = self.half_extents();
+        self.min = center - half_extents;
+        self.max = center + half_extents;
+    }
+
+    /// Linearly interpolates the bounds of this [`AABB`] and `other` component-wise, where
+    /// `t = 0.0` returns this [`AABB`] and `t = 1.0` returns `other`. `t` is not clamped,
+    /// so values outside of `[0, 1]` extrapolate beyond either [`AABB`]. Both [`AABB`]s
+    /// should be finite, as the infinite bounds of an [`empty`] [`AABB`] yield `NaN`s.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let start = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+    /// let end = AABB::with_bounds(Vec3::new(10.0, 0.0, 0.0), Vec3::new(13.0, 1.0, 1.0));
+    ///
+    /// let halfway = start.lerp(&end, 0.5);
+    /// assert_eq!(halfway.min, Vec3::new(5.0, 0.0, 0.0));
+    /// assert_eq!(halfway.max, Vec3::new(7.0, 1.0, 1.0));
+    ///
+    /// let beyond = start.lerp(&end, 2.0);
+    /// assert_eq!(beyond.min, Vec3::new(20.0, 0.0, 0.0));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`empty`]: struct.AABB.html#method.empty
+    ///
+    pub fn lerp(&self, other: &AABB, t: f32) -> AABB {
+        AABB::with_bounds(
+            self.min * (1.0 - t) + other.min * t,
+            self.max * (1.0 - t) + other.max * t,
+        )
+    }
+
+    /// Returns a new minimal [`AABB`] which contains both this [`AABB`] and the [`Bounded`]
+    /// `other`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::{AABB, Bounded};
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// struct Something;
+    ///
+    /// impl Bounded for Something {
+    ///     fn aabb(&self) -> AABB {
+    ///         let point1 = Vec3::new(0.0,0.0,0.0);
+    ///         let point2 = Vec3::new(1.0,1.0,1.0);
+    ///         AABB::with_bounds(point1, point2)
+    ///     }
+    /// }
+    ///
+    /// let aabb = AABB::empty();
+    /// let something = Something;
+    /// let aabb1 = aabb.join_bounded(&something);
+    ///
+    /// let center = something.aabb().center();
+    /// assert!(aabb1.contains(&center));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`Bounded`]: trait.Bounded.html
+    ///
+    pub fn join_bounded<T: Bounded>(&self, other: &T) -> AABB {
+        self.join(&other.aabb())
+    }
+
+    /// Mutable version of [`AABB::join_bounded`], which grows this [`AABB`] in place to
+    /// contain the [`Bounded`] `other`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let points = vec![Vec3::new(1.0, -2.0, 0.0), Vec3::new(-1.0, 4.0, 0.5)];
+    ///
+    /// let mut aabb = AABB::empty();
+    /// for point in &points {
+    ///     aabb.join_bounded_mut(point);
+    /// }
+    /// assert_eq!(aabb.min, Vec3::new(-1.0, -2.0, 0.0));
+    /// assert_eq!(aabb.max, Vec3::new(1.0, 4.0, 0.5));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`AABB::join_bounded`]: struct.AABB.html#method.join_bounded
+    /// [`Bounded`]: trait.Bounded.html
+    ///
+    pub fn join_bounded_mut<T: Bounded>(&mut self, other: &T) {
+        self.join_mut(&other.aabb());
+    }
+
+    /// Returns the joint [`AABB`] of all [`Bounded`] `items`, folding them with
+    /// [`join_bounded`] starting from [`empty`]. No `items` yield an [`empty`] [`AABB`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let points = vec![
+    ///     Vec3::new(1.0, -2.0, 0.0),
+    ///     Vec3::new(-1.0, 4.0, 0.5),
+    ///     Vec3::new(0.0, 0.0, -3.0),
+    /// ];
+    ///
+    /// let aabb = AABB::of_bounded(&points);
+    /// assert_eq!(aabb.min, Vec3::new(-1.0, -2.0, -3.0));
+    /// assert_eq!(aabb.max, Vec3::new(1.0, 4.0, 0.5));
+    ///
+    /// let no_points: Vec<Vec3> = Vec::new();
+    /// assert!(AABB::of_bounded(&no_points).is_empty());
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`Bounded`]: trait.Bounded.html
+    /// [`empty`]: struct.AABB.html#method.empty
+    /// [`join_bounded`]: struct.AABB.html#method.join_bounded
+    ///
+    pub fn of_bounded<'a, T: Bounded + 'a, I: IntoIterator<Item = &'a T>>(items: I) -> AABB {
+        items
+            .into_iter()
+            .fold(AABB::empty(), |aabb, item| aabb.join_bounded(item))
+    }
+
+    /// Returns the joint [`AABB`] of all `shapes`, such as the bounds of a whole scene.
+    /// An empty slice yields an [`empty`] [`AABB`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let boxes = vec![
+    ///     AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0)),
+    ///     AABB::with_bounds(Vec3::new(-2.0, 3.0, 0.0), Vec3::new(-1.0, 4.0, 5.0)),
+    /// ];
+    ///
+    /// let scene = AABB::enclosing(&boxes);
+    /// assert_eq!(scene.min, Vec3::new(-2.0, 0.0, 0.0));
+    /// assert_eq!(scene.max, Vec3::new(1.0, 4.0, 5.0));
+    /// assert!(AABB::enclosing::<AABB>(&[]).is_empty());
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`empty`]: struct.AABB.html#method.empty
+    ///
+    pub fn enclosing<Shape: Bounded>(shapes: &[Shape]) -> AABB {
+        AABB::of_bounded(shapes)
+    }
+
+    /// Parallel version of [`enclosing`] using `rayon`, which computes the [`AABB`]s of the
+    /// `shapes` on all threads and joins them. Falls back to [`enclosing`] when there are
+    /// fewer than [`PARALLEL_JOINT_AABB_THRESHOLD`] `shapes`.
+    ///
+    /// The result is identical to the one of [`enclosing`], as the order of the joins
+    /// does not matter. An empty slice yields an [`empty`] [`AABB`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let boxes: Vec<AABB> = (0..100_000)
+    ///     .map(|i| {
+    ///         let position = Vec3::new((i % 97) as f32, (i % 89) as f32 * 0.5, i as f32 * 0.1);
+    ///         AABB::with_bounds(position, position + Vec3::one())
+    ///     })
+    ///     .collect();
+    ///
+    /// let parallel = AABB::enclosing_par(&boxes);
+    /// assert_eq!(parallel.min, AABB::enclosing(&boxes).min);
+    /// assert_eq!(parallel.max, AABB::enclosing(&boxes).max);
+    /// assert!(AABB::enclosing_par::<AABB>(&[]).is_empty());
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`PARALLEL_JOINT_AABB_THRESHOLD`]: constant.PARALLEL_JOINT_AABB_THRESHOLD.html
+    /// [`empty`]: struct.AABB.html#method.empty
+    /// [`enclosing`]: struct.AABB.html#method.enclosing
+    ///
+    #[cfg(feature = "rayon")]
+    pub fn enclosing_par<Shape: Bounded + Sync>(shapes: &[Shape]) -> AABB {
+        if shapes.len() < PARALLEL_JOINT_AABB_THRESHOLD {
+            return AABB::enclosing(shapes);
+        }
+
+        shapes
+            .par_iter()
+            .fold(AABB::empty, |aabb, shape| aabb.join_bounded(shape))
+            .reduce(AABB::empty, |a, b| a.join(&b))
+    }
+
+    /// Returns the size of this [`AABB`] in all three dimensions.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0,-1.0,-1.0), Vec3::new(1.0,1.0,1.0));
+    /// let size = aabb.size();
+    /// assert!(size.x == 2.0 && size.y == 2.0 && size.z == 2.0);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    /// Returns the center [`Vec3`] of the [`AABB`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let min = Vec3::new(41.0,41.0,41.0);
+    /// let max = Vec3::new(43.0,43.0,43.0);
+    ///
+    /// let aabb = AABB::with_bounds(min, max);
+    /// let center = aabb.center();
+    /// assert!(center.x == 42.0 && center.y == 42.0 && center.z == 42.0);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`Vec3`]: http://nalgebra.org/doc/nalgebra/struct.Vec3.html
+    ///
+    pub fn center(&self) -> Vec3 {
+        self.min + (self.size() / 2.0)
+    }
+
+    /// Returns the half extents of this [`AABB`], i.e. half of its [`size()`] along every axis.
+    /// The corners of the [`AABB`] are reproduced by `center() ± half_extents()`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, 0.0, 2.0), Vec3::new(3.0, 2.0, 3.0));
+    /// let half_extents = aabb.half_extents();
+    /// assert_eq!(half_extents, Vec3::new(2.0, 1.0, 0.5));
+    ///
+    /// assert_eq!(aabb.center() - half_extents, aabb.min);
+    /// assert_eq!(aabb.center() + half_extents, aabb.max);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`size()`]: #method.size
+    ///
+    pub fn half_extents(&self) -> Vec3 {
+        self.size() * 0.5
+    }
+
+    /// Returns the center and the radius of the smallest sphere enclosing this [`AABB`].
+    /// Useful as a cheap rejection test before the exact [`AABB`] test.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-2.0, -2.0, -1.0), Vec3::new(2.0, 2.0, 1.0));
+    /// let (center, radius) = aabb.bounding_sphere();
+    ///
+    /// assert_eq!(center, Vec3::new(0.0, 0.0, 0.0));
+    /// assert_eq!(radius, 3.0);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn bounding_sphere(&self) -> (Vec3, f32) {
+        (self.center(), self.half_extents().mag())
+    }
+
+    /// Returns the length of the diagonal of this [`AABB`], i.e. the magnitude of its [`size()`].
+    /// For an empty [`AABB`] `-1.0` is returned instead, as its [`size()`] has negative
+    /// (or infinite) components which would otherwise yield a meaningless length.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 3.0, 6.0));
+    /// assert_eq!(aabb.diagonal(), 7.0);
+    ///
+    /// assert_eq!(AABB::empty().diagonal(), -1.0);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`size()`]: #method.size
+    ///
+    pub fn diagonal(&self) -> f32 {
+        if self.is_empty() {
+            -1.0
+        } else {
+            self.size().mag()
+        }
+    }
+
+    /// Returns the squared length of the diagonal of this [`AABB`].
+    /// Cheaper than [`diagonal()`] when only comparing lengths.
+    /// Like [`diagonal()`], this returns `-1.0` for an empty [`AABB`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 3.0, 6.0));
+    /// assert_eq!(aabb.diagonal_squared(), 49.0);
+    ///
+    /// assert_eq!(AABB::empty().diagonal_squared(), -1.0);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`diagonal()`]: #method.diagonal
+    ///
+    pub fn diagonal_squared(&self) -> f32 {
+        if self.is_empty() {
+            -1.0
+        } else {
+            self.size().mag_sq()
+        }
+    }
+
+    /// An empty [`AABB`] is an [`AABB`] where the lower bound is greater than
+    /// the upper bound in at least one component
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let empty_aabb = AABB::empty();
+    /// assert!(empty_aabb.is_empty());
+    ///
+    /// let min = Vec3::new(41.0,41.0,41.0);
+    /// let max = Vec3::new(43.0,43.0,43.0);
+    ///
+    /// let aabb = AABB::with_bounds(min, max);
+    /// assert!(!aabb.is_empty());
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    /// Returns true if all components of `min` and `max` are finite, which means that
+    /// neither is `NaN` or infinite. Note that an empty [`AABB`] is not finite.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    /// assert!(aabb.is_finite());
+    ///
+    /// let broken = AABB::with_bounds(Vec3::new(f32::NAN, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+    /// assert!(!broken.is_finite());
+    /// assert!(!AABB::empty().is_finite());
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn is_finite(&self) -> bool {
+        self.min.x.is_finite()
+            && self.min.y.is_finite()
+            && self.min.z.is_finite()
+            && self.max.x.is_finite()
+            && self.max.y.is_finite()
+            && self.max.z.is_finite()
+    }
+
+    /// Returns the total surface area of this [`AABB`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let min = Vec3::new(41.0,41.0,41.0);
+    /// let max = Vec3::new(43.0,43.0,43.0);
+    ///
+    /// let aabb = AABB::with_bounds(min, max);
+    /// let surface_area = aabb.surface_area();
+    /// assert!(surface_area == 24.0);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn surface_area(&self) -> f32 {
+        let size = self.size();
+        2.0 * (size.x * size.y + size.x * size.z + size.y * size.z)
+    }
+
+    /// Returns the volume of this [`AABB`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let min = Vec3::new(41.0,41.0,41.0);
+    /// let max = Vec3::new(43.0,43.0,43.0);
+    ///
+    /// let aabb = AABB::with_bounds(min, max);
+    /// let volume = aabb.volume();
+    /// assert!(volume == 8.0);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn volume(&self) -> f32 {
+        let size = self.size();
+        size.x * size.y * size.z
+    }
+
+    /// Returns the volume of the intersection of this [`AABB`] and `other`, which is `0.0`
+    /// if they are disjoint.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb1 = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 2.0, 2.0));
+    /// let aabb2 = AABB::with_bounds(Vec3::new(1.0, 1.0, 0.0), Vec3::new(3.0, 3.0, 2.0));
+    /// assert_eq!(aabb1.overlap_volume(&aabb2), 2.0);
+    ///
+    /// let far_away = AABB::with_bounds(Vec3::new(10.0, 10.0, 10.0), Vec3::new(11.0, 11.0, 11.0));
+    /// assert_eq!(aabb1.overlap_volume(&far_away), 0.0);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn overlap_volume(&self, other: &AABB) -> f32 {
+        let overlap = self.intersection(other);
+        if overlap.is_empty() {
+            0.0
+        } else {
+            overlap.volume()
+        }
+    }
+
+    /// Returns the intersection over union of this [`AABB`] and `other`, the ratio of
+    /// their [`overlap_volume`] to the volume of their union. The result lies in
+    /// `[0.0, 1.0]` and is `1.0` for identical [`AABB`]s with a positive volume.
+    /// If the union has no volume, for example because both [`AABB`]s are flat, the
+    /// result is `0.0`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb1 = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 2.0, 2.0));
+    /// let aabb2 = AABB::with_bounds(Vec3::new(1.0, 0.0, 0.0), Vec3::new(3.0, 2.0, 2.0));
+    /// assert_eq!(aabb1.iou(&aabb2), 4.0 / 12.0);
+    /// assert_eq!(aabb1.iou(&aabb1), 1.0);
+    ///
+    /// let point = AABB::with_bounds(Vec3::new(1.0, 1.0, 1.0), Vec3::new(1.0, 1.0, 1.0));
+    /// assert_eq!(point.iou(&point), 0.0);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`overlap_volume`]: struct.AABB.html#method.overlap_volume
+    ///
+    pub fn iou(&self, other: &AABB) -> f32 {
+        let overlap = self.overlap_volume(other);
+        let union = self.volume() + other.volume() - overlap;
+        if union > 0.0 {
+            overlap / union
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the axis along which the [`AABB`] is stretched the most.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::axis::Axis;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let min = Vec3::new(-100.0,0.0,0.0);
     /// let max = Vec3::new(100.0,0.0,0.0);
     ///
     /// let aabb = AABB::with_bounds(min, max);
@@ -576,16 +1829,530 @@ impl AABB {
             Axis::Z
         }
     }
+
+    /// Splits this [`AABB`] at the plane through `position` perpendicular to `axis`.
+    /// Returns the half below and the half above the plane. Both halves are clamped
+    /// to the bounds of this [`AABB`], so their join is this [`AABB`] again.
+    ///
+    /// If `position` lies outside of `[min[axis], max[axis]]`, the half on that side
+    /// of the [`AABB`] is [`empty`] and the other half is this [`AABB`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::axis::Axis;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(4.0, 2.0, 2.0));
+    ///
+    /// let (below, above) = aabb.split(aabb.largest_axis(), 1.0);
+    /// assert_eq!(below.max, Vec3::new(1.0, 2.0, 2.0));
+    /// assert_eq!(above.min, Vec3::new(1.0, 0.0, 0.0));
+    ///
+    /// let (below, above) = aabb.split(Axis::Y, 5.0);
+    /// assert_eq!(below.max, aabb.max);
+    /// assert!(above.is_empty());
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`empty`]: struct.AABB.html#method.empty
+    ///
+    pub fn split(&self, axis: Axis, position: f32) -> (AABB, AABB) {
+        if position < self.min[axis] {
+            return (AABB::empty(), *self);
+        }
+        if position > self.max[axis] {
+            return (*self, AABB::empty());
+        }
+
+        let mut below = *self;
+        below.max[axis] = position;
+        let mut above = *self;
+        above.min[axis] = position;
+        (below, above)
+    }
+
+    /// Returns the point on or inside this [`AABB`] which is closest to `p`.
+    /// Each component of `p` is clamped into the bounds of the [`AABB`],
+    /// so a point inside the [`AABB`] is returned unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    ///
+    /// let point_inside = Vec3::new(0.5, -0.5, 0.0);
+    /// assert_eq!(aabb.closest_point(&point_inside), point_inside);
+    ///
+    /// let point_outside = Vec3::new(3.0, 0.5, -2.0);
+    /// assert_eq!(aabb.closest_point(&point_outside), Vec3::new(1.0, 0.5, -1.0));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn closest_point(&self, p: &Vec3) -> Vec3 {
+        Vec3::new(
+            p.x.max(self.min.x).min(self.max.x),
+            p.y.max(self.min.y).min(self.max.y),
+            p.z.max(self.min.z).min(self.max.z),
+        )
+    }
+
+    /// Alias of [`closest_point`], which clamps every component of `p` into the bounds of
+    /// this [`AABB`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    /// assert_eq!(aabb.clamp_point(&Vec3::new(3.0, 0.5, -2.0)), Vec3::new(1.0, 0.5, -1.0));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`closest_point`]: #method.closest_point
+    ///
+    pub fn clamp_point(&self, p: &Vec3) -> Vec3 {
+        self.closest_point(p)
+    }
+
+    /// Returns by how much `p` lies outside of this [`AABB`] along every axis. A component
+    /// is `0.0` if `p` lies within the bounds of the [`AABB`] on that axis, positive by
+    /// the overshoot if `p` lies beyond `max`, and negative by the undershoot if `p` lies
+    /// below `min`.
+    ///
+    /// The result is `p - closest_point(p)`, so its magnitude is the
+    /// [`distance_to_point`], and it points from the [`AABB`] to `p`, which is the
+    /// direction of a penalty force pushing `p` back.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    ///
+    /// let point = Vec3::new(3.0, 0.5, -4.0);
+    /// assert_eq!(aabb.signed_distance_per_axis(&point), Vec3::new(2.0, 0.0, -3.0));
+    /// assert_eq!(aabb.signed_distance_per_axis(&point).mag(), aabb.distance_to_point(&point));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`distance_to_point`]: #method.distance_to_point
+    ///
+    pub fn signed_distance_per_axis(&self, p: &Vec3) -> Vec3 {
+        *p - self.closest_point(p)
+    }
+
+    /// Returns the squared distance from `p` to the closest point of this [`AABB`].
+    /// The distance is `0.0` if `p` is inside the [`AABB`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    ///
+    /// assert_eq!(aabb.distance_squared_to_point(&Vec3::new(0.5, 0.0, 0.0)), 0.0);
+    /// assert_eq!(aabb.distance_squared_to_point(&Vec3::new(3.0, 3.0, 0.0)), 8.0);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn distance_squared_to_point(&self, p: &Vec3) -> f32 {
+        (*p - self.closest_point(p)).mag_sq()
+    }
+
+    /// Returns the distance from `p` to the closest point of this [`AABB`].
+    /// Prefer [`distance_squared_to_point`] when only comparing distances.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    ///
+    /// assert_eq!(aabb.distance_to_point(&Vec3::new(0.5, 0.0, 0.0)), 0.0);
+    /// assert_eq!(aabb.distance_to_point(&Vec3::new(0.0, -4.0, 0.0)), 3.0);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`distance_squared_to_point`]: #method.distance_squared_to_point
+    ///
+    pub fn distance_to_point(&self, p: &Vec3) -> f32 {
+        self.distance_squared_to_point(p).sqrt()
+    }
+
+    /// Returns the squared distance between the line segment from `a` to `b` and the
+    /// closest point of this [`AABB`]. The distance is `0.0` if the segment touches or
+    /// crosses the [`AABB`].
+    ///
+    /// The planes of the [`AABB`] cut the segment into at most seven pieces. On each of
+    /// them the squared distance is a quadratic function of the position along the
+    /// segment, whose minimum is computed exactly.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    ///
+    /// // A segment passing above the box.
+    /// let a = Vec3::new(-5.0, 3.0, 0.0);
+    /// let b = Vec3::new(5.0, 3.0, 0.0);
+    /// assert_eq!(aabb.distance_squared_to_segment(&a, &b), 4.0);
+    ///
+    /// // A segment passing diagonally by an edge of the box.
+    /// let a = Vec3::new(5.0, -1.0, 0.0);
+    /// let b = Vec3::new(-1.0, 5.0, 0.0);
+    /// assert_eq!(aabb.distance_squared_to_segment(&a, &b), 2.0);
+    ///
+    /// // A segment crossing the box.
+    /// let b = Vec3::new(-5.0, 0.0, 0.0);
+    /// assert_eq!(aabb.distance_squared_to_segment(&a, &b), 0.0);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn distance_squared_to_segment(&self, a: &Vec3, b: &Vec3) -> f32 {
+        let direction = *b - *a;
+        let point_at = |t: f32| *a + direction * t;
+
+        // The positions along the segment where it crosses a plane of the `AABB`.
+        let mut cuts = [0.0; 8];
+        let mut cut_count = 1;
+        for axis in Axis::iter() {
+            if direction[axis] != 0.0 {
+                for bound in &[self.min[axis], self.max[axis]] {
+                    let t = (bound - a[axis]) / direction[axis];
+                    if t > 0.0 && t < 1.0 {
+                        cuts[cut_count] = t;
+                        cut_count += 1;
+                    }
+                }
+            }
+        }
+        cuts[cut_count] = 1.0;
+        let cuts = &mut cuts[..=cut_count];
+        cuts.sort_by(|x, y| x.partial_cmp(y).unwrap());
+
+        let mut min_distance_squared = self.distance_squared_to_point(a);
+        for piece in cuts.windows(2) {
+            // Within a piece every axis is either inside the slab or clamped to the same
+            // bound, so the squared distance is a quadratic function of `t`.
+            let middle = point_at((piece[0] + piece[1]) * 0.5);
+            let offset = *a - self.closest_point(&middle);
+            let mut numerator = 0.0;
+            let mut denominator = 0.0;
+            for axis in Axis::iter() {
+                if middle[axis] < self.min[axis] || middle[axis] > self.max[axis] {
+                    numerator -= offset[axis] * direction[axis];
+                    denominator += direction[axis] * direction[axis];
+                }
+            }
+            let t = if denominator > 0.0 {
+                (numerator / denominator).max(piece[0]).min(piece[1])
+            } else {
+                piece[0]
+            };
+            let distance_squared = self.distance_squared_to_point(&point_at(t));
+            min_distance_squared = min_distance_squared.min(distance_squared);
+        }
+        min_distance_squared
+    }
+
+    /// Returns the eight corners of this [`AABB`].
+    /// The order is stable: bit 0, 1 and 2 of the index of a corner select
+    /// the maximum instead of the minimum for the x, y and z axis respectively.
+    /// Corner `0` is therefore `min` and corner `7` is `max`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -2.0, -3.0), Vec3::new(1.0, 2.0, 3.0));
+    /// let corners = aabb.corners();
+    ///
+    /// assert_eq!(corners[0], aabb.min);
+    /// assert_eq!(corners[1], Vec3::new(1.0, -2.0, -3.0));
+    /// assert_eq!(corners[6], Vec3::new(-1.0, 2.0, 3.0));
+    /// assert_eq!(corners[7], aabb.max);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn corners(&self) -> [Vec3; 8] {
+        let mut corners = [self.min; 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            *corner = Vec3::new(self[i & 1].x, self[(i >> 1) & 1].y, self[(i >> 2) & 1].z);
+        }
+        corners
+    }
+
+    /// Splits this [`AABB`] at its [`center()`] into eight octants, which tile it without
+    /// gaps or overlap. Octant `i` contains corner `i` of [`corners()`], so bit 0, 1 and 2
+    /// of its index select the upper half of the x, y and z axis respectively. Octant `0`
+    /// therefore spans from `min` to the center and octant `7` from the center to `max`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 4.0, 6.0));
+    /// let octants = aabb.subdivide();
+    ///
+    /// assert_eq!(octants[0].min, aabb.min);
+    /// assert_eq!(octants[0].max, Vec3::new(1.0, 2.0, 3.0));
+    /// assert_eq!(octants[1].min, Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(octants[1].max, Vec3::new(2.0, 2.0, 3.0));
+    /// assert_eq!(octants[7].max, aabb.max);
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    /// [`center()`]: #method.center
+    /// [`corners()`]: #method.corners
+    ///
+    pub fn subdivide(&self) -> [AABB; 8] {
+        let center = self.center();
+        let corners = self.corners();
+        let mut octants = [*self; 8];
+        for (octant, corner) in octants.iter_mut().zip(corners.iter()) {
+            *octant = AABB::with_bounds(
+                corner.min_by_component(center),
+                corner.max_by_component(center),
+            );
+        }
+        octants
+    }
+}
+
+/// Default instance for [`AABB`]s. Returns an [`AABB`] which is [`empty()`].
+///
+/// [`AABB`]: struct.AABB.html
+/// [`empty()`]: #method.empty
+///
+impl Default for AABB {
+    fn default() -> AABB {
+        AABB::empty()
+    }
+}
+
+#[cfg(feature = "glam")]
+impl AABB {
+    /// Creates a new [`AABB`] with the given bounds as `glam::Vec3`s, as used by bevy.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    ///
+    /// let aabb = AABB::from_glam(glam::Vec3::new(-1.0, -1.0, -1.0), glam::Vec3::ONE);
+    /// assert!(aabb.contains(&bvh_ultraviolet::ultraviolet::Vec3::zero()));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn from_glam(min: glam::Vec3, max: glam::Vec3) -> AABB {
+        AABB::with_bounds(vec3_from_glam(min), vec3_from_glam(max))
+    }
+
+    /// Returns the bounds of this [`AABB`] as `glam::Vec3`s, in the order `(min, max)`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    ///
+    /// let aabb = AABB::from_glam(glam::Vec3::ZERO, glam::Vec3::new(1.0, 2.0, 3.0));
+    /// let (min, max) = aabb.to_glam_min_max();
+    /// assert_eq!(min, glam::Vec3::ZERO);
+    /// assert_eq!(max, glam::Vec3::new(1.0, 2.0, 3.0));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn to_glam_min_max(&self) -> (glam::Vec3, glam::Vec3) {
+        (vec3_to_glam(self.min), vec3_to_glam(self.max))
+    }
+}
+
+/// Creates an [`AABB`] from a `(min, max)` pair of `glam::Vec3`s.
+/// See [`AABB::from_glam`].
+///
+/// [`AABB`]: struct.AABB.html
+/// [`AABB::from_glam`]: struct.AABB.html#method.from_glam
+///
+#[cfg(feature = "glam")]
+impl From<(glam::Vec3, glam::Vec3)> for AABB {
+    fn from((min, max): (glam::Vec3, glam::Vec3)) -> AABB {
+        AABB::from_glam(min, max)
+    }
+}
+
+/// Converts an [`AABB`] into a `(min, max)` pair of `glam::Vec3`s.
+/// See [`AABB::to_glam_min_max`].
+///
+/// [`AABB`]: struct.AABB.html
+/// [`AABB::to_glam_min_max`]: struct.AABB.html#method.to_glam_min_max
+///
+#[cfg(feature = "glam")]
+impl From<AABB> for (glam::Vec3, glam::Vec3) {
+    fn from(aabb: AABB) -> (glam::Vec3, glam::Vec3) {
+        aabb.to_glam_min_max()
+    }
+}
+
+#[cfg(feature = "mint")]
+impl AABB {
+    /// Creates a new [`AABB`] with the given bounds as `mint` vectors. This allows to
+    /// pass bounds from any math library which supports `mint`, such as nalgebra, cgmath
+    /// or glam.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let min = mint::Vector3 { x: -1.0, y: -1.0, z: -1.0 };
+    /// let max = mint::Vector3 { x: 1.0, y: 1.0, z: 1.0 };
+    /// let aabb = AABB::from_mint(min, max);
+    /// assert!(aabb.contains(&Vec3::zero()));
+    /// ```
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn from_mint(min: mint::Vector3<f32>, max: mint::Vector3<f32>) -> AABB {
+        AABB::with_bounds(vec3_from_mint(min), vec3_from_mint(max))
+    }
+
+    /// Returns the bounds of this [`AABB`] as `mint` vectors, in the order `(min, max)`.
+    ///
+    /// [`AABB`]: struct.AABB.html
+    ///
+    pub fn to_mint_min_max(&self) -> (mint::Vector3<f32>, mint::Vector3<f32>) {
+        (vec3_to_mint(self.min), vec3_to_mint(self.max))
+    }
+}
+
+/// Creates an [`AABB`] from a `(min, max)` pair of `mint` vectors.
+/// See [`AABB::from_mint`].
+///
+/// [`AABB`]: struct.AABB.html
+/// [`AABB::from_mint`]: struct.AABB.html#method.from_mint
+///
+#[cfg(feature = "mint")]
+impl From<(mint::Vector3<f32>, mint::Vector3<f32>)> for AABB {
+    fn from((min, max): (mint::Vector3<f32>, mint::Vector3<f32>)) -> AABB {
+        AABB::from_mint(min, max)
+    }
+}
+
+/// Converts an [`AABB`] into a `(min, max)` pair of `mint` vectors.
+/// See [`AABB::to_mint_min_max`].
+///
+/// [`AABB`]: struct.AABB.html
+/// [`AABB::to_mint_min_max`]: struct.AABB.html#method.to_mint_min_max
+///
+#[cfg(feature = "mint")]
+impl From<AABB> for (mint::Vector3<f32>, mint::Vector3<f32>) {
+    fn from(aabb: AABB) -> (mint::Vector3<f32>, mint::Vector3<f32>) {
+        aabb.to_mint_min_max()
+    }
+}
+
+/// Collects [`Vec3`]s into the minimal [`AABB`] containing all of them.
+/// Collecting no points yields an empty [`AABB`].
+///
+/// # Examples
+/// ```
+/// use bvh_ultraviolet::aabb::AABB;
+/// use bvh_ultraviolet::ultraviolet::Vec3;
+///
+/// let points = vec![Vec3::new(1.0, -2.0, 0.0), Vec3::new(-1.0, 3.0, 0.5)];
+/// let aabb: AABB = points.iter().copied().collect();
+///
+/// assert_eq!(aabb.min, Vec3::new(-1.0, -2.0, 0.0));
+/// assert_eq!(aabb.max, Vec3::new(1.0, 3.0, 0.5));
+/// ```
+///
+/// [`AABB`]: struct.AABB.html
+/// [`Vec3`]: http://nalgebra.org/doc/nalgebra/struct.Vec3.html
+///
+impl FromIterator<Vec3> for AABB {
+    fn from_iter<I: IntoIterator<Item = Vec3>>(iter: I) -> AABB {
+        let mut aabb = AABB::empty();
+        aabb.extend(iter);
+        aabb
+    }
+}
+
+/// Collects [`AABB`]s into the minimal [`AABB`] containing all of them.
+///
+/// # Examples
+/// ```
+/// use bvh_ultraviolet::aabb::AABB;
+/// use bvh_ultraviolet::ultraviolet::Vec3;
+///
+/// let aabbs = vec![
+///     AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0)),
+///     AABB::with_bounds(Vec3::new(-2.0, 0.5, 0.0), Vec3::new(0.0, 4.0, 0.5)),
+/// ];
+/// let aabb: AABB = aabbs.into_iter().collect();
+///
+/// assert_eq!(aabb.min, Vec3::new(-2.0, 0.0, 0.0));
+/// assert_eq!(aabb.max, Vec3::new(1.0, 4.0, 1.0));
+/// ```
+///
+/// [`AABB`]: struct.AABB.html
+///
+impl FromIterator<AABB> for AABB {
+    fn from_iter<I: IntoIterator<Item = AABB>>(iter: I) -> AABB {
+        let mut aabb = AABB::empty();
+        aabb.extend(iter);
+        aabb
+    }
+}
+
+/// Grows an [`AABB`] to contain all [`Vec3`]s of an iterator.
+///
+/// # Examples
+/// ```
+/// use bvh_ultraviolet::aabb::AABB;
+/// use bvh_ultraviolet::ultraviolet::Vec3;
+///
+/// let mut aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+/// aabb.extend(vec![Vec3::new(2.0, 0.5, 0.5), Vec3::new(0.5, -1.0, 0.5)]);
+///
+/// assert_eq!(aabb.min, Vec3::new(0.0, -1.0, 0.0));
+/// assert_eq!(aabb.max, Vec3::new(2.0, 1.0, 1.0));
+/// ```
+///
+/// [`AABB`]: struct.AABB.html
+/// [`Vec3`]: http://nalgebra.org/doc/nalgebra/struct.Vec3.html
+///
+impl Extend<Vec3> for AABB {
+    fn extend<I: IntoIterator<Item = Vec3>>(&mut self, iter: I) {
+        for point in iter {
+            self.grow_mut(&point);
+        }
+    }
 }
 
-/// Default instance for [`AABB`]s. Returns an [`AABB`] which is [`empty()`].
+/// Grows an [`AABB`] to contain all [`AABB`]s of an iterator.
 ///
 /// [`AABB`]: struct.AABB.html
-/// [`empty()`]: #method.empty
 ///
-impl Default for AABB {
-    fn default() -> AABB {
-        AABB::empty()
+impl Extend<AABB> for AABB {
+    fn extend<I: IntoIterator<Item = AABB>>(&mut self, iter: I) {
+        for aabb in iter {
+            self.join_mut(&aabb);
+        }
     }
 }
 
@@ -601,7 +2368,7 @@ impl Default for AABB {
 /// let max = Vec3::new(123.0,123.0,123.0);
 ///
 /// let aabb = AABB::with_bounds(min, max);
-/// 
+///
 /// # for i in 0..3 {
 /// assert_eq!(aabb[0][i], min[i]);
 /// assert_eq!(aabb[1][i], max[i]);
@@ -675,10 +2442,13 @@ impl Bounded for Vec3 {
 #[cfg(test)]
 mod tests {
     use crate::aabb::{Bounded, AABB};
+    use crate::axis::Axis;
+    use crate::ray::Ray;
     use crate::testbase::{tuple_to_point, tuple_to_vector, TupleVec};
     use crate::EPSILON;
 
-    use ultraviolet::Vec3;
+    use std::convert::TryFrom;
+    use ultraviolet::{Rotor3, Vec3};
     // use ultraviolet::{Vec3, Vec3};
     use quickcheck::quickcheck;
 
@@ -761,6 +2531,264 @@ mod tests {
         }
     }
 
+    /// Test whether the intersection of two `AABB`s is contained in both of them.
+    quickcheck! {
+        fn test_intersection_contained_in_both(a: TupleVec, b: TupleVec,
+                                               c: TupleVec, d: TupleVec) -> bool {
+            let aabb1 = AABB::empty()
+                .grow(&tuple_to_point(&a))
+                .grow(&tuple_to_point(&b));
+            let aabb2 = AABB::empty()
+                .grow(&tuple_to_point(&c))
+                .grow(&tuple_to_point(&d));
+
+            let intersection = aabb1.intersection(&aabb2);
+
+            // A disjoint pair yields an empty `AABB`, which lies in anything.
+            intersection.is_empty()
+                || (aabb1.approx_contains_aabb_eps(&intersection, EPSILON)
+                    && aabb2.approx_contains_aabb_eps(&intersection, EPSILON))
+        }
+    }
+
+    /// Test whether `intersection_mut` yields the same result as `intersection`.
+    quickcheck! {
+        fn test_intersection_mut_equals_intersection(a: TupleVec, b: TupleVec,
+                                                     c: TupleVec, d: TupleVec) -> bool {
+            let aabb1 = AABB::empty()
+                .grow(&tuple_to_point(&a))
+                .grow(&tuple_to_point(&b));
+            let aabb2 = AABB::empty()
+                .grow(&tuple_to_point(&c))
+                .grow(&tuple_to_point(&d));
+
+            let intersection = aabb1.intersection(&aabb2);
+            let mut intersection_mut = aabb1;
+            intersection_mut.intersection_mut(&aabb2);
+
+            intersection.min == intersection_mut.min && intersection.max == intersection_mut.max
+        }
+    }
+
+    /// Test whether two `AABB`s always intersect their own joint and their non-empty intersection.
+    quickcheck! {
+        fn test_intersects_aabb_joint(a: TupleVec, b: TupleVec, c: TupleVec, d: TupleVec) -> bool {
+            let aabb1 = AABB::empty()
+                .grow(&tuple_to_point(&a))
+                .grow(&tuple_to_point(&b));
+            let aabb2 = AABB::empty()
+                .grow(&tuple_to_point(&c))
+                .grow(&tuple_to_point(&d));
+
+            let joint = aabb1.join(&aabb2);
+            let overlap = aabb1.intersection(&aabb2);
+
+            joint.intersects_aabb(&aabb1)
+                && joint.intersects_aabb(&aabb2)
+                && aabb1.intersects_aabb(&aabb2) != overlap.is_empty()
+        }
+    }
+
+    #[test]
+    /// Test exact containment of `AABB`s on the boundary and one ulp outside of it.
+    fn test_contains_aabb_boundary() {
+        let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        assert!(aabb.contains_aabb(&aabb));
+
+        let on_boundary = AABB::with_bounds(Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 1.0));
+        assert!(aabb.contains_aabb(&on_boundary));
+
+        let one_ulp_above = f32::from_bits(1.0f32.to_bits() + 1);
+        let outside_max = AABB::with_bounds(Vec3::zero(), Vec3::new(1.0, one_ulp_above, 1.0));
+        assert!(!aabb.contains_aabb(&outside_max));
+        assert!(aabb.approx_contains_aabb_eps(&outside_max, EPSILON));
+
+        let one_ulp_below = -one_ulp_above;
+        let outside_min = AABB::with_bounds(Vec3::new(-1.0, -1.0, one_ulp_below), Vec3::zero());
+        assert!(!aabb.contains_aabb(&outside_min));
+    }
+
+    /// Test whether splitting an `AABB` inside of its bounds yields two halves
+    /// which touch at the plane and join back to the original `AABB`.
+    quickcheck! {
+        fn test_split_mid(a: TupleVec, b: TupleVec, t: f32, axis: usize) -> bool {
+            let aabb = AABB::empty()
+                .grow(&tuple_to_point(&a))
+                .grow(&tuple_to_point(&b));
+            let axis = Axis::try_from(axis % 3).unwrap();
+            let t = if t.is_finite() { t.abs().fract() } else { 0.5 };
+            let position = aabb.min[axis] + (aabb.max[axis] - aabb.min[axis]) * t;
+            let position = position.max(aabb.min[axis]).min(aabb.max[axis]);
+
+            let (below, above) = aabb.split(axis, position);
+            let joint = below.join(&above);
+            below.max[axis] == position
+                && above.min[axis] == position
+                && aabb.contains_aabb(&below)
+                && aabb.contains_aabb(&above)
+                && joint.min == aabb.min
+                && joint.max == aabb.max
+        }
+    }
+
+    #[test]
+    /// Test splitting an `AABB` at positions outside of its bounds.
+    fn test_split_out_of_range() {
+        let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 2.0, 3.0));
+
+        for &axis in &Axis::all() {
+            let (below, above) = aabb.split(axis, -1.0);
+            assert!(below.is_empty());
+            assert_eq!((above.min, above.max), (aabb.min, aabb.max));
+
+            let (below, above) = aabb.split(axis, 10.0);
+            assert_eq!((below.min, below.max), (aabb.min, aabb.max));
+            assert!(above.is_empty());
+        }
+
+        // Splitting at the boundary yields a flat, but not an empty half.
+        let (below, above) = aabb.split(Axis::X, 0.0);
+        assert!(!below.is_empty());
+        assert_eq!(below.size().x, 0.0);
+        assert_eq!((above.min, above.max), (aabb.min, aabb.max));
+    }
+
+    /// Test whether the joint `AABB` of some points contains exactly the points' bounds,
+    /// and whether the joint `AABB` of their `AABB`s is the same.
+    quickcheck! {
+        fn test_of_bounded(points: Vec<TupleVec>) -> bool {
+            let points: Vec<Vec3> = points.iter().map(tuple_to_point).collect();
+            let aabb = AABB::of_bounded(&points);
+
+            let aabbs: Vec<AABB> = points.iter().map(|point| point.aabb()).collect();
+            let joint = AABB::of_bounded(aabbs.iter());
+
+            let expected: AABB = points.iter().copied().collect();
+            aabb.min == expected.min
+                && aabb.max == expected.max
+                && joint.min == expected.min
+                && joint.max == expected.max
+                && points.iter().all(|point| aabb.contains(point))
+                && points.is_empty() == aabb.is_empty()
+        }
+    }
+
+    #[test]
+    /// Test whether `AABB`s which merely touch are considered intersecting.
+    fn test_intersects_aabb_touching() {
+        let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+
+        // Touching on a face, an edge and a corner.
+        let face = AABB::with_bounds(Vec3::new(1.0, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0));
+        let edge = AABB::with_bounds(Vec3::new(1.0, 1.0, 0.0), Vec3::new(2.0, 2.0, 1.0));
+        let corner = AABB::with_bounds(Vec3::new(1.0, 1.0, 1.0), Vec3::new(2.0, 2.0, 2.0));
+
+        for other in &[face, edge, corner] {
+            assert!(aabb.intersects_aabb(other));
+            assert!(other.intersects_aabb(&aabb));
+            assert!(aabb.intersects_aabb_eps(other, EPSILON));
+        }
+    }
+
+    #[test]
+    /// Test the intersection of disjoint `AABB`s with and without tolerance.
+    fn test_intersects_aabb_disjoint() {
+        let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        let close = AABB::with_bounds(Vec3::new(0.0, 0.0, 1.000_001), Vec3::new(1.0, 1.0, 2.0));
+        let far = AABB::with_bounds(Vec3::new(0.0, 0.0, 1.1), Vec3::new(1.0, 1.0, 2.0));
+
+        assert!(!aabb.intersects_aabb(&close));
+        assert!(aabb.intersects_aabb_eps(&close, EPSILON));
+        assert!(!aabb.intersects_aabb(&far));
+        assert!(!aabb.intersects_aabb_eps(&far, EPSILON));
+
+        // Empty `AABB`s intersect nothing.
+        assert!(!aabb.intersects_aabb(&AABB::empty()));
+        assert!(!AABB::empty().intersects_aabb(&aabb));
+    }
+
+    /// Test whether a `Ray` pointing at the center of an `AABB` enters it at a point
+    /// on its boundary, or at its origin if the origin is inside.
+    quickcheck! {
+        fn test_intersect_ray_distance_to_center(a: TupleVec, b: TupleVec, o: TupleVec) -> bool {
+            let aabb = AABB::empty()
+                .grow(&tuple_to_point(&a))
+                .grow(&tuple_to_point(&b));
+            let origin = tuple_to_point(&o);
+            let ray = Ray::new(origin, aabb.center() - origin);
+
+            match aabb.intersect_ray_distance(&ray) {
+                Some(distance) => {
+                    // Allow some tolerance relative to the magnitude of the input data.
+                    let epsilon = EPSILON * (1.0 + origin.mag() + aabb.size().mag());
+                    let entry = ray.origin + ray.direction * distance;
+                    aabb.intersects_ray(&ray)
+                        && distance >= 0.0
+                        && aabb.approx_contains_eps(&entry, epsilon)
+                        && (distance > 0.0 || aabb.approx_contains_eps(&origin, epsilon))
+                }
+                // Degenerate rays with the origin in the center have no direction.
+                None => aabb.center() == origin,
+            }
+        }
+    }
+
+    #[test]
+    /// Test the entry distances of some fixed `Ray`s.
+    fn test_intersect_ray_distance() {
+        let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+
+        let ray_x = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(aabb.intersect_ray_distance(&ray_x), Some(4.0));
+
+        let ray_z = Ray::new(Vec3::new(0.5, 0.5, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(aabb.intersect_ray_distance(&ray_z), Some(2.0));
+
+        // Origin inside of the `AABB`.
+        let ray_inside = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+        assert_eq!(aabb.intersect_ray_distance(&ray_inside), Some(0.0));
+        assert!(aabb.intersects_ray(&ray_inside));
+
+        // Pointing away and passing by.
+        let ray_away = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert_eq!(aabb.intersect_ray_distance(&ray_away), None);
+        let ray_past = Ray::new(Vec3::new(-5.0, 2.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(aabb.intersect_ray_distance(&ray_past), None);
+        assert!(!aabb.intersects_ray(&ray_past));
+    }
+
+    #[test]
+    /// Test `intersect_ray_entering` for origins outside, inside and on the boundary of
+    /// an `AABB`, and for entries before `t_min` and after `t_max`.
+    fn test_intersect_ray_entering() {
+        let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let direction = Vec3::new(1.0, 0.0, 0.0);
+
+        let ray_outside = Ray::new(Vec3::new(-5.0, 0.5, 0.0), direction);
+        assert_eq!(aabb.intersect_ray_entering(&ray_outside), Some(4.0));
+        assert_eq!(aabb.intersect_ray_distance(&ray_outside), Some(4.0));
+
+        // Inside, the ray only leaves the `AABB`.
+        let ray_inside = Ray::new(Vec3::new(0.0, 0.5, 0.0), direction);
+        assert_eq!(aabb.intersect_ray_entering(&ray_inside), None);
+        assert_eq!(aabb.intersect_ray_distance(&ray_inside), Some(0.0));
+
+        // On the boundary, pointing inwards and outwards.
+        let ray_boundary_in = Ray::new(Vec3::new(-1.0, 0.5, 0.0), direction);
+        assert_eq!(aabb.intersect_ray_entering(&ray_boundary_in), Some(0.0));
+        let ray_boundary_out = Ray::new(Vec3::new(-1.0, 0.5, 0.0), -direction);
+        assert_eq!(aabb.intersect_ray_entering(&ray_boundary_out), None);
+
+        // Behind the origin and outside of `[t_min, t_max]`.
+        let ray_away = Ray::new(Vec3::new(-5.0, 0.5, 0.0), -direction);
+        assert_eq!(aabb.intersect_ray_entering(&ray_away), None);
+        let ray_late = Ray::with_bounds(Vec3::new(-5.0, 0.5, 0.0), direction, 4.5, 10.0);
+        assert_eq!(aabb.intersect_ray_entering(&ray_late), None);
+        assert_eq!(aabb.intersect_ray_distance(&ray_late), Some(4.5));
+        let ray_short = Ray::with_bounds(Vec3::new(-5.0, 0.5, 0.0), direction, 0.0, 3.0);
+        assert_eq!(aabb.intersect_ray_entering(&ray_short), None);
+    }
+
     /// Test whether some points relative to the center of an AABB are classified correctly.
     quickcheck! {
         fn test_points_relative_to_center_and_size(a: TupleVec, b: TupleVec) -> bool {
@@ -791,6 +2819,48 @@ mod tests {
         }
     }
 
+    /// Test whether all corners of an AABB are inside its bounding sphere and can be
+    /// reproduced from its center and half extents.
+    quickcheck! {
+        fn test_half_extents_and_bounding_sphere(a: TupleVec, b: TupleVec) -> bool {
+            let aabb = AABB::empty()
+                .grow(&tuple_to_point(&a))
+                .grow(&tuple_to_point(&b));
+            let center = aabb.center();
+            let half_extents = aabb.half_extents();
+            let (sphere_center, radius) = aabb.bounding_sphere();
+
+            // Allow some tolerance relative to the magnitude of the input data.
+            let epsilon = EPSILON * (1.0 + aabb.min.mag() + aabb.max.mag());
+            let reproduced = AABB::with_bounds(center - half_extents, center + half_extents);
+            sphere_center == center
+                && reproduced.relative_eq(&aabb, epsilon)
+                && aabb
+                    .corners()
+                    .iter()
+                    .all(|corner| (*corner - sphere_center).mag() <= radius + epsilon)
+        }
+    }
+
+    /// Test whether the diagonal of an AABB is the distance between its min and max corner,
+    /// and whether empty AABBs yield the sentinel value.
+    quickcheck! {
+        fn test_diagonal(a: TupleVec, b: TupleVec) -> bool {
+            let min = tuple_to_point(&a);
+            let max = tuple_to_point(&b);
+            let aabb = AABB::with_bounds(min, max);
+
+            if aabb.is_empty() {
+                aabb.diagonal() == -1.0 && aabb.diagonal_squared() == -1.0
+            } else {
+                let distance = (max - min).mag();
+                let epsilon = EPSILON * (1.0 + distance);
+                (aabb.diagonal() - distance).abs() < epsilon
+                    && (aabb.diagonal_squared().sqrt() - distance).abs() < epsilon
+            }
+        }
+    }
+
     /// Test whether the surface of a nonempty AABB is always positive.
     quickcheck! {
         fn test_surface_always_positive(a: TupleVec, b: TupleVec) -> bool {
@@ -841,6 +2911,436 @@ mod tests {
         }
     }
 
+    /// Test whether the closest point of an `AABB` is inside of it, and equal to
+    /// the query point if that is inside as well.
+    quickcheck! {
+        fn test_closest_point_contained(a: TupleVec, b: TupleVec, p: TupleVec) -> bool {
+            let aabb = AABB::empty()
+                .grow(&tuple_to_point(&a))
+                .grow(&tuple_to_point(&b));
+            let point = tuple_to_point(&p);
+            let closest = aabb.closest_point(&point);
+
+            aabb.contains(&closest)
+                && (!aabb.contains(&point) || closest == point)
+                && aabb.contains(&point) == (aabb.distance_squared_to_point(&point) == 0.0)
+        }
+    }
+
+    /// Test whether no corner of an `AABB` is closer to a point than its closest point.
+    quickcheck! {
+        fn test_distance_to_point_not_greater_than_corners(a: TupleVec, b: TupleVec, p: TupleVec)
+            -> bool {
+            let aabb = AABB::empty()
+                .grow(&tuple_to_point(&a))
+                .grow(&tuple_to_point(&b));
+            let point = tuple_to_point(&p);
+            let distance = aabb.distance_to_point(&point);
+
+            let corner_min = (point - aabb.min).mag();
+            let corner_max = (point - aabb.max).mag();
+            distance <= corner_min && distance <= corner_max
+        }
+    }
+
+    /// Test whether all corners of an `AABB` are contained in it, span it and
+    /// follow the documented order.
+    quickcheck! {
+        fn test_corners(a: TupleVec, b: TupleVec) -> bool {
+            let aabb = AABB::empty()
+                .grow(&tuple_to_point(&a))
+                .grow(&tuple_to_point(&b));
+            let corners = aabb.corners();
+
+            let ordered = corners.iter().enumerate().all(|(i, corner)| {
+                let x = if i & 1 == 0 { aabb.min.x } else { aabb.max.x };
+                let y = if i & 2 == 0 { aabb.min.y } else { aabb.max.y };
+                let z = if i & 4 == 0 { aabb.min.z } else { aabb.max.z };
+                *corner == Vec3::new(x, y, z)
+            });
+            let joint = corners
+                .iter()
+                .fold(AABB::empty(), |joint, corner| joint.grow(corner));
+
+            ordered
+                && corners.iter().all(|corner| aabb.contains(corner))
+                && joint.min == aabb.min
+                && joint.max == aabb.max
+        }
+    }
+
+    /// Compare the surface area and volume of an expanded cube to those computed by hand.
+    quickcheck! {
+        fn test_expand_cube(pos: TupleVec, size: f32, margin: f32) -> bool {
+            // Generate some non-empty cube and a margin which does not make it empty
+            let pos = tuple_to_point(&pos);
+            let size = size.abs() + 1.0;
+            let margin = margin % (size / 2.0);
+            let aabb = AABB::with_bounds(pos, pos + Vec3::new(size, size, size));
+
+            let expanded = aabb.expand(margin);
+            let mut expanded_mut = aabb;
+            expanded_mut.expand_mut(margin);
+
+            let new_size = size + 2.0 * margin;
+            let area = 6.0 * new_size * new_size;
+            let volume = new_size * new_size * new_size;
+            let epsilon = EPSILON * (1.0 + pos.mag() + size);
+            expanded.relative_eq(&expanded_mut, epsilon)
+                && !expanded.is_empty()
+                && (1.0 - expanded.surface_area() / area).abs() < epsilon
+                && (1.0 - expanded.volume() / volume).abs() < epsilon
+        }
+    }
+
+    /// Test whether scaling an `AABB` preserves its center and scales its size exactly
+    /// by the factor, uniformly and per axis.
+    quickcheck! {
+        fn test_scaled_preserves_center(a: TupleVec, b: TupleVec, f: TupleVec) -> bool {
+            let aabb = AABB::empty()
+                .grow(&tuple_to_point(&a))
+                .grow(&tuple_to_point(&b));
+            let factors = Vec3::new(f.0.abs() % 4.0, f.1.abs() % 4.0, f.2.abs() % 4.0);
+
+            let scaled = aabb.scaled(factors.x);
+            let mut scaled_mut = aabb;
+            scaled_mut.scaled_mut(factors.x);
+            let scaled_by = aabb.scaled_by(factors);
+
+            let epsilon = EPSILON * (1.0 + aabb.min.mag() + aabb.max.mag()) * 4.0;
+            let close = |a: Vec3, b: Vec3| (a - b).abs().component_max() <= epsilon;
+            scaled.relative_eq(&scaled_mut, 0.0)
+                && close(scaled.center(), aabb.center())
+                && close(scaled.size(), aabb.size() * factors.x)
+                && close(scaled_by.center(), aabb.center())
+                && close(scaled_by.size(), aabb.size() * factors)
+        }
+    }
+
+    #[test]
+    /// Test whether scaling by a negative factor empties an `AABB` and whether an empty
+    /// `AABB` stays empty.
+    fn test_scaled_degenerate() {
+        let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(4.0, 2.0, 1.0));
+        assert!(aabb.scaled(-1.0).is_empty());
+        assert!(aabb.scaled_by(Vec3::new(1.0, 1.0, -0.5)).is_empty());
+        assert_eq!(aabb.scaled(0.0).volume(), 0.0);
+        assert!(AABB::empty().scaled(2.0).is_empty());
+    }
+
+    /// Test whether `center()` and `half_extents()` reproduce the inputs of
+    /// `from_center_half_extents`, and `size()` the input of `from_center_size`.
+    quickcheck! {
+        fn test_from_center_half_extents_round_trip(c: TupleVec, h: TupleVec) -> bool {
+            let center = tuple_to_point(&c);
+            let half_extents = tuple_to_vector(&h).abs();
+            let aabb = AABB::from_center_half_extents(center, half_extents);
+            let sized = AABB::from_center_size(center, half_extents * 2.0);
+
+            let epsilon = EPSILON * (1.0 + center.mag() + half_extents.mag()) * 4.0;
+            let close = |a: Vec3, b: Vec3| (a - b).abs().component_max() <= epsilon;
+            close(aabb.center(), center)
+                && close(aabb.half_extents(), half_extents)
+                && close(sized.size(), half_extents * 2.0)
+                && sized.relative_eq(&aabb, 0.0)
+        }
+    }
+
+    /// Test whether interpolating two `AABB`s returns the first one at `t = 0` and the
+    /// second one at `t = 1`, and a box between them at `t = 0.5`.
+    quickcheck! {
+        fn test_lerp_endpoints(a: TupleVec, b: TupleVec, c: TupleVec, d: TupleVec) -> bool {
+            let first = AABB::empty().grow(&tuple_to_point(&a)).grow(&tuple_to_point(&b));
+            let second = AABB::empty().grow(&tuple_to_point(&c)).grow(&tuple_to_point(&d));
+            let halfway = first.lerp(&second, 0.5);
+
+            let epsilon = EPSILON * (1.0 + first.max.mag() + second.max.mag()
+                + first.min.mag() + second.min.mag());
+            first.lerp(&second, 0.0).relative_eq(&first, 0.0)
+                && first.lerp(&second, 1.0).relative_eq(&second, 0.0)
+                && first.join(&second).approx_contains_aabb_eps(&halfway, epsilon)
+        }
+    }
+
+    /// Test whether translating an `AABB` preserves its size, shifts its center by the
+    /// offset, and whether `translate_mut` and `set_center` agree with `translate`.
+    quickcheck! {
+        fn test_translate(a: TupleVec, b: TupleVec, offset: TupleVec) -> bool {
+            let aabb = AABB::empty().grow(&tuple_to_point(&a)).grow(&tuple_to_point(&b));
+            let offset = tuple_to_point(&offset);
+            let moved = aabb.translate(offset);
+
+            let mut moved_mut = aabb;
+            moved_mut.translate_mut(offset);
+            let mut centered = aabb;
+            centered.set_center(aabb.center() + offset);
+
+            let epsilon = EPSILON * (1.0 + aabb.min.mag() + aabb.max.mag() + offset.mag());
+            let size_difference = moved.size() - aabb.size();
+            let center_difference = moved.center() - (aabb.center() + offset);
+            size_difference.abs().component_max() <= epsilon
+                && center_difference.abs().component_max() <= epsilon
+                && moved_mut.relative_eq(&moved, 0.0)
+                && centered.relative_eq(&moved, epsilon)
+        }
+    }
+
+    /// Test whether `distance_squared_to_segment` agrees with the minimum distance of
+    /// densely sampled points of the segment, which may only be slightly larger.
+    quickcheck! {
+        fn test_distance_squared_to_segment_equals_sampling(
+            a: TupleVec,
+            b: TupleVec,
+            c: TupleVec,
+            d: TupleVec
+        ) -> bool {
+            let aabb = AABB::empty()
+                .grow(&tuple_to_point(&a))
+                .grow(&tuple_to_point(&b));
+            let start = tuple_to_point(&c);
+            let end = tuple_to_point(&d);
+            let distance = aabb.distance_squared_to_segment(&start, &end).sqrt();
+
+            let sample_count = 1_000;
+            let sampled = (0..=sample_count)
+                .map(|i| start + (end - start) * (i as f32 / sample_count as f32))
+                .map(|point| aabb.distance_to_point(&point))
+                .fold(f32::INFINITY, f32::min);
+
+            // Distances are 1-Lipschitz, and the best point is at most half the spacing
+            // of the samples away from a sample.
+            let spacing = (end - start).mag() / sample_count as f32;
+            let epsilon = EPSILON * (1.0 + aabb.max.mag() + aabb.min.mag() + start.mag() + end.mag());
+            distance <= sampled + epsilon && sampled <= distance + spacing * 0.5 + epsilon
+        }
+    }
+
+    /// Test whether the octants of an `AABB` contain the corners of the same index, join
+    /// to the `AABB`, add up to its volume and do not overlap.
+    quickcheck! {
+        fn test_subdivide_tiles_aabb(a: TupleVec, b: TupleVec) -> bool {
+            let aabb = AABB::empty()
+                .grow(&tuple_to_point(&a))
+                .grow(&tuple_to_point(&b));
+            let octants = aabb.subdivide();
+            let corners = aabb.corners();
+
+            let joint = octants.iter().fold(AABB::empty(), |joint, octant| joint.join(octant));
+            let volume: f32 = octants.iter().map(AABB::volume).sum();
+            let disjoint = octants.iter().enumerate().all(|(i, octant)| {
+                octants[i + 1..]
+                    .iter()
+                    .all(|other| octant.overlap_volume(other) == 0.0)
+            });
+
+            octants.iter().zip(corners.iter()).all(|(octant, corner)| octant.contains(corner))
+                && joint.relative_eq(&aabb, 0.0)
+                && (volume - aabb.volume()).abs() <= 1e-4 * aabb.volume()
+                && disjoint
+        }
+    }
+
+    /// Test whether `from_obb` equals the `AABB` of the eight corners of a rotated box.
+    quickcheck! {
+        fn test_from_obb_equals_corners(c: TupleVec, e: TupleVec, angles: TupleVec) -> bool {
+            let center = tuple_to_point(&c);
+            let half_extents = tuple_to_vector(&e).abs();
+            let rotor = Rotor3::from_euler_angles(angles.0, angles.1, angles.2);
+            let axes = [
+                rotor * Vec3::unit_x(),
+                rotor * Vec3::unit_y(),
+                rotor * Vec3::unit_z(),
+            ];
+            let aabb = AABB::from_obb(center, axes, half_extents);
+
+            let mut corners = AABB::empty();
+            for &x in &[-1.0, 1.0] {
+                for &y in &[-1.0, 1.0] {
+                    for &z in &[-1.0, 1.0] {
+                        corners.grow_mut(
+                            &(center
+                                + axes[0] * (x * half_extents.x)
+                                + axes[1] * (y * half_extents.y)
+                                + axes[2] * (z * half_extents.z)),
+                        );
+                    }
+                }
+            }
+            let epsilon = 1e-5 * (1.0 + center.abs().component_max() + half_extents.mag());
+            aabb.relative_eq(&corners, epsilon)
+        }
+    }
+
+    #[test]
+    /// Test `normalized` on an `AABB` with swapped corners and on a valid one, and whether
+    /// `try_set_bounds` only accepts ordered bounds.
+    fn test_normalized_and_try_set_bounds() {
+        let min = Vec3::new(-1.0, -2.0, -3.0);
+        let max = Vec3::new(1.0, 2.0, 3.0);
+        let aabb = AABB::with_bounds(min, max);
+
+        let swapped = AABB::with_bounds(max, min);
+        assert!(swapped.is_empty());
+        assert_eq!(swapped.normalized(), aabb);
+        assert_eq!(aabb.normalized(), aabb);
+
+        let mixed = AABB::with_bounds(Vec3::new(1.0, -2.0, 3.0), Vec3::new(-1.0, 2.0, -3.0));
+        assert_eq!(mixed.normalized(), aabb);
+
+        let mut target = AABB::empty();
+        assert_eq!(target.try_set_bounds(max, min), Err(()));
+        assert!(target.is_empty());
+        assert_eq!(
+            target.try_set_bounds(min, Vec3::new(f32::NAN, 2.0, 3.0)),
+            Err(())
+        );
+        assert_eq!(target.try_set_bounds(min, max), Ok(()));
+        assert_eq!(target, aabb);
+        assert_eq!(target.try_set_bounds(min, min), Ok(()));
+        assert_eq!(target.volume(), 0.0);
+    }
+
+    #[test]
+    /// Test `signed_distance_per_axis` with points inside, beyond `max` and below `min`,
+    /// mixing the cases on different axes.
+    fn test_signed_distance_per_axis() {
+        let aabb = AABB::with_bounds(Vec3::new(-1.0, 0.0, 2.0), Vec3::new(1.0, 4.0, 3.0));
+
+        let inside = Vec3::new(0.5, 4.0, 2.0);
+        assert_eq!(aabb.signed_distance_per_axis(&inside), Vec3::zero());
+
+        let beyond_max = Vec3::new(1.5, 5.0, 2.5);
+        assert_eq!(
+            aabb.signed_distance_per_axis(&beyond_max),
+            Vec3::new(0.5, 1.0, 0.0)
+        );
+
+        let below_min = Vec3::new(0.0, -2.0, 1.0);
+        assert_eq!(
+            aabb.signed_distance_per_axis(&below_min),
+            Vec3::new(0.0, -2.0, -1.0)
+        );
+
+        let mixed = Vec3::new(-3.0, 2.0, 7.0);
+        let offset = aabb.signed_distance_per_axis(&mixed);
+        assert_eq!(offset, Vec3::new(-2.0, 0.0, 4.0));
+        assert_eq!(aabb.clamp_point(&mixed) + offset, mixed);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    /// Test whether `enclosing_par` is identical to `enclosing` below and above the
+    /// parallel threshold, and whether both return an empty `AABB` for no shapes.
+    fn test_enclosing_par_equals_enclosing() {
+        use crate::testbase::{create_n_cubes, default_bounds};
+
+        let triangles = create_n_cubes(2_000, &default_bounds());
+        for &count in &[0, 100, triangles.len()] {
+            let shapes = &triangles[..count];
+            let serial = AABB::enclosing(shapes);
+            let parallel = AABB::enclosing_par(shapes);
+            assert_eq!(serial.min, parallel.min);
+            assert_eq!(serial.max, parallel.max);
+            assert_eq!(serial.is_empty(), count == 0);
+        }
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    /// Test whether a slice of `AABB`s can be cast to bytes in the documented layout and
+    /// back without changing it.
+    fn test_bytemuck_cast_slice() {
+        let aabbs = vec![
+            AABB::with_bounds(Vec3::new(-1.0, -2.0, -3.0), Vec3::new(1.0, 2.0, 3.0)),
+            AABB::with_bounds(Vec3::new(4.0, 5.0, 6.0), Vec3::new(7.0, 8.0, 9.0)),
+        ];
+
+        let bytes: &[u8] = bytemuck::cast_slice(&aabbs);
+        assert_eq!(bytes.len(), aabbs.len() * 6 * 4);
+        let floats: &[f32] = bytemuck::cast_slice(&aabbs);
+        assert_eq!(&floats[6..], &[4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
+
+        let cast: &[AABB] = bytemuck::cast_slice(bytes);
+        assert_eq!(cast, &aabbs[..]);
+    }
+
+    #[test]
+    /// Test whether shrinking an `AABB` by more than its half extent empties it.
+    fn test_expand_negative() {
+        let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(4.0, 2.0, 1.0));
+
+        let shrunk = aabb.expand(-0.25);
+        assert_eq!(shrunk.min, Vec3::new(0.25, 0.25, 0.25));
+        assert_eq!(shrunk.max, Vec3::new(3.75, 1.75, 0.75));
+        assert_eq!(shrunk.volume(), 3.5 * 1.5 * 0.5);
+
+        assert!(aabb.expand(-0.75).is_empty());
+        assert!(!aabb.expand_by(Vec3::new(-1.5, -0.75, 0.0)).is_empty());
+        assert!(aabb.expand_by(Vec3::new(0.0, 0.0, -0.75)).is_empty());
+    }
+
+    /// Test whether growing an `AABB` by a sphere contains the extreme points of the sphere,
+    /// whether `grow_sphere_mut` equals `grow_sphere` and whether growing by a sphere of
+    /// radius zero equals `grow`.
+    quickcheck! {
+        fn test_grow_sphere(a: TupleVec, b: TupleVec, c: TupleVec, radius: f32) -> bool {
+            let aabb = AABB::empty().grow(&tuple_to_point(&a)).grow(&tuple_to_point(&b));
+            let center = tuple_to_point(&c);
+            let radius = radius.abs();
+
+            let grown = aabb.grow_sphere(center, radius);
+            let mut grown_mut = aabb;
+            grown_mut.grow_sphere_mut(center, radius);
+
+            let extreme_points = [
+                center + Vec3::new(radius, 0.0, 0.0),
+                center - Vec3::new(radius, 0.0, 0.0),
+                center + Vec3::new(0.0, radius, 0.0),
+                center - Vec3::new(0.0, radius, 0.0),
+                center + Vec3::new(0.0, 0.0, radius),
+                center - Vec3::new(0.0, 0.0, radius),
+            ];
+            grown == grown_mut
+                && grown.contains_aabb(&aabb)
+                && extreme_points.iter().all(|point| grown.contains(point))
+                && aabb.grow_sphere(center, 0.0) == aabb.grow(&center)
+        }
+    }
+
+    /// Test whether `join_bounded_mut` grows an `AABB` like `join_bounded`.
+    quickcheck! {
+        fn test_join_bounded_mut_equals_join_bounded(points: Vec<TupleVec>) -> bool {
+            let points: Vec<Vec3> = points.iter().map(tuple_to_point).collect();
+            let mut aabb = AABB::empty();
+            for point in &points {
+                aabb.join_bounded_mut(point);
+            }
+            let joined = points
+                .iter()
+                .fold(AABB::empty(), |aabb, point| aabb.join_bounded(point));
+            aabb == joined && aabb == AABB::of_bounded(&points)
+        }
+    }
+
+    /// Test whether collecting points and `AABB`s is equivalent to folding with `grow` and `join`.
+    quickcheck! {
+        fn test_from_iterator_equals_fold(points: Vec<TupleVec>) -> bool {
+            let points: Vec<Vec3> = points.iter().map(tuple_to_point).collect();
+            let folded = points
+                .iter()
+                .fold(AABB::empty(), |aabb, point| aabb.grow(point));
+
+            let collected: AABB = points.iter().copied().collect();
+            let joined: AABB = points.iter().map(|point| point.aabb()).collect();
+            let mut extended = AABB::empty();
+            extended.extend(points.iter().copied());
+
+            [collected, joined, extended]
+                .iter()
+                .all(|aabb| aabb.min == folded.min && aabb.max == folded.max)
+        }
+    }
+
     /// Test whether generating an `AABB` from the min and max bounds yields the same `AABB`.
     quickcheck! {
         fn test_create_aabb_from_indexable(a: TupleVec, b: TupleVec, p: TupleVec) -> bool {
@@ -859,4 +3359,172 @@ mod tests {
             aabb.contains(&point) == aabb_by_index.contains(&point)
         }
     }
+
+    /// Test whether the intersection over union of two `AABB`s lies in `[0, 1]`, is
+    /// symmetric, and is `1` for an `AABB` with itself.
+    quickcheck! {
+        fn test_iou(a: TupleVec, b: TupleVec, c: TupleVec, d: TupleVec) -> bool {
+            let aabb1 = AABB::empty().grow(&tuple_to_point(&a)).grow(&tuple_to_point(&b));
+            let aabb2 = AABB::empty().grow(&tuple_to_point(&c)).grow(&tuple_to_point(&d));
+            let iou = aabb1.iou(&aabb2);
+
+            let self_iou_is_one = aabb1.volume() == 0.0 || aabb1.iou(&aabb1) == 1.0;
+            (0.0..=1.0).contains(&iou) && iou == aabb2.iou(&aabb1) && self_iou_is_one
+        }
+    }
+
+    /// Test whether the overlap volume of two `AABB`s is bounded by both volumes, and
+    /// zero for disjoint `AABB`s.
+    quickcheck! {
+        fn test_overlap_volume(a: TupleVec, b: TupleVec, c: TupleVec, d: TupleVec) -> bool {
+            let aabb1 = AABB::empty().grow(&tuple_to_point(&a)).grow(&tuple_to_point(&b));
+            let aabb2 = AABB::empty().grow(&tuple_to_point(&c)).grow(&tuple_to_point(&d));
+            let overlap = aabb1.overlap_volume(&aabb2);
+
+            let disjoint = !aabb1.intersects_aabb(&aabb2);
+            overlap >= 0.0
+                && overlap <= aabb1.volume()
+                && overlap <= aabb2.volume()
+                && (!disjoint || overlap == 0.0)
+        }
+    }
+
+    #[test]
+    /// Tests the entry and exit distances of a `Ray` which starts inside an `AABB`, and of
+    /// `Ray`s which graze a face of an `AABB`.
+    fn test_ray_interval() {
+        let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+
+        // The origin lies inside, so the ray entered the `AABB` behind the origin.
+        let inside = Ray::new(Vec3::new(0.5, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(aabb.ray_interval(&inside), Some((-1.5, 0.5)));
+
+        // A ray within the plane of the top face.
+        let on_face = Ray::new(Vec3::new(-5.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(aabb.ray_interval(&on_face), Some((4.0, 6.0)));
+        let above_face = Ray::new(Vec3::new(-5.0, 1.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(aabb.ray_interval(&above_face), None);
+
+        // A diagonal ray which touches the top face in a single point.
+        let touching = Ray::new(Vec3::new(-2.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+        let (t_near, t_far) = aabb.ray_interval(&touching).unwrap();
+        assert!((t_near - 2.0f32.sqrt()).abs() < EPSILON);
+        assert!((t_far - t_near).abs() < EPSILON);
+
+        // The `AABB` lies behind the origin, or beyond `t_max`.
+        let behind = Ray::new(Vec3::new(5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(aabb.ray_interval(&behind), None);
+        let short = Ray::with_bounds(
+            Vec3::new(-5.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            0.0,
+            3.0,
+        );
+        assert_eq!(aabb.ray_interval(&short), None);
+    }
+
+    #[test]
+    /// Tests whether axis-aligned `Ray`s fired at each of the six faces of an `AABB` report
+    /// that face, and whether an entry through an edge reports the first axis.
+    fn test_ray_hit_face() {
+        let aabb = AABB::with_bounds(Vec3::new(-1.0, -2.0, -3.0), Vec3::new(1.0, 2.0, 3.0));
+
+        for axis in Axis::iter() {
+            for &is_max in &[false, true] {
+                let mut direction = Vec3::new(0.0, 0.0, 0.0);
+                direction[axis] = if is_max { -1.0 } else { 1.0 };
+                let mut origin = Vec3::new(0.25, 0.5, 0.75);
+                origin[axis] = -10.0 * direction[axis];
+
+                let ray = Ray::new(origin, direction);
+                let face_position = if is_max {
+                    aabb.max[axis]
+                } else {
+                    aabb.min[axis]
+                };
+                let distance = 10.0 - face_position.abs();
+                assert_eq!(aabb.ray_hit_face(&ray), Some((distance, axis, is_max)));
+            }
+        }
+
+        // The ray enters through the edge between the faces at `min.x` and `max.y`.
+        let edge = Ray::new(Vec3::new(-2.0, 3.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+        let (distance, axis, is_max) = aabb.ray_hit_face(&edge).unwrap();
+        assert!((distance - 2.0f32.sqrt()).abs() < EPSILON);
+        assert_eq!((axis, is_max), (Axis::X, false));
+
+        let miss = Ray::new(Vec3::new(-10.0, 5.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(aabb.ray_hit_face(&miss), None);
+        let behind = Ray::new(Vec3::new(10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(aabb.ray_hit_face(&behind), None);
+    }
+
+    /// Test whether the interval of a `Ray` through an `AABB` starts at the distance
+    /// returned by `intersect_ray_distance` when the origin lies outside.
+    quickcheck! {
+        fn test_ray_interval_matches_distance(a: TupleVec, b: TupleVec, p: TupleVec) -> bool {
+            let aabb = AABB::empty().grow(&tuple_to_point(&a)).grow(&tuple_to_point(&b));
+            let origin = tuple_to_point(&p);
+            let ray = Ray::new(origin, aabb.center() - origin);
+            if aabb.contains(&origin) || (aabb.center() - origin).mag() < EPSILON {
+                return true;
+            }
+
+            match (aabb.ray_interval(&ray), aabb.intersect_ray_distance(&ray)) {
+                (Some((t_near, t_far)), Some(distance)) => t_near == distance && t_far >= t_near,
+                (None, None) => true,
+                _ => false,
+            }
+        }
+    }
+
+    #[cfg(feature = "glam")]
+    /// Test whether converting an `AABB` to `glam` vectors and back yields the same
+    /// bounds.
+    quickcheck! {
+        fn test_glam_round_trip(a: TupleVec, b: TupleVec) -> bool {
+            let aabb = AABB::empty().grow(&tuple_to_point(&a)).grow(&tuple_to_point(&b));
+            let (min, max) = aabb.to_glam_min_max();
+            let converted: AABB = (min, max).into();
+            let pair: (glam::Vec3, glam::Vec3) = converted.into();
+            converted.min == aabb.min && converted.max == aabb.max && pair == (min, max)
+        }
+    }
+
+    #[cfg(feature = "mint")]
+    /// Test whether converting `mint` vectors to an `AABB` and back yields the same
+    /// vectors.
+    quickcheck! {
+        fn test_mint_round_trip(a: TupleVec, b: TupleVec) -> bool {
+            let min = mint::Vector3::from([a.0, a.1, a.2]);
+            let max = mint::Vector3::from([b.0, b.1, b.2]);
+            let aabb: AABB = (min, max).into();
+            let pair: (mint::Vector3<f32>, mint::Vector3<f32>) = aabb.into();
+            pair == (min, max) && AABB::from_mint(min, max).to_mint_min_max() == (min, max)
+        }
+    }
+
+    #[test]
+    /// Tests whether exactly equal `AABB`s are deduplicated by a `HashSet`, while `AABB`s
+    /// which differ in a single bit, or only in the sign of a zero, are kept apart.
+    fn test_aabb_hash_set() {
+        use std::collections::HashSet;
+
+        let aabb = AABB::with_bounds(Vec3::new(-1.0, 0.0, 2.0), Vec3::new(1.0, 3.0, 4.0));
+        let copy = AABB::with_bounds(aabb.min, aabb.max);
+        let mut nudged = aabb;
+        nudged.max.z = f32::from_bits(nudged.max.z.to_bits() + 1);
+        let mut negative_zero = aabb;
+        negative_zero.min.y = -0.0;
+
+        let set: HashSet<AABB> = vec![aabb, copy, aabb, nudged, negative_zero]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&copy));
+        assert_ne!(aabb, negative_zero);
+
+        let nan = AABB::with_bounds(Vec3::new(f32::NAN, 0.0, 0.0), Vec3::one());
+        assert_eq!(nan, nan);
+    }
 }
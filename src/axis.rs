@@ -1,10 +1,14 @@
-//! Axis enum for indexing three-dimensional structures.
+//! Axis enums for indexing two- and three-dimensional structures.
 
 #![allow(unused)]
-use ultraviolet::Vec3;
+use ultraviolet::{DVec3, Vec2, Vec3};
+use std::convert::TryFrom;
 use std::fmt::{Display, Formatter, Result};
 use std::ops::{Index, IndexMut};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 struct MyType<T>(T);
 
 /// An `Axis` in a three-dimensional coordinate system.
@@ -37,6 +41,7 @@ struct MyType<T>(T);
 /// # }
 /// ```
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Axis {
     /// Index of the X axis.
     X = 0,
@@ -48,6 +53,111 @@ pub enum Axis {
     Z = 2,
 }
 
+/// All three axes in the order X, Y, Z.
+const AXES: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+impl Axis {
+    /// Returns all three axes in the order X, Y, Z.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::axis::Axis;
+    ///
+    /// assert_eq!(Axis::all(), [Axis::X, Axis::Y, Axis::Z]);
+    /// ```
+    pub fn all() -> [Axis; 3] {
+        AXES
+    }
+
+    /// Returns an iterator over all three axes in the order X, Y, Z.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::axis::Axis;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let size = Vec3::new(1.0, 4.0, 2.0);
+    /// let sum: f32 = Axis::iter().map(|axis| size[axis]).sum();
+    /// assert_eq!(sum, 7.0);
+    /// ```
+    pub fn iter() -> impl Iterator<Item = Axis> {
+        AXES.iter().copied()
+    }
+
+    /// Returns the axis following this one, cycling from X to Y to Z and back to X.
+    /// Useful for round-robin splitting strategies.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::axis::Axis;
+    ///
+    /// assert_eq!(Axis::X.next(), Axis::Y);
+    /// assert_eq!(Axis::Z.next(), Axis::X);
+    /// ```
+    pub fn next(self) -> Axis {
+        match self {
+            Axis::X => Axis::Y,
+            Axis::Y => Axis::Z,
+            Axis::Z => Axis::X,
+        }
+    }
+
+    /// Returns the component of `v` along this axis. Equivalent to `v[axis]`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::axis::Axis;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let point = Vec3::new(1.0, 2.0, 3.0);
+    /// assert_eq!(Axis::Z.get(point), 3.0);
+    /// assert_eq!(point[Axis::Z], 3.0);
+    /// ```
+    pub fn get(self, v: Vec3) -> f32 {
+        v[self]
+    }
+
+    /// Sets the component of `v` along this axis to `value`. Equivalent to
+    /// `v[axis] = value`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::axis::Axis;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let mut point = Vec3::new(1.0, 2.0, 3.0);
+    /// Axis::Y.set(&mut point, 5.0);
+    /// assert_eq!(point, Vec3::new(1.0, 5.0, 3.0));
+    /// ```
+    pub fn set(self, v: &mut Vec3, value: f32) {
+        v[self] = value;
+    }
+}
+
+/// Converts an axis number to an `Axis`: 0 is X, 1 is Y and 2 is Z.
+/// Any other number is returned as the error.
+///
+/// # Examples
+/// ```
+/// use bvh_ultraviolet::axis::Axis;
+/// use std::convert::TryFrom;
+///
+/// assert_eq!(Axis::try_from(1), Ok(Axis::Y));
+/// assert_eq!(Axis::try_from(3), Err(3));
+/// ```
+impl TryFrom<usize> for Axis {
+    type Error = usize;
+
+    fn try_from(index: usize) -> std::result::Result<Axis, usize> {
+        match index {
+            0 => Ok(Axis::X),
+            1 => Ok(Axis::Y),
+            2 => Ok(Axis::Z),
+            _ => Err(index),
+        }
+    }
+}
+
 /// Display implementation for `Axis`.
 impl Display for Axis {
     fn fmt(&self, f: &mut Formatter) -> Result {
@@ -85,6 +195,19 @@ impl Index<Axis> for Vec3 {
     }
 }
 
+/// Make `DVec3` indexable by `Axis`.
+impl Index<Axis> for DVec3 {
+    type Output = f64;
+
+    fn index(&self, axis: Axis) -> &f64 {
+        match axis {
+            Axis::X => &self.x,
+            Axis::Y => &self.y,
+            Axis::Z => &self.z,
+        }
+    }
+}
+
 /// Make `Vec3` indexable by `Axis`.
 impl Index<Axis> for MyType<Vec3> {
     type Output = f32;
@@ -127,9 +250,73 @@ impl IndexMut<Axis> for MyType<Vec3> {
     }
 }
 
+/// An `Axis2` in a two-dimensional coordinate system.
+/// Used to access `Vec2` structs via index, for example by the [`BVH2`].
+///
+/// # Examples
+/// ```
+/// use bvh_ultraviolet::axis::Axis2;
+/// use bvh_ultraviolet::ultraviolet::Vec2;
+///
+/// let mut position = Vec2::new(1.0, 2.0);
+/// position[Axis2::Y] *= 4.0;
+///
+/// assert_eq!(position[Axis2::Y], 8.0);
+/// ```
+///
+/// [`BVH2`]: ../bvh2d/struct.BVH2.html
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Axis2 {
+    /// Index of the X axis.
+    X = 0,
+
+    /// Index of the Y axis.
+    Y = 1,
+}
+
+/// Display implementation for `Axis2`.
+impl Display for Axis2 {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Axis2::X => "x",
+                Axis2::Y => "y",
+            }
+        )
+    }
+}
+
+/// Make `Vec2` indexable by `Axis2`.
+impl Index<Axis2> for Vec2 {
+    type Output = f32;
+
+    fn index(&self, axis: Axis2) -> &f32 {
+        match axis {
+            Axis2::X => &self.x,
+            Axis2::Y => &self.y,
+        }
+    }
+}
+
+/// Make `Vec2` mutably accessible by `Axis2`.
+impl IndexMut<Axis2> for Vec2 {
+    fn index_mut(&mut self, axis: Axis2) -> &mut f32 {
+        match axis {
+            Axis2::X => &mut self.x,
+            Axis2::Y => &mut self.y,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::axis::Axis;
+    use crate::axis::{Axis, Axis2};
+    use std::convert::TryFrom;
+    use ultraviolet::{Vec2, Vec3};
     use quickcheck::quickcheck;
 
     /// Test whether accessing arrays by index is the same as accessing them by `Axis`.
@@ -153,4 +340,55 @@ mod test {
             a[0] == tpl.0 && a[1] == tpl.1 && a[2] == tpl.2
         }
     }
+
+    #[test]
+    /// Test whether iterating, cycling and converting axes agree with their indices.
+    fn test_iterate_and_convert_axes() {
+        let axes: Vec<Axis> = Axis::iter().collect();
+        assert_eq!(axes, Axis::all().to_vec());
+
+        for (index, axis) in Axis::iter().enumerate() {
+            assert_eq!(axis as usize, index);
+            assert_eq!(Axis::try_from(index), Ok(axis));
+            assert_eq!(axis.next() as usize, (index + 1) % 3);
+        }
+        assert_eq!(Axis::try_from(3), Err(3));
+        assert_eq!(Axis::X.next().next().next(), Axis::X);
+    }
+
+    /// Test whether `Vec3`s can be read and set along all three axes, both by indexing
+    /// and with the helper methods.
+    quickcheck! {
+        fn test_vec3_by_axis(tpl: (f32, f32, f32), value: f32) -> bool {
+            let v = Vec3::new(tpl.0, tpl.1, tpl.2);
+            let components = [tpl.0, tpl.1, tpl.2];
+
+            Axis::iter().all(|axis| {
+                let mut by_index = v;
+                let mut by_helper = v;
+                by_index[axis] = value;
+                axis.set(&mut by_helper, value);
+
+                v[axis] == components[axis as usize]
+                    && axis.get(v) == components[axis as usize]
+                    && by_index == by_helper
+                    && by_index[axis] == value
+                    && Axis::iter()
+                        .filter(|other| *other != axis)
+                        .all(|other| by_index[other] == v[other])
+            })
+        }
+    }
+
+    /// Test whether `Vec2`s can be read and set by indexing via `Axis2`.
+    quickcheck! {
+        fn test_vec2_by_axis2(tpl: (f32, f32)) -> bool {
+            let mut v = Vec2::new(0.0, 0.0);
+
+            v[Axis2::X] = tpl.0;
+            v[Axis2::Y] = tpl.1;
+
+            v.x == tpl.0 && v.y == tpl.1 && v[Axis2::X] == tpl.0 && v[Axis2::Y] == tpl.1
+        }
+    }
 }
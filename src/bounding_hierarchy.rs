@@ -20,6 +20,59 @@ pub trait BHShape: Bounded {
     /// [`BoundingHierarchy`]: struct.BoundingHierarchy.html
     ///
     fn bh_node_index(&self) -> usize;
+
+    /// Returns the relative cost of intersecting this shape, which the SAH builder of the
+    /// [`BVH`] uses to weight the shapes on either side of a split. Shapes which are
+    /// expensive to intersect are thereby moved into smaller nodes, which fewer rays hit.
+    ///
+    /// The default cost is `1.0`, which weights all shapes equally. Costs should be finite
+    /// and greater than zero. If no split of a node has a finite cost, for example because
+    /// one of its shapes reports `f32::INFINITY` or `NaN`, the builder splits that node at
+    /// the median of the shape centroids instead.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::{AABB, Bounded};
+    /// use bvh_ultraviolet::bounding_hierarchy::BHShape;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// struct Patch {
+    ///     bounds: AABB,
+    ///     subdivisions: u32,
+    ///     node_index: usize,
+    /// }
+    ///
+    /// impl Bounded for Patch {
+    ///     fn aabb(&self) -> AABB {
+    ///         self.bounds
+    ///     }
+    /// }
+    ///
+    /// impl BHShape for Patch {
+    ///     fn set_bh_node_index(&mut self, index: usize) {
+    ///         self.node_index = index;
+    ///     }
+    ///
+    ///     fn bh_node_index(&self) -> usize {
+    ///         self.node_index
+    ///     }
+    ///
+    ///     // Every subdivision level quadruples the number of triangles.
+    ///     fn intersection_cost(&self) -> f32 {
+    ///         4.0f32.powi(self.subdivisions as i32)
+    ///     }
+    /// }
+    ///
+    /// let bounds = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+    /// let patch = Patch { bounds, subdivisions: 2, node_index: 0 };
+    /// assert_eq!(patch.intersection_cost(), 16.0);
+    /// ```
+    ///
+    /// [`BVH`]: ../bvh/struct.BVH.html
+    ///
+    fn intersection_cost(&self) -> f32 {
+        1.0
+    }
 }
 
 /// This trait defines an acceleration structure with space partitioning.
@@ -5,24 +5,117 @@
 //!
 
 use crate::aabb::{Bounded, AABB};
+use crate::axis::Axis;
 use crate::bounding_hierarchy::{BHShape, BoundingHierarchy};
-use crate::ray::Ray;
-use crate::utils::{concatenate_vectors, joint_aabb_of_shapes, Bucket};
+use crate::bvh::spatial::build_with_spatial_splits;
+use crate::bvh::BvhError;
+use crate::ray::{HitDistance, Ray};
+use crate::utils::{concatenate_vectors, has_valid_aabb, joint_aabb_of_shapes, Bucket};
 use crate::EPSILON;
-use ultraviolet::Vec3;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::f32;
-use std::iter::repeat;
+use std::mem;
+use std::ops::Range;
+use ultraviolet::Vec3;
+
+/// The number of node indices [`BVHNode::traverse_iterative`] keeps in its fixed-size
+/// stack. A tree built by SAH has a depth of about `log2(n)`, which leaves plenty of slack.
+/// Deeper trees, see [`BVH::depth`], spill the remaining entries into a `Vec` on the heap,
+/// so the traversal never reads past its stack.
+///
+/// [`BVH::depth`]: struct.BVH.html#method.depth
+/// [`BVHNode::traverse_iterative`]: enum.BVHNode.html#method.traverse_iterative
+///
+pub const TRAVERSAL_STACK_SIZE: usize = 64;
+
+/// The maximum number of shapes accepted by [`BVH::try_build`]. A [`BVH`] over `n` shapes
+/// has up to `2n - 1` nodes, which must be addressable by the `u32` indices of a
+/// flattened [`BVH`], see [`flatten`].
+///
+/// [`BVH`]: struct.BVH.html
+/// [`BVH::try_build`]: struct.BVH.html#method.try_build
+/// [`flatten`]: struct.BVH.html#method.flatten
+///
+pub const MAX_SHAPES: usize = (u32::MAX / 2) as usize;
+
+/// A stack of node indices for the iterative traversals, which keeps the first
+/// [`TRAVERSAL_STACK_SIZE`] entries in a fixed-size array and spills the rest into a `Vec`.
+///
+/// [`TRAVERSAL_STACK_SIZE`]: constant.TRAVERSAL_STACK_SIZE.html
+///
+pub(crate) struct TraversalStack {
+    /// The first entries of the stack.
+    entries: [u32; TRAVERSAL_STACK_SIZE],
+
+    /// The number of used `entries`.
+    size: usize,
+
+    /// The entries above the first [`TRAVERSAL_STACK_SIZE`].
+    ///
+    /// [`TRAVERSAL_STACK_SIZE`]: constant.TRAVERSAL_STACK_SIZE.html
+    ///
+    spilled: Vec<usize>,
+}
+
+impl TraversalStack {
+    /// Creates an empty stack. Does not allocate.
+    pub(crate) fn new() -> TraversalStack {
+        TraversalStack {
+            entries: [0; TRAVERSAL_STACK_SIZE],
+            size: 0,
+            spilled: Vec::new(),
+        }
+    }
+
+    /// Pushes a node index onto the stack.
+    pub(crate) fn push(&mut self, node_index: usize) {
+        if self.size < TRAVERSAL_STACK_SIZE {
+            self.entries[self.size] = node_index as u32;
+            self.size += 1;
+        } else {
+            self.spilled.push(node_index);
+        }
+    }
+
+    /// Pops the most recently pushed node index.
+    pub(crate) fn pop(&mut self) -> Option<usize> {
+        // The spilled entries were pushed last, so they are popped first.
+        if let Some(node_index) = self.spilled.pop() {
+            Some(node_index)
+        } else if self.size > 0 {
+            self.size -= 1;
+            Some(self.entries[self.size] as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the most recently pushed node index without popping it.
+    #[cfg(feature = "simd")]
+    fn peek(&self) -> Option<usize> {
+        match self.spilled.last() {
+            Some(node_index) => Some(*node_index),
+            None if self.size > 0 => Some(self.entries[self.size - 1] as usize),
+            None => None,
+        }
+    }
+}
 
 /// The [`BVHNode`] enum that describes a node in a [`BVH`].
-/// It's either a leaf node and references a shape (by holding its index)
-/// or a regular node that has two child nodes.
-/// The non-leaf node stores the [`AABB`]s of its children.
+/// It's either a leaf node and references a range of entries of [`BVH::shape_indices`]
+/// (by holding the index of the first entry and the number of entries) or a regular node
+/// that has two child nodes. The non-leaf node stores the [`AABB`]s of its children.
 ///
 /// [`AABB`]: ../aabb/struct.AABB.html
 /// [`BVH`]: struct.BVH.html
 /// [`BVH`]: struct.BVHNode.html
+/// [`BVH::shape_indices`]: struct.BVH.html#structfield.shape_indices
 ///
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BVHNode {
     /// Leaf node.
     Leaf {
@@ -32,8 +125,12 @@ pub enum BVHNode {
         /// The node's depth.
         depth: u32,
 
-        /// The shape contained in this leaf.
+        /// The index of the first entry of this leaf in the `shape_indices` of the `BVH`.
         shape_index: usize,
+
+        /// The number of shapes contained in this leaf. The leaf contains the shapes whose
+        /// indices are stored in `shape_indices[shape_index..shape_index + shape_count]`.
+        shape_count: usize,
     },
     /// Inner node.
     Node {
@@ -54,6 +151,15 @@ pub enum BVHNode {
 
         /// The convex hull of the shapes' `AABB`s in child_r.
         child_r_aabb: AABB,
+
+        /// The axis along which the builder split the shapes into the two children.
+        split_axis: Axis,
+
+        /// The coordinate of the split plane on `split_axis`. The centroids of the shapes
+        /// in child_l lie below or on it, those in child_r above or on it. If all
+        /// centroids coincide, it is their position. It is recorded by the builder and not
+        /// updated by `optimize`.
+        split_position: f32,
     },
 }
 
@@ -87,16 +193,19 @@ impl PartialEq for BVHNode {
                     parent_index: self_parent_index,
                     depth: self_depth,
                     shape_index: self_shape_index,
+                    shape_count: self_shape_count,
                 },
                 &BVHNode::Leaf {
                     parent_index: other_parent_index,
                     depth: other_depth,
                     shape_index: other_shape_index,
+                    shape_count: other_shape_count,
                 },
             ) => {
                 self_parent_index == other_parent_index
                     && self_depth == other_depth
                     && self_shape_index == other_shape_index
+                    && self_shape_count == other_shape_count
             }
             _ => false,
         }
@@ -133,7 +242,7 @@ impl BVHNode {
         }
     }
 
-    /// Returns the `AABB` of the right child node.
+    /// Returns the `AABB` of the left child node.
     pub fn child_l_aabb(&self) -> AABB {
         match *self {
             BVHNode::Node { child_l_aabb, .. } => child_l_aabb,
@@ -179,6 +288,22 @@ impl BVHNode {
         }
     }
 
+    /// Returns the axis along which the shapes were split into the two children.
+    pub fn split_axis(&self) -> Axis {
+        match *self {
+            BVHNode::Node { split_axis, .. } => split_axis,
+            _ => panic!("Tried to get the split axis of a leaf node."),
+        }
+    }
+
+    /// Returns the coordinate of the split plane on the split axis.
+    pub fn split_position(&self) -> f32 {
+        match *self {
+            BVHNode::Node { split_position, .. } => split_position,
+            _ => panic!("Tried to get the split position of a leaf node."),
+        }
+    }
+
     /// Returns the depth of the node. The root node has depth `0`.
     pub fn depth(&self) -> u32 {
         match *self {
@@ -187,21 +312,30 @@ impl BVHNode {
     }
 
     /// Gets the `AABB` for a `BVHNode`.
-    /// Returns the shape's `AABB` for leaves, and the joined `AABB` of
-    /// the two children's `AABB`s for non-leaves.
-    pub fn get_node_aabb<Shape: BHShape>(&self, shapes: &[Shape]) -> AABB {
+    /// Returns the joint `AABB` of the shapes for leaves, and the joined `AABB` of
+    /// the two children's `AABB`s for non-leaves. `shape_indices` are the
+    /// `shape_indices` of the `BVH` the node belongs to.
+    pub fn get_node_aabb<Shape: BHShape>(&self, shape_indices: &[usize], shapes: &[Shape]) -> AABB {
         match *self {
             BVHNode::Node {
                 child_l_aabb,
                 child_r_aabb,
                 ..
             } => child_l_aabb.join(&child_r_aabb),
-            BVHNode::Leaf { shape_index, .. } => shapes[shape_index].aabb(),
+            BVHNode::Leaf {
+                shape_index,
+                shape_count,
+                ..
+            } => shape_indices[shape_index..shape_index + shape_count]
+                .iter()
+                .fold(AABB::empty(), |aabb, index| {
+                    aabb.join(&shapes[*index].aabb())
+                }),
         }
     }
 
-    /// Returns the index of the shape contained within the node if is a leaf,
-    /// or `None` if it is an interior node.
+    /// Returns the index of the first entry of the node in the `shape_indices` of the
+    /// `BVH` if it is a leaf, or `None` if it is an interior node.
     pub fn shape_index(&self) -> Option<usize> {
         match *self {
             BVHNode::Leaf { shape_index, .. } => Some(shape_index),
@@ -209,6 +343,19 @@ impl BVHNode {
         }
     }
 
+    /// Returns the range of the entries of the node in the `shape_indices` of the `BVH`
+    /// if it is a leaf, or `None` if it is an interior node.
+    pub fn shape_range(&self) -> Option<Range<usize>> {
+        match *self {
+            BVHNode::Leaf {
+                shape_index,
+                shape_count,
+                ..
+            } => Some(shape_index..shape_index + shape_count),
+            _ => None,
+        }
+    }
+
     /// The build function sometimes needs to add nodes while their data is not available yet.
     /// A dummy cerated by this function serves the purpose of being changed later on.
     fn create_dummy() -> BVHNode {
@@ -216,11 +363,13 @@ impl BVHNode {
             parent_index: 0,
             depth: 0,
             shape_index: 0,
+            shape_count: 0,
         }
     }
 
     /// Builds a [`BVHNode`] recursively using SAH partitioning.
     /// Returns the index of the new node in the nodes vector.
+    /// The shape indices of every new leaf are appended to `shape_indices`.
     ///
     /// [`BVHNode`]: enum.BVHNode.html
     ///
@@ -228,6 +377,57 @@ impl BVHNode {
         shapes: &mut [T],
         indices: &[usize],
         nodes: &mut Vec<BVHNode>,
+        shape_indices: &mut Vec<usize>,
+        parent_index: usize,
+        depth: u32,
+    ) -> usize {
+        let aabbs: Vec<AABB> = shapes.iter().map(Bounded::aabb).collect();
+        BVHNode::build_with_config(
+            &BuildConfig::default(),
+            shapes,
+            &aabbs,
+            indices,
+            nodes,
+            shape_indices,
+            &mut |_| {},
+            parent_index,
+            depth,
+        )
+    }
+
+    /// Builds a [`BVHNode`] recursively using SAH partitioning with the settings
+    /// from `config`. Returns the index of the new node in the nodes vector.
+    ///
+    /// The split candidates are the borders of `config.bucket_count` equally sized
+    /// buckets along the largest axis of the bounds of the shape centroids, not of the
+    /// shapes' full extents. A few huge shapes therefore do not squeeze all other shapes
+    /// into a single bucket. If all centroids coincide on that axis, the shapes are
+    /// split in half instead. The median strategies of [`BuildConfig::split_strategy`]
+    /// replace the SAH buckets. SAH weights every shape by its
+    /// [`BHShape::intersection_cost`], so expensive shapes end up in smaller children. If
+    /// no bucket border has a finite cost, the node is split at the median centroid.
+    ///
+    /// `aabbs[i]` is the [`AABB`] of `shapes[i]`. The shapes themselves are only told
+    /// the index of their leaf, so `Bounded::aabb` is never called.
+    ///
+    /// The shape indices of every new leaf are appended to `shape_indices`, and `progress`
+    /// is called with the new length of `shape_indices` after every leaf. Leaves store the
+    /// index of their first entry in `shape_indices`, so `shapes` keeps its order.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BHShape::intersection_cost`]: ../bounding_hierarchy/trait.BHShape.html#method.intersection_cost
+    /// [`BVHNode`]: enum.BVHNode.html
+    /// [`BuildConfig::split_strategy`]: struct.BuildConfig.html#structfield.split_strategy
+    ///
+    #[allow(clippy::too_many_arguments)]
+    fn build_with_config<T: BHShape>(
+        config: &BuildConfig,
+        shapes: &mut [T],
+        aabbs: &[AABB],
+        indices: &[usize],
+        nodes: &mut Vec<BVHNode>,
+        shape_indices: &mut Vec<usize>,
+        progress: &mut dyn FnMut(usize),
         parent_index: usize,
         depth: u32,
     ) -> usize {
@@ -244,21 +444,25 @@ impl BVHNode {
 
         let mut convex_hull = Default::default();
         for index in indices {
-            convex_hull = grow_convex_hull(convex_hull, &shapes[*index].aabb());
+            convex_hull = grow_convex_hull(convex_hull, &aabbs[*index]);
         }
         let (aabb_bounds, centroid_bounds) = convex_hull;
 
-        // If there is only one element left, don't split anymore
-        if indices.len() == 1 {
-            let shape_index = indices[0];
+        // If there are few enough elements left, don't split anymore
+        if !indices.is_empty() && indices.len() <= config.max_leaf_size {
             let node_index = nodes.len();
             nodes.push(BVHNode::Leaf {
                 parent_index,
                 depth,
-                shape_index,
+                shape_index: shape_indices.len(),
+                shape_count: indices.len(),
             });
-            // Let the shape know the index of the node that represents it.
-            shapes[shape_index].set_bh_node_index(node_index);
+            // Let the shapes know the index of the node that represents them.
+            for index in indices {
+                shapes[*index].set_bh_node_index(node_index);
+            }
+            shape_indices.extend_from_slice(indices);
+            progress(shape_indices.len());
             return node_index;
         }
 
@@ -270,34 +474,72 @@ impl BVHNode {
         // Find the axis along which the shapes are spread the most.
         let split_axis = centroid_bounds.largest_axis();
         let split_axis_size = centroid_bounds.max[split_axis] - centroid_bounds.min[split_axis];
+        let mut split_position = centroid_bounds.center()[split_axis];
 
         // The following `if` partitions `indices` for recursively calling `BVH::build`.
         let (child_l_index, child_l_aabb, child_r_index, child_r_aabb) = if split_axis_size
-            < EPSILON
+            < config.epsilon
+            || config.split_strategy != SplitStrategy::Sah
         {
-            // In this branch the shapes lie too close together so that splitting them in a
-            // sensible way is not possible. Instead we just split the list of shapes in half.
-            let (child_l_indices, child_r_indices) = indices.split_at(indices.len() / 2);
-            let child_l_aabb = joint_aabb_of_shapes(child_l_indices, shapes);
-            let child_r_aabb = joint_aabb_of_shapes(child_r_indices, shapes);
+            // If the shapes lie too close together, splitting them in a sensible way is not
+            // possible. Instead we just split the list of shapes in half. The median strategies
+            // partition the shapes without evaluating any costs.
+            let mut ordered_indices = indices.to_vec();
+            let split = if split_axis_size < config.epsilon {
+                indices.len() / 2
+            } else {
+                config.split_strategy.partition(
+                    &mut ordered_indices,
+                    aabbs,
+                    split_axis,
+                    &centroid_bounds,
+                )
+            };
+            let (child_l_indices, child_r_indices) = ordered_indices.split_at(split);
+            let child_l_aabb = joint_aabb_of_shapes(child_l_indices, aabbs);
+            let child_r_aabb = joint_aabb_of_shapes(child_r_indices, aabbs);
+            if split_axis_size >= config.epsilon {
+                // The median strategies put all centroids below this one into child_l.
+                split_position = child_r_indices
+                    .iter()
+                    .map(|index| aabbs[*index].center()[split_axis])
+                    .fold(f32::INFINITY, f32::min);
+            }
 
             // Proceed recursively.
-            let child_l_index =
-                BVHNode::build(shapes, child_l_indices, nodes, node_index, depth + 1);
-            let child_r_index =
-                BVHNode::build(shapes, child_r_indices, nodes, node_index, depth + 1);
+            let child_l_index = BVHNode::build_with_config(
+                config,
+                shapes,
+                aabbs,
+                child_l_indices,
+                nodes,
+                shape_indices,
+                progress,
+                node_index,
+                depth + 1,
+            );
+            let child_r_index = BVHNode::build_with_config(
+                config,
+                shapes,
+                aabbs,
+                child_r_indices,
+                nodes,
+                shape_indices,
+                progress,
+                node_index,
+                depth + 1,
+            );
             (child_l_index, child_l_aabb, child_r_index, child_r_aabb)
         } else {
-            // Create six `Bucket`s, and six index assignment vector.
-            const NUM_BUCKETS: usize = 6;
-            let mut buckets = [Bucket::empty(); NUM_BUCKETS];
-            let mut bucket_assignments: [Vec<usize>; NUM_BUCKETS] = Default::default();
+            // Create `config.bucket_count` `Bucket`s, and as many index assignment vectors.
+            let bucket_count = config.bucket_count;
+            let mut buckets = vec![Bucket::empty(); bucket_count];
+            let mut bucket_assignments: Vec<Vec<usize>> = vec![Vec::new(); bucket_count];
 
             // In this branch the `split_axis_size` is large enough to perform meaningful splits.
             // We start by assigning the shapes to `Bucket`s.
             for idx in indices {
-                let shape = &shapes[*idx];
-                let shape_aabb = shape.aabb();
+                let shape_aabb = aabbs[*idx];
                 let shape_center = shape_aabb.center();
 
                 // Get the relative position of the shape centroid `[0.0..1.0]`.
@@ -305,10 +547,11 @@ impl BVHNode {
                     (shape_center[split_axis] - centroid_bounds.min[split_axis]) / split_axis_size;
 
                 // Convert that to the actual `Bucket` number.
-                let bucket_num = (bucket_num_relative * (NUM_BUCKETS as f32 - 0.01)) as usize;
+                let bucket_num = (bucket_num_relative * (bucket_count as f32 - 0.01)) as usize;
 
                 // Extend the selected `Bucket` and add the index to the actual bucket.
-                buckets[bucket_num].add_aabb(&shape_aabb);
+                buckets[bucket_num]
+                    .add_aabb_with_cost(&shape_aabb, shapes[*idx].intersection_cost());
                 bucket_assignments[bucket_num].push(*idx);
             }
 
@@ -317,13 +560,13 @@ impl BVHNode {
             let mut min_cost = f32::INFINITY;
             let mut child_l_aabb = AABB::empty();
             let mut child_r_aabb = AABB::empty();
-            for i in 0..(NUM_BUCKETS - 1) {
+            for i in 0..(bucket_count - 1) {
                 let (l_buckets, r_buckets) = buckets.split_at(i + 1);
                 let child_l = l_buckets.iter().fold(Bucket::empty(), Bucket::join_bucket);
                 let child_r = r_buckets.iter().fold(Bucket::empty(), Bucket::join_bucket);
 
-                let cost = (child_l.size as f32 * child_l.aabb.surface_area()
-                    + child_r.size as f32 * child_r.aabb.surface_area())
+                let cost = (child_l.cost * child_l.aabb.surface_area()
+                    + child_r.cost * child_r.aabb.surface_area())
                     / aabb_bounds.surface_area();
                 if cost < min_cost {
                     min_bucket = i;
@@ -335,14 +578,57 @@ impl BVHNode {
 
             // Join together all index buckets.
             let (l_assignments, r_assignments) = bucket_assignments.split_at_mut(min_bucket + 1);
-            let child_l_indices = concatenate_vectors(l_assignments);
-            let child_r_indices = concatenate_vectors(r_assignments);
+            let (child_l_indices, child_r_indices) = if min_cost.is_finite() {
+                // The border between `min_bucket` and the next bucket, see `bucket_num` above.
+                split_position = centroid_bounds.min[split_axis]
+                    + split_axis_size * (min_bucket + 1) as f32 / (bucket_count as f32 - 0.01);
+                (
+                    concatenate_vectors(l_assignments),
+                    concatenate_vectors(r_assignments),
+                )
+            } else {
+                // No split has a finite cost, because some shapes report an infinite or `NaN`
+                // `intersection_cost`. Split at the median centroid, which ignores the costs.
+                let mut child_l_indices = indices.to_vec();
+                let split = SplitStrategy::ObjectMedian.partition(
+                    &mut child_l_indices,
+                    aabbs,
+                    split_axis,
+                    &centroid_bounds,
+                );
+                let child_r_indices = child_l_indices.split_off(split);
+                child_l_aabb = joint_aabb_of_shapes(&child_l_indices, aabbs);
+                child_r_aabb = joint_aabb_of_shapes(&child_r_indices, aabbs);
+                split_position = child_r_indices
+                    .iter()
+                    .map(|index| aabbs[*index].center()[split_axis])
+                    .fold(f32::INFINITY, f32::min);
+                (child_l_indices, child_r_indices)
+            };
 
             // Proceed recursively.
-            let child_l_index =
-                BVHNode::build(shapes, &child_l_indices, nodes, node_index, depth + 1);
-            let child_r_index =
-                BVHNode::build(shapes, &child_r_indices, nodes, node_index, depth + 1);
+            let child_l_index = BVHNode::build_with_config(
+                config,
+                shapes,
+                aabbs,
+                &child_l_indices,
+                nodes,
+                shape_indices,
+                progress,
+                node_index,
+                depth + 1,
+            );
+            let child_r_index = BVHNode::build_with_config(
+                config,
+                shapes,
+                aabbs,
+                &child_r_indices,
+                nodes,
+                shape_indices,
+                progress,
+                node_index,
+                depth + 1,
+            );
             (child_l_index, child_l_aabb, child_r_index, child_r_aabb)
         };
 
@@ -356,22 +642,27 @@ impl BVHNode {
             child_l_index,
             child_r_aabb,
             child_r_index,
+            split_axis,
+            split_position,
         };
 
         node_index
     }
 
     /// Traverses the [`BVH`] recursively and returns all shapes whose [`AABB`] is
-    /// intersected by the given [`Ray`].
+    /// intersected by the given [`Ray`]. `nodes` and `shape_indices` are the fields of
+    /// the [`BVH`].
     ///
     /// [`AABB`]: ../aabb/struct.AABB.html
     /// [`BVH`]: struct.BVH.html
     /// [`Ray`]: ../ray/struct.Ray.html
     ///
-    pub fn traverse_recursive(
+    pub fn traverse_recursive<Shape: Bounded>(
         nodes: &[BVHNode],
+        shape_indices: &[usize],
         node_index: usize,
         ray: &Ray,
+        shapes: &[Shape],
         indices: &mut Vec<usize>,
     ) {
         match nodes[node_index] {
@@ -383,384 +674,3573 @@ impl BVHNode {
                 ..
             } => {
                 if ray.intersects_aabb(child_l_aabb) {
-                    BVHNode::traverse_recursive(nodes, child_l_index, ray, indices);
+                    BVHNode::traverse_recursive(
+                        nodes,
+                        shape_indices,
+                        child_l_index,
+                        ray,
+                        shapes,
+                        indices,
+                    );
                 }
                 if ray.intersects_aabb(child_r_aabb) {
-                    BVHNode::traverse_recursive(nodes, child_r_index, ray, indices);
+                    BVHNode::traverse_recursive(
+                        nodes,
+                        shape_indices,
+                        child_r_index,
+                        ray,
+                        shapes,
+                        indices,
+                    );
                 }
             }
-            BVHNode::Leaf { shape_index, .. } => {
-                indices.push(shape_index);
+            BVHNode::Leaf {
+                shape_index,
+                shape_count: 1,
+                ..
+            } => {
+                // The parent already tested the `AABB` of this shape.
+                indices.push(shape_indices[shape_index]);
+            }
+            BVHNode::Leaf {
+                shape_index,
+                shape_count,
+                ..
+            } => {
+                let leaf_shapes = &shape_indices[shape_index..shape_index + shape_count];
+                indices.extend(
+                    leaf_shapes
+                        .iter()
+                        .filter(|index| ray.intersects_aabb(&shapes[**index].aabb())),
+                );
             }
         }
     }
-}
 
-/// The [`BVH`] data structure. Contains the list of [`BVHNode`]s.
-///
-/// [`BVH`]: struct.BVH.html
-///
-pub struct BVH {
-    /// The list of nodes of the [`BVH`].
+    /// Traverses the [`BVH`] like [`traverse_recursive`], but iteratively with an explicit
+    /// stack of node indices, so it never recurses. The shape indices are pushed in the same
+    /// order. The stack lives in a fixed-size array which is enough for any tree up to a
+    /// depth of [`TRAVERSAL_STACK_SIZE`]; deeper, degenerate trees spill into a `Vec`.
     ///
     /// [`BVH`]: struct.BVH.html
+    /// [`TRAVERSAL_STACK_SIZE`]: constant.TRAVERSAL_STACK_SIZE.html
+    /// [`traverse_recursive`]: enum.BVHNode.html#method.traverse_recursive
     ///
-    pub nodes: Vec<BVHNode>,
-}
+    pub fn traverse_iterative<Shape: Bounded>(
+        nodes: &[BVHNode],
+        shape_indices: &[usize],
+        ray: &Ray,
+        shapes: &[Shape],
+        indices: &mut Vec<usize>,
+    ) {
+        BVHNode::traverse_iterative_with(nodes, shape_indices, ray, shapes, &mut |index| {
+            indices.push(index)
+        });
+    }
 
-impl BVH {
-    /// Creates a new [`BVH`] from the `shapes` slice.
+    /// Implementation of [`traverse_iterative`], which passes the index of every hit shape
+    /// to `hit` instead of pushing it.
     ///
-    /// [`BVH`]: struct.BVH.html
+    /// [`traverse_iterative`]: enum.BVHNode.html#method.traverse_iterative
     ///
-    pub fn build<Shape: BHShape>(shapes: &mut [Shape]) -> BVH {
-        let indices = (0..shapes.len()).collect::<Vec<usize>>();
-        let expected_node_count = shapes.len() * 2;
-        let mut nodes = Vec::with_capacity(expected_node_count);
-        BVHNode::build(shapes, &indices, &mut nodes, 0, 0);
-        BVH { nodes }
+    fn traverse_iterative_with<Shape: Bounded, F: FnMut(usize)>(
+        nodes: &[BVHNode],
+        shape_indices: &[usize],
+        ray: &Ray,
+        shapes: &[Shape],
+        hit: &mut F,
+    ) {
+        let mut stack = TraversalStack::new();
+        let mut node_index = 0;
+        loop {
+            match nodes[node_index] {
+                BVHNode::Node {
+                    ref child_l_aabb,
+                    child_l_index,
+                    ref child_r_aabb,
+                    child_r_index,
+                    ..
+                } => {
+                    let hit_l = ray.intersects_aabb(child_l_aabb);
+                    let hit_r = ray.intersects_aabb(child_r_aabb);
+                    if hit_l && hit_r {
+                        // Visit the left subtree first, like `traverse_recursive`.
+                        stack.push(child_r_index);
+                        node_index = child_l_index;
+                        continue;
+                    } else if hit_l {
+                        node_index = child_l_index;
+                        continue;
+                    } else if hit_r {
+                        node_index = child_r_index;
+                        continue;
+                    }
+                }
+                BVHNode::Leaf {
+                    shape_index,
+                    shape_count,
+                    ..
+                } => BVHNode::traverse_leaf(
+                    &shape_indices[shape_index..shape_index + shape_count],
+                    ray,
+                    shapes,
+                    hit,
+                ),
+            }
+
+            node_index = match stack.pop() {
+                Some(index) => index,
+                None => break,
+            };
+        }
     }
 
-    /// Traverses the [`BVH`].
-    /// Returns a subset of `shapes`, in which the [`AABB`]s of the elements were hit by `ray`.
+    /// Traverses the [`BVH`] like [`traverse_iterative`], but tests the children of two inner
+    /// nodes at once with [`Ray::intersects_aabb_x4`]. An inner node is paired with the next
+    /// inner node on the stack, whose children are pushed below its own, so the shape indices
+    /// are pushed in the same order as by [`traverse_iterative`].
     ///
     /// [`BVH`]: struct.BVH.html
-    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`Ray::intersects_aabb_x4`]: ../ray/struct.Ray.html#method.intersects_aabb_x4
+    /// [`traverse_iterative`]: enum.BVHNode.html#method.traverse_iterative
     ///
-    pub fn traverse<'a, Shape: Bounded>(&'a self, ray: &Ray, shapes: &'a [Shape]) -> Vec<&Shape> {
-        let mut indices = Vec::new();
-        BVHNode::traverse_recursive(&self.nodes, 0, ray, &mut indices);
-        indices
-            .iter()
-            .map(|index| &shapes[*index])
-            .collect::<Vec<_>>()
+    #[cfg(feature = "simd")]
+    pub fn traverse_iterative_x4<Shape: Bounded>(
+        nodes: &[BVHNode],
+        shape_indices: &[usize],
+        ray: &Ray,
+        shapes: &[Shape],
+        indices: &mut Vec<usize>,
+    ) {
+        BVHNode::traverse_iterative_x4_with(nodes, shape_indices, ray, shapes, &mut |index| {
+            indices.push(index)
+        });
     }
 
-    /// Prints the [`BVH`] in a tree-like visualization.
+    /// Implementation of [`traverse_iterative_x4`], which passes the index of every hit
+    /// shape to `hit` instead of pushing it.
     ///
-    /// [`BVH`]: struct.BVH.html
+    /// [`traverse_iterative_x4`]: enum.BVHNode.html#method.traverse_iterative_x4
     ///
-    pub fn pretty_print(&self) {
-        let nodes = &self.nodes;
-        fn print_node(nodes: &[BVHNode], node_index: usize) {
+    #[cfg(feature = "simd")]
+    fn traverse_iterative_x4_with<Shape: Bounded, F: FnMut(usize)>(
+        nodes: &[BVHNode],
+        shape_indices: &[usize],
+        ray: &Ray,
+        shapes: &[Shape],
+        hit: &mut F,
+    ) {
+        let mut stack = TraversalStack::new();
+        stack.push(0);
+        while let Some(node_index) = stack.pop() {
             match nodes[node_index] {
                 BVHNode::Node {
+                    ref child_l_aabb,
                     child_l_index,
+                    ref child_r_aabb,
                     child_r_index,
-                    depth,
-                    child_l_aabb,
-                    child_r_aabb,
                     ..
                 } => {
-                    let padding: String = repeat(" ").take(depth as usize).collect();
-                    println!("{}child_l {}", padding, child_l_aabb);
-                    print_node(nodes, child_l_index);
-                    println!("{}child_r {}", padding, child_r_aabb);
-                    print_node(nodes, child_r_index);
+                    if let Some(BVHNode::Node {
+                        child_l_aabb: next_l_aabb,
+                        child_l_index: next_l_index,
+                        child_r_aabb: next_r_aabb,
+                        child_r_index: next_r_index,
+                        ..
+                    }) = stack.peek().map(|index| nodes[index])
+                    {
+                        stack.pop();
+                        let hits = ray.intersects_aabb_x4([
+                            child_l_aabb,
+                            child_r_aabb,
+                            &next_l_aabb,
+                            &next_r_aabb,
+                        ]);
+                        let children = [child_l_index, child_r_index, next_l_index, next_r_index];
+                        for (hit, child_index) in hits.iter().zip(children.iter()).rev() {
+                            if *hit {
+                                stack.push(*child_index);
+                            }
+                        }
+                    } else {
+                        if ray.intersects_aabb(child_r_aabb) {
+                            stack.push(child_r_index);
+                        }
+                        if ray.intersects_aabb(child_l_aabb) {
+                            stack.push(child_l_index);
+                        }
+                    }
                 }
                 BVHNode::Leaf {
-                    shape_index, depth, ..
-                } => {
-                    let padding: String = repeat(" ").take(depth as usize).collect();
-                    println!("{}shape\t{:?}", padding, shape_index);
-                }
+                    shape_index,
+                    shape_count,
+                    ..
+                } => BVHNode::traverse_leaf(
+                    &shape_indices[shape_index..shape_index + shape_count],
+                    ray,
+                    shapes,
+                    hit,
+                ),
             }
         }
-        print_node(nodes, 0);
     }
 
-    /// Verifies that the node at index `node_index` lies inside `expected_outer_aabb`,
-    /// its parent index is equal to `expected_parent_index`, its depth is equal to
-    /// `expected_depth`. Increares `node_count` by the number of visited nodes.
-    fn is_consistent_subtree<Shape: BHShape>(
-        &self,
-        node_index: usize,
-        expected_parent_index: usize,
-        expected_outer_aabb: &AABB,
-        expected_depth: u32,
-        node_count: &mut usize,
+    /// Passes the indices of the shapes of a leaf, `leaf_shapes`, which are hit by the
+    /// `ray` to `hit`. A leaf with a single shape is passed without a test, as its parent
+    /// already tested the [`AABB`] of the shape.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    fn traverse_leaf<Shape: Bounded, F: FnMut(usize)>(
+        leaf_shapes: &[usize],
+        ray: &Ray,
         shapes: &[Shape],
-    ) -> bool {
-        *node_count += 1;
-        match self.nodes[node_index] {
-            BVHNode::Node {
-                parent_index,
-                depth,
-                child_l_index,
-                child_l_aabb,
-                child_r_index,
-                child_r_aabb,
-            } => {
-                let correct_parent_index = expected_parent_index == parent_index;
-                let correct_depth = expected_depth == depth;
-                let left_aabb_in_parent =
-                    expected_outer_aabb.approx_contains_aabb_eps(&child_l_aabb, EPSILON);
-                let right_aabb_in_parent =
-                    expected_outer_aabb.approx_contains_aabb_eps(&child_r_aabb, EPSILON);
-                let left_subtree_consistent = self.is_consistent_subtree(
-                    child_l_index,
-                    node_index,
-                    &child_l_aabb,
-                    expected_depth + 1,
-                    node_count,
-                    shapes,
-                );
-                let right_subtree_consistent = self.is_consistent_subtree(
-                    child_r_index,
-                    node_index,
-                    &child_r_aabb,
-                    expected_depth + 1,
-                    node_count,
-                    shapes,
-                );
-
-                correct_parent_index
-                    && correct_depth
-                    && left_aabb_in_parent
-                    && right_aabb_in_parent
-                    && left_subtree_consistent
-                    && right_subtree_consistent
-            }
-            BVHNode::Leaf {
-                parent_index,
-                depth,
-                shape_index,
-            } => {
-                let correct_parent_index = expected_parent_index == parent_index;
-                let correct_depth = expected_depth == depth;
-                let shape_aabb = shapes[shape_index].aabb();
-                let shape_aabb_in_parent =
-                    expected_outer_aabb.approx_contains_aabb_eps(&shape_aabb, EPSILON);
-
-                correct_parent_index && correct_depth && shape_aabb_in_parent
-            }
+        hit: &mut F,
+    ) {
+        if let [index] = *leaf_shapes {
+            hit(index);
+        } else {
+            leaf_shapes
+                .iter()
+                .copied()
+                .filter(|index| ray.intersects_aabb(&shapes[*index].aabb()))
+                .for_each(hit);
         }
     }
+}
 
-    /// Checks if all children of a node have the correct parent index, and that there is no
-    /// detached subtree. Also checks if the `AABB` hierarchy is consistent.
-    pub fn is_consistent<Shape: BHShape>(&self, shapes: &[Shape]) -> bool {
-        // The root node of the bvh is not bounded by anything.
-        let space = AABB {
-            min: Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
-            max: Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
-        };
+/// Settings which control how a [`BVH`] is built by [`BVH::build_with_config`].
+///
+/// The SAH builder sorts the shapes of every node into `bucket_count` equally sized
+/// buckets along the largest axis and only considers splits between two buckets.
+/// Fewer buckets make the build faster, but the chosen splits are coarser and the
+/// resulting tree is more expensive to traverse. More buckets find better splits at
+/// the price of a slower build. The returns diminish quickly; the default of 6 buckets
+/// is a good tradeoff for most scenes.
+///
+/// The recursion stops as soon as a node holds at most `max_leaf_size` shapes. Larger
+/// leaves make the tree smaller and save node visits, which pays off for cheap shapes
+/// such as triangles, while every shape in a hit leaf has to be tested individually.
+/// The build never reorders the `shapes` slice. The leaves reference their shapes through
+/// [`BVH::shape_indices`], and [`BVH::reorder_shapes`] moves the shapes into leaf order
+/// for callers who want the shapes of every leaf next to each other in memory.
+///
+/// # Examples
+/// ```
+/// use bvh_ultraviolet::bvh::BuildConfig;
+///
+/// let config = BuildConfig {
+///     bucket_count: 12,
+///     ..Default::default()
+/// };
+/// assert_eq!(config.bucket_count, 12);
+/// ```
+///
+/// [`BVH`]: struct.BVH.html
+/// [`BVH::build_with_config`]: struct.BVH.html#method.build_with_config
+/// [`BVH::reorder_shapes`]: struct.BVH.html#method.reorder_shapes
+/// [`BVH::shape_indices`]: struct.BVH.html#structfield.shape_indices
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BuildConfig {
+    /// The number of SAH buckets used to evaluate split candidates. Must be at least 2.
+    pub bucket_count: usize,
 
-        // The counter for all nodes.
-        let mut node_count = 0;
-        let subtree_consistent =
-            self.is_consistent_subtree(0, 0, &space, 0, &mut node_count, shapes);
+    /// The maximum number of shapes per leaf. Must be at least 1.
+    pub max_leaf_size: usize,
 
-        // Check if all nodes have been counted from the root node.
-        // If this is false, it means we have a detached subtree.
-        let is_connected = node_count == self.nodes.len();
-        subtree_consistent && is_connected
+    /// Whether nodes may also be split by a plane, like in a split bounding volume
+    /// hierarchy (SBVH). Shapes which straddle the plane are then referenced by both
+    /// children, each bounded by the part of its [`AABB`] on that side. This is only tried
+    /// where the children of the best partition of the shapes overlap, and pays off for
+    /// long, thin shapes with large, overlapping [`AABB`]s. Off by default.
+    ///
+    /// The clipped references cost memory: spatial splits may at most double the number
+    /// of shape references, so the [`BVH`] holds up to twice as many leaves and nodes as
+    /// without them. A shape referenced by several leaves can be returned several times
+    /// by a traversal, see [`traverse_dedup`], and knows the index of only one of its
+    /// leaves, so such a [`BVH`] must not be passed to [`optimize`], [`refit`] or
+    /// [`validate`]. The spatial split builder weights all shapes equally and ignores
+    /// [`BHShape::intersection_cost`].
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BHShape::intersection_cost`]: ../bounding_hierarchy/trait.BHShape.html#method.intersection_cost
+    /// [`BVH`]: struct.BVH.html
+    /// [`optimize`]: struct.BVH.html#method.optimize
+    /// [`refit`]: struct.BVH.html#method.refit
+    /// [`traverse_dedup`]: struct.BVH.html#method.traverse_dedup
+    /// [`validate`]: struct.BVH.html#method.validate
+    ///
+    pub spatial_splits: bool,
+
+    /// The way the shapes of every node are partitioned between its two children.
+    /// Defaults to [`SplitStrategy::Sah`]. The spatial split builder and the [`BVH2`] and
+    /// [`BVH64`] builders always use SAH and ignore this setting.
+    ///
+    /// [`BVH2`]: ../bvh2d/struct.BVH2.html
+    /// [`BVH64`]: ../f64/struct.BVH64.html
+    /// [`SplitStrategy::Sah`]: enum.SplitStrategy.html#variant.Sah
+    ///
+    pub split_strategy: SplitStrategy,
+
+    /// The extent below which the centroids of the shapes count as coincident along the
+    /// split axis, so that the shapes are split in half instead of being binned. Defaults
+    /// to [`EPSILON`], which suits scenes with coordinates around `1.0`. Scenes at much
+    /// larger or smaller scales should scale it accordingly.
+    ///
+    /// [`EPSILON`]: ../constant.EPSILON.html
+    ///
+    pub epsilon: f32,
+}
+
+impl Default for BuildConfig {
+    fn default() -> BuildConfig {
+        BuildConfig {
+            bucket_count: 6,
+            max_leaf_size: 1,
+            spatial_splits: false,
+            split_strategy: SplitStrategy::Sah,
+            epsilon: EPSILON,
+        }
+    }
+}
+
+/// The strategy with which the [`BVH`] builder partitions the shapes of a node, see
+/// [`BuildConfig::split_strategy`]. All strategies split along the largest axis of the
+/// bounds of the shape centroids.
+///
+/// The median strategies skip the cost evaluation of SAH and build considerably faster,
+/// which suits trees that are rebuilt every frame. The resulting trees are more
+/// expensive to traverse, see [`BvhStats::sah_cost`].
+///
+/// # Examples
+/// ```
+/// use bvh_ultraviolet::bvh::{BuildConfig, SplitStrategy};
+///
+/// let config = BuildConfig {
+///     split_strategy: SplitStrategy::ObjectMedian,
+///     ..Default::default()
+/// };
+/// assert_eq!(BuildConfig::default().split_strategy, SplitStrategy::Sah);
+/// assert_ne!(config, BuildConfig::default());
+/// ```
+///
+/// [`BVH`]: struct.BVH.html
+/// [`BvhStats::sah_cost`]: struct.BvhStats.html#structfield.sah_cost
+/// [`BuildConfig::split_strategy`]: struct.BuildConfig.html#structfield.split_strategy
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum SplitStrategy {
+    /// Chooses the split with the lowest surface area heuristic among the borders of
+    /// [`BuildConfig::bucket_count`] buckets.
+    ///
+    /// [`BuildConfig::bucket_count`]: struct.BuildConfig.html#structfield.bucket_count
+    ///
+    #[default]
+    Sah,
+
+    /// Orders the shapes by their centroids and splits them into two halves of equal
+    /// size, so the tree is always balanced.
+    ObjectMedian,
+
+    /// Splits the bounds of the centroids at their midpoint. Falls back to
+    /// [`ObjectMedian`] if all centroids end up on the same side.
+    ///
+    /// [`ObjectMedian`]: enum.SplitStrategy.html#variant.ObjectMedian
+    ///
+    SpatialMedian,
+}
+
+impl SplitStrategy {
+    /// Reorders `indices` so that the shapes of the left child come first and returns
+    /// their number. Only used for the median strategies.
+    fn partition(
+        self,
+        indices: &mut [usize],
+        aabbs: &[AABB],
+        axis: Axis,
+        centroid_bounds: &AABB,
+    ) -> usize {
+        let centroid = |index: &usize| aabbs[*index].center()[axis];
+        let median = indices.len() / 2;
+        if self == SplitStrategy::SpatialMedian {
+            let midpoint = (centroid_bounds.min[axis] + centroid_bounds.max[axis]) * 0.5;
+            let mut split = 0;
+            for i in 0..indices.len() {
+                if centroid(&indices[i]) < midpoint {
+                    indices.swap(i, split);
+                    split += 1;
+                }
+            }
+            if split > 0 && split < indices.len() {
+                return split;
+            }
+        }
+        indices.select_nth_unstable_by(median, |a, b| {
+            centroid(a)
+                .partial_cmp(&centroid(b))
+                .unwrap_or(Ordering::Equal)
+        });
+        median
+    }
+}
+
+/// The [`BVH`] data structure. Contains the list of [`BVHNode`]s.
+///
+/// A [`BVH`] is [`Sync`] and holds no interior mutability, so a `&BVH` can be shared
+/// across threads. All queries which take `&self`, such as [`traverse`], only read the
+/// nodes and may run concurrently, e.g. one thread per tile of an image. Methods which
+/// change the tree, such as [`refit`] or [`optimize`], take `&mut self`.
+///
+/// [`BVH`]: struct.BVH.html
+/// [`BVHNode`]: enum.BVHNode.html
+/// [`Sync`]: https://doc.rust-lang.org/std/marker/trait.Sync.html
+/// [`optimize`]: struct.BVH.html#method.optimize
+/// [`refit`]: struct.BVH.html#method.refit
+/// [`traverse`]: struct.BVH.html#method.traverse
+///
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BVH {
+    /// The list of nodes of the [`BVH`].
+    ///
+    /// [`BVH`]: struct.BVH.html
+    ///
+    pub nodes: Vec<BVHNode>,
+
+    /// The indices into `shapes` of the shapes referenced by the leaves. Every leaf owns
+    /// the entries `shape_index..shape_index + shape_count`, so the shapes of a leaf can be
+    /// looked up without reordering the `shapes` the [`BVH`] was built from. A shape which
+    /// is referenced by several leaves, see [`BuildConfig::spatial_splits`], has an entry
+    /// for each of them.
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`BuildConfig::spatial_splits`]: struct.BuildConfig.html#structfield.spatial_splits
+    ///
+    pub shape_indices: Vec<usize>,
+}
+
+// Fails to compile if a `Cell` or another non-`Sync` type is ever added to the `BVH`.
+const _: fn() = || {
+    fn assert_sync<T: Sync>() {}
+    assert_sync::<BVH>();
+};
+
+impl BVH {
+    /// Creates a new [`BVH`] from the `shapes` slice. Shapes with an empty or non-finite
+    /// [`AABB`] are skipped, see [`build_with_config`]. Use [`try_build`] to reject such
+    /// input instead.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`build_with_config`]: struct.BVH.html#method.build_with_config
+    /// [`try_build`]: struct.BVH.html#method.try_build
+    ///
+    pub fn build<Shape: BHShape>(shapes: &mut [Shape]) -> BVH {
+        BVH::build_with_config(shapes, &BuildConfig::default())
+    }
+
+    /// Creates a new [`BVH`] from the `shapes` slice like [`build`], and reports the
+    /// approximate completion of the build to `progress`, for example to drive a progress
+    /// bar while building a huge scene.
+    ///
+    /// `progress` is called on the building thread with the fraction of the shapes which
+    /// have been placed in leaves so far, in steps of about one percent, and with exactly
+    /// `1.0` once the [`BVH`] is complete. It is called at least once, even for no shapes.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..1000 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    ///
+    /// let mut last_progress = 0.0;
+    /// let bvh = BVH::build_with_progress(&mut spheres, |fraction| last_progress = fraction);
+    /// assert_eq!(last_progress, 1.0);
+    /// assert_eq!(bvh.nodes.len(), 1999);
+    /// ```
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`build`]: struct.BVH.html#method.build
+    ///
+    pub fn build_with_progress<Shape: BHShape, F: FnMut(f32)>(
+        shapes: &mut [Shape],
+        mut progress: F,
+    ) -> BVH {
+        let aabbs: Vec<AABB> = shapes.iter().map(Bounded::aabb).collect();
+        BVH::build_from_aabbs(shapes, &aabbs, &BuildConfig::default(), &mut progress)
+    }
+
+    /// Creates a new [`BVH`] from the `shapes` slice like [`build`], but returns a
+    /// [`BvhError`] for malformed input instead of building a [`BVH`] from what is left.
+    /// Use it for shapes from untrusted sources, such as user-supplied meshes.
+    ///
+    /// Returns [`BvhError::EmptyInput`] if `shapes` is empty,
+    /// [`BvhError::TooManyShapes`] if there are more than [`MAX_SHAPES`] shapes, and for
+    /// the first shape which [`build`] would silently skip either
+    /// [`BvhError::EmptyBounds`] if its [`AABB`] is empty, or [`BvhError::NonFiniteBounds`]
+    /// if its [`AABB`] has a `NaN` or infinite component.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::{BvhError, BVH};
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// assert!(BVH::try_build(&mut spheres).is_ok());
+    ///
+    /// spheres[4].radius = f32::NAN;
+    /// assert_eq!(
+    ///     BVH::try_build(&mut spheres).err(),
+    ///     Some(BvhError::NonFiniteBounds { shape_index: 4 })
+    /// );
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`BvhError`]: enum.BvhError.html
+    /// [`BvhError::EmptyBounds`]: enum.BvhError.html#variant.EmptyBounds
+    /// [`BvhError::EmptyInput`]: enum.BvhError.html#variant.EmptyInput
+    /// [`BvhError::NonFiniteBounds`]: enum.BvhError.html#variant.NonFiniteBounds
+    /// [`BvhError::TooManyShapes`]: enum.BvhError.html#variant.TooManyShapes
+    /// [`MAX_SHAPES`]: constant.MAX_SHAPES.html
+    /// [`build`]: struct.BVH.html#method.build
+    ///
+    pub fn try_build<Shape: BHShape>(shapes: &mut [Shape]) -> Result<BVH, BvhError> {
+        BVH::check_shape_count(shapes.len())?;
+        let aabbs: Vec<AABB> = shapes.iter().map(Bounded::aabb).collect();
+        if let Some(shape_index) = aabbs.iter().position(|aabb| !has_valid_aabb(aabb)) {
+            // An empty `AABB` such as `AABB::empty()` is not finite either.
+            return Err(if aabbs[shape_index].is_empty() {
+                BvhError::EmptyBounds { shape_index }
+            } else {
+                BvhError::NonFiniteBounds { shape_index }
+            });
+        }
+        Ok(BVH::build_from_aabbs(
+            shapes,
+            &aabbs,
+            &BuildConfig::default(),
+            &mut |_| {},
+        ))
+    }
+
+    /// Checks whether [`try_build`] accepts `shape_count` shapes.
+    ///
+    /// [`try_build`]: struct.BVH.html#method.try_build
+    ///
+    fn check_shape_count(shape_count: usize) -> Result<(), BvhError> {
+        if shape_count == 0 {
+            Err(BvhError::EmptyInput)
+        } else if shape_count > MAX_SHAPES {
+            Err(BvhError::TooManyShapes { shape_count })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Creates a new [`BVH`] from the `shapes` slice using the settings from `config`.
+    /// See [`BuildConfig`] for the tradeoffs of each setting.
+    ///
+    /// Shapes whose [`AABB`] is empty or has a `NaN` or infinite component (see
+    /// [`AABB::is_finite`]) are skipped. They are not referenced by any leaf, so they are
+    /// never returned by a traversal, and their node index is left unchanged.
+    ///
+    /// With `config.spatial_splits` some shapes may be referenced by several leaves, see
+    /// [`BuildConfig::spatial_splits`].
+    ///
+    /// # Panics
+    /// Panics if `config.bucket_count` is less than 2 or `config.max_leaf_size` is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::{BuildConfig, SplitStrategy, BVH};
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let config = BuildConfig {
+    ///     bucket_count: 16,
+    ///     max_leaf_size: 4,
+    ///     spatial_splits: false,
+    ///     split_strategy: SplitStrategy::Sah,
+    ///     epsilon: bvh_ultraviolet::EPSILON,
+    /// };
+    /// let bvh = BVH::build_with_config(&mut spheres, &config);
+    ///
+    /// let ray = Ray::new(Vec3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(bvh.traverse(&ray, &spheres).len(), 10);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`AABB::is_finite`]: ../aabb/struct.AABB.html#method.is_finite
+    /// [`BVH`]: struct.BVH.html
+    /// [`BuildConfig`]: struct.BuildConfig.html
+    /// [`BuildConfig::spatial_splits`]: struct.BuildConfig.html#structfield.spatial_splits
+    ///
+    pub fn build_with_config<Shape: BHShape>(shapes: &mut [Shape], config: &BuildConfig) -> BVH {
+        assert!(
+            config.bucket_count >= 2,
+            "BuildConfig::bucket_count must be at least 2"
+        );
+        assert!(
+            config.max_leaf_size >= 1,
+            "BuildConfig::max_leaf_size must be at least 1"
+        );
+        let aabbs: Vec<AABB> = shapes.iter().map(Bounded::aabb).collect();
+        BVH::build_from_aabbs(shapes, &aabbs, config, &mut |_| {})
+    }
+
+    /// Creates a new [`BVH`] from the `shapes` slice and their precomputed [`AABB`]s,
+    /// without ever calling [`Bounded::aabb`] on the shapes. This pays off for shapes whose
+    /// [`AABB`] is expensive to compute, because the builder needs it many times per shape.
+    ///
+    /// `aabbs[i]` has to be the [`AABB`] of `shapes[i]`, so both slices must have the same
+    /// length. The default [`BuildConfig`] is used and shapes with an invalid [`AABB`] are
+    /// skipped, just like in [`build`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::{AABB, Bounded};
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let aabbs: Vec<AABB> = spheres.iter().map(|sphere| sphere.aabb()).collect();
+    /// let bvh = BVH::build_with_aabbs(&mut spheres, &aabbs);
+    ///
+    /// let ray = Ray::new(Vec3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(bvh.traverse(&ray, &spheres).len(), 10);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`Bounded::aabb`]: ../aabb/trait.Bounded.html#tymethod.aabb
+    /// [`BuildConfig`]: struct.BuildConfig.html
+    /// [`build`]: struct.BVH.html#method.build
+    ///
+    pub fn build_with_aabbs<Shape: BHShape>(shapes: &mut [Shape], aabbs: &[AABB]) -> BVH {
+        debug_assert_eq!(
+            shapes.len(),
+            aabbs.len(),
+            "BVH::build_with_aabbs requires one AABB per shape"
+        );
+        BVH::build_from_aabbs(shapes, aabbs, &BuildConfig::default(), &mut |_| {})
+    }
+
+    /// Rebuilds this [`BVH`] from the `shapes` slice in place. The result is the same as
+    /// the one of [`build`], but the node vector is cleared and refilled instead of
+    /// allocated anew, so its capacity is reused.
+    ///
+    /// This is meant for dynamic scenes which are rebuilt every frame. As long as the
+    /// number of shapes stays roughly constant, the nodes need no new allocation after
+    /// the first frame. Scenes whose shapes only move a little may get away with the
+    /// cheaper [`refit`] instead.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let mut bvh = BVH::build(&mut spheres);
+    ///
+    /// for frame in 0..10 {
+    ///     for sphere in &mut spheres {
+    ///         sphere.position.y = frame as f32;
+    ///     }
+    ///     bvh.rebuild(&mut spheres);
+    ///
+    ///     let ray = Ray::new(Vec3::new(-10.0, frame as f32, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    ///     assert_eq!(bvh.traverse(&ray, &spheres).len(), 10);
+    /// }
+    /// ```
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`build`]: struct.BVH.html#method.build
+    /// [`refit`]: struct.BVH.html#method.refit
+    ///
+    pub fn rebuild<Shape: BHShape>(&mut self, shapes: &mut [Shape]) {
+        let aabbs: Vec<AABB> = shapes.iter().map(Bounded::aabb).collect();
+        BVH::build_nodes(
+            shapes,
+            &aabbs,
+            &BuildConfig::default(),
+            &mut |_| {},
+            &mut self.nodes,
+            &mut self.shape_indices,
+        );
+    }
+
+    /// Shared part of [`build_with_config`] and [`build_with_aabbs`], which builds the
+    /// [`BVH`] from the [`AABB`]s of the shapes only.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`build_with_aabbs`]: struct.BVH.html#method.build_with_aabbs
+    /// [`build_with_config`]: struct.BVH.html#method.build_with_config
+    ///
+    fn build_from_aabbs<Shape: BHShape>(
+        shapes: &mut [Shape],
+        aabbs: &[AABB],
+        config: &BuildConfig,
+        progress: &mut dyn FnMut(f32),
+    ) -> BVH {
+        let mut nodes = Vec::new();
+        let mut shape_indices = Vec::new();
+        BVH::build_nodes(
+            shapes,
+            aabbs,
+            config,
+            progress,
+            &mut nodes,
+            &mut shape_indices,
+        );
+        BVH {
+            nodes,
+            shape_indices,
+        }
+    }
+
+    /// Builds the nodes and shape indices of a [`BVH`] into `nodes` and `shape_indices`,
+    /// which are cleared first and keep their capacity. Used by [`build_from_aabbs`] and
+    /// [`rebuild`].
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`build_from_aabbs`]: struct.BVH.html#method.build_from_aabbs
+    /// [`rebuild`]: struct.BVH.html#method.rebuild
+    ///
+    fn build_nodes<Shape: BHShape>(
+        shapes: &mut [Shape],
+        aabbs: &[AABB],
+        config: &BuildConfig,
+        progress: &mut dyn FnMut(f32),
+        nodes: &mut Vec<BVHNode>,
+        shape_indices: &mut Vec<usize>,
+    ) {
+        let indices: Vec<usize> = (0..shapes.len())
+            .filter(|index| has_valid_aabb(&aabbs[*index]))
+            .collect();
+        let expected_node_count = shapes.len() * 2;
+        nodes.clear();
+        nodes.reserve(expected_node_count);
+        shape_indices.clear();
+        shape_indices.reserve(indices.len());
+        if !indices.is_empty() && config.spatial_splits {
+            build_with_spatial_splits(config, shapes, aabbs, &indices, nodes, shape_indices);
+        } else if !indices.is_empty() {
+            // Report in steps of about one percent, so that huge scenes do not flood the
+            // callback. The completion is reported once all nodes are built.
+            let shape_count = indices.len();
+            let mut reported = 0.0;
+            let mut leaf_progress = |placed: usize| {
+                let fraction = placed as f32 / shape_count as f32;
+                if placed < shape_count && fraction - reported >= 0.01 {
+                    reported = fraction;
+                    progress(fraction);
+                }
+            };
+            BVHNode::build_with_config(
+                config,
+                shapes,
+                aabbs,
+                &indices,
+                nodes,
+                shape_indices,
+                &mut leaf_progress,
+                0,
+                0,
+            );
+        }
+        progress(1.0);
+    }
+
+    /// Permutes `shapes` in place, so that they are stored in the order in which the
+    /// leaves reference them, see [`leaf_shape_indices`]. The [`shape_indices`] of the
+    /// leaves are updated accordingly and every shape is told the index of its leaf again.
+    /// Shapes which are not referenced by any leaf are moved to the end. A shape which
+    /// several leaves reference, see [`BuildConfig::spatial_splits`], is moved to the
+    /// position of its first reference.
+    ///
+    /// Afterwards a traversal touches the shapes in roughly increasing memory order,
+    /// which improves the cache behavior of testing the shapes themselves. The builders
+    /// leave the shapes in their original order, and so does [`optimize`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for &x in &[30.0, 0.0, 20.0, 10.0] {
+    ///     let position = Vec3::new(x, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let mut bvh = BVH::build(&mut spheres);
+    /// bvh.reorder_shapes(&mut spheres);
+    ///
+    /// let xs: Vec<f32> = spheres.iter().map(|sphere| sphere.position.x).collect();
+    /// assert_eq!(xs, vec![0.0, 10.0, 20.0, 30.0]);
+    /// assert!(bvh.leaf_shape_indices().eq(0..4));
+    /// ```
+    ///
+    /// [`leaf_shape_indices`]: struct.BVH.html#method.leaf_shape_indices
+    /// [`optimize`]: struct.BVH.html#method.optimize
+    /// [`shape_indices`]: struct.BVH.html#structfield.shape_indices
+    /// [`BuildConfig::spatial_splits`]: struct.BuildConfig.html#structfield.spatial_splits
+    ///
+    pub fn reorder_shapes<Shape: BHShape>(&mut self, shapes: &mut [Shape]) {
+        // Shapes shared by several leaves keep the position of their first reference.
+        let mut referenced = vec![false; shapes.len()];
+        let mut leaf_order: Vec<usize> = self
+            .leaf_shape_indices()
+            .filter(|index| !mem::replace(&mut referenced[*index], true))
+            .collect();
+        leaf_order.extend((0..shapes.len()).filter(|index| !referenced[*index]));
+        apply_leaf_order(&mut self.shape_indices, shapes, &leaf_order);
+
+        for (node_index, node) in self.nodes.iter().enumerate() {
+            if let Some(range) = node.shape_range() {
+                for shape_index in &self.shape_indices[range] {
+                    shapes[*shape_index].set_bh_node_index(node_index);
+                }
+            }
+        }
+    }
+
+    /// Returns the nodes of the [`BVH`]. The root node is at index `0`, unless the [`BVH`]
+    /// is empty. Together with the accessors of [`BVHNode`] this allows for custom traversal
+    /// and visualization code.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::{BVHNode, BVH};
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..8 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let leaf_count = bvh
+    ///     .nodes()
+    ///     .iter()
+    ///     .filter(|node| node.shape_index().is_some())
+    ///     .count();
+    /// assert_eq!(leaf_count, 8);
+    /// assert_eq!(bvh.nodes().len(), 15);
+    /// assert_eq!(bvh.depth(), 3);
+    /// ```
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`BVHNode`]: enum.BVHNode.html
+    ///
+    pub fn nodes(&self) -> &[BVHNode] {
+        &self.nodes
+    }
+
+    /// Returns the indices into `shapes` of the shapes of the leaf at `node_index`, which
+    /// are stored in [`shape_indices`]. Returns an empty slice for an inner node.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..8 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// assert_eq!(bvh.leaf_shapes(spheres[3].node_index), &[3]);
+    /// assert!(bvh.leaf_shapes(0).is_empty());
+    /// ```
+    ///
+    /// [`shape_indices`]: struct.BVH.html#structfield.shape_indices
+    ///
+    pub fn leaf_shapes(&self, node_index: usize) -> &[usize] {
+        match self.nodes[node_index].shape_range() {
+            Some(range) => &self.shape_indices[range],
+            None => &[],
+        }
+    }
+
+    /// Returns true if the [`BVH`] references no shapes, which is checked in constant time.
+    ///
+    /// A [`BVH`] built from no shapes, or only from shapes whose [`AABB`]s are empty or not
+    /// finite, has no nodes at all. All traversals and queries of such a [`BVH`] return
+    /// empty results.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres: Vec<Sphere> = Vec::new();
+    /// let bvh = BVH::build(&mut spheres);
+    /// assert!(bvh.is_empty());
+    ///
+    /// let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert!(bvh.traverse(&ray, &spheres).is_empty());
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the number of shapes referenced by the leaves of the [`BVH`], which takes
+    /// a pass over all nodes. Shapes whose [`AABB`]s are empty or not finite are skipped
+    /// by the builders and not counted. A shape which spatial splits reference from
+    /// several leaves, see [`BuildConfig::spatial_splits`], is counted once per leaf.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// // A sphere with a `NaN` position is skipped.
+    /// let position = Vec3::new(f32::NAN, 0.0, 0.0);
+    /// spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    ///
+    /// let bvh = BVH::build(&mut spheres);
+    /// assert_eq!(bvh.len(), 10);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`BuildConfig::spatial_splits`]: struct.BuildConfig.html#structfield.spatial_splits
+    ///
+    pub fn len(&self) -> usize {
+        self.nodes
+            .iter()
+            .map(|node| match *node {
+                BVHNode::Node { .. } => 0,
+                BVHNode::Leaf { shape_count, .. } => shape_count,
+            })
+            .sum()
+    }
+
+    /// Returns the depth of the [`BVH`], i.e. the depth of its deepest leaf. The root node
+    /// has depth `0`, which is also returned for an empty [`BVH`].
+    ///
+    /// [`BVH`]: struct.BVH.html
+    ///
+    pub fn depth(&self) -> usize {
+        self.nodes
+            .iter()
+            .map(|node| node.depth() as usize)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the [`AABB`] of the whole [`BVH`] in constant time, by joining the [`AABB`]s
+    /// of the two children of the root, instead of joining the [`AABB`]s of all shapes.
+    ///
+    /// The result is valid right after [`build`], [`refit`] and [`tighten`]. If shapes
+    /// were moved without refitting afterwards, it describes their old positions.
+    ///
+    /// The root stores no [`AABB`] for itself. If it is a leaf, i.e. if the [`BVH`] has at
+    /// most [`BuildConfig::max_leaf_size`] shapes, or if the [`BVH`] is empty, `None` is
+    /// returned. The bounds are then those of the shapes, see [`AABB::enclosing`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let bounds = bvh.bounds().unwrap();
+    /// assert_eq!(bounds.min, Vec3::new(-1.0, -1.0, -1.0));
+    /// assert_eq!(bounds.max, Vec3::new(91.0, 1.0, 1.0));
+    ///
+    /// // The root of a single sphere is a leaf.
+    /// let bvh = BVH::build(&mut spheres[..1]);
+    /// assert_eq!(bvh.bounds(), None);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`AABB::enclosing`]: ../aabb/struct.AABB.html#method.enclosing
+    /// [`BVH`]: struct.BVH.html
+    /// [`BuildConfig::max_leaf_size`]: struct.BuildConfig.html#structfield.max_leaf_size
+    /// [`build`]: struct.BVH.html#method.build
+    /// [`refit`]: struct.BVH.html#method.refit
+    /// [`tighten`]: struct.BVH.html#method.tighten
+    ///
+    pub fn bounds(&self) -> Option<AABB> {
+        match self.nodes.first() {
+            Some(BVHNode::Node {
+                child_l_aabb,
+                child_r_aabb,
+                ..
+            }) => Some(child_l_aabb.join(child_r_aabb)),
+            _ => None,
+        }
+    }
+
+    /// Returns the indices of the nodes on the path from the root down to the leaf which
+    /// holds the shape at `shape_index` in `shapes`, both included. The path is found by
+    /// walking up the parent indices from the leaf, so comparing the [`AABB`]s along it
+    /// with a [`Ray`] shows where a traversal missed the shape.
+    ///
+    /// Returns an empty `Vec` if no leaf holds the shape. If spatial splits reference the
+    /// shape from several leaves, the path to the first of them in `nodes` is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..8 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let path = bvh.path_to_shape(5);
+    /// assert_eq!(path.len(), 4);
+    /// assert_eq!(path[0], 0);
+    /// assert_eq!(path[3], spheres[5].node_index);
+    /// assert!(bvh.path_to_shape(8).is_empty());
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`Ray`]: ../ray/struct.Ray.html
+    ///
+    pub fn path_to_shape(&self, shape_index: usize) -> Vec<usize> {
+        let leaf_index = (0..self.nodes.len())
+            .find(|node_index| self.leaf_shapes(*node_index).contains(&shape_index));
+        let mut path = Vec::new();
+        if let Some(mut node_index) = leaf_index {
+            path.push(node_index);
+            while node_index != 0 {
+                node_index = self.nodes[node_index].parent();
+                path.push(node_index);
+            }
+            path.reverse();
+        }
+        path
+    }
+
+    /// Traverses the [`BVH`].
+    /// Returns a subset of `shapes`, in which the [`AABB`]s of the elements were hit by `ray`.
+    ///
+    /// The traversal never recurses, see [`BVHNode::traverse_iterative`], so it is safe
+    /// to use on arbitrarily deep trees.
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVHNode::traverse_iterative`]: enum.BVHNode.html#method.traverse_iterative
+    ///
+    pub fn traverse<'a, Shape: Bounded>(&'a self, ray: &Ray, shapes: &'a [Shape]) -> Vec<&Shape> {
+        let mut hits = Vec::new();
+        self.traverse_into(ray, shapes, &mut hits);
+        hits
+    }
+
+    /// Traverses the [`BVH`] like [`traverse`], but clears `out` and pushes the hit shapes
+    /// into it instead of allocating a new `Vec`. Reusing one buffer for many rays saves
+    /// an allocation per ray.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let mut hits = Vec::new();
+    /// for i in 0..10 {
+    ///     let origin = Vec3::new(i as f32 * 10.0, 0.0, -10.0);
+    ///     bvh.traverse_into(&Ray::new(origin, Vec3::new(0.0, 0.0, 1.0)), &spheres, &mut hits);
+    ///     assert_eq!(hits.len(), 1);
+    ///     assert_eq!(hits[0].position.x, i as f32 * 10.0);
+    /// }
+    /// ```
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`traverse`]: struct.BVH.html#method.traverse
+    ///
+    pub fn traverse_into<'a, Shape: Bounded>(
+        &'a self,
+        ray: &Ray,
+        shapes: &'a [Shape],
+        out: &mut Vec<&'a Shape>,
+    ) {
+        out.clear();
+        self.traverse_hits(ray, shapes, |index| out.push(&shapes[index]));
+    }
+
+    /// Traverses the [`BVH`] like [`traverse`], but returns every hit shape at most once,
+    /// in the order of its first hit. A [`BVH`] built with [`BuildConfig::spatial_splits`]
+    /// can reference a shape from several leaves, which [`traverse`] reports once for
+    /// every leaf that is hit, so this saves the caller from intersecting a shape twice.
+    ///
+    /// The hit shape indices are collected in a `HashSet`, which costs an allocation and a
+    /// hash lookup per hit. For a [`BVH`] without spatial splits every shape lies in exactly
+    /// one leaf, so [`traverse`] already returns no duplicates and is cheaper.
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`BuildConfig::spatial_splits`]: struct.BuildConfig.html#structfield.spatial_splits
+    /// [`traverse`]: struct.BVH.html#method.traverse
+    ///
+    pub fn traverse_dedup<'a, Shape: Bounded>(
+        &'a self,
+        ray: &Ray,
+        shapes: &'a [Shape],
+    ) -> Vec<&'a Shape> {
+        let mut visited = HashSet::new();
+        let mut hits = Vec::new();
+        self.traverse_hits(ray, shapes, |index| {
+            if visited.insert(index) {
+                hits.push(&shapes[index]);
+            }
+        });
+        hits
+    }
+
+    /// Passes the indices of the shapes whose [`AABB`]s are hit by `ray` to `hit` in
+    /// traversal order.
+    /// Uses [`BVHNode::traverse_iterative_x4`] if the `simd` feature is enabled and
+    /// [`BVHNode::traverse_iterative`] otherwise.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVHNode::traverse_iterative`]: enum.BVHNode.html#method.traverse_iterative
+    /// [`BVHNode::traverse_iterative_x4`]: enum.BVHNode.html#method.traverse_iterative_x4
+    ///
+    fn traverse_hits<Shape: Bounded, F: FnMut(usize)>(
+        &self,
+        ray: &Ray,
+        shapes: &[Shape],
+        mut hit: F,
+    ) {
+        match self.nodes.first() {
+            None => {}
+            // No parent has tested the `AABB` of a root leaf, so all its shapes are tested.
+            Some(&BVHNode::Leaf { .. }) => self
+                .leaf_shapes(0)
+                .iter()
+                .copied()
+                .filter(|index| ray.intersects_aabb(&shapes[*index].aabb()))
+                .for_each(hit),
+            Some(&BVHNode::Node { .. }) => {
+                #[cfg(feature = "simd")]
+                BVHNode::traverse_iterative_x4_with(
+                    &self.nodes,
+                    &self.shape_indices,
+                    ray,
+                    shapes,
+                    &mut hit,
+                );
+                #[cfg(not(feature = "simd"))]
+                BVHNode::traverse_iterative_with(
+                    &self.nodes,
+                    &self.shape_indices,
+                    ray,
+                    shapes,
+                    &mut hit,
+                );
+            }
+        }
+    }
+
+    /// Traverses the [`BVH`].
+    /// Returns the indices into `shapes` of the elements whose [`AABB`]s were hit by `ray`,
+    /// in the same order in which [`traverse`] returns the shapes.
+    /// `shapes` must be the slice the [`BVH`] was built from; in debug builds the returned
+    /// indices are checked against it.
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`traverse`]: struct.BVH.html#method.traverse
+    ///
+    pub fn traverse_indices<Shape: Bounded>(&self, ray: &Ray, shapes: &[Shape]) -> Vec<usize> {
+        let mut indices = Vec::new();
+        self.traverse_hits(ray, shapes, |index| indices.push(index));
+        debug_assert!(indices.iter().all(|index| *index < shapes.len()));
+        indices
+    }
+
+    /// Traverses the [`BVH`] like [`traverse`], but returns mutable references to the shapes
+    /// whose [`AABB`]s were hit by `ray`. The shapes are returned in the order of their index
+    /// in `shapes`.
+    ///
+    /// In a well-formed [`BVH`] every shape is referenced by exactly one leaf, so each hit
+    /// shape is returned exactly once. Should several leaves reference the same shape, it is
+    /// still only returned once.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`traverse`]: struct.BVH.html#method.traverse
+    ///
+    pub fn traverse_mut<'a, Shape: BHShape>(
+        &self,
+        ray: &Ray,
+        shapes: &'a mut [Shape],
+    ) -> Vec<&'a mut Shape> {
+        let mut indices = Vec::new();
+        self.traverse_hits(ray, shapes, |index| indices.push(index));
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut hit_indices = indices.into_iter().peekable();
+        shapes
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, shape)| {
+                if hit_indices.peek() == Some(&index) {
+                    hit_indices.next();
+                    Some(shape)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Traverses the [`BVH`] front-to-back and returns the index and hit of the closest
+    /// shape actually hit by `ray`, or `None` if no shape is hit.
+    ///
+    /// `intersect` computes the exact intersection of the `ray` with a shape and returns
+    /// a hit, or `None` on a miss. The hit can be the plain distance along the `ray` or any
+    /// [`HitDistance`], such as an [`Intersection`] with barycentric coordinates, so the
+    /// surface data of the closest shape does not have to be recomputed. `intersect` is
+    /// only called for shapes whose [`AABB`]s are hit and not entered behind the closest
+    /// hit found so far. In leaves with several shapes and in a root leaf, the [`AABB`] of
+    /// every shape is tested on its own before `intersect` is called.
+    ///
+    /// Of two children, the near one is descended into first and the far one is pushed
+    /// onto a stack. The near child is chosen by the stored [`split_axis`] of the node and
+    /// the sign of the `ray` direction along it: the left child, which holds the smaller
+    /// centroids, if the `ray` points along the axis, and the right child otherwise.
+    /// Subtrees whose [`AABB`] is entered behind the closest hit found so far are skipped,
+    /// so early hits prune most of the far subtrees. The stack is kept explicitly instead
+    /// of on the call stack, so arbitrarily deep trees can be traversed.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// // Intersects the ray with the actual sphere.
+    /// let intersect = |ray: &Ray, sphere: &Sphere| {
+    ///     let to_center = sphere.position - ray.origin;
+    ///     let projection = to_center.dot(ray.direction);
+    ///     let distance_sq = to_center.mag_sq() - projection * projection;
+    ///     let radius_sq = sphere.radius * sphere.radius;
+    ///     if distance_sq > radius_sq {
+    ///         return None;
+    ///     }
+    ///     let t = projection - (radius_sq - distance_sq).sqrt();
+    ///     if t >= 0.0 { Some(t) } else { None }
+    /// };
+    ///
+    /// let ray = Ray::new(Vec3::new(25.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(bvh.traverse_nearest(&ray, &spheres, intersect), Some((3, 4.0)));
+    ///
+    /// let ray_miss = Ray::new(Vec3::new(25.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    /// assert_eq!(bvh.traverse_nearest(&ray_miss, &spheres, intersect), None);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`HitDistance`]: ../ray/trait.HitDistance.html
+    /// [`Intersection`]: ../ray/struct.Intersection.html
+    /// [`split_axis`]: enum.BVHNode.html#method.split_axis
+    ///
+    pub fn traverse_nearest<Shape, Hit, F>(
+        &self,
+        ray: &Ray,
+        shapes: &[Shape],
+        intersect: F,
+    ) -> Option<(usize, Hit)>
+    where
+        Shape: Bounded,
+        Hit: HitDistance,
+        F: Fn(&Ray, &Shape) -> Option<Hit>,
+    {
+        let mut nearest: Option<(usize, Hit)> = None;
+        if self.nodes.is_empty() {
+            return nearest;
+        }
+        // Whether a subtree entered at `entry_distance` lies behind the closest hit so far.
+        let is_behind = |entry_distance: f32, nearest: &Option<(usize, Hit)>| match *nearest {
+            Some((_, ref best)) => entry_distance > best.t(),
+            None => false,
+        };
+
+        // The near child is descended into right away, the far child is postponed on the
+        // stack. The stack is used instead of recursion, so deep trees cannot overflow.
+        let sign = ray.sign();
+        let mut stack = TraversalStack::new();
+        let mut node_index = 0;
+        loop {
+            match self.nodes[node_index] {
+                BVHNode::Node {
+                    ref child_l_aabb,
+                    child_l_index,
+                    ref child_r_aabb,
+                    child_r_index,
+                    split_axis,
+                    ..
+                } => {
+                    let distance_l = child_l_aabb
+                        .intersect_ray_distance(ray)
+                        .filter(|distance| !is_behind(*distance, &nearest));
+                    let distance_r = child_r_aabb
+                        .intersect_ray_distance(ray)
+                        .filter(|distance| !is_behind(*distance, &nearest));
+                    match (distance_l, distance_r) {
+                        (Some(_), Some(_)) => {
+                            let (near_index, far_index) = if sign[split_axis as usize] == 0 {
+                                (child_l_index, child_r_index)
+                            } else {
+                                (child_r_index, child_l_index)
+                            };
+                            stack.push(far_index);
+                            node_index = near_index;
+                            continue;
+                        }
+                        (Some(_), None) => {
+                            node_index = child_l_index;
+                            continue;
+                        }
+                        (None, Some(_)) => {
+                            node_index = child_r_index;
+                            continue;
+                        }
+                        (None, None) => {}
+                    }
+                }
+                BVHNode::Leaf { .. } => {
+                    // The parent already tested the `AABB` of a single shape, unless the leaf
+                    // is the root.
+                    let leaf_shapes = self.leaf_shapes(node_index);
+                    let test_aabbs = leaf_shapes.len() > 1 || node_index == 0;
+                    for &index in leaf_shapes {
+                        if test_aabbs {
+                            match shapes[index].aabb().intersect_ray_distance(ray) {
+                                Some(entry_distance) if !is_behind(entry_distance, &nearest) => {}
+                                _ => continue,
+                            }
+                        }
+                        if let Some(hit) = intersect(ray, &shapes[index]) {
+                            let is_closer = match nearest {
+                                Some((_, ref best)) => hit.t() < best.t(),
+                                None => true,
+                            };
+                            if is_closer {
+                                nearest = Some((index, hit));
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Resume with the most recently postponed subtree which is not entered behind
+            // the closest hit found since it was pushed. Its `AABB` is stored in its parent.
+            node_index = loop {
+                match stack.pop() {
+                    Some(far_index) => {
+                        let entry_distance = self.child_aabb(far_index).intersect_ray_distance(ray);
+                        if let Some(entry_distance) = entry_distance {
+                            if !is_behind(entry_distance, &nearest) {
+                                break far_index;
+                            }
+                        }
+                    }
+                    None => return nearest,
+                }
+            };
+        }
+    }
+
+    /// Prints the [`BVH`] in a tree-like visualization, see [`pretty_string`].
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`pretty_string`]: struct.BVH.html#method.pretty_string
+    ///
+    pub fn pretty_print(&self) {
+        print!("{}", self.pretty_string());
+    }
+
+    /// Renders the [`BVH`] as an indented tree with one line per node. Inner nodes show
+    /// their [`AABB`] and its surface area, leaves show the indices of their shapes.
+    /// The lines are connected with box-drawing characters to show the depth of each node.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..2 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let lines: Vec<String> = bvh.pretty_string().lines().map(String::from).collect();
+    /// assert_eq!(
+    ///     lines,
+    ///     vec![
+    ///         "node 0: min [-1, -1, -1], max [11, 1, 1], surface area 104",
+    ///         "├── leaf 1: shape 0",
+    ///         "└── leaf 2: shape 1",
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    ///
+    pub fn pretty_string(&self) -> String {
+        fn format_vec3(v: Vec3) -> String {
+            format!("[{}, {}, {}]", v.x, v.y, v.z)
+        }
+
+        fn format_node(
+            bvh: &BVH,
+            node_index: usize,
+            prefix: &str,
+            child_prefix: &str,
+            output: &mut String,
+        ) {
+            let node = &bvh.nodes[node_index];
+            let line = match *node {
+                BVHNode::Node {
+                    child_l_aabb,
+                    child_r_aabb,
+                    ..
+                } => {
+                    let aabb = child_l_aabb.join(&child_r_aabb);
+                    format!(
+                        "node {}: min {}, max {}, surface area {}",
+                        node_index,
+                        format_vec3(aabb.min),
+                        format_vec3(aabb.max),
+                        aabb.surface_area()
+                    )
+                }
+                BVHNode::Leaf { .. } => match *bvh.leaf_shapes(node_index) {
+                    [shape_index] => format!("leaf {}: shape {}", node_index, shape_index),
+                    ref leaf_shapes => format!("leaf {}: shapes {:?}", node_index, leaf_shapes),
+                },
+            };
+            output.push_str(prefix);
+            output.push_str(&line);
+            output.push('\n');
+
+            if let BVHNode::Node {
+                child_l_index,
+                child_r_index,
+                ..
+            } = *node
+            {
+                format_node(
+                    bvh,
+                    child_l_index,
+                    &format!("{}├── ", child_prefix),
+                    &format!("{}│   ", child_prefix),
+                    output,
+                );
+                format_node(
+                    bvh,
+                    child_r_index,
+                    &format!("{}└── ", child_prefix),
+                    &format!("{}    ", child_prefix),
+                    output,
+                );
+            }
+        }
+
+        let mut output = String::new();
+        if !self.nodes.is_empty() {
+            format_node(self, 0, "", "", &mut output);
+        }
+        output
+    }
+
+    /// Exports the [`BVH`] as a directed graph in the Graphviz DOT language, which can be
+    /// rendered with `dot -Tsvg`. Every node of the [`BVH`] becomes one graph node, labeled
+    /// with the surface area of its [`AABB`] for inner nodes or the indices of its shapes
+    /// for leaves, and every inner node has an edge to each of its two children.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..2 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let dot = bvh.to_dot();
+    /// assert!(dot.starts_with("digraph bvh {"));
+    /// assert!(dot.contains("n0 -> n1;"));
+    /// assert!(dot.contains("n0 -> n2;"));
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    ///
+    pub fn to_dot(&self) -> String {
+        let mut output = String::from("digraph bvh {\n");
+        for (node_index, node) in self.nodes.iter().enumerate() {
+            match *node {
+                BVHNode::Node {
+                    child_l_index,
+                    ref child_l_aabb,
+                    child_r_index,
+                    ref child_r_aabb,
+                    ..
+                } => {
+                    let surface_area = child_l_aabb.join(child_r_aabb).surface_area();
+                    output.push_str(&format!(
+                        "    n{} [shape=box, label=\"node {}\\nsurface area {}\"];\n",
+                        node_index, node_index, surface_area
+                    ));
+                    output.push_str(&format!("    n{} -> n{};\n", node_index, child_l_index));
+                    output.push_str(&format!("    n{} -> n{};\n", node_index, child_r_index));
+                }
+                BVHNode::Leaf { .. } => {
+                    let shapes = match *self.leaf_shapes(node_index) {
+                        [shape_index] => format!("shape {}", shape_index),
+                        ref leaf_shapes => format!("shapes {:?}", leaf_shapes),
+                    };
+                    output.push_str(&format!(
+                        "    n{} [shape=ellipse, label=\"leaf {}\\n{}\"];\n",
+                        node_index, node_index, shapes
+                    ));
+                }
+            }
+        }
+        output.push_str("}\n");
+        output
+    }
+
+    /// Verifies that the node at index `node_index` lies inside `expected_outer_aabb`,
+    /// its parent index is equal to `expected_parent_index`, its depth is equal to
+    /// `expected_depth`. Increares `node_count` by the number of visited nodes.
+    fn is_consistent_subtree<Shape: BHShape>(
+        &self,
+        node_index: usize,
+        expected_parent_index: usize,
+        expected_outer_aabb: &AABB,
+        expected_depth: u32,
+        node_count: &mut usize,
+        shapes: &[Shape],
+    ) -> bool {
+        *node_count += 1;
+        match self.nodes[node_index] {
+            BVHNode::Node {
+                parent_index,
+                depth,
+                child_l_index,
+                child_l_aabb,
+                child_r_index,
+                child_r_aabb,
+                ..
+            } => {
+                let correct_parent_index = expected_parent_index == parent_index;
+                let correct_depth = expected_depth == depth;
+                let left_aabb_in_parent =
+                    expected_outer_aabb.approx_contains_aabb_eps(&child_l_aabb, EPSILON);
+                let right_aabb_in_parent =
+                    expected_outer_aabb.approx_contains_aabb_eps(&child_r_aabb, EPSILON);
+                let left_subtree_consistent = self.is_consistent_subtree(
+                    child_l_index,
+                    node_index,
+                    &child_l_aabb,
+                    expected_depth + 1,
+                    node_count,
+                    shapes,
+                );
+                let right_subtree_consistent = self.is_consistent_subtree(
+                    child_r_index,
+                    node_index,
+                    &child_r_aabb,
+                    expected_depth + 1,
+                    node_count,
+                    shapes,
+                );
+
+                correct_parent_index
+                    && correct_depth
+                    && left_aabb_in_parent
+                    && right_aabb_in_parent
+                    && left_subtree_consistent
+                    && right_subtree_consistent
+            }
+            BVHNode::Leaf {
+                parent_index,
+                depth,
+                ..
+            } => {
+                let correct_parent_index = expected_parent_index == parent_index;
+                let correct_depth = expected_depth == depth;
+                let shape_aabbs_in_parent = self.leaf_shapes(node_index).iter().all(|index| {
+                    expected_outer_aabb.approx_contains_aabb_eps(&shapes[*index].aabb(), EPSILON)
+                });
+
+                correct_parent_index && correct_depth && shape_aabbs_in_parent
+            }
+        }
+    }
+
+    /// Checks if all children of a node have the correct parent index, and that there is no
+    /// detached subtree. Also checks if the `AABB` hierarchy is consistent.
+    pub fn is_consistent<Shape: BHShape>(&self, shapes: &[Shape]) -> bool {
+        // A `BVH` without nodes has nothing to check.
+        if self.nodes.is_empty() {
+            return true;
+        }
+
+        // The root node of the bvh is not bounded by anything.
+        let space = AABB {
+            min: Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            max: Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        };
+
+        // The counter for all nodes.
+        let mut node_count = 0;
+        let subtree_consistent =
+            self.is_consistent_subtree(0, 0, &space, 0, &mut node_count, shapes);
+
+        // Check if all nodes have been counted from the root node.
+        // If this is false, it means we have a detached subtree.
+        let is_connected = node_count == self.nodes.len();
+        subtree_consistent && is_connected
     }
 
     /// Assert version of `is_consistent_subtree`.
     fn assert_consistent_subtree<Shape: BHShape>(
         &self,
         node_index: usize,
-        expected_parent_index: usize,
-        expected_outer_aabb: &AABB,
-        expected_depth: u32,
-        node_count: &mut usize,
+        expected_parent_index: usize,
+        expected_outer_aabb: &AABB,
+        expected_depth: u32,
+        node_count: &mut usize,
+        shapes: &[Shape],
+    ) {
+        *node_count += 1;
+        let node = &self.nodes[node_index];
+
+        let parent = node.parent();
+        assert_eq!(
+            expected_parent_index, parent,
+            "Wrong parent index. Expected: {}; Actual: {}",
+            expected_parent_index, parent
+        );
+        let depth = node.depth();
+        assert_eq!(
+            expected_depth, depth,
+            "Wrong depth. Expected: {}; Actual: {}",
+            expected_depth, depth
+        );
+
+        match *node {
+            BVHNode::Node {
+                child_l_index,
+                child_l_aabb,
+                child_r_index,
+                child_r_aabb,
+                ..
+            } => {
+                assert!(
+                    expected_outer_aabb.approx_contains_aabb_eps(&child_l_aabb, EPSILON),
+                    "Left child lies outside the expected bounds.
+                         \tBounds: {}
+                         \tLeft child: {}",
+                    expected_outer_aabb,
+                    child_l_aabb
+                );
+                assert!(
+                    expected_outer_aabb.approx_contains_aabb_eps(&child_r_aabb, EPSILON),
+                    "Right child lies outside the expected bounds.
+                         \tBounds: {}
+                         \tRight child: {}",
+                    expected_outer_aabb,
+                    child_r_aabb
+                );
+                self.assert_consistent_subtree(
+                    child_l_index,
+                    node_index,
+                    &child_l_aabb,
+                    expected_depth + 1,
+                    node_count,
+                    shapes,
+                );
+                self.assert_consistent_subtree(
+                    child_r_index,
+                    node_index,
+                    &child_r_aabb,
+                    expected_depth + 1,
+                    node_count,
+                    shapes,
+                );
+            }
+            BVHNode::Leaf { .. } => {
+                for index in self.leaf_shapes(node_index) {
+                    let shape_aabb = shapes[*index].aabb();
+                    assert!(
+                        expected_outer_aabb.approx_contains_aabb_eps(&shape_aabb, EPSILON),
+                        "Shape's AABB lies outside the expected bounds.\n\tBounds: {}\n\tShape: {}",
+                        expected_outer_aabb,
+                        shape_aabb
+                    );
+                }
+            }
+        }
+    }
+
+    /// Assert version of `is_consistent`.
+    pub fn assert_consistent<Shape: BHShape>(&self, shapes: &[Shape]) {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        // The root node of the bvh is not bounded by anything.
+        let space = AABB {
+            min: Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            max: Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        };
+
+        // The counter for all nodes.
+        let mut node_count = 0;
+        self.assert_consistent_subtree(0, 0, &space, 0, &mut node_count, shapes);
+
+        // Check if all nodes have been counted from the root node.
+        // If this is false, it means we have a detached subtree.
+        assert_eq!(node_count, self.nodes.len(), "Detached subtree");
+    }
+
+    /// Check that the `AABB`s in the `BVH` are tight, which means, that parent `AABB`s are not
+    /// larger than they should be. This function checks, whether the children of node `node_index`
+    /// lie inside `outer_aabb`.
+    pub fn assert_tight_subtree<Shape: BHShape>(
+        &self,
+        node_index: usize,
+        outer_aabb: &AABB,
         shapes: &[Shape],
     ) {
-        *node_count += 1;
-        let node = &self.nodes[node_index];
+        self.assert_tight_subtree_eps(node_index, outer_aabb, shapes, EPSILON);
+    }
+
+    /// Version of `assert_tight_subtree` which allows the `AABB`s to differ by up to
+    /// `epsilon` instead of `EPSILON`.
+    pub fn assert_tight_subtree_eps<Shape: BHShape>(
+        &self,
+        node_index: usize,
+        outer_aabb: &AABB,
+        shapes: &[Shape],
+        epsilon: f32,
+    ) {
+        if let BVHNode::Node {
+            child_l_index,
+            child_l_aabb,
+            child_r_index,
+            child_r_aabb,
+            ..
+        } = self.nodes[node_index]
+        {
+            let joint_aabb = child_l_aabb.join(&child_r_aabb);
+            assert!(joint_aabb.relative_eq(outer_aabb, epsilon));
+            self.assert_tight_subtree_eps(child_l_index, &child_l_aabb, shapes, epsilon);
+            self.assert_tight_subtree_eps(child_r_index, &child_r_aabb, shapes, epsilon);
+        }
+    }
+
+    /// Check that the `AABB`s in the `BVH` are tight, which means, that parent `AABB`s are not
+    /// larger than they should be.
+    pub fn assert_tight<Shape: BHShape>(&self, shapes: &[Shape]) {
+        self.assert_tight_eps(shapes, EPSILON);
+    }
+
+    /// Version of `assert_tight` which allows the `AABB`s to differ by up to `epsilon`
+    /// instead of `EPSILON`. Scenes with large coordinates need a larger `epsilon`, as the
+    /// rounding errors of refitting grow with the magnitude of the bounds.
+    pub fn assert_tight_eps<Shape: BHShape>(&self, shapes: &[Shape], epsilon: f32) {
+        // When starting to check whether the `BVH` is tight, we cannot provide a minimum
+        // outer `AABB`, therefore we compute the correct one in this instance.
+        if let Some(&BVHNode::Node {
+            child_l_aabb,
+            child_r_aabb,
+            ..
+        }) = self.nodes.first()
+        {
+            let joint_aabb = child_l_aabb.join(&child_r_aabb);
+            self.assert_tight_subtree_eps(0, &joint_aabb, shapes, epsilon);
+        }
+    }
+}
+
+/// Moves the shape at `leaf_order[i]` to position `i` of `shapes` and updates the
+/// `shape_indices` of the leaves. `leaf_order` must be a permutation of the shape indices.
+fn apply_leaf_order<Shape>(
+    shape_indices: &mut [usize],
+    shapes: &mut [Shape],
+    leaf_order: &[usize],
+) {
+    let mut new_positions = vec![0; shapes.len()];
+    for (new_position, old_position) in leaf_order.iter().enumerate() {
+        new_positions[*old_position] = new_position;
+    }
+    for shape_index in shape_indices.iter_mut() {
+        *shape_index = new_positions[*shape_index];
+    }
+    for position in 0..shapes.len() {
+        while new_positions[position] != position {
+            let target = new_positions[position];
+            shapes.swap(position, target);
+            new_positions.swap(position, target);
+        }
+    }
+}
+
+impl BoundingHierarchy for BVH {
+    fn build<Shape: BHShape>(shapes: &mut [Shape]) -> BVH {
+        BVH::build(shapes)
+    }
+
+    fn traverse<'a, Shape: Bounded>(&'a self, ray: &Ray, shapes: &'a [Shape]) -> Vec<&Shape> {
+        self.traverse(ray, shapes)
+    }
+
+    fn pretty_print(&self) {
+        self.pretty_print();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bvh::{BVHNode, BVH};
+    use crate::ray::Ray;
+    use crate::testbase::{build_some_bh, traverse_some_bh, UnitBox};
+    use std::f32;
+    use ultraviolet::Vec3;
+
+    #[test]
+    /// Tests whether the building procedure succeeds in not failing.
+    fn test_build_bvh() {
+        build_some_bh::<BVH>();
+    }
+
+    #[test]
+    /// Runs some primitive tests for intersections of a ray with a fixed scene given as a BVH.
+    fn test_traverse_bvh() {
+        traverse_some_bh::<BVH>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    /// Tests whether a deserialized `BVH` traverses exactly like the original one.
+    fn test_serde_round_trip_bvh() {
+        use crate::testbase::{assert_identical_traversal, create_n_cubes, default_bounds};
+
+        let bounds = default_bounds();
+        let mut triangles = create_n_cubes(100, &bounds);
+        let bvh = BVH::build(&mut triangles);
+
+        let bytes = bincode::serialize(&bvh).expect("serializing the BVH");
+        let deserialized: BVH = bincode::deserialize(&bytes).expect("deserializing the BVH");
+
+        deserialized.assert_consistent(&triangles);
+        assert_identical_traversal(&bvh, &deserialized, &triangles, &bounds);
+    }
+
+    #[test]
+    /// Tests whether boxes outside of the `[t_min, t_max]` interval of a ray are excluded
+    /// from the traversal.
+    fn test_traverse_ray_bounds() {
+        let (shapes, bvh) = build_some_bh::<BVH>();
+        let origin = Vec3::new(-1000.0, 0.0, 0.0);
+        let direction = Vec3::new(1.0, 0.0, 0.0);
+
+        // The boxes centered at `x` are entered at `t = 999.5 + x`.
+        let short_ray = Ray::with_bounds(origin, direction, 0.0, 995.25);
+        let mut ids: Vec<i32> = bvh
+            .traverse(&short_ray, &shapes)
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, (-10..-4).collect::<Vec<i32>>());
+
+        // The boxes centered at `x` are left at `t = 1000.5 + x`.
+        let late_ray = Ray::with_bounds(origin, direction, 1004.75, f32::INFINITY);
+        let mut ids: Vec<i32> = bvh
+            .traverse(&late_ray, &shapes)
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, (5..11).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    /// Compares `traverse_nearest` to intersecting every triangle of random scenes.
+    fn test_traverse_nearest_equals_brute_force() {
+        use crate::testbase::{create_n_cubes, default_bounds, next_Vec3, Triangle};
+        use crate::EPSILON;
+
+        let intersect = |ray: &Ray, triangle: &Triangle| {
+            let distance = ray
+                .intersects_triangle(&triangle.a, &triangle.b, &triangle.c)
+                .distance;
+            if distance < f32::INFINITY {
+                Some(distance)
+            } else {
+                None
+            }
+        };
+
+        let bounds = default_bounds();
+        let mut seed = 0;
+        for &cube_count in &[1, 10, 1_000] {
+            let mut triangles = create_n_cubes(cube_count, &bounds);
+            let bvh = BVH::build(&mut triangles);
+
+            for _ in 0..100 {
+                // Aim at a random triangle, so that most rays actually hit something.
+                let origin = next_Vec3(&mut seed, &bounds);
+                let triangle = &triangles[seed as usize % triangles.len()];
+                let target = (triangle.a + triangle.b + triangle.c) / 3.0;
+                let ray = Ray::new(origin, target - origin);
+
+                let expected = triangles
+                    .iter()
+                    .filter_map(|triangle| intersect(&ray, triangle))
+                    .fold(None, |nearest: Option<f32>, distance| {
+                        Some(nearest.map_or(distance, |nearest| nearest.min(distance)))
+                    });
+                let nearest = bvh.traverse_nearest(&ray, &triangles, intersect);
+
+                // The entry distance of a flat `AABB` and the distance to the triangle in it
+                // may differ by rounding, so the order of almost equidistant hits is not exact.
+                match (nearest, expected) {
+                    (Some((_, distance)), Some(expected)) => {
+                        assert!((distance - expected).abs() <= expected * EPSILON)
+                    }
+                    (nearest, expected) => assert!(nearest.is_none() && expected.is_none()),
+                }
+                if let Some((index, distance)) = nearest {
+                    assert_eq!(intersect(&ray, &triangles[index]), Some(distance));
+                }
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether `traverse_nearest` only calls `intersect` for shapes whose `AABB` is
+    /// hit, also in leaves with several shapes and in a root leaf.
+    fn test_traverse_nearest_tests_shape_aabbs() {
+        use crate::aabb::Bounded;
+        use crate::bvh::BuildConfig;
+        use crate::testbase::{create_n_cubes, default_bounds, next_Vec3, Triangle};
+
+        let intersect = |ray: &Ray, triangle: &Triangle| {
+            assert!(ray.intersects_aabb(&triangle.aabb()));
+            let distance = ray
+                .intersects_triangle(&triangle.a, &triangle.b, &triangle.c)
+                .distance;
+            if distance < f32::INFINITY {
+                Some(distance)
+            } else {
+                None
+            }
+        };
+
+        let bounds = default_bounds();
+        let mut seed = 0;
+        // With 96 triangles and up to 100 shapes per leaf, the root is a leaf.
+        for &(cube_count, max_leaf_size) in &[(100, 4), (8, 100)] {
+            let mut triangles = create_n_cubes(cube_count, &bounds);
+            let config = BuildConfig {
+                max_leaf_size,
+                ..Default::default()
+            };
+            let bvh = BVH::build_with_config(&mut triangles, &config);
+
+            for _ in 0..100 {
+                let ray = Ray::new(next_Vec3(&mut seed, &bounds), next_Vec3(&mut seed, &bounds));
+                bvh.traverse_nearest(&ray, &triangles, intersect);
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether `traverse_nearest` visits the leaves front-to-back in both directions
+    /// along a row of boxes, so that the first hit reported to `intersect` is the nearest
+    /// one and all boxes behind it are pruned.
+    fn test_traverse_nearest_front_to_back() {
+        use crate::aabb::Bounded;
+        use std::cell::RefCell;
+
+        let (boxes, bvh) = build_some_bh::<BVH>();
+        for &direction in &[1.0, -1.0] {
+            let ray = Ray::new(
+                Vec3::new(-100.0 * direction, 0.0, 0.0),
+                Vec3::new(direction, 0.0, 0.0),
+            );
+            let visited = RefCell::new(Vec::new());
+            let nearest = bvh.traverse_nearest(&ray, &boxes, |ray, unit_box| {
+                visited.borrow_mut().push(unit_box.id);
+                unit_box.aabb().intersect_ray_distance(ray)
+            });
+
+            let nearest_id = -10 * direction as i32;
+            assert_eq!(boxes[nearest.unwrap().0].id, nearest_id);
+            assert_eq!(visited.into_inner(), vec![nearest_id]);
+        }
+    }
+
+    #[test]
+    /// Tests whether `traverse_nearest` picks the near child by the split axis and the
+    /// direction of the ray, even if the far child's `AABB` is entered first.
+    fn test_traverse_nearest_orders_children_by_split_axis() {
+        use crate::aabb::Bounded;
+        use crate::axis::Axis;
+        use crate::testbase::Triangle;
+        use std::cell::RefCell;
+
+        // The long triangle has the larger centroid, but its `AABB` starts before the
+        // one of the short triangle.
+        let mut triangles = vec![
+            Triangle::new(
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(10.0, 0.0, 0.0),
+                Vec3::new(10.0, 1.0, 0.0),
+            ),
+            Triangle::new(
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(2.0, 0.0, 0.0),
+                Vec3::new(2.0, 1.0, 0.0),
+            ),
+        ];
+        let bvh = BVH::build(&mut triangles);
+        assert_eq!(bvh.nodes[0].split_axis(), Axis::X);
+        assert_eq!(bvh.leaf_shapes(bvh.nodes[0].child_l()), &[1]);
+
+        let ray = Ray::new(Vec3::new(-10.0, 0.2, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let visited = RefCell::new(Vec::new());
+        let nearest = bvh.traverse_nearest(&ray, &triangles, |ray, triangle: &Triangle| {
+            visited.borrow_mut().push(triangle.a.x);
+            triangle.aabb().intersect_ray_distance(ray)
+        });
+        assert_eq!(nearest.map(|(index, _)| index), Some(0));
+        assert_eq!(visited.into_inner(), vec![1.0, 0.0]);
+    }
+
+    #[test]
+    /// Tests whether `traverse_nearest` handles a `BVH` which is too deep to be traversed
+    /// recursively, from both ends of the chain.
+    fn test_traverse_nearest_deep_chain() {
+        use crate::aabb::Bounded;
+        use crate::testbase::build_chain_bvh;
+
+        let n = 300_000;
+        let (boxes, bvh) = build_chain_bvh(n);
+        let intersect = |ray: &Ray, unit_box: &UnitBox| unit_box.aabb().intersect_ray_distance(ray);
+
+        let ray = Ray::new(Vec3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let nearest = bvh.traverse_nearest(&ray, &boxes, intersect);
+        assert_eq!(nearest.map(|(index, _)| boxes[index].id), Some(0));
+
+        // From this side the nearest box is the deepest leaf of the chain.
+        let ray = Ray::new(
+            Vec3::new(n as f32 + 10.0, 0.0, 0.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+        );
+        let nearest = bvh.traverse_nearest(&ray, &boxes, intersect);
+        assert_eq!(
+            nearest.map(|(index, _)| boxes[index].id),
+            Some(n as i32 - 1)
+        );
+    }
+
+    #[test]
+    /// Tests whether `traverse_nearest` returns the full hit record of a custom `Hit` type
+    /// with barycentric coordinates for the same shape as a plain distance.
+    fn test_traverse_nearest_custom_hit() {
+        use crate::ray::HitDistance;
+        use crate::testbase::{create_n_cubes, default_bounds, next_Vec3, Triangle};
+
+        #[derive(Debug, PartialEq)]
+        struct BarycentricHit {
+            distance: f32,
+            barycentrics: Vec3,
+        }
+
+        impl HitDistance for BarycentricHit {
+            fn t(&self) -> f32 {
+                self.distance
+            }
+        }
+
+        let intersect_barycentric = |ray: &Ray, triangle: &Triangle| {
+            ray.intersect_triangle(triangle.a, triangle.b, triangle.c)
+                .map(|intersection| BarycentricHit {
+                    distance: intersection.distance,
+                    barycentrics: Vec3::new(
+                        1.0 - intersection.u - intersection.v,
+                        intersection.u,
+                        intersection.v,
+                    ),
+                })
+        };
+        let intersect_distance = |ray: &Ray, triangle: &Triangle| {
+            intersect_barycentric(ray, triangle).map(|hit| hit.distance)
+        };
+
+        let bounds = default_bounds();
+        let mut seed = 0;
+        let mut triangles = create_n_cubes(100, &bounds);
+        let bvh = BVH::build(&mut triangles);
+        let mut hit_count = 0;
+        for _ in 0..100 {
+            let origin = next_Vec3(&mut seed, &bounds);
+            let triangle = &triangles[seed as usize % triangles.len()];
+            let target = (triangle.a + triangle.b + triangle.c) / 3.0;
+            let ray = Ray::new(origin, target - origin);
+
+            let nearest = bvh.traverse_nearest(&ray, &triangles, intersect_barycentric);
+            let nearest_distance = bvh.traverse_nearest(&ray, &triangles, intersect_distance);
+            assert_eq!(
+                nearest.as_ref().map(|(index, hit)| (*index, hit.distance)),
+                nearest_distance
+            );
+            if let Some((index, hit)) = nearest {
+                assert_eq!(Some(hit), intersect_barycentric(&ray, &triangles[index]));
+                hit_count += 1;
+            }
+        }
+        assert!(hit_count > 0);
+    }
+
+    #[test]
+    /// Tests whether `traverse_indices` returns the indices of the shapes `traverse` returns.
+    fn test_traverse_indices_equals_traverse() {
+        let (shapes, bvh) = build_some_bh::<BVH>();
+        let ray = Ray::new(Vec3::new(-1000.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        let indices = bvh.traverse_indices(&ray, &shapes);
+        let hits = bvh.traverse(&ray, &shapes);
+        assert_eq!(indices.len(), shapes.len());
+        for (index, hit) in indices.iter().zip(hits) {
+            assert_eq!(shapes[*index].id, hit.id);
+        }
+    }
+
+    #[test]
+    /// Tests whether the `TraversalStack` pops in last-in, first-out order when it spills
+    /// beyond its fixed-size array and shrinks back below it.
+    fn test_traversal_stack_spills() {
+        use crate::bvh::bvh::TraversalStack;
+        use crate::bvh::TRAVERSAL_STACK_SIZE;
+
+        let mut stack = TraversalStack::new();
+        let n = 3 * TRAVERSAL_STACK_SIZE;
+        for node_index in 0..n {
+            stack.push(node_index);
+        }
+        for node_index in (TRAVERSAL_STACK_SIZE - 2..n).rev() {
+            assert_eq!(stack.pop(), Some(node_index));
+        }
+
+        // Push across the boundary again while the array is almost full.
+        stack.push(1_000);
+        stack.push(1_001);
+        stack.push(1_002);
+        assert_eq!(stack.pop(), Some(1_002));
+        assert_eq!(stack.pop(), Some(1_001));
+        assert_eq!(stack.pop(), Some(1_000));
+        for node_index in (0..TRAVERSAL_STACK_SIZE - 2).rev() {
+            assert_eq!(stack.pop(), Some(node_index));
+        }
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    /// Tests whether the iterative traversal handles a deliberately unbalanced tree, which
+    /// is deeper than the fixed-size traversal stack, and returns what the recursive
+    /// traversal returns.
+    fn test_traverse_unbalanced_bvh() {
+        use crate::bvh::TRAVERSAL_STACK_SIZE;
+        use crate::testbase::build_chain_bvh;
+
+        let (shapes, bvh) = build_chain_bvh(200);
+        assert!(bvh.depth() > TRAVERSAL_STACK_SIZE);
+
+        for origin_y in &[0.0, 0.25, 10.0] {
+            let ray = Ray::new(Vec3::new(-1.0, *origin_y, 0.0), Vec3::new(1.0, 0.0, 0.0));
+            let mut expected = Vec::new();
+            BVHNode::traverse_recursive(
+                &bvh.nodes,
+                &bvh.shape_indices,
+                0,
+                &ray,
+                &shapes,
+                &mut expected,
+            );
+
+            let indices = bvh.traverse_indices(&ray, &shapes);
+            assert_eq!(indices, expected);
+            assert_eq!(bvh.traverse(&ray, &shapes).len(), expected.len());
+        }
+        let ray = Ray::new(Vec3::new(-1.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(bvh.traverse(&ray, &shapes).len(), shapes.len());
+    }
+
+    #[test]
+    #[cfg(feature = "simd")]
+    /// Tests whether the SIMD traversal returns the same shapes in the same order as the
+    /// scalar traversal.
+    fn test_traverse_x4_equals_scalar() {
+        use crate::bvh::BuildConfig;
+        use crate::testbase::{create_n_cubes, default_bounds, next_Vec3};
+
+        let bounds = default_bounds();
+        let mut seed = 0;
+        for &max_leaf_size in &[1, 4] {
+            let mut triangles = create_n_cubes(1_000, &bounds);
+            let config = BuildConfig {
+                max_leaf_size,
+                ..Default::default()
+            };
+            let bvh = BVH::build_with_config(&mut triangles, &config);
+
+            for _ in 0..100 {
+                let origin = next_Vec3(&mut seed, &bounds);
+                let ray = Ray::new(origin, next_Vec3(&mut seed, &bounds) - origin);
+
+                let mut expected = Vec::new();
+                BVHNode::traverse_iterative(
+                    &bvh.nodes,
+                    &bvh.shape_indices,
+                    &ray,
+                    &triangles,
+                    &mut expected,
+                );
+                let mut indices = Vec::new();
+                BVHNode::traverse_iterative_x4(
+                    &bvh.nodes,
+                    &bvh.shape_indices,
+                    &ray,
+                    &triangles,
+                    &mut indices,
+                );
+                assert_eq!(indices, expected);
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether `nodes` and `depth` agree with the node array and its statistics.
+    fn test_nodes_and_depth() {
+        let (shapes, bvh) = build_some_bh::<BVH>();
+        assert_eq!(bvh.nodes().len(), 2 * shapes.len() - 1);
+        assert_eq!(bvh.depth(), bvh.statistics().max_depth as usize);
+        for (node_index, node) in bvh.nodes().iter().enumerate().skip(1) {
+            let parent = &bvh.nodes()[node.parent()];
+            assert!(parent.child_l() == node_index || parent.child_r() == node_index);
+            assert_eq!(node.depth(), parent.depth() + 1);
+        }
+
+        assert_eq!(
+            BVH {
+                nodes: Vec::new(),
+                shape_indices: Vec::new()
+            }
+            .depth(),
+            0
+        );
+    }
+
+    #[test]
+    /// Tests whether `pretty_string` renders one line per node, indented by its depth.
+    fn test_pretty_string() {
+        let (shapes, bvh) = build_some_bh::<BVH>();
+        let output = bvh.pretty_string();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), bvh.nodes.len());
+        assert!(lines[0].starts_with("node 0: min "));
+
+        let leaf_count = lines.iter().filter(|line| line.contains("leaf ")).count();
+        assert_eq!(leaf_count, shapes.len());
+        for line in &lines[1..] {
+            let depth = line.chars().take_while(|c| !c.is_alphanumeric()).count() / 4;
+            let node_index: usize = line
+                .split(&[' ', ':'][..])
+                .find_map(|word| word.parse().ok())
+                .unwrap();
+            assert_eq!(depth as u32, bvh.nodes[node_index].depth());
+        }
 
-        let parent = node.parent();
         assert_eq!(
-            expected_parent_index, parent,
-            "Wrong parent index. Expected: {}; Actual: {}",
-            expected_parent_index, parent
+            BVH {
+                nodes: Vec::new(),
+                shape_indices: Vec::new()
+            }
+            .pretty_string(),
+            ""
         );
-        let depth = node.depth();
+    }
+
+    #[test]
+    /// Tests whether `to_dot` emits one graph node per `BVH` node and one edge per child.
+    fn test_to_dot() {
+        let (shapes, bvh) = build_some_bh::<BVH>();
+        let dot = bvh.to_dot();
+
+        assert!(dot.starts_with("digraph bvh {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot.matches("label=").count(), bvh.nodes.len());
+        assert_eq!(dot.matches("leaf ").count(), shapes.len());
+        assert_eq!(dot.matches(" -> ").count(), bvh.nodes.len() - 1);
+
         assert_eq!(
-            expected_depth, depth,
-            "Wrong depth. Expected: {}; Actual: {}",
-            expected_depth, depth
+            BVH {
+                nodes: Vec::new(),
+                shape_indices: Vec::new()
+            }
+            .to_dot(),
+            "digraph bvh {\n}\n"
         );
+    }
 
-        match *node {
-            BVHNode::Node {
-                child_l_index,
-                child_l_aabb,
-                child_r_index,
-                child_r_aabb,
-                ..
-            } => {
-                assert!(
-                    expected_outer_aabb.approx_contains_aabb_eps(&child_l_aabb, EPSILON),
-                    "Left child lies outside the expected bounds.
-                         \tBounds: {}
-                         \tLeft child: {}",
-                    expected_outer_aabb,
-                    child_l_aabb
-                );
-                assert!(
-                    expected_outer_aabb.approx_contains_aabb_eps(&child_r_aabb, EPSILON),
-                    "Right child lies outside the expected bounds.
-                         \tBounds: {}
-                         \tRight child: {}",
-                    expected_outer_aabb,
-                    child_r_aabb
-                );
-                self.assert_consistent_subtree(
+    #[test]
+    /// Tests whether `path_to_shape` leads from the root through linked parents and
+    /// children to the leaf of the shape, and whether it is empty for unknown shapes.
+    fn test_path_to_shape() {
+        use crate::bounding_hierarchy::BHShape;
+
+        let (shapes, bvh) = build_some_bh::<BVH>();
+
+        for (shape_index, shape) in shapes.iter().enumerate() {
+            let path = bvh.path_to_shape(shape_index);
+            assert_eq!(path[0], 0);
+            let leaf_index = shape.bh_node_index();
+            assert_eq!(*path.last().unwrap(), leaf_index);
+            assert_eq!(path.len(), bvh.nodes[leaf_index].depth() as usize + 1);
+            for pair in path.windows(2) {
+                let node = &bvh.nodes[pair[0]];
+                assert!(node.child_l() == pair[1] || node.child_r() == pair[1]);
+                assert_eq!(bvh.nodes[pair[1]].parent(), pair[0]);
+            }
+        }
+
+        assert!(bvh.path_to_shape(shapes.len()).is_empty());
+        assert!(BVH {
+            nodes: Vec::new(),
+            shape_indices: Vec::new()
+        }
+        .path_to_shape(0)
+        .is_empty());
+    }
+
+    #[test]
+    /// Tests whether `bounds` equals the joint `AABB` of all shapes after building and
+    /// after refitting moved shapes, and whether it is `None` if the root is a leaf or
+    /// there are no shapes.
+    fn test_bounds() {
+        use crate::aabb::{Bounded, AABB};
+        use crate::bvh::BuildConfig;
+        use crate::testbase::{create_n_cubes, default_bounds, randomly_transform_scene, Triangle};
+
+        let bounds = default_bounds();
+        let mut triangles = create_n_cubes(1_000, &bounds);
+        let mut bvh = BVH::build(&mut triangles);
+        assert_eq!(bvh.bounds(), Some(AABB::enclosing(&triangles)));
+
+        let mut seed = 0;
+        randomly_transform_scene(&mut triangles, 3_000, &bounds, None, &mut seed);
+        bvh.refit(&triangles);
+        assert_eq!(bvh.bounds(), Some(AABB::enclosing(&triangles)));
+
+        let mut single = create_n_cubes(1, &bounds);
+        single.truncate(1);
+        let bvh = BVH::build(&mut single);
+        assert_eq!(bvh.bounds(), None);
+
+        let mut two = create_n_cubes(1, &bounds);
+        two.truncate(2);
+        let bvh = BVH::build(&mut two);
+        assert_eq!(bvh.bounds(), Some(two[0].aabb().join(&two[1].aabb())));
+
+        // Three triangles, which fit into the root leaf.
+        let mut few = create_n_cubes(1, &bounds);
+        few.truncate(3);
+        let config = BuildConfig {
+            max_leaf_size: 4,
+            ..Default::default()
+        };
+        let bvh = BVH::build_with_config(&mut few, &config);
+        assert!(bvh.nodes[0].shape_index().is_some());
+        assert_eq!(bvh.bounds(), None);
+
+        let mut no_shapes: Vec<Triangle> = Vec::new();
+        assert_eq!(BVH::build(&mut no_shapes).bounds(), None);
+    }
+
+    /// Builds a `BVH` of cubes whose root stores an `AABB` for its left child which
+    /// exceeds the joint `AABB` of the grandchildren by `0.01` on each side.
+    fn build_loose_bvh() -> (Vec<crate::testbase::Triangle>, BVH) {
+        use crate::testbase::{create_n_cubes, default_bounds};
+
+        let mut triangles = create_n_cubes(100, &default_bounds());
+        let mut bvh = BVH::build(&mut triangles);
+        if let BVHNode::Node {
+            ref mut child_l_aabb,
+            ..
+        } = bvh.nodes[0]
+        {
+            let margin = Vec3::new(0.01, 0.01, 0.01);
+            child_l_aabb.min -= margin;
+            child_l_aabb.max += margin;
+        }
+        (triangles, bvh)
+    }
+
+    #[test]
+    /// Tests whether `assert_tight_eps` accepts `AABB`s which differ by less than the given
+    /// `epsilon`.
+    fn test_assert_tight_eps() {
+        let (triangles, bvh) = build_loose_bvh();
+        bvh.assert_tight_eps(&triangles, 0.1);
+    }
+
+    #[test]
+    #[should_panic]
+    /// Tests whether `assert_tight` rejects `AABB`s which differ by more than `EPSILON`.
+    fn test_assert_tight_loose_aabb_panics() {
+        let (triangles, bvh) = build_loose_bvh();
+        bvh.assert_tight(&triangles);
+    }
+
+    #[test]
+    /// Tests `is_empty`, `len` and the traversals and queries of a `BVH` without shapes
+    /// and of one whose root is the leaf of a single shape.
+    fn test_empty_and_single_shape_bvh() {
+        use crate::aabb::{Bounded, AABB};
+        use crate::bvh::TraversalCache;
+        use crate::testbase::UnitBox;
+
+        // Rays along the z axis, which hit the unit box at the origin for `x == 0.0`.
+        let ray_at = |x: f32| Ray::new(Vec3::new(x, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        let everywhere = AABB::with_bounds(Vec3::new(-9.0, -9.0, -9.0), Vec3::new(9.0, 9.0, 9.0));
+        let origin = Vec3::new(0.0, 0.0, 0.0);
+        let intersect = |ray: &Ray, shape: &UnitBox| shape.aabb().intersect_ray_distance(ray);
+
+        let mut shapes: Vec<UnitBox> = Vec::new();
+        let bvh = BVH::build(&mut shapes);
+        let ray = ray_at(0.0);
+        let mut cache = TraversalCache::new();
+        assert!(bvh.is_empty());
+        assert_eq!(bvh.len(), 0);
+        assert!(bvh.traverse(&ray, &shapes).is_empty());
+        assert!(bvh.traverse_indices(&ray, &shapes).is_empty());
+        assert!(bvh.traverse_dedup(&ray, &shapes).is_empty());
+        assert!(bvh.traverse_mut(&ray, &mut shapes).is_empty());
+        assert!(bvh.traverse_nearest(&ray, &shapes, intersect).is_none());
+        assert_eq!(bvh.traverse_iterator(&ray, &shapes).count(), 0);
+        assert!(bvh.traverse_cached(&ray, &shapes, &mut cache).is_empty());
+        assert!(bvh.traverse_packet(&[ray_at(0.0)], &shapes)[0].is_empty());
+        assert!(bvh.traverse_aabb(&everywhere, &shapes).is_empty());
+        assert!(bvh.traverse_point(origin, &shapes).is_empty());
+        assert!(bvh.k_nearest(origin, 3, &shapes).is_empty());
+        assert_eq!(bvh.bounds(), None);
+
+        let mut shapes = vec![UnitBox::new(0, origin)];
+        let bvh = BVH::build(&mut shapes);
+        assert!(!bvh.is_empty());
+        assert_eq!(bvh.len(), 1);
+        for &(x, expected) in &[(0.0, 1), (3.0, 0)] {
+            let ray = ray_at(x);
+            let mut cache = TraversalCache::new();
+            assert_eq!(bvh.traverse(&ray, &shapes).len(), expected);
+            assert_eq!(bvh.traverse_indices(&ray, &shapes).len(), expected);
+            assert_eq!(bvh.traverse_dedup(&ray, &shapes).len(), expected);
+            assert_eq!(bvh.traverse_mut(&ray, &mut shapes).len(), expected);
+            let nearest = bvh.traverse_nearest(&ray, &shapes, intersect);
+            assert_eq!(nearest.is_some() as usize, expected);
+            assert_eq!(bvh.traverse_iterator(&ray, &shapes).count(), expected);
+            assert_eq!(
+                bvh.traverse_cached(&ray, &shapes, &mut cache).len(),
+                expected
+            );
+            assert_eq!(
+                bvh.traverse_packet(&[ray_at(x)], &shapes)[0].len(),
+                expected
+            );
+        }
+        assert_eq!(bvh.traverse_aabb(&everywhere, &shapes).len(), 1);
+        assert!(bvh
+            .traverse_point(Vec3::new(3.0, 0.0, 0.0), &shapes)
+            .is_empty());
+        assert_eq!(bvh.k_nearest(origin, 3, &shapes).len(), 1);
+        assert_eq!(bvh.bounds(), None);
+    }
+
+    #[test]
+    /// Tests whether flattening and the consistency checks accept a `BVH` without shapes,
+    /// one built only from invalid shapes and one whose root is a single leaf.
+    fn test_empty_bvh_flatten_and_checks() {
+        use crate::testbase::UnitBox;
+
+        let mut no_shapes: Vec<UnitBox> = Vec::new();
+        let mut only_invalid = vec![
+            UnitBox::new(0, Vec3::new(f32::NAN, 0.0, 0.0)),
+            UnitBox::new(1, Vec3::new(f32::NAN, f32::NAN, f32::NAN)),
+        ];
+        for shapes in &mut [&mut no_shapes, &mut only_invalid] {
+            let bvh = BVH::build(shapes);
+            assert!(bvh.is_empty());
+            assert!(bvh.flatten().is_empty());
+            assert!(bvh.flatten_custom(&|_, _, _, shape| shape).is_empty());
+            assert!(bvh.is_consistent(shapes));
+            bvh.assert_consistent(shapes);
+            bvh.assert_tight(shapes);
+        }
+
+        let mut single = vec![UnitBox::new(0, Vec3::new(0.0, 0.0, 0.0))];
+        let bvh = BVH::build(&mut single);
+        assert_eq!(bvh.flatten().len(), 1);
+        assert!(bvh.is_consistent(&single));
+        bvh.assert_consistent(&single);
+        bvh.assert_tight(&single);
+    }
+
+    #[test]
+    /// Marks all shapes hit by a ray through `traverse_mut` and checks that exactly
+    /// these shapes were modified.
+    fn test_traverse_mut() {
+        let (mut shapes, bvh) = build_some_bh::<BVH>();
+        let ray = Ray::new(Vec3::new(6.0, 0.5, 0.0), Vec3::new(-2.0, -1.0, 0.0));
+
+        for shape in bvh.traverse_mut(&ray, &mut shapes) {
+            shape.id += 100;
+        }
+
+        let marked: Vec<i32> = shapes
+            .iter()
+            .filter(|shape| shape.id >= 100)
+            .map(|shape| shape.id - 100)
+            .collect();
+        assert_eq!(marked, vec![4, 5, 6]);
+    }
+
+    #[test]
+    /// Verify contents of the bounding hierarchy for a fixed scene structure
+    fn test_bvh_shape_indices() {
+        use std::collections::HashSet;
+
+        let (all_shapes, bh) = build_some_bh::<BVH>();
+
+        // It should find all shape indices.
+        let expected_shapes: HashSet<_> = (0..all_shapes.len()).collect();
+        let mut found_shapes = HashSet::new();
+
+        for node in bh.nodes.iter() {
+            match *node {
+                BVHNode::Node { .. } => {
+                    assert_eq!(node.shape_index(), None);
+                }
+                BVHNode::Leaf { .. } => {
+                    let entry = node
+                        .shape_index()
+                        .expect("getting a shape index from a leaf node");
+                    found_shapes.insert(bh.shape_indices[entry]);
+                }
+            }
+        }
+
+        assert_eq!(expected_shapes, found_shapes);
+    }
+
+    #[test]
+    /// Tests whether `BVH`s built with different bucket counts are consistent, tight and
+    /// find exactly the shapes whose `AABB`s are hit by a ray.
+    fn test_build_with_bucket_counts() {
+        use crate::aabb::Bounded;
+        use crate::bvh::BuildConfig;
+        use crate::testbase::{create_n_cubes, default_bounds, next_Vec3};
+
+        let bounds = default_bounds();
+        let mut seed = 0;
+        for &bucket_count in &[2, 3, 6, 12, 32] {
+            let mut triangles = create_n_cubes(100, &bounds);
+            let config = BuildConfig {
+                bucket_count,
+                ..Default::default()
+            };
+            let bvh = BVH::build_with_config(&mut triangles, &config);
+            bvh.assert_consistent(&triangles);
+            bvh.assert_tight(&triangles);
+
+            for _ in 0..100 {
+                let ray = Ray::new(next_Vec3(&mut seed, &bounds), next_Vec3(&mut seed, &bounds));
+                let mut hits = bvh.traverse_indices(&ray, &triangles);
+                hits.sort();
+                let expected: Vec<usize> = (0..triangles.len())
+                    .filter(|&i| ray.intersects_aabb(&triangles[i].aabb()))
+                    .collect();
+                assert_eq!(hits, expected);
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether a few huge shapes, whose centroids lie in the middle of the scene,
+    /// leave the splits between the many tiny shapes intact. The SAH cost of the mixed
+    /// scene may only grow by a fraction of the cost of the tiny shapes alone.
+    fn test_build_mixed_scale_scene() {
+        use crate::testbase::{create_mixed_scale_scene, create_n_cubes, default_bounds};
+
+        let bounds = default_bounds();
+        let mut cubes = create_n_cubes(1_000, &bounds);
+        let mut mixed = create_mixed_scale_scene(1_000, &bounds);
+        let cubes_bvh = BVH::build(&mut cubes);
+        let mixed_bvh = BVH::build(&mut mixed);
+        mixed_bvh.assert_consistent(&mixed);
+        mixed_bvh.assert_tight(&mixed);
+
+        let cubes_cost = cubes_bvh.statistics().sah_cost;
+        let mixed_cost = mixed_bvh.statistics().sah_cost;
+        assert!(mixed_cost < cubes_cost * 1.5);
+        assert!(mixed_bvh.depth() <= cubes_bvh.depth() + 3);
+    }
+
+    #[test]
+    /// Tests whether a `BVH` with spatial splits over long, thin triangles still returns
+    /// every triangle which is hit, stays within its reference budget and is cheaper than
+    /// one built without spatial splits.
+    fn test_build_with_spatial_splits() {
+        use crate::aabb::AABB;
+        use crate::bvh::BuildConfig;
+        use crate::testbase::{create_thin_triangles, next_Vec3};
+
+        let bounds = AABB::with_bounds(
+            Vec3::new(-100.0, -100.0, -100.0),
+            Vec3::new(100.0, 100.0, 100.0),
+        );
+        let mut seed = 0;
+        for &max_leaf_size in &[1, 4] {
+            let mut triangles = create_thin_triangles(500, &bounds);
+            let config = BuildConfig {
+                max_leaf_size,
+                ..Default::default()
+            };
+            let object_bvh = BVH::build_with_config(&mut triangles, &config);
+            let config = BuildConfig {
+                spatial_splits: true,
+                ..config
+            };
+            let bvh = BVH::build_with_config(&mut triangles, &config);
+
+            let references = bvh.leaf_shape_indices().count();
+            assert!(references > triangles.len());
+            assert!(references <= 2 * triangles.len());
+            assert!(bvh.statistics().sah_cost < object_bvh.statistics().sah_cost);
+
+            // Every triangle is referenced, and the shapes of leaves are stored in order.
+            let mut referenced = vec![false; triangles.len()];
+            for index in bvh.leaf_shape_indices() {
+                referenced[index] = true;
+            }
+            assert!(referenced.iter().all(|referenced| *referenced));
+
+            for (index, triangle) in triangles.iter().enumerate() {
+                // Aim at a point inside of every triangle.
+                let u = 0.1 + (index % 7) as f32 / 10.0;
+                let v = 0.05 + (index % 5) as f32 / 20.0;
+                let target =
+                    triangle.a + (triangle.b - triangle.a) * u + (triangle.c - triangle.a) * v;
+                let origin = next_Vec3(&mut seed, &bounds);
+                let ray = Ray::new(origin, target - origin);
+
+                let hits = bvh.traverse(&ray, &triangles);
+                for (other_index, other) in triangles.iter().enumerate() {
+                    let distance = ray
+                        .intersects_triangle(&other.a, &other.b, &other.c)
+                        .distance;
+                    if distance < f32::INFINITY || other_index == index {
+                        assert!(hits.iter().any(|hit| std::ptr::eq(*hit, other)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether `traverse_dedup` returns every shape which `traverse` returns exactly
+    /// once, for `BVH`s with several shapes per leaf, with and without spatial splits.
+    fn test_traverse_dedup() {
+        use crate::aabb::AABB;
+        use crate::bvh::BuildConfig;
+        use crate::testbase::{create_thin_triangles, next_Vec3};
+
+        let bounds = AABB::with_bounds(
+            Vec3::new(-100.0, -100.0, -100.0),
+            Vec3::new(100.0, 100.0, 100.0),
+        );
+        let mut seed = 0;
+        let mut duplicate_count = 0;
+        for &spatial_splits in &[false, true] {
+            let mut triangles = create_thin_triangles(500, &bounds);
+            let config = BuildConfig {
+                max_leaf_size: 4,
+                spatial_splits,
+                ..Default::default()
+            };
+            let bvh = BVH::build_with_config(&mut triangles, &config);
+
+            for _ in 0..200 {
+                let origin = next_Vec3(&mut seed, &bounds);
+                let ray = Ray::new(origin, next_Vec3(&mut seed, &bounds) - origin);
+                let hits = bvh.traverse(&ray, &triangles);
+                let unique = bvh.traverse_dedup(&ray, &triangles);
+
+                let mut expected: Vec<*const _> = Vec::new();
+                for hit in hits.iter().map(|hit| *hit as *const _) {
+                    if !expected.contains(&hit) {
+                        expected.push(hit);
+                    }
+                }
+                let unique: Vec<*const _> = unique.iter().map(|hit| *hit as *const _).collect();
+                assert_eq!(unique, expected);
+                if !spatial_splits {
+                    assert_eq!(unique.len(), hits.len());
+                }
+                duplicate_count += hits.len() - unique.len();
+            }
+        }
+        assert!(duplicate_count > 0);
+    }
+
+    #[test]
+    /// Tests whether shapes with `NaN`, infinite or empty `AABB`s are left out of the
+    /// `BVH`, while the valid shapes are still found exactly.
+    fn test_build_skips_invalid_shapes() {
+        use crate::aabb::Bounded;
+        use crate::aabb::AABB;
+        use crate::bvh::BuildConfig;
+        use crate::testbase::{next_Vec3, UnitBox};
+
+        let bounds = AABB::with_bounds(Vec3::new(-20.0, -20.0, -20.0), Vec3::new(20.0, 20.0, 20.0));
+        let create_invalid = || {
+            vec![
+                UnitBox::new(-1, Vec3::new(f32::NAN, 0.0, 0.0)),
+                UnitBox::new(-2, Vec3::new(f32::NAN, f32::NAN, f32::NAN)),
+                UnitBox::new(-3, Vec3::new(0.0, f32::INFINITY, 0.0)),
+            ]
+        };
+
+        let mut only_invalid = create_invalid();
+        assert!(BVH::build(&mut only_invalid).nodes.is_empty());
+        assert!(BVH::build_lbvh(&mut only_invalid).nodes.is_empty());
+
+        let mut seed = 0;
+        for &max_leaf_size in &[1, 4] {
+            let mut boxes: Vec<UnitBox> = (0..1_000)
+                .map(|id| UnitBox::new(id, next_Vec3(&mut seed, &bounds)))
+                .collect();
+            for (i, unit_box) in create_invalid().into_iter().enumerate() {
+                boxes.insert(i * 400, unit_box);
+            }
+            let config = BuildConfig {
+                max_leaf_size,
+                ..Default::default()
+            };
+            let bvh = BVH::build_with_config(&mut boxes, &config);
+            assert_eq!(bvh.validate(&boxes), Ok(()));
+            let lbvh = BVH::build_lbvh(&mut boxes);
+            assert_eq!(lbvh.validate(&boxes), Ok(()));
+
+            for _ in 0..100 {
+                let ray = Ray::new(next_Vec3(&mut seed, &bounds), next_Vec3(&mut seed, &bounds));
+                let expected: Vec<i32> = boxes
+                    .iter()
+                    .filter(|unit_box| unit_box.aabb().is_finite())
+                    .filter(|unit_box| ray.intersects_aabb(&unit_box.aabb()))
+                    .map(|unit_box| unit_box.id)
+                    .collect();
+                for bvh in &[&bvh, &lbvh] {
+                    let mut hits: Vec<i32> = bvh
+                        .traverse(&ray, &boxes)
+                        .iter()
+                        .map(|unit_box| unit_box.id)
+                        .collect();
+                    hits.sort();
+                    let mut expected = expected.clone();
+                    expected.sort();
+                    assert_eq!(hits, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether threads which traverse the same `&BVH` concurrently get the same
+    /// results as a single thread.
+    fn test_traverse_from_threads() {
+        use crate::testbase::{create_n_cubes, default_bounds, next_Vec3};
+
+        let bounds = default_bounds();
+        let mut triangles = create_n_cubes(1_000, &bounds);
+        let bvh = BVH::build(&mut triangles);
+
+        let mut seed = 0;
+        let rays: Vec<Ray> = (0..400)
+            .map(|_| {
+                let origin = next_Vec3(&mut seed, &bounds);
+                Ray::new(origin, next_Vec3(&mut seed, &bounds) - origin)
+            })
+            .collect();
+        let expected: Vec<usize> = rays
+            .iter()
+            .map(|ray| bvh.traverse(ray, &triangles).len())
+            .collect();
+
+        let (bvh, triangles) = (&bvh, &triangles);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = rays
+                .chunks(100)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|ray| bvh.traverse(ray, triangles).len())
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            let hit_counts: Vec<usize> = handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect();
+            assert_eq!(hit_counts, expected);
+        });
+    }
+
+    #[test]
+    /// Tests whether `rebuild` produces the same tree as `build` for a transformed scene,
+    /// and whether it reuses the capacity of the nodes.
+    fn test_rebuild_equals_build() {
+        use crate::testbase::{
+            create_n_cubes, default_bounds, next_Vec3, randomly_transform_scene,
+        };
+
+        let bounds = default_bounds();
+        let mut seed = 0;
+        // Two copies of the same scene, one rebuilt in place and one built anew.
+        let mut rebuilt_triangles = create_n_cubes(1_000, &bounds);
+        let mut triangles = create_n_cubes(1_000, &bounds);
+        let mut bvh = BVH::build(&mut rebuilt_triangles);
+
+        for _ in 0..3 {
+            let mut transform_seed = seed;
+            randomly_transform_scene(&mut rebuilt_triangles, 3_000, &bounds, None, &mut seed);
+            randomly_transform_scene(&mut triangles, 3_000, &bounds, None, &mut transform_seed);
+
+            let capacity = bvh.nodes.capacity();
+            let nodes_ptr = bvh.nodes.as_ptr();
+            bvh.rebuild(&mut rebuilt_triangles);
+            assert_eq!(bvh.nodes.capacity(), capacity);
+            assert_eq!(bvh.nodes.as_ptr(), nodes_ptr);
+
+            let built = BVH::build(&mut triangles);
+            assert_eq!(format!("{:?}", bvh.nodes), format!("{:?}", built.nodes));
+            bvh.assert_consistent(&rebuilt_triangles);
+            for _ in 0..100 {
+                let origin = next_Vec3(&mut seed, &bounds);
+                let ray = Ray::new(origin, next_Vec3(&mut seed, &bounds) - origin);
+                let hits = bvh.traverse_indices(&ray, &rebuilt_triangles);
+                assert_eq!(hits, built.traverse_indices(&ray, &triangles));
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether a buffer reused by `traverse_into` for many rays always holds exactly
+    /// the shapes a fresh `traverse` returns, in the same order.
+    fn test_traverse_into_reuses_buffer() {
+        use crate::bvh::BuildConfig;
+        use crate::testbase::{create_n_cubes, default_bounds, next_Vec3};
+
+        let bounds = default_bounds();
+        let mut seed = 0;
+        for &max_leaf_size in &[1, 4] {
+            let mut triangles = create_n_cubes(1_000, &bounds);
+            let config = BuildConfig {
+                max_leaf_size,
+                ..Default::default()
+            };
+            let bvh = BVH::build_with_config(&mut triangles, &config);
+
+            let mut hits = Vec::new();
+            for _ in 0..100 {
+                let origin = next_Vec3(&mut seed, &bounds);
+                let ray = Ray::new(origin, next_Vec3(&mut seed, &bounds) - origin);
+                bvh.traverse_into(&ray, &triangles, &mut hits);
+
+                let expected = bvh.traverse(&ray, &triangles);
+                assert_eq!(hits.len(), expected.len());
+                assert!(hits.iter().zip(expected).all(|(a, b)| std::ptr::eq(*a, b)));
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether `BuildConfig::epsilon` decides when centroids count as coincident.
+    /// With an infinite `epsilon` no split is binned, so every node splits its shapes in
+    /// half in their original order.
+    fn test_build_config_epsilon() {
+        use crate::bvh::BuildConfig;
+        use crate::testbase::{default_bounds, next_Vec3, UnitBox};
+
+        let bounds = default_bounds();
+        let mut seed = 0;
+        let mut boxes: Vec<UnitBox> = (0..100)
+            .map(|id| UnitBox::new(id, next_Vec3(&mut seed, &bounds)))
+            .collect();
+
+        let config = BuildConfig {
+            epsilon: f32::INFINITY,
+            ..Default::default()
+        };
+        let bvh = BVH::build_with_config(&mut boxes, &config);
+        assert_eq!(bvh.validate(&boxes), Ok(()));
+        assert_eq!(bvh.depth(), 7);
+        let order: Vec<usize> = bvh.leaf_shape_indices().collect();
+        assert_eq!(order, (0..boxes.len()).collect::<Vec<usize>>());
+
+        let bvh = BVH::build(&mut boxes);
+        let order: Vec<usize> = bvh.leaf_shape_indices().collect();
+        assert_ne!(order, (0..boxes.len()).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    /// Tests whether the median split strategies build valid trees in which every shape
+    /// can be found, and whether `ObjectMedian` builds a balanced tree.
+    fn test_split_strategies() {
+        use crate::bvh::{BuildConfig, SplitStrategy};
+        use crate::testbase::{create_n_cubes, default_bounds};
+
+        let bounds = default_bounds();
+        for &split_strategy in &[
+            SplitStrategy::Sah,
+            SplitStrategy::ObjectMedian,
+            SplitStrategy::SpatialMedian,
+        ] {
+            for &max_leaf_size in &[1, 4] {
+                let mut triangles = create_n_cubes(100, &bounds);
+                let config = BuildConfig {
+                    max_leaf_size,
+                    split_strategy,
+                    ..Default::default()
+                };
+                let bvh = BVH::build_with_config(&mut triangles, &config);
+                assert_eq!(bvh.validate(&triangles), Ok(()));
+
+                for triangle in &triangles {
+                    let hits = bvh.traverse_point(triangle.a, &triangles);
+                    assert!(hits.iter().any(|hit| std::ptr::eq(*hit, triangle)));
+                }
+            }
+        }
+
+        // 1,200 triangles in leaves of single shapes need a depth of ceil(log2(1200)).
+        let mut triangles = create_n_cubes(100, &bounds);
+        let config = BuildConfig {
+            split_strategy: SplitStrategy::ObjectMedian,
+            ..Default::default()
+        };
+        let bvh = BVH::build_with_config(&mut triangles, &config);
+        assert_eq!(bvh.depth(), 11);
+    }
+
+    #[test]
+    /// Tests whether the split plane of every inner node lies within the node's `AABB` on
+    /// its axis, for all builders, and whether it separates the centroids of the two
+    /// subtrees for the object splits of `build_with_config`.
+    fn test_split_planes() {
+        use crate::aabb::{Bounded, AABB};
+        use crate::bvh::{BuildConfig, SplitStrategy};
+        use crate::testbase::{create_n_cubes, default_bounds, Triangle};
+        use crate::EPSILON;
+
+        /// Returns the joint `AABB` of the centroids of the shapes below `node_index`.
+        fn centroid_bounds(bvh: &BVH, node_index: usize, shapes: &[Triangle]) -> AABB {
+            match bvh.nodes[node_index] {
+                BVHNode::Node {
                     child_l_index,
-                    node_index,
-                    &child_l_aabb,
-                    expected_depth + 1,
-                    node_count,
-                    shapes,
-                );
-                self.assert_consistent_subtree(
                     child_r_index,
-                    node_index,
-                    &child_r_aabb,
-                    expected_depth + 1,
-                    node_count,
+                    ..
+                } => centroid_bounds(bvh, child_l_index, shapes).join(&centroid_bounds(
+                    bvh,
+                    child_r_index,
                     shapes,
-                );
+                )),
+                BVHNode::Leaf {
+                    shape_index,
+                    shape_count,
+                    ..
+                } => bvh.shape_indices[shape_index..shape_index + shape_count]
+                    .iter()
+                    .fold(AABB::empty(), |aabb, &index| {
+                        aabb.grow(&shapes[index].aabb().center())
+                    }),
+            }
+        }
+
+        /// Asserts that the split plane of every inner node lies within its `AABB`.
+        fn assert_planes_inside(bvh: &BVH) {
+            for node in &bvh.nodes {
+                if let BVHNode::Node {
+                    child_l_aabb,
+                    child_r_aabb,
+                    ..
+                } = *node
+                {
+                    let aabb = child_l_aabb.join(&child_r_aabb);
+                    let axis = node.split_axis();
+                    let position = node.split_position();
+                    assert!(aabb.min[axis] - EPSILON <= position);
+                    assert!(position <= aabb.max[axis] + EPSILON);
+                }
+            }
+        }
+
+        let bounds = default_bounds();
+        for &split_strategy in &[
+            SplitStrategy::Sah,
+            SplitStrategy::ObjectMedian,
+            SplitStrategy::SpatialMedian,
+        ] {
+            for &max_leaf_size in &[1, 4] {
+                let mut triangles = create_n_cubes(100, &bounds);
+                let config = BuildConfig {
+                    max_leaf_size,
+                    split_strategy,
+                    ..Default::default()
+                };
+                let bvh = BVH::build_with_config(&mut triangles, &config);
+                assert_planes_inside(&bvh);
+
+                for node in &bvh.nodes {
+                    if let BVHNode::Node {
+                        child_l_index,
+                        child_r_index,
+                        ..
+                    } = *node
+                    {
+                        let axis = node.split_axis();
+                        let position = node.split_position();
+                        let l = centroid_bounds(&bvh, child_l_index, &triangles);
+                        let r = centroid_bounds(&bvh, child_r_index, &triangles);
+                        assert!(l.max[axis] <= position + EPSILON);
+                        assert!(r.min[axis] >= position - EPSILON);
+                    }
+                }
+            }
+        }
+
+        let mut triangles = create_n_cubes(100, &bounds);
+        let config = BuildConfig {
+            spatial_splits: true,
+            ..Default::default()
+        };
+        assert_planes_inside(&BVH::build_with_config(&mut triangles, &config));
+        assert_planes_inside(&BVH::build_lbvh(&mut triangles));
+    }
+
+    #[test]
+    /// Tests whether `build_with_progress` reports increasing fractions between `0.0` and
+    /// `1.0`, ends with exactly `1.0`, and builds the same tree as `build`.
+    fn test_build_with_progress() {
+        use crate::testbase::{create_n_cubes, default_bounds, Triangle};
+
+        let mut triangles = create_n_cubes(1_000, &default_bounds());
+        let mut fractions = Vec::new();
+        let bvh = BVH::build_with_progress(&mut triangles, |fraction| fractions.push(fraction));
+
+        assert!(fractions.len() > 2);
+        assert_eq!(*fractions.last().unwrap(), 1.0);
+        assert!(fractions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(fractions[0] > 0.0);
+        assert_eq!(bvh.nodes, BVH::build(&mut triangles).nodes);
+
+        let mut fractions = Vec::new();
+        BVH::build_with_progress::<Triangle, _>(&mut [], |fraction| fractions.push(fraction));
+        assert_eq!(fractions, vec![1.0]);
+    }
+
+    #[test]
+    /// Tests whether `try_build` reports every kind of malformed input, and builds the
+    /// same tree as `build` from valid shapes.
+    fn test_try_build_errors() {
+        use crate::bvh::{BvhError, MAX_SHAPES};
+        use crate::testbase::UnitBox;
+
+        let mut boxes: Vec<UnitBox> = Vec::new();
+        assert_eq!(BVH::try_build(&mut boxes).err(), Some(BvhError::EmptyInput));
+
+        boxes = (0..10)
+            .map(|id| UnitBox::new(id, Vec3::new(id as f32 * 2.0, 0.0, 0.0)))
+            .collect();
+        let bvh = BVH::try_build(&mut boxes).unwrap();
+        assert_eq!(bvh.nodes, BVH::build(&mut boxes).nodes);
+
+        for &invalid in &[f32::NAN, f32::INFINITY] {
+            boxes[7].pos.y = invalid;
+            assert_eq!(
+                BVH::try_build(&mut boxes).err(),
+                Some(BvhError::NonFiniteBounds { shape_index: 7 })
+            );
+        }
+
+        // More than `MAX_SHAPES` shapes do not fit into the memory of a test.
+        assert_eq!(BVH::check_shape_count(MAX_SHAPES), Ok(()));
+        assert_eq!(
+            BVH::check_shape_count(MAX_SHAPES + 1),
+            Err(BvhError::TooManyShapes {
+                shape_count: MAX_SHAPES + 1
+            })
+        );
+    }
+
+    #[test]
+    /// Tests whether `try_build` tells empty and inverted `AABB`s apart from `AABB`s with
+    /// `NaN` or infinite components, and reports the first invalid shape.
+    fn test_try_build_invalid_bounds() {
+        use crate::aabb::{Bounded, AABB};
+        use crate::bounding_hierarchy::BHShape;
+        use crate::bvh::BvhError;
+
+        /// A shape with a fixed `AABB`.
+        struct FixedBox {
+            aabb: AABB,
+            node_index: usize,
+        }
+
+        impl Bounded for FixedBox {
+            fn aabb(&self) -> AABB {
+                self.aabb
             }
-            BVHNode::Leaf { shape_index, .. } => {
-                let shape_aabb = shapes[shape_index].aabb();
-                assert!(
-                    expected_outer_aabb.approx_contains_aabb_eps(&shape_aabb, EPSILON),
-                    "Shape's AABB lies outside the expected bounds.\n\tBounds: {}\n\tShape: {}",
-                    expected_outer_aabb,
-                    shape_aabb
-                );
+        }
+
+        impl BHShape for FixedBox {
+            fn set_bh_node_index(&mut self, index: usize) {
+                self.node_index = index;
             }
+
+            fn bh_node_index(&self) -> usize {
+                self.node_index
+            }
+        }
+
+        let unit = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        let inverted = AABB::with_bounds(Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 1.0));
+        let infinite =
+            AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, f32::INFINITY, 1.0));
+        let nan = AABB::with_bounds(Vec3::new(0.0, f32::NAN, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        let empty = BvhError::EmptyBounds { shape_index: 2 };
+        let non_finite = BvhError::NonFiniteBounds { shape_index: 2 };
+
+        for &(invalid, expected) in &[
+            (AABB::empty(), empty),
+            (inverted, empty),
+            (infinite, non_finite),
+            (nan, non_finite),
+        ] {
+            let mut boxes: Vec<FixedBox> = (0..5)
+                .map(|_| FixedBox {
+                    aabb: unit,
+                    node_index: 0,
+                })
+                .collect();
+            boxes[2].aabb = invalid;
+            boxes[4].aabb = AABB::empty();
+            assert_eq!(BVH::try_build(&mut boxes).err(), Some(expected));
         }
     }
 
-    /// Assert version of `is_consistent`.
-    pub fn assert_consistent<Shape: BHShape>(&self, shapes: &[Shape]) {
-        // The root node of the bvh is not bounded by anything.
-        let space = AABB {
-            min: Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
-            max: Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
-        };
+    #[test]
+    /// Tests whether `build_with_aabbs` never asks the shapes for their `AABB`s, and builds
+    /// the same tree as `build` does from the shapes themselves.
+    fn test_build_with_aabbs() {
+        use crate::aabb::{Bounded, AABB};
+        use crate::bounding_hierarchy::BHShape;
+        use crate::testbase::{create_n_cubes, default_bounds};
 
-        // The counter for all nodes.
-        let mut node_count = 0;
-        self.assert_consistent_subtree(0, 0, &space, 0, &mut node_count, shapes);
+        /// A shape which only knows its node index and refuses to compute its `AABB`.
+        struct Opaque {
+            node_index: usize,
+        }
 
-        // Check if all nodes have been counted from the root node.
-        // If this is false, it means we have a detached subtree.
-        assert_eq!(node_count, self.nodes.len(), "Detached subtree");
-    }
+        impl Bounded for Opaque {
+            fn aabb(&self) -> AABB {
+                panic!("The AABB of an Opaque shape was requested");
+            }
+        }
 
-    /// Check that the `AABB`s in the `BVH` are tight, which means, that parent `AABB`s are not
-    /// larger than they should be. This function checks, whether the children of node `node_index`
-    /// lie inside `outer_aabb`.
-    pub fn assert_tight_subtree<Shape: BHShape>(
-        &self,
-        node_index: usize,
-        outer_aabb: &AABB,
-        shapes: &[Shape],
-    ) {
-        if let BVHNode::Node {
-            child_l_index,
-            child_l_aabb,
-            child_r_index,
-            child_r_aabb,
-            ..
-        } = self.nodes[node_index]
-        {
-            let joint_aabb = child_l_aabb.join(&child_r_aabb);
-            assert!(joint_aabb.relative_eq(outer_aabb, EPSILON));
-            self.assert_tight_subtree(child_l_index, &child_l_aabb, shapes);
-            self.assert_tight_subtree(child_r_index, &child_r_aabb, shapes);
+        impl BHShape for Opaque {
+            fn set_bh_node_index(&mut self, index: usize) {
+                self.node_index = index;
+            }
+
+            fn bh_node_index(&self) -> usize {
+                self.node_index
+            }
         }
-    }
 
-    /// Check that the `AABB`s in the `BVH` are tight, which means, that parent `AABB`s are not
-    /// larger than they should be.
-    pub fn assert_tight<Shape: BHShape>(&self, shapes: &[Shape]) {
-        // When starting to check whether the `BVH` is tight, we cannot provide a minimum
-        // outer `AABB`, therefore we compute the correct one in this instance.
-        if let BVHNode::Node {
-            child_l_aabb,
-            child_r_aabb,
-            ..
-        } = self.nodes[0]
-        {
-            let joint_aabb = child_l_aabb.join(&child_r_aabb);
-            self.assert_tight_subtree(0, &joint_aabb, shapes);
+        let mut triangles = create_n_cubes(200, &default_bounds());
+        let expected = BVH::build(&mut triangles);
+
+        let aabbs: Vec<AABB> = triangles.iter().map(Bounded::aabb).collect();
+        let mut opaque: Vec<Opaque> = (0..triangles.len())
+            .map(|_| Opaque { node_index: 0 })
+            .collect();
+        let bvh = BVH::build_with_aabbs(&mut opaque, &aabbs);
+
+        assert_eq!(format!("{:?}", bvh.nodes), format!("{:?}", expected.nodes));
+        for (shape, triangle) in opaque.iter().zip(triangles.iter()) {
+            assert_eq!(shape.bh_node_index(), triangle.bh_node_index());
         }
     }
-}
 
-impl BoundingHierarchy for BVH {
-    fn build<Shape: BHShape>(shapes: &mut [Shape]) -> BVH {
-        BVH::build(shapes)
-    }
+    #[test]
+    /// Tests whether SAH weights the shapes by their `intersection_cost`: for a row of
+    /// unit boxes, whose left half is ten times as expensive as the right half, the root
+    /// moves its split from the middle towards the expensive boxes.
+    fn test_intersection_cost_shifts_split() {
+        use crate::aabb::{Bounded, AABB};
+        use crate::bounding_hierarchy::BHShape;
 
-    fn traverse<'a, Shape: Bounded>(&'a self, ray: &Ray, shapes: &'a [Shape]) -> Vec<&Shape> {
-        self.traverse(ray, shapes)
-    }
+        /// A unit box at `x` on the x axis with a fixed intersection cost.
+        struct CostlyBox {
+            x: f32,
+            cost: f32,
+            node_index: usize,
+        }
 
-    fn pretty_print(&self) {
-        self.pretty_print();
-    }
-}
+        impl Bounded for CostlyBox {
+            fn aabb(&self) -> AABB {
+                let center = Vec3::new(self.x, 0.0, 0.0);
+                AABB::from_center_size(center, Vec3::new(1.0, 1.0, 1.0))
+            }
+        }
 
-#[cfg(test)]
-mod tests {
-    use crate::bvh::{BVHNode, BVH};
-    use crate::testbase::{build_some_bh, traverse_some_bh};
+        impl BHShape for CostlyBox {
+            fn set_bh_node_index(&mut self, index: usize) {
+                self.node_index = index;
+            }
+
+            fn bh_node_index(&self) -> usize {
+                self.node_index
+            }
+
+            fn intersection_cost(&self) -> f32 {
+                self.cost
+            }
+        }
+
+        let row = |expensive_cost: f32| -> Vec<CostlyBox> {
+            (0..20)
+                .map(|i| CostlyBox {
+                    x: i as f32,
+                    cost: if i < 10 { expensive_cost } else { 1.0 },
+                    node_index: 0,
+                })
+                .collect()
+        };
+
+        // With equal costs the root splits the row in half.
+        let mut uniform = row(1.0);
+        let bvh = BVH::build(&mut uniform);
+        assert_eq!(bvh.nodes[0].child_l_aabb().max.x, 9.5);
+
+        // The expensive boxes are split off into a smaller left child.
+        let mut weighted = row(10.0);
+        let bvh = BVH::build(&mut weighted);
+        assert_eq!(bvh.nodes[0].child_l_aabb().max.x, 6.5);
+        assert_eq!(bvh.validate(&weighted), Ok(()));
+
+        // Without any finite split cost the nodes are split at the median centroid.
+        for &cost in &[f32::INFINITY, f32::NAN] {
+            let mut broken = row(cost);
+            let bvh = BVH::build(&mut broken);
+            assert_eq!(bvh.nodes[0].child_l_aabb().max.x, 9.5);
+            assert_eq!(bvh.validate(&broken), Ok(()));
+        }
+    }
 
     #[test]
-    /// Tests whether the building procedure succeeds in not failing.
-    fn test_build_bvh() {
-        build_some_bh::<BVH>();
+    /// Tests whether `BVH`s with up to 8 shapes per leaf are consistent, reference every
+    /// shape exactly once and find exactly the shapes whose `AABB`s are hit by a ray.
+    fn test_build_with_leaf_sizes() {
+        use crate::aabb::{Bounded, AABB};
+        use crate::bounding_hierarchy::BHShape;
+        use crate::bvh::BuildConfig;
+        use crate::testbase::{create_n_cubes, default_bounds, next_Vec3};
+
+        let bounds = default_bounds();
+        let mut seed = 0;
+        for max_leaf_size in 1..=8 {
+            let mut triangles = create_n_cubes(100, &bounds);
+            let original: Vec<AABB> = triangles.iter().map(Bounded::aabb).collect();
+            let config = BuildConfig {
+                max_leaf_size,
+                ..Default::default()
+            };
+            let bvh = BVH::build_with_config(&mut triangles, &config);
+            bvh.assert_consistent(&triangles);
+            bvh.assert_tight(&triangles);
+
+            // The build leaves the shapes where they were.
+            for (triangle, original) in triangles.iter().zip(&original) {
+                let aabb = triangle.aabb();
+                assert_eq!((aabb.min, aabb.max), (original.min, original.max));
+            }
+
+            // Every shape lies in exactly one leaf, which the shape knows about.
+            let mut leaf_count = vec![0; triangles.len()];
+            for (node_index, node) in bvh.nodes.iter().enumerate() {
+                if let Some(range) = node.shape_range() {
+                    assert!(!range.is_empty() && range.len() <= max_leaf_size);
+                    for &shape_index in bvh.leaf_shapes(node_index) {
+                        leaf_count[shape_index] += 1;
+                        assert_eq!(triangles[shape_index].bh_node_index(), node_index);
+                    }
+                }
+            }
+            assert!(leaf_count.iter().all(|count| *count == 1));
+
+            for _ in 0..100 {
+                let ray = Ray::new(next_Vec3(&mut seed, &bounds), next_Vec3(&mut seed, &bounds));
+                let mut hits = bvh.traverse_indices(&ray, &triangles);
+                hits.sort();
+                let expected: Vec<usize> = (0..triangles.len())
+                    .filter(|&i| ray.intersects_aabb(&triangles[i].aabb()))
+                    .collect();
+                assert_eq!(hits, expected);
+                assert_eq!(bvh.traverse_iterator(&ray, &triangles).count(), hits.len());
+            }
+        }
     }
 
     #[test]
-    /// Runs some primitive tests for intersections of a ray with a fixed scene given as a BVH.
-    fn test_traverse_bvh() {
-        traverse_some_bh::<BVH>();
+    /// Tests whether reordering the shapes of an optimized `BVH` stores them in leaf
+    /// order, keeps the `BVH` valid and leaves the traversal results unchanged.
+    fn test_reorder_shapes() {
+        use crate::testbase::{
+            create_n_cubes, default_bounds, next_Vec3, randomly_transform_scene, Triangle,
+        };
+
+        // Identifies a triangle by the bit patterns of its vertices.
+        fn key(triangle: &Triangle) -> Vec<u32> {
+            let vertices = [triangle.a, triangle.b, triangle.c];
+            let components = vertices.iter().flat_map(|v| vec![v.x, v.y, v.z]);
+            components.map(f32::to_bits).collect()
+        }
+        fn hit_keys(bvh: &BVH, ray: &Ray, triangles: &[Triangle]) -> Vec<Vec<u32>> {
+            let mut keys: Vec<Vec<u32>> =
+                bvh.traverse(ray, triangles).into_iter().map(key).collect();
+            keys.sort();
+            keys
+        }
+
+        let bounds = default_bounds();
+        let mut seed = 0;
+        let mut triangles = create_n_cubes(1_000, &bounds);
+        let mut bvh = BVH::build(&mut triangles);
+        let updated = randomly_transform_scene(&mut triangles, 3_000, &bounds, None, &mut seed);
+        bvh.optimize(&updated, &triangles);
+
+        let rays: Vec<Ray> = (0..100)
+            .map(|_| Ray::new(next_Vec3(&mut seed, &bounds), next_Vec3(&mut seed, &bounds)))
+            .collect();
+        let expected: Vec<Vec<Vec<u32>>> = rays
+            .iter()
+            .map(|ray| hit_keys(&bvh, ray, &triangles))
+            .collect();
+
+        bvh.reorder_shapes(&mut triangles);
+        assert_eq!(bvh.validate(&triangles), Ok(()));
+        assert!(bvh.leaf_shape_indices().eq(0..triangles.len()));
+        for (ray, expected) in rays.iter().zip(expected) {
+            assert_eq!(hit_keys(&bvh, ray, &triangles), expected);
+        }
     }
 
     #[test]
-    /// Verify contents of the bounding hierarchy for a fixed scene structure
-    fn test_bvh_shape_indices() {
-        use std::collections::HashSet;
+    /// Tests whether reordering the shapes of a `BVH` with spatial splits, where some
+    /// shapes are referenced by several leaves, keeps every leaf pointing at its shapes.
+    fn test_reorder_shapes_with_spatial_splits() {
+        use crate::aabb::AABB;
+        use crate::bounding_hierarchy::BHShape;
+        use crate::bvh::BuildConfig;
+        use crate::testbase::{create_thin_triangles, next_Vec3, Triangle};
+        use std::mem;
 
-        let (all_shapes, bh) = build_some_bh::<BVH>();
+        fn key(triangle: &Triangle) -> Vec<u32> {
+            let vertices = [triangle.a, triangle.b, triangle.c];
+            let components = vertices.iter().flat_map(|v| vec![v.x, v.y, v.z]);
+            components.map(f32::to_bits).collect()
+        }
+        fn hit_keys(bvh: &BVH, ray: &Ray, triangles: &[Triangle]) -> Vec<Vec<u32>> {
+            let mut keys: Vec<Vec<u32>> = bvh
+                .traverse_dedup(ray, triangles)
+                .into_iter()
+                .map(key)
+                .collect();
+            keys.sort();
+            keys
+        }
 
-        // It should find all shape indices.
-        let expected_shapes: HashSet<_> = (0..all_shapes.len()).collect();
-        let mut found_shapes = HashSet::new();
+        let bounds = AABB::with_bounds(
+            Vec3::new(-100.0, -100.0, -100.0),
+            Vec3::new(100.0, 100.0, 100.0),
+        );
+        let mut seed = 0;
+        let mut triangles = create_thin_triangles(500, &bounds);
+        let config = BuildConfig {
+            spatial_splits: true,
+            ..Default::default()
+        };
+        let mut bvh = BVH::build_with_config(&mut triangles, &config);
+        assert!(bvh.leaf_shape_indices().count() > triangles.len());
 
-        for node in bh.nodes.iter() {
-            match *node {
-                BVHNode::Node { .. } => {
-                    assert_eq!(node.shape_index(), None);
-                }
-                BVHNode::Leaf { .. } => {
-                    found_shapes.insert(
-                        node.shape_index()
-                            .expect("getting a shape index from a leaf node"),
-                    );
-                }
-            }
+        let rays: Vec<Ray> = (0..100)
+            .map(|_| Ray::new(next_Vec3(&mut seed, &bounds), next_Vec3(&mut seed, &bounds)))
+            .collect();
+        let expected: Vec<Vec<Vec<u32>>> = rays
+            .iter()
+            .map(|ray| hit_keys(&bvh, ray, &triangles))
+            .collect();
+
+        bvh.reorder_shapes(&mut triangles);
+        for (ray, expected) in rays.iter().zip(expected) {
+            assert_eq!(hit_keys(&bvh, ray, &triangles), expected);
         }
 
-        assert_eq!(expected_shapes, found_shapes);
+        // The first references are in increasing order, and every shape lies in the leaf
+        // it was told about.
+        let mut referenced = vec![false; triangles.len()];
+        let first_references: Vec<usize> = bvh
+            .leaf_shape_indices()
+            .filter(|index| !mem::replace(&mut referenced[*index], true))
+            .collect();
+        assert_eq!(
+            first_references,
+            (0..triangles.len()).collect::<Vec<usize>>()
+        );
+        for (index, triangle) in triangles.iter().enumerate() {
+            assert!(bvh.leaf_shapes(triangle.bh_node_index()).contains(&index));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    /// Tests whether building with less than two buckets is rejected.
+    fn test_build_with_one_bucket_panics() {
+        use crate::bvh::BuildConfig;
+
+        let (mut shapes, _) = build_some_bh::<BVH>();
+        BVH::build_with_config(
+            &mut shapes,
+            &BuildConfig {
+                bucket_count: 1,
+                ..Default::default()
+            },
+        );
     }
 }
 
-#[cfg(all(features = "nightly", test))]
+#[cfg(all(feature = "bench", test))]
 mod bench {
-    use bvh_ultraviolet::BVH;
-    use testbase::{
-        build_1200_triangles_bh, build_120k_triangles_bh, build_12k_triangles_bh, build_some_bh,
+    use crate::bvh::{BuildConfig, SplitStrategy, BVH};
+    use crate::testbase::{
+        build_1200_triangles_bh, build_120k_triangles_bh, build_12k_triangles_bh,
+        create_mixed_scale_scene, create_n_cubes, create_thin_triangles, default_bounds,
         intersect_1200_triangles_bh, intersect_120k_triangles_bh, intersect_12k_triangles_bh,
-        intersect_bh, load_sponza_scene, traverse_some_bh,
+        intersect_bh, load_sponza_scene,
     };
 
     #[bench]
@@ -815,4 +4295,203 @@ mod bench {
         let bvh = BVH::build(&mut triangles);
         intersect_bh(&bvh, &triangles, &bounds, b)
     }
+
+    #[bench]
+    /// Benchmark the construction of a `BVH` with 12,000 small triangles and a few huge
+    /// ones.
+    fn bench_build_mixed_scale_bvh(b: &mut ::test::Bencher) {
+        let mut triangles = create_mixed_scale_scene(1_000, &default_bounds());
+        b.iter(|| {
+            BVH::build(&mut triangles);
+        });
+    }
+
+    #[bench]
+    /// Benchmark intersecting 12,000 small triangles and a few huge ones using the
+    /// recursive `BVH`.
+    fn bench_intersect_mixed_scale_bvh(b: &mut ::test::Bencher) {
+        let bounds = default_bounds();
+        let mut triangles = create_mixed_scale_scene(1_000, &bounds);
+        let bvh = BVH::build(&mut triangles);
+        intersect_bh(&bvh, &triangles, &bounds, b)
+    }
+
+    /// Benchmark the construction of a `BVH` over long, thin triangles with or without
+    /// spatial splits.
+    fn build_thin_triangles(spatial_splits: bool, b: &mut ::test::Bencher) {
+        let mut triangles = create_thin_triangles(10_000, &default_bounds());
+        let config = BuildConfig {
+            spatial_splits,
+            ..Default::default()
+        };
+        b.iter(|| {
+            BVH::build_with_config(&mut triangles, &config);
+        });
+    }
+
+    /// Benchmark intersecting long, thin triangles using a `BVH` built with or without
+    /// spatial splits.
+    fn intersect_thin_triangles(spatial_splits: bool, b: &mut ::test::Bencher) {
+        let bounds = default_bounds();
+        let mut triangles = create_thin_triangles(10_000, &bounds);
+        let config = BuildConfig {
+            spatial_splits,
+            ..Default::default()
+        };
+        let bvh = BVH::build_with_config(&mut triangles, &config);
+        intersect_bh(&bvh, &triangles, &bounds, b)
+    }
+
+    #[bench]
+    /// Benchmark the construction of a `BVH` over 10,000 thin triangles.
+    fn bench_build_thin_triangles_bvh(b: &mut ::test::Bencher) {
+        build_thin_triangles(false, b);
+    }
+
+    #[bench]
+    /// Benchmark the construction of a `BVH` with spatial splits over 10,000 thin triangles.
+    fn bench_build_thin_triangles_spatial_splits_bvh(b: &mut ::test::Bencher) {
+        build_thin_triangles(true, b);
+    }
+
+    #[bench]
+    /// Benchmark intersecting 10,000 thin triangles using a `BVH`.
+    fn bench_intersect_thin_triangles_bvh(b: &mut ::test::Bencher) {
+        intersect_thin_triangles(false, b);
+    }
+
+    #[bench]
+    /// Benchmark intersecting 10,000 thin triangles using a `BVH` with spatial splits.
+    fn bench_intersect_thin_triangles_spatial_splits_bvh(b: &mut ::test::Bencher) {
+        intersect_thin_triangles(true, b);
+    }
+
+    /// Benchmark the construction of a `BVH` with 12,000 triangles using `bucket_count`
+    /// SAH buckets.
+    fn build_12k_triangles_with_buckets(bucket_count: usize, b: &mut ::test::Bencher) {
+        let mut triangles = create_n_cubes(1_000, &default_bounds());
+        let config = BuildConfig {
+            bucket_count,
+            ..Default::default()
+        };
+        b.iter(|| {
+            BVH::build_with_config(&mut triangles, &config);
+        });
+    }
+
+    /// Benchmark intersecting 12,000 triangles using a `BVH` built with `bucket_count`
+    /// SAH buckets.
+    fn intersect_12k_triangles_with_buckets(bucket_count: usize, b: &mut ::test::Bencher) {
+        let bounds = default_bounds();
+        let mut triangles = create_n_cubes(1_000, &bounds);
+        let bvh = BVH::build_with_config(
+            &mut triangles,
+            &BuildConfig {
+                bucket_count,
+                ..Default::default()
+            },
+        );
+        intersect_bh(&bvh, &triangles, &bounds, b)
+    }
+
+    /// Benchmark intersecting 12,000 triangles using a `BVH` with up to `max_leaf_size`
+    /// shapes per leaf.
+    fn intersect_12k_triangles_with_leaf_size(max_leaf_size: usize, b: &mut ::test::Bencher) {
+        let bounds = default_bounds();
+        let mut triangles = create_n_cubes(1_000, &bounds);
+        let config = BuildConfig {
+            max_leaf_size,
+            ..Default::default()
+        };
+        let bvh = BVH::build_with_config(&mut triangles, &config);
+        intersect_bh(&bvh, &triangles, &bounds, b)
+    }
+
+    #[bench]
+    fn bench_intersect_12k_triangles_bvh_leaf_size_1(b: &mut ::test::Bencher) {
+        intersect_12k_triangles_with_leaf_size(1, b);
+    }
+
+    #[bench]
+    fn bench_intersect_12k_triangles_bvh_leaf_size_2(b: &mut ::test::Bencher) {
+        intersect_12k_triangles_with_leaf_size(2, b);
+    }
+
+    #[bench]
+    fn bench_intersect_12k_triangles_bvh_leaf_size_4(b: &mut ::test::Bencher) {
+        intersect_12k_triangles_with_leaf_size(4, b);
+    }
+
+    #[bench]
+    fn bench_intersect_12k_triangles_bvh_leaf_size_8(b: &mut ::test::Bencher) {
+        intersect_12k_triangles_with_leaf_size(8, b);
+    }
+
+    #[bench]
+    fn bench_build_12k_triangles_bvh_2_buckets(b: &mut ::test::Bencher) {
+        build_12k_triangles_with_buckets(2, b);
+    }
+
+    #[bench]
+    fn bench_build_12k_triangles_bvh_6_buckets(b: &mut ::test::Bencher) {
+        build_12k_triangles_with_buckets(6, b);
+    }
+
+    #[bench]
+    fn bench_build_12k_triangles_bvh_12_buckets(b: &mut ::test::Bencher) {
+        build_12k_triangles_with_buckets(12, b);
+    }
+
+    #[bench]
+    fn bench_build_12k_triangles_bvh_32_buckets(b: &mut ::test::Bencher) {
+        build_12k_triangles_with_buckets(32, b);
+    }
+
+    /// Benchmark the construction of a `BVH` with 12,000 triangles using the given
+    /// `SplitStrategy`.
+    fn build_12k_triangles_with_strategy(split_strategy: SplitStrategy, b: &mut ::test::Bencher) {
+        let mut triangles = create_n_cubes(1_000, &default_bounds());
+        let config = BuildConfig {
+            split_strategy,
+            ..Default::default()
+        };
+        b.iter(|| {
+            BVH::build_with_config(&mut triangles, &config);
+        });
+    }
+
+    #[bench]
+    fn bench_build_12k_triangles_bvh_sah(b: &mut ::test::Bencher) {
+        build_12k_triangles_with_strategy(SplitStrategy::Sah, b);
+    }
+
+    #[bench]
+    fn bench_build_12k_triangles_bvh_object_median(b: &mut ::test::Bencher) {
+        build_12k_triangles_with_strategy(SplitStrategy::ObjectMedian, b);
+    }
+
+    #[bench]
+    fn bench_build_12k_triangles_bvh_spatial_median(b: &mut ::test::Bencher) {
+        build_12k_triangles_with_strategy(SplitStrategy::SpatialMedian, b);
+    }
+
+    #[bench]
+    fn bench_intersect_12k_triangles_bvh_2_buckets(b: &mut ::test::Bencher) {
+        intersect_12k_triangles_with_buckets(2, b);
+    }
+
+    #[bench]
+    fn bench_intersect_12k_triangles_bvh_6_buckets(b: &mut ::test::Bencher) {
+        intersect_12k_triangles_with_buckets(6, b);
+    }
+
+    #[bench]
+    fn bench_intersect_12k_triangles_bvh_12_buckets(b: &mut ::test::Bencher) {
+        intersect_12k_triangles_with_buckets(12, b);
+    }
+
+    #[bench]
+    fn bench_intersect_12k_triangles_bvh_32_buckets(b: &mut ::test::Bencher) {
+        intersect_12k_triangles_with_buckets(32, b);
+    }
 }
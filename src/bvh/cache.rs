@@ -0,0 +1,292 @@
+//! This module defines a [`TraversalCache`], which carries state from one traversal of a
+//! [`BVH`] to the next.
+//!
+//! [`BVH`]: struct.BVH.html
+//! [`TraversalCache`]: struct.TraversalCache.html
+//!
+
+use crate::aabb::{Bounded, AABB};
+use crate::bvh::*;
+use crate::ray::Ray;
+use ultraviolet::Vec3;
+
+/// State which is reused by consecutive calls to [`BVH::traverse_cached`].
+///
+/// It holds the stack of node indices, which keeps its allocation from one traversal to
+/// the next, and a hint: the deepest node whose [`AABB`] contains the origin of the last
+/// [`Ray`]. A [`TraversalCache`] can be used with any [`BVH`], but the hint is only useful
+/// for the [`BVH`] it was last used with.
+///
+/// [`AABB`]: ../aabb/struct.AABB.html
+/// [`BVH`]: struct.BVH.html
+/// [`BVH::traverse_cached`]: struct.BVH.html#method.traverse_cached
+/// [`Ray`]: ../ray/struct.Ray.html
+/// [`TraversalCache`]: struct.TraversalCache.html
+///
+#[derive(Debug, Clone, Default)]
+pub struct TraversalCache {
+    /// The stack of node indices, which is empty between traversals.
+    stack: Vec<usize>,
+
+    /// The index of the deepest node whose `AABB` contained the last origin.
+    node_index: usize,
+}
+
+impl TraversalCache {
+    /// Creates an empty [`TraversalCache`]. Does not allocate.
+    ///
+    /// [`TraversalCache`]: struct.TraversalCache.html
+    ///
+    pub fn new() -> TraversalCache {
+        TraversalCache::default()
+    }
+}
+
+impl BVH {
+    /// Traverses the [`BVH`] like [`traverse`], but reuses the stack and the node hint
+    /// stored in `cache`.
+    ///
+    /// This is meant for temporally coherent queries, where the origin of each [`Ray`]
+    /// lies close to the origin of the previous one, such as the rays cast from a moving
+    /// camera or character frame after frame. The descent starts at the deepest node whose
+    /// [`AABB`] contains the origin of `ray`. That node is found by climbing up from the
+    /// node of the previous origin until an [`AABB`] contains the new origin, and then
+    /// descending again, which only takes a few steps if the origin moved a little. Its
+    /// subtree is traversed first, followed by the siblings on the path back to the root.
+    /// If the origin lies outside of the [`BVH`], the descent starts at the root.
+    ///
+    /// The result contains the same shapes as [`traverse`]. The shapes near the origin of
+    /// `ray` are returned first, so the order may differ; it is identical whenever the
+    /// descent starts at the root. Incoherent rays are still traversed correctly, but
+    /// gain nothing over [`traverse`] apart from the reused stack.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::{TraversalCache, BVH};
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// // A ray which moves along the x axis from frame to frame.
+    /// let mut cache = TraversalCache::new();
+    /// for frame in 0..100 {
+    ///     let origin = Vec3::new(frame as f32 * 0.5, 0.0, 0.0);
+    ///     let ray = Ray::new(origin, Vec3::new(0.0, 1.0, 0.0));
+    ///     let hits = bvh.traverse_cached(&ray, &spheres, &mut cache);
+    ///     assert_eq!(hits.len(), bvh.traverse(&ray, &spheres).len());
+    /// }
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`Ray`]: ../ray/struct.Ray.html
+    /// [`traverse`]: struct.BVH.html#method.traverse
+    ///
+    pub fn traverse_cached<'a, Shape: Bounded>(
+        &'a self,
+        ray: &Ray,
+        shapes: &'a [Shape],
+        cache: &mut TraversalCache,
+    ) -> Vec<&'a Shape> {
+        let mut hits = Vec::new();
+        if self.nodes.is_empty() {
+            return hits;
+        }
+
+        let start_index = self.find_origin_node(&ray.origin, cache.node_index);
+        cache.node_index = start_index;
+        let stack = &mut cache.stack;
+        stack.clear();
+
+        // The root has no stored `AABB` and is entered unconditionally, like in `traverse`.
+        if start_index == 0 || ray.intersects_aabb(&self.child_aabb(start_index)) {
+            self.traverse_subtree(start_index, ray, shapes, stack, &mut |index| {
+                hits.push(&shapes[index])
+            });
+        }
+
+        // Visit the siblings on the path from the start node back up to the root.
+        let mut node_index = start_index;
+        while node_index != 0 {
+            let parent_index = self.nodes[node_index].parent();
+            let parent = &self.nodes[parent_index];
+            let (sibling_index, sibling_aabb) = if parent.child_l() == node_index {
+                (parent.child_r(), parent.child_r_aabb())
+            } else {
+                (parent.child_l(), parent.child_l_aabb())
+            };
+            if ray.intersects_aabb(&sibling_aabb) {
+                self.traverse_subtree(sibling_index, ray, shapes, stack, &mut |index| {
+                    hits.push(&shapes[index])
+                });
+            }
+            node_index = parent_index;
+        }
+        hits
+    }
+
+    /// Returns the index of the deepest node whose [`AABB`] contains `origin`, starting the
+    /// search at `hint_index`. Returns `0` if no node contains `origin`.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    fn find_origin_node(&self, origin: &Vec3, hint_index: usize) -> usize {
+        // The hint may stem from another `BVH`.
+        let mut node_index = if hint_index < self.nodes.len() {
+            hint_index
+        } else {
+            0
+        };
+        while node_index != 0 && !self.child_aabb(node_index).contains(origin) {
+            node_index = self.nodes[node_index].parent();
+        }
+        while let BVHNode::Node {
+            child_l_aabb,
+            child_l_index,
+            child_r_aabb,
+            child_r_index,
+            ..
+        } = self.nodes[node_index]
+        {
+            node_index = if child_l_aabb.contains(origin) {
+                child_l_index
+            } else if child_r_aabb.contains(origin) {
+                child_r_index
+            } else {
+                break;
+            };
+        }
+        node_index
+    }
+
+    /// Returns the [`AABB`] of the node at `node_index`, which is stored in its parent.
+    /// Must not be called for the root.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    pub(crate) fn child_aabb(&self, node_index: usize) -> AABB {
+        let parent = &self.nodes[self.nodes[node_index].parent()];
+        if parent.child_l() == node_index {
+            parent.child_l_aabb()
+        } else {
+            parent.child_r_aabb()
+        }
+    }
+
+    /// Traverses the subtree below `node_index`, whose [`AABB`] has already been tested,
+    /// with `stack` and passes the indices of the hit shapes to `hit` in the order of
+    /// [`traverse`].
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`traverse`]: struct.BVH.html#method.traverse
+    ///
+    fn traverse_subtree<Shape: Bounded, F: FnMut(usize)>(
+        &self,
+        node_index: usize,
+        ray: &Ray,
+        shapes: &[Shape],
+        stack: &mut Vec<usize>,
+        hit: &mut F,
+    ) {
+        stack.push(node_index);
+        while let Some(node_index) = stack.pop() {
+            match self.nodes[node_index] {
+                BVHNode::Node {
+                    ref child_l_aabb,
+                    child_l_index,
+                    ref child_r_aabb,
+                    child_r_index,
+                    ..
+                } => {
+                    // Push the right child first, so the left subtree is visited first.
+                    if ray.intersects_aabb(child_r_aabb) {
+                        stack.push(child_r_index);
+                    }
+                    if ray.intersects_aabb(child_l_aabb) {
+                        stack.push(child_l_index);
+                    }
+                }
+                BVHNode::Leaf { .. } => match *self.leaf_shapes(node_index) {
+                    // The parent has already tested the `AABB` of this shape.
+                    [shape_index] if node_index != 0 => hit(shape_index),
+                    ref leaf_shapes => leaf_shapes
+                        .iter()
+                        .copied()
+                        .filter(|index| ray.intersects_aabb(&shapes[*index].aabb()))
+                        .for_each(&mut *hit),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bvh::{BuildConfig, TraversalCache, BVH};
+    use crate::ray::Ray;
+    use crate::testbase::{create_n_cubes, default_bounds, next_Vec3, Triangle};
+    use std::collections::HashSet;
+    use ultraviolet::Vec3;
+
+    /// Returns the addresses of `triangles`, to compare results regardless of their order.
+    fn to_set(triangles: Vec<&Triangle>) -> HashSet<*const Triangle> {
+        triangles
+            .into_iter()
+            .map(|triangle| triangle as *const _)
+            .collect()
+    }
+
+    #[test]
+    /// Tests whether `traverse_cached` returns the same shapes as `traverse` for a ray
+    /// which moves a little from query to query, for one which jumps through the scene
+    /// and for one which starts outside of the scene, in the same order in the latter case.
+    fn test_traverse_cached_equals_traverse() {
+        let bounds = default_bounds();
+        let mut seed = 0;
+
+        for &max_leaf_size in &[1, 4] {
+            let mut triangles = create_n_cubes(1_000, &bounds);
+            let config = BuildConfig {
+                max_leaf_size,
+                ..Default::default()
+            };
+            let bvh = BVH::build_with_config(&mut triangles, &config);
+            let mut cache = TraversalCache::new();
+
+            let mut seeded = 0;
+            let mut origin = next_Vec3(&mut seed, &bounds);
+            for step in 0..200 {
+                origin = if step % 50 == 0 {
+                    next_Vec3(&mut seed, &bounds)
+                } else {
+                    origin + Vec3::new(1.0, 2.0, -1.5)
+                };
+                let ray = Ray::new(origin, next_Vec3(&mut seed, &bounds) - origin);
+
+                let hits = bvh.traverse_cached(&ray, &triangles, &mut cache);
+                let expected = bvh.traverse(&ray, &triangles);
+                assert_eq!(hits.len(), expected.len());
+                assert_eq!(to_set(hits), to_set(expected));
+                if cache.node_index != 0 {
+                    seeded += 1;
+                }
+            }
+            assert!(seeded > 0);
+
+            // Outside of the scene the descent starts at the root, like `traverse`.
+            let origin = Vec3::new(10_000.0, 0.0, 0.0);
+            let ray = Ray::new(origin, -origin);
+            let hits = bvh.traverse_cached(&ray, &triangles, &mut cache);
+            let expected = bvh.traverse(&ray, &triangles);
+            assert_eq!(hits.len(), expected.len());
+            assert!(hits.iter().zip(expected).all(|(a, b)| std::ptr::eq(*a, b)));
+        }
+    }
+}
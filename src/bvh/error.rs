@@ -0,0 +1,269 @@
+//! This module defines [`BvhError`], the error type for operations on a [`BVH`].
+//!
+//! [`BVH`]: struct.BVH.html
+//! [`BvhError`]: enum.BvhError.html
+//!
+
+use crate::bvh::MAX_SHAPES;
+
+use std::error::Error;
+use std::fmt;
+
+/// Describes what is wrong with a [`BVH`] or the shapes it is built from. Returned by
+/// [`BVH::validate`] and [`BVH::try_build`].
+///
+/// [`BVH`]: struct.BVH.html
+/// [`BVH::try_build`]: struct.BVH.html#method.try_build
+/// [`BVH::validate`]: struct.BVH.html#method.validate
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BvhError {
+    /// A node references a child node which does not exist.
+    ChildIndexOutOfBounds {
+        /// The index of the inner node.
+        node_index: usize,
+
+        /// The index of the missing child.
+        child_index: usize,
+    },
+
+    /// A node is referenced by more than one parent, or the tree contains a cycle.
+    NodeReachedTwice {
+        /// The index of the node.
+        node_index: usize,
+    },
+
+    /// A node cannot be reached from the root.
+    DetachedNode {
+        /// The index of the node.
+        node_index: usize,
+    },
+
+    /// A node does not store the index of the node which references it as its parent.
+    WrongParent {
+        /// The index of the node.
+        node_index: usize,
+
+        /// The index of the node which references this node.
+        expected: usize,
+
+        /// The parent index stored in the node.
+        actual: usize,
+    },
+
+    /// A node does not store its actual depth.
+    WrongDepth {
+        /// The index of the node.
+        node_index: usize,
+
+        /// The actual depth of the node.
+        expected: u32,
+
+        /// The depth stored in the node.
+        actual: u32,
+    },
+
+    /// The [`AABB`] which an inner node stores for a child does not contain the
+    /// [`AABB`]s of the child's subtree.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    ChildOutsideParent {
+        /// The index of the inner node.
+        node_index: usize,
+
+        /// The index of the child.
+        child_index: usize,
+    },
+
+    /// A leaf references entries past the end of [`BVH::shape_indices`].
+    ///
+    /// [`BVH::shape_indices`]: struct.BVH.html#structfield.shape_indices
+    ///
+    LeafOutOfBounds {
+        /// The index of the leaf.
+        node_index: usize,
+    },
+
+    /// A leaf references a shape which does not exist.
+    ShapeIndexOutOfBounds {
+        /// The index of the leaf.
+        node_index: usize,
+
+        /// The index of the missing shape.
+        shape_index: usize,
+    },
+
+    /// The [`AABB`] of a shape lies outside of the [`AABB`] its parent stores for its leaf.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    ShapeOutsideLeaf {
+        /// The index of the leaf.
+        node_index: usize,
+
+        /// The index of the shape.
+        shape_index: usize,
+    },
+
+    /// A shape is referenced by more than one leaf.
+    DuplicateShape {
+        /// The index of the shape.
+        shape_index: usize,
+    },
+
+    /// A shape is not referenced by any leaf.
+    MissingShape {
+        /// The index of the shape.
+        shape_index: usize,
+    },
+
+    /// A shape does not store the index of the leaf which references it.
+    WrongShapeNodeIndex {
+        /// The index of the shape.
+        shape_index: usize,
+
+        /// The index of the leaf which references the shape.
+        expected: usize,
+
+        /// The node index stored in the shape.
+        actual: usize,
+    },
+
+    /// A [`BVH`] was to be built from no shapes at all.
+    ///
+    /// [`BVH`]: struct.BVH.html
+    ///
+    EmptyInput,
+
+    /// The [`AABB`] of a shape is empty, i.e. its `min` exceeds its `max` on some axis,
+    /// like [`AABB::empty`].
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`AABB::empty`]: ../aabb/struct.AABB.html#method.empty
+    ///
+    EmptyBounds {
+        /// The index of the shape.
+        shape_index: usize,
+    },
+
+    /// The [`AABB`] of a shape has a `NaN` or infinite component.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    NonFiniteBounds {
+        /// The index of the shape.
+        shape_index: usize,
+    },
+
+    /// A [`BVH`] was to be built from more than [`MAX_SHAPES`] shapes.
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`MAX_SHAPES`]: constant.MAX_SHAPES.html
+    ///
+    TooManyShapes {
+        /// The number of shapes.
+        shape_count: usize,
+    },
+}
+
+impl fmt::Display for BvhError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BvhError::ChildIndexOutOfBounds {
+                node_index,
+                child_index,
+            } => write!(
+                f,
+                "Node {} references the non-existent child {}",
+                node_index, child_index
+            ),
+            BvhError::NodeReachedTwice { node_index } => {
+                write!(f, "Node {} is reachable on more than one path", node_index)
+            }
+            BvhError::DetachedNode { node_index } => {
+                write!(f, "Node {} is not reachable from the root", node_index)
+            }
+            BvhError::WrongParent {
+                node_index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Wrong parent index of node {}. Expected: {}; Actual: {}",
+                node_index, expected, actual
+            ),
+            BvhError::WrongDepth {
+                node_index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Wrong depth of node {}. Expected: {}; Actual: {}",
+                node_index, expected, actual
+            ),
+            BvhError::ChildOutsideParent {
+                node_index,
+                child_index,
+            } => write!(
+                f,
+                "The subtree of child {} lies outside the bounds stored in node {}",
+                child_index, node_index
+            ),
+            BvhError::LeafOutOfBounds { node_index } => write!(
+                f,
+                "Leaf {} references entries past the end of the shape indices",
+                node_index
+            ),
+            BvhError::ShapeIndexOutOfBounds {
+                node_index,
+                shape_index,
+            } => write!(
+                f,
+                "Leaf {} references the non-existent shape {}",
+                node_index, shape_index
+            ),
+            BvhError::ShapeOutsideLeaf {
+                node_index,
+                shape_index,
+            } => write!(
+                f,
+                "Shape {} lies outside the bounds of leaf {}",
+                shape_index, node_index
+            ),
+            BvhError::DuplicateShape { shape_index } => {
+                write!(
+                    f,
+                    "Shape {} is referenced by more than one leaf",
+                    shape_index
+                )
+            }
+            BvhError::MissingShape { shape_index } => {
+                write!(f, "Shape {} is not referenced by any leaf", shape_index)
+            }
+            BvhError::WrongShapeNodeIndex {
+                shape_index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Wrong node index of shape {}. Expected: {}; Actual: {}",
+                shape_index, expected, actual
+            ),
+            BvhError::EmptyInput => write!(f, "Cannot build a BVH without shapes"),
+            BvhError::EmptyBounds { shape_index } => {
+                write!(f, "Shape {} has empty bounds", shape_index)
+            }
+            BvhError::NonFiniteBounds { shape_index } => {
+                write!(f, "Shape {} has non-finite bounds", shape_index)
+            }
+            BvhError::TooManyShapes { shape_count } => write!(
+                f,
+                "Cannot build a BVH from {} shapes, the maximum is {}",
+                shape_count, MAX_SHAPES
+            ),
+        }
+    }
+}
+
+impl Error for BvhError {}
@@ -0,0 +1,344 @@
+//! This module defines iterators over the shapes of a [`BVH`], one for traversing it with
+//! a [`Ray`] without allocating and one for visiting the shapes in leaf order.
+//!
+//! [`BVH`]: struct.BVH.html
+//! [`Ray`]: ../ray/struct.Ray.html
+//!
+
+use crate::aabb::Bounded;
+use crate::bvh::*;
+use crate::ray::Ray;
+use std::iter;
+use std::slice;
+
+/// Iterator over the shapes of a [`BVH`] whose [`AABB`]s are hit by a [`Ray`].
+/// Created by [`BVH::traverse_iterator`].
+///
+/// Instead of keeping a stack, the iterator walks back up the tree using the parent
+/// indices stored in the [`BVHNode`]s, much like the [`FlatBVH`] uses its exit indices.
+/// It therefore never allocates and works for trees of any depth.
+///
+/// [`AABB`]: ../aabb/struct.AABB.html
+/// [`BVH`]: struct.BVH.html
+/// [`BVH::traverse_iterator`]: struct.BVH.html#method.traverse_iterator
+/// [`BVHNode`]: enum.BVHNode.html
+/// [`FlatBVH`]: ../flat_bvh/type.FlatBVH.html
+/// [`Ray`]: ../ray/struct.Ray.html
+///
+pub struct TraverseIterator<'a, Shape: Bounded> {
+    /// The [`BVH`] to traverse.
+    ///
+    /// [`BVH`]: struct.BVH.html
+    ///
+    bvh: &'a BVH,
+
+    /// The [`Ray`] to test the [`AABB`]s against.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`Ray`]: ../ray/struct.Ray.html
+    ///
+    ray: &'a Ray,
+
+    /// The shapes the [`BVH`] was built from.
+    ///
+    /// [`BVH`]: struct.BVH.html
+    ///
+    shapes: &'a [Shape],
+
+    /// The index of the next node to visit, whose [`AABB`] is already known to be hit.
+    /// `None` once the traversal has finished.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    next_node: Option<usize>,
+
+    /// The indices of the shapes of the current leaf which have not been yielded yet.
+    leaf_shapes: slice::Iter<'a, usize>,
+
+    /// Whether the [`AABB`]s of the `leaf_shapes` have to be tested, which is the case
+    /// for leaves with more than one shape.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    test_leaf_shapes: bool,
+}
+
+impl<'a, Shape: Bounded> TraverseIterator<'a, Shape> {
+    /// Creates a new [`TraverseIterator`] starting at the root of the `bvh`.
+    ///
+    /// [`TraverseIterator`]: struct.TraverseIterator.html
+    ///
+    pub fn new(bvh: &'a BVH, ray: &'a Ray, shapes: &'a [Shape]) -> TraverseIterator<'a, Shape> {
+        TraverseIterator {
+            bvh,
+            ray,
+            shapes,
+            next_node: if bvh.nodes.is_empty() { None } else { Some(0) },
+            leaf_shapes: [].iter(),
+            test_leaf_shapes: false,
+        }
+    }
+
+    /// Finds the next node to visit after the subtree at `node_index` has been processed.
+    /// Walks up the tree until it finds a right sibling whose [`AABB`] is hit by the [`Ray`].
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`Ray`]: ../ray/struct.Ray.html
+    ///
+    fn next_after_subtree(&self, mut node_index: usize) -> Option<usize> {
+        let nodes = &self.bvh.nodes;
+        while node_index != 0 {
+            let parent_index = nodes[node_index].parent();
+            if let BVHNode::Node {
+                child_l_index,
+                ref child_r_aabb,
+                child_r_index,
+                ..
+            } = nodes[parent_index]
+            {
+                if child_l_index == node_index && self.ray.intersects_aabb(child_r_aabb) {
+                    return Some(child_r_index);
+                }
+            }
+            node_index = parent_index;
+        }
+        None
+    }
+}
+
+impl<'a, Shape: Bounded> Iterator for TraverseIterator<'a, Shape> {
+    type Item = &'a Shape;
+
+    fn next(&mut self) -> Option<&'a Shape> {
+        loop {
+            for &shape_index in &mut self.leaf_shapes {
+                let shape = &self.shapes[shape_index];
+                if !self.test_leaf_shapes || self.ray.intersects_aabb(&shape.aabb()) {
+                    return Some(shape);
+                }
+            }
+
+            let node_index = self.next_node?;
+            match self.bvh.nodes[node_index] {
+                BVHNode::Node {
+                    ref child_l_aabb,
+                    child_l_index,
+                    ref child_r_aabb,
+                    child_r_index,
+                    ..
+                } => {
+                    self.next_node = if self.ray.intersects_aabb(child_l_aabb) {
+                        Some(child_l_index)
+                    } else if self.ray.intersects_aabb(child_r_aabb) {
+                        Some(child_r_index)
+                    } else {
+                        self.next_after_subtree(node_index)
+                    };
+                }
+                BVHNode::Leaf { .. } => {
+                    self.next_node = self.next_after_subtree(node_index);
+                    self.leaf_shapes = self.bvh.leaf_shapes(node_index).iter();
+                    // No parent has tested the `AABB` of a root leaf.
+                    self.test_leaf_shapes = self.leaf_shapes.len() > 1 || node_index == 0;
+                }
+            }
+        }
+    }
+}
+
+impl BVH {
+    /// Creates a [`TraverseIterator`] which lazily yields the shapes whose [`AABB`]s
+    /// are hit by `ray`, without allocating. Yields the same shapes in the same order
+    /// as [`traverse`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let ray = Ray::new(Vec3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(bvh.traverse_iterator(&ray, &spheres).count(), 10);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`TraverseIterator`]: struct.TraverseIterator.html
+    /// [`traverse`]: struct.BVH.html#method.traverse
+    ///
+    pub fn traverse_iterator<'a, Shape: Bounded>(
+        &'a self,
+        ray: &'a Ray,
+        shapes: &'a [Shape],
+    ) -> TraverseIterator<'a, Shape> {
+        TraverseIterator::new(self, ray, shapes)
+    }
+
+    /// Returns an iterator over the indices of all shapes referenced by the leaves of the
+    /// [`BVH`]. The leaves are visited depth-first, left child first, which is the order
+    /// in which [`traverse`] visits them. Shapes which are close to each other in this
+    /// order are usually close to each other in space, so reordering other per-shape
+    /// data to match improves the memory locality of later passes.
+    ///
+    /// The order only depends on the nodes, so it is stable for a given tree. It changes
+    /// when the tree is rebuilt or restructured by [`optimize`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for &x in &[30.0, 0.0, 20.0, 10.0] {
+    ///     let position = Vec3::new(x, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let order: Vec<f32> = bvh
+    ///     .leaf_shape_indices()
+    ///     .map(|index| spheres[index].position.x)
+    ///     .collect();
+    /// assert_eq!(order, vec![0.0, 10.0, 20.0, 30.0]);
+    /// ```
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`optimize`]: struct.BVH.html#method.optimize
+    /// [`traverse`]: struct.BVH.html#method.traverse
+    ///
+    pub fn leaf_shape_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        iter::from_fn(move || {
+            while let Some(node_index) = stack.pop() {
+                match self.nodes[node_index] {
+                    BVHNode::Node {
+                        child_l_index,
+                        child_r_index,
+                        ..
+                    } => {
+                        // Push the right child first, so that the left subtree is visited first.
+                        stack.push(child_r_index);
+                        stack.push(child_l_index);
+                    }
+                    BVHNode::Leaf { .. } => return Some(self.leaf_shapes(node_index)),
+                }
+            }
+            None
+        })
+        .flatten()
+        .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bvh::BVH;
+    use crate::ray::Ray;
+    use crate::testbase::{
+        build_some_bh, create_n_cubes, default_bounds, next_Vec3, randomly_transform_scene,
+        Triangle,
+    };
+    use std::ptr;
+    use ultraviolet::Vec3;
+
+    /// Asserts that the iterator yields exactly the shapes `traverse` returns for
+    /// some random rays, in the same order.
+    fn assert_iterator_equals_traverse(bvh: &BVH, triangles: &[Triangle], seed: &mut u64) {
+        let bounds = default_bounds();
+        for _ in 0..100 {
+            let ray = Ray::new(next_Vec3(seed, &bounds), next_Vec3(seed, &bounds));
+            let expected = bvh.traverse(&ray, triangles);
+            let actual: Vec<&Triangle> = bvh.traverse_iterator(&ray, triangles).collect();
+
+            assert_eq!(expected.len(), actual.len());
+            assert!(expected.iter().zip(&actual).all(|(a, b)| ptr::eq(*a, *b)));
+        }
+    }
+
+    #[test]
+    /// Compares the iterator to `traverse` for random scenes and rays.
+    fn test_traverse_iterator_equals_traverse() {
+        let mut seed = 0;
+        for &cube_count in &[1, 10, 1_000] {
+            let mut triangles = create_n_cubes(cube_count, &default_bounds());
+            let bvh = BVH::build(&mut triangles);
+            assert_iterator_equals_traverse(&bvh, &triangles, &mut seed);
+        }
+    }
+
+    #[test]
+    /// Compares the iterator to `traverse` after the `BVH` has been restructured by
+    /// `optimize`, so that the node order no longer matches the build order.
+    fn test_traverse_iterator_after_optimize() {
+        let bounds = default_bounds();
+        let mut triangles = create_n_cubes(1_000, &bounds);
+        let mut bvh = BVH::build(&mut triangles);
+
+        let mut seed = 0;
+        let updated = randomly_transform_scene(&mut triangles, 3_000, &bounds, None, &mut seed);
+        bvh.optimize(&updated, &triangles);
+        assert_iterator_equals_traverse(&bvh, &triangles, &mut seed);
+    }
+
+    #[test]
+    /// Runs the fixed scene tests, collecting hits with the iterator.
+    fn test_traverse_iterator_fixed_scene() {
+        let (boxes, bvh) = build_some_bh::<BVH>();
+        let ray = Ray::new(Vec3::new(-1000.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let mut ids: Vec<i32> = bvh.traverse_iterator(&ray, &boxes).map(|b| b.id).collect();
+        ids.sort();
+        assert_eq!(ids, (-10..11).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    /// Tests whether `leaf_shape_indices` yields every shape exactly once, and in
+    /// increasing order once `reorder_shapes` has sorted the shapes by leaf.
+    fn test_leaf_shape_indices() {
+        use crate::bvh::BuildConfig;
+
+        let bounds = default_bounds();
+        let mut seed = 0;
+        for &max_leaf_size in &[1, 4] {
+            let mut triangles = create_n_cubes(1_000, &bounds);
+            let config = BuildConfig {
+                max_leaf_size,
+                ..Default::default()
+            };
+            let mut bvh = BVH::build_with_config(&mut triangles, &config);
+            if max_leaf_size > 1 {
+                let indices: Vec<usize> = bvh.leaf_shape_indices().collect();
+                assert_eq!(indices, bvh.shape_indices);
+
+                bvh.reorder_shapes(&mut triangles);
+                let indices: Vec<usize> = bvh.leaf_shape_indices().collect();
+                assert_eq!(indices, (0..triangles.len()).collect::<Vec<usize>>());
+            }
+
+            if max_leaf_size == 1 {
+                let updated =
+                    randomly_transform_scene(&mut triangles, 3_000, &bounds, None, &mut seed);
+                bvh.optimize(&updated, &triangles);
+            }
+            let mut indices: Vec<usize> = bvh.leaf_shape_indices().collect();
+            indices.sort();
+            assert_eq!(indices, (0..triangles.len()).collect::<Vec<usize>>());
+        }
+
+        let empty = BVH {
+            nodes: Vec::new(),
+            shape_indices: Vec::new(),
+        };
+        assert_eq!(empty.leaf_shape_indices().count(), 0);
+    }
+}
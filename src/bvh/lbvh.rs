@@ -0,0 +1,408 @@
+//! This module defines the construction of a linear [`BVH`] (LBVH) from Morton codes.
+//! The LBVH builds much faster than the SAH builder, but the resulting trees are of
+//! lower quality and therefore slower to traverse.
+//!
+//! [`BVH`]: struct.BVH.html
+//!
+
+use crate::aabb::AABB;
+use crate::axis::Axis;
+use crate::bounding_hierarchy::BHShape;
+use crate::bvh::*;
+use crate::utils::has_valid_aabb;
+use ultraviolet::Vec3;
+
+/// The number of bits per axis in a Morton code.
+const MORTON_BITS_PER_AXIS: u32 = 10;
+
+/// Spreads the lower 10 bits of `value` so that there are two zero bits between
+/// each pair of neighbouring bits.
+fn expand_bits(value: u32) -> u32 {
+    let mut value = value;
+    value = value.wrapping_mul(0x0001_0001) & 0xFF00_00FF;
+    value = value.wrapping_mul(0x0000_0101) & 0x0F00_F00F;
+    value = value.wrapping_mul(0x0000_0011) & 0xC30C_30C3;
+    value = value.wrapping_mul(0x0000_0005) & 0x4924_9249;
+    value
+}
+
+/// Inverse of [`expand_bits`], which gathers every third bit of `value` into the lower
+/// 10 bits.
+///
+/// [`expand_bits`]: fn.expand_bits.html
+///
+fn compact_bits(value: u32) -> u32 {
+    let mut value = value & 0x0924_9249;
+    value = (value | (value >> 2)) & 0x030C_30C3;
+    value = (value | (value >> 4)) & 0x0300_F00F;
+    value = (value | (value >> 8)) & 0x0300_00FF;
+    value = (value | (value >> 16)) & 0x0000_03FF;
+    value
+}
+
+/// Returns the plane between the cells of the Morton codes `first_code` and `last_code`,
+/// which is given by their highest differing bit, as its axis and position relative to
+/// `bounds`. The codes must differ.
+fn split_plane(first_code: u32, last_code: u32, bounds: &AABB) -> (Axis, f32) {
+    let bit = 31 - (first_code ^ last_code).leading_zeros();
+    let axis = match bit % 3 {
+        2 => Axis::X,
+        1 => Axis::Y,
+        _ => Axis::Z,
+    };
+
+    // The cells of `last_code` start where the differing bit of its coordinate is set.
+    let level = bit / 3;
+    let cell = (compact_bits(last_code >> (bit % 3)) >> level) << level;
+    let scale = (1 << MORTON_BITS_PER_AXIS) as f32;
+    let position = bounds.min[axis] + bounds.size()[axis] * cell as f32 / scale;
+    (axis, position)
+}
+
+/// Computes the 30-bit Morton code of `point` relative to `bounds`. Points outside
+/// of `bounds` are clamped onto it.
+fn morton_code(point: Vec3, bounds: &AABB) -> u32 {
+    let size = bounds.size();
+    let scale = (1 << MORTON_BITS_PER_AXIS) as f32;
+    let quantize = |value: f32, min: f32, extent: f32| {
+        let relative = if extent > 0.0 {
+            (value - min) / extent
+        } else {
+            0.0
+        };
+        (relative * scale).max(0.0).min(scale - 1.0) as u32
+    };
+
+    let x = quantize(point.x, bounds.min.x, size.x);
+    let y = quantize(point.y, bounds.min.y, size.y);
+    let z = quantize(point.z, bounds.min.z, size.z);
+    (expand_bits(x) << 2) | (expand_bits(y) << 1) | expand_bits(z)
+}
+
+/// Sorts `(code, shape index)` pairs by their code using a stable least significant
+/// digit radix sort with 8-bit digits.
+fn radix_sort(pairs: &mut Vec<(u32, usize)>) {
+    let mut scratch = vec![(0, 0); pairs.len()];
+    for shift in (0..32).step_by(8) {
+        let mut offsets = [0usize; 256];
+        for &(code, _) in pairs.iter() {
+            offsets[((code >> shift) & 0xFF) as usize] += 1;
+        }
+
+        // Turn the digit counts into the index of the first pair with each digit.
+        let mut total = 0;
+        for offset in offsets.iter_mut() {
+            let count = *offset;
+            *offset = total;
+            total += count;
+        }
+
+        for &pair in pairs.iter() {
+            let digit = ((pair.0 >> shift) & 0xFF) as usize;
+            scratch[offsets[digit]] = pair;
+            offsets[digit] += 1;
+        }
+        std::mem::swap(pairs, &mut scratch);
+    }
+}
+
+/// Finds the index at which the sorted `codes` in `first..=last` should be split, which
+/// is the last index whose code shares more leading bits with `codes[first]` than
+/// `codes[last]` does. Ranges of identical codes are split in the middle.
+fn find_split(codes: &[(u32, usize)], first: usize, last: usize) -> usize {
+    let first_code = codes[first].0;
+    let last_code = codes[last].0;
+    if first_code == last_code {
+        return (first + last) / 2;
+    }
+
+    // Binary search for the last code which shares more than `common_prefix` bits
+    // with the first one.
+    let common_prefix = (first_code ^ last_code).leading_zeros();
+    let mut split = first;
+    let mut step = last - first;
+    while step > 1 {
+        step -= step / 2;
+        let new_split = split + step;
+        if new_split < last && (first_code ^ codes[new_split].0).leading_zeros() > common_prefix {
+            split = new_split;
+        }
+    }
+    split
+}
+
+impl BVH {
+    /// Creates a new [`BVH`] from the `shapes` slice using Morton codes (LBVH).
+    ///
+    /// The center of each shape's [`AABB`] is quantized into a 30-bit Morton code relative
+    /// to the bounds of all centers. The codes are radix-sorted, and the hierarchy is
+    /// formed by recursively splitting the sorted range where the highest differing bit
+    /// changes, as proposed by Karras. This is several times faster than [`build`], but
+    /// ignores the size of the shapes, so the tree is usually slower to traverse. It is a
+    /// good choice for scenes which are rebuilt every frame.
+    ///
+    /// Like [`build`], this skips shapes whose [`AABB`] is empty or not finite.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build_lbvh(&mut spheres);
+    ///
+    /// let ray = Ray::new(Vec3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(bvh.traverse(&ray, &spheres).len(), 10);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`build`]: struct.BVH.html#method.build
+    ///
+    pub fn build_lbvh<Shape: BHShape>(shapes: &mut [Shape]) -> BVH {
+        let mut nodes = Vec::with_capacity(shapes.len() * 2);
+        let indices: Vec<usize> = (0..shapes.len())
+            .filter(|index| has_valid_aabb(&shapes[*index]))
+            .collect();
+        if indices.is_empty() {
+            return BVH {
+                nodes,
+                shape_indices: indices,
+            };
+        }
+
+        let centroid_bounds = indices.iter().fold(AABB::empty(), |bounds, index| {
+            bounds.grow(&shapes[*index].aabb().center())
+        });
+        let mut codes: Vec<(u32, usize)> = indices
+            .iter()
+            .map(|index| {
+                let center = shapes[*index].aabb().center();
+                (morton_code(center, &centroid_bounds), *index)
+            })
+            .collect();
+        radix_sort(&mut codes);
+
+        BVH::build_lbvh_subtree(
+            shapes,
+            &codes,
+            &centroid_bounds,
+            0,
+            codes.len() - 1,
+            &mut nodes,
+            0,
+            0,
+        );
+        // The leaves are created in the order of the sorted codes, see `build_lbvh_subtree`.
+        let shape_indices = codes.iter().map(|&(_, index)| index).collect();
+        BVH {
+            nodes,
+            shape_indices,
+        }
+    }
+
+    /// Builds the subtree for the sorted `codes` in `first..=last` and returns the index
+    /// of its root node and its [`AABB`]. The codes are relative to `centroid_bounds`.
+    /// The leaf of `codes[i]` references entry `i` of the shape indices of the [`BVH`].
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    ///
+    #[allow(clippy::too_many_arguments)]
+    fn build_lbvh_subtree<Shape: BHShape>(
+        shapes: &mut [Shape],
+        codes: &[(u32, usize)],
+        centroid_bounds: &AABB,
+        first: usize,
+        last: usize,
+        nodes: &mut Vec<BVHNode>,
+        parent_index: usize,
+        depth: u32,
+    ) -> (usize, AABB) {
+        let node_index = nodes.len();
+        if first == last {
+            let shape_index = codes[first].1;
+            nodes.push(BVHNode::Leaf {
+                parent_index,
+                depth,
+                shape_index: first,
+                shape_count: 1,
+            });
+            shapes[shape_index].set_bh_node_index(node_index);
+            return (node_index, shapes[shape_index].aabb());
+        }
+
+        // Reserve the slot for this node, the children need to know its index.
+        nodes.push(BVHNode::Leaf {
+            parent_index: 0,
+            depth: 0,
+            shape_index: 0,
+            shape_count: 0,
+        });
+
+        let split = find_split(codes, first, last);
+        let (child_l_index, child_l_aabb) = BVH::build_lbvh_subtree(
+            shapes,
+            codes,
+            centroid_bounds,
+            first,
+            split,
+            nodes,
+            node_index,
+            depth + 1,
+        );
+        let (child_r_index, child_r_aabb) = BVH::build_lbvh_subtree(
+            shapes,
+            codes,
+            centroid_bounds,
+            split + 1,
+            last,
+            nodes,
+            node_index,
+            depth + 1,
+        );
+        let aabb = child_l_aabb.join(&child_r_aabb);
+
+        // Ranges of identical codes are split in the middle, which has no plane.
+        let (split_axis, split_position) = if codes[first].0 == codes[last].0 {
+            let axis = aabb.largest_axis();
+            (axis, aabb.center()[axis])
+        } else {
+            split_plane(codes[first].0, codes[last].0, centroid_bounds)
+        };
+        nodes[node_index] = BVHNode::Node {
+            parent_index,
+            depth,
+            child_l_index,
+            child_l_aabb,
+            child_r_index,
+            child_r_aabb,
+            split_axis,
+            split_position,
+        };
+
+        (node_index, aabb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{morton_code, radix_sort};
+    use crate::aabb::{Bounded, AABB};
+    use crate::bvh::BVH;
+    use crate::ray::Ray;
+    use crate::testbase::{create_n_cubes, default_bounds, next_Vec3, Triangle};
+    use quickcheck::quickcheck;
+    use ultraviolet::Vec3;
+
+    /// Checks whether the radix sort orders codes like a stable comparison sort.
+    quickcheck! {
+        fn test_radix_sort_equals_sort(codes: Vec<u32>) -> bool {
+            let mut pairs: Vec<(u32, usize)> =
+                codes.iter().enumerate().map(|(index, code)| (*code, index)).collect();
+            let mut expected = pairs.clone();
+            expected.sort_by_key(|pair| pair.0);
+            radix_sort(&mut pairs);
+            pairs == expected
+        }
+    }
+
+    #[test]
+    /// Tests the Morton codes of the corners of the bounds.
+    fn test_morton_code_corners() {
+        let bounds = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 2.0, 4.0));
+        assert_eq!(morton_code(bounds.min, &bounds), 0);
+        assert_eq!(morton_code(bounds.max, &bounds), (1 << 30) - 1);
+        assert_eq!(morton_code(Vec3::new(1.0, 0.0, 0.0), &bounds), 0x2492_4924);
+        assert_eq!(morton_code(Vec3::new(0.0, 0.0, 4.0), &bounds), 0x0924_9249);
+    }
+
+    #[test]
+    /// Tests whether the LBVH is consistent, tight and finds the same shapes as the
+    /// SAH-built `BVH`.
+    fn test_lbvh_traversal_equals_sah() {
+        let bounds = default_bounds();
+        let mut seed = 0;
+        for &cube_count in &[1, 10, 1_000] {
+            let mut triangles = create_n_cubes(cube_count, &bounds);
+            let sah = BVH::build(&mut triangles);
+            let lbvh = BVH::build_lbvh(&mut triangles);
+            lbvh.assert_consistent(&triangles);
+            lbvh.assert_tight(&triangles);
+
+            for _ in 0..100 {
+                let ray = Ray::new(next_Vec3(&mut seed, &bounds), next_Vec3(&mut seed, &bounds));
+                let mut expected = sah.traverse_indices(&ray, &triangles);
+                let mut actual = lbvh.traverse_indices(&ray, &triangles);
+                expected.sort();
+                actual.sort();
+                assert_eq!(expected, actual);
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether shapes sharing the same center still end up in separate leaves.
+    fn test_lbvh_identical_centers() {
+        let cube = create_n_cubes(1, &default_bounds());
+        let mut triangles: Vec<Triangle> = (0..100)
+            .map(|_| Triangle::new(cube[0].a, cube[0].b, cube[0].c))
+            .collect();
+        let lbvh = BVH::build_lbvh(&mut triangles);
+        lbvh.assert_consistent(&triangles);
+
+        let center = triangles[0].aabb().center();
+        let ray = Ray::new(
+            center - Vec3::new(0.0, 0.0, 100.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        );
+        assert_eq!(lbvh.traverse(&ray, &triangles).len(), 100);
+    }
+}
+
+#[cfg(all(feature = "bench", test))]
+mod bench {
+    use crate::bvh::BVH;
+    use crate::testbase::{create_n_cubes, default_bounds, intersect_bh};
+
+    /// Benchmark the LBVH construction of a `BVH` with `n` cubes, i.e. `12 * n` triangles.
+    fn build_lbvh_n_cubes(n: usize, b: &mut ::test::Bencher) {
+        let mut triangles = create_n_cubes(n, &default_bounds());
+        b.iter(|| {
+            BVH::build_lbvh(&mut triangles);
+        });
+    }
+
+    #[bench]
+    /// Benchmark the LBVH construction of a `BVH` with 1,200 triangles.
+    fn bench_build_1200_triangles_lbvh(b: &mut ::test::Bencher) {
+        build_lbvh_n_cubes(100, b);
+    }
+
+    #[bench]
+    /// Benchmark the LBVH construction of a `BVH` with 12,000 triangles.
+    fn bench_build_12k_triangles_lbvh(b: &mut ::test::Bencher) {
+        build_lbvh_n_cubes(1_000, b);
+    }
+
+    #[bench]
+    /// Benchmark the LBVH construction of a `BVH` with 120,000 triangles.
+    fn bench_build_120k_triangles_lbvh(b: &mut ::test::Bencher) {
+        build_lbvh_n_cubes(10_000, b);
+    }
+
+    #[bench]
+    /// Benchmark intersecting 12,000 triangles using a `BVH` built as LBVH.
+    fn bench_intersect_12k_triangles_lbvh(b: &mut ::test::Bencher) {
+        let bounds = default_bounds();
+        let mut triangles = create_n_cubes(1_000, &bounds);
+        let bvh = BVH::build_lbvh(&mut triangles);
+        intersect_bh(&bvh, &triangles, &bounds, b)
+    }
+}
@@ -4,7 +4,23 @@
 //!
 
 mod bvh;
+mod cache;
+mod error;
+mod iter;
+mod lbvh;
 mod optimization;
+mod packet;
+mod queries;
+mod refit;
+mod spatial;
+mod statistics;
+mod validate;
+mod visit;
 
 pub use self::bvh::*;
+pub use self::cache::*;
+pub use self::error::*;
+pub use self::iter::*;
 pub use self::optimization::*;
+pub use self::statistics::*;
+pub use self::visit::*;
@@ -160,16 +160,12 @@ impl BVH {
         info!("   [{}]\t", node_index);
 
         match self.nodes[node_index] {
-            BVHNode::Leaf {
-                parent_index,
-                shape_index,
-                ..
-            } => {
+            BVHNode::Leaf { parent_index, .. } => {
                 // The current node is a leaf.
                 info!(
                     "Leaf node. Queueing parent ({}). {}.",
                     parent_index,
-                    shapes[shape_index].aabb()
+                    self.nodes[node_index].get_node_aabb(&self.shape_indices, shapes)
                 );
                 Some(OptimizationIndex::Refit(parent_index))
             }
@@ -180,17 +176,13 @@ impl BVH {
                 ..
             } => {
                 // The current node is a parent.
-                if let (
-                    &BVHNode::Leaf {
-                        shape_index: shape_l_index,
-                        ..
-                    },
-                    &BVHNode::Leaf {
-                        shape_index: shape_r_index,
-                        ..
-                    },
-                ) = (&self.nodes[child_l_index], &self.nodes[child_r_index])
+                if let (&BVHNode::Leaf { .. }, &BVHNode::Leaf { .. }) =
+                    (&self.nodes[child_l_index], &self.nodes[child_r_index])
                 {
+                    let new_child_l_aabb =
+                        self.nodes[child_l_index].get_node_aabb(&self.shape_indices, shapes);
+                    let new_child_r_aabb =
+                        self.nodes[child_r_index].get_node_aabb(&self.shape_indices, shapes);
                     // The current node is a final parent. Update its `AABB`s, because at least
                     // one of its children was updated and queue its parent for refitting.
                     if let BVHNode::Node {
@@ -199,8 +191,8 @@ impl BVH {
                         ..
                     } = self.nodes[node_index]
                     {
-                        *child_l_aabb = shapes[shape_l_index].aabb();
-                        *child_r_aabb = shapes[shape_r_index].aabb();
+                        *child_l_aabb = new_child_l_aabb;
+                        *child_r_aabb = new_child_r_aabb;
                         info!("Setting {} from {}", child_l_aabb, child_l_index);
                         info!("\tand {} from {}.", child_r_aabb, child_r_index);
                         return Some(OptimizationIndex::Refit(parent_index));
@@ -304,8 +296,8 @@ impl BVH {
         // Recalculate `AABB`s for the children since at least one of them changed.  Don't update
         // the `AABB`s in the node yet because they're still subject to change during potential
         // upcoming rotations.
-        let child_l_aabb = self.nodes[child_l_index].get_node_aabb(shapes);
-        let child_r_aabb = self.nodes[child_r_index].get_node_aabb(shapes);
+        let child_l_aabb = self.nodes[child_l_index].get_node_aabb(&self.shape_indices, shapes);
+        let child_r_aabb = self.nodes[child_r_index].get_node_aabb(&self.shape_indices, shapes);
 
         let best_rotation =
             self.find_better_rotation(child_l_index, &child_l_aabb, child_r_index, &child_r_aabb);
@@ -366,9 +358,9 @@ impl BVH {
         self.fix_aabbs(child_r_index, shapes);
 
         *self.nodes[node_index].child_l_aabb_mut() =
-            self.nodes[child_l_index].get_node_aabb(shapes);
+            self.nodes[child_l_index].get_node_aabb(&self.shape_indices, shapes);
         *self.nodes[node_index].child_r_aabb_mut() =
-            self.nodes[child_r_index].get_node_aabb(shapes);
+            self.nodes[child_r_index].get_node_aabb(&self.shape_indices, shapes);
     }
 
     /// Updates `child_l_aabb` and `child_r_aabb` of the `BVHNode::Node`
@@ -386,9 +378,9 @@ impl BVH {
                 ..
             } => {
                 *self.nodes[node_index].child_l_aabb_mut() =
-                    self.nodes[child_l_index].get_node_aabb(shapes);
+                    self.nodes[child_l_index].get_node_aabb(&self.shape_indices, shapes);
                 *self.nodes[node_index].child_r_aabb_mut() =
-                    self.nodes[child_r_index].get_node_aabb(shapes);
+                    self.nodes[child_r_index].get_node_aabb(&self.shape_indices, shapes);
 
                 if node_index > 0 {
                     Some(OptimizationIndex::FixAABBs(parent_index))
@@ -475,7 +467,7 @@ impl BVH {
         left_child: bool,
         shapes: &[Shape],
     ) {
-        let child_aabb = self.nodes[child_index].get_node_aabb(shapes);
+        let child_aabb = self.nodes[child_index].get_node_aabb(&self.shape_indices, shapes);
         info!("\tConnecting: {} < {}.", child_index, parent_index);
         // Set parent's child and child_aabb; and get its depth.
         let parent_depth = {
@@ -514,14 +506,15 @@ impl BVH {
 #[cfg(test)]
 mod tests {
     use crate::aabb::Bounded;
+    use crate::axis::Axis;
     use crate::bounding_hierarchy::BHShape;
     use crate::bvh::{BVHNode, BVH};
     use crate::testbase::{
         build_some_bh, create_n_cubes, default_bounds, randomly_transform_scene, UnitBox,
     };
     use crate::EPSILON;
-    use ultraviolet::Vec3;
     use std::collections::HashSet;
+    use ultraviolet::Vec3;
 
     #[test]
     /// Tests if `optimize` does not modify a fresh `BVH`.
@@ -642,6 +635,8 @@ mod tests {
             child_l_index: 1,
             child_r_aabb: shapes[2].aabb().join(&shapes[3].aabb()),
             child_r_index: 2,
+            split_axis: Axis::X,
+            split_position: 3.0,
         });
 
         // Depth 1 nodes.
@@ -652,6 +647,8 @@ mod tests {
             child_l_index: 3,
             child_r_aabb: shapes[1].aabb(),
             child_r_index: 4,
+            split_axis: Axis::X,
+            split_position: 1.0,
         });
         nodes.push(BVHNode::Node {
             parent_index: 0,
@@ -660,6 +657,8 @@ mod tests {
             child_l_index: 5,
             child_r_aabb: shapes[3].aabb(),
             child_r_index: 6,
+            split_axis: Axis::X,
+            split_position: 5.0,
         });
 
         // Depth 2 nodes (leaves).
@@ -667,24 +666,35 @@ mod tests {
             parent_index: 1,
             depth: 2,
             shape_index: 0,
+            shape_count: 1,
         });
         nodes.push(BVHNode::Leaf {
             parent_index: 1,
             depth: 2,
             shape_index: 1,
+            shape_count: 1,
         });
         nodes.push(BVHNode::Leaf {
             parent_index: 2,
             depth: 2,
             shape_index: 2,
+            shape_count: 1,
         });
         nodes.push(BVHNode::Leaf {
             parent_index: 2,
             depth: 2,
             shape_index: 3,
+            shape_count: 1,
         });
 
-        (shapes, BVH { nodes })
+        let shape_indices = (0..shapes.len()).collect();
+        (
+            shapes,
+            BVH {
+                nodes,
+                shape_indices,
+            },
+        )
     }
 
     #[test]
@@ -696,7 +706,7 @@ mod tests {
         bvh.connect_nodes(5, 1, true, &shapes);
 
         // Check if the resulting tree is as expected.
-        let BVH { nodes } = bvh;
+        let BVH { nodes, .. } = bvh;
 
         assert_eq!(nodes[0].parent(), 0);
         assert_eq!(nodes[0].child_l(), 1);
@@ -738,7 +748,7 @@ mod tests {
         bvh.connect_nodes(5, 0, true, &shapes);
 
         // Check if the resulting tree is as expected.
-        let BVH { nodes } = bvh;
+        let BVH { nodes, .. } = bvh;
 
         assert_eq!(nodes[0].parent(), 0);
         assert_eq!(nodes[0].child_l(), 5);
@@ -779,7 +789,7 @@ mod tests {
         bvh.rotate(3, 5, &shapes);
 
         // Check if the resulting tree is as expected.
-        let BVH { nodes } = bvh;
+        let BVH { nodes, .. } = bvh;
 
         assert_eq!(nodes[0].parent(), 0);
         assert_eq!(nodes[0].child_l(), 1);
@@ -820,7 +830,7 @@ mod tests {
         bvh.rotate(1, 5, &shapes);
 
         // Check if the resulting tree is as expected.
-        let BVH { nodes } = bvh;
+        let BVH { nodes, .. } = bvh;
 
         assert_eq!(nodes[0].parent(), 0);
         assert_eq!(nodes[0].child_l(), 5);
@@ -867,7 +877,7 @@ mod tests {
         bvh.try_rotate(0, &shapes);
 
         // Check if the resulting tree is as expected.
-        let BVH { nodes } = bvh;
+        let BVH { nodes, .. } = bvh;
 
         assert_eq!(nodes[0].parent(), 0);
         assert_eq!(nodes[0].child_l(), 5);
@@ -0,0 +1,210 @@
+//! This module defines the traversal of a [`BVH`] with packets of coherent [`Ray`]s.
+//!
+//! [`BVH`]: struct.BVH.html
+//! [`Ray`]: ../ray/struct.Ray.html
+//!
+
+use crate::aabb::Bounded;
+use crate::bvh::*;
+use crate::ray::Ray;
+
+impl BVH {
+    /// Traverses the [`BVH`] with a packet of `N` [`Ray`]s at once and returns, for every
+    /// [`Ray`] of the packet, the shapes whose [`AABB`]s it hits.
+    ///
+    /// The tree is descended only once for the whole packet. Every visited node is tested
+    /// against the [`Ray`]s which hit its parent, and a child is only entered if at least
+    /// one of them hits it. For coherent packets, such as the rays of a 2x2 or 4x4 pixel
+    /// block, this saves most of the repeated node fetches of tracing each [`Ray`] on its
+    /// own. The result of each lane is identical to [`traverse`] with the same [`Ray`],
+    /// including the order of the shapes.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let direction = Vec3::new(0.0, 0.0, 1.0);
+    /// let rays = [
+    ///     Ray::new(Vec3::new(20.0, 0.0, -10.0), direction),
+    ///     Ray::new(Vec3::new(20.5, 0.5, -10.0), direction),
+    ///     Ray::new(Vec3::new(25.0, 0.0, -10.0), direction),
+    ///     Ray::new(Vec3::new(30.0, 0.0, -10.0), direction),
+    /// ];
+    /// let hits = bvh.traverse_packet(&rays, &spheres);
+    ///
+    /// assert_eq!(hits[0].len(), 1);
+    /// assert_eq!(hits[1][0].position.x, 20.0);
+    /// assert!(hits[2].is_empty());
+    /// assert_eq!(hits[3][0].position.x, 30.0);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`Ray`]: ../ray/struct.Ray.html
+    /// [`traverse`]: struct.BVH.html#method.traverse
+    ///
+    pub fn traverse_packet<'a, Shape: Bounded, const N: usize>(
+        &self,
+        rays: &[Ray; N],
+        shapes: &'a [Shape],
+    ) -> [Vec<&'a Shape>; N] {
+        let indices = self.traverse_packet_indices(rays, shapes);
+        let mut indices = indices.iter();
+        [(); N].map(|_| {
+            indices
+                .next()
+                .unwrap()
+                .iter()
+                .map(|index| &shapes[*index])
+                .collect()
+        })
+    }
+
+    /// Traverses the [`BVH`] like [`traverse_packet`], but returns the indices into
+    /// `shapes` of the hit shapes for every [`Ray`] of the packet.
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`Ray`]: ../ray/struct.Ray.html
+    /// [`traverse_packet`]: struct.BVH.html#method.traverse_packet
+    ///
+    pub fn traverse_packet_indices<Shape: Bounded, const N: usize>(
+        &self,
+        rays: &[Ray; N],
+        shapes: &[Shape],
+    ) -> [Vec<usize>; N] {
+        let mut indices = [(); N].map(|_| Vec::new());
+        if self.nodes.is_empty() {
+            return indices;
+        }
+
+        // Each node is pushed together with the mask of the `Ray`s which hit its `AABB`.
+        // Unlike the node indices on a `TraversalStack`, the masks need an element type of
+        // their own, so a plain `Vec` is used as the stack.
+        let mut stack = vec![(0, [true; N])];
+        while let Some((node_index, active)) = stack.pop() {
+            match self.nodes[node_index] {
+                BVHNode::Node {
+                    ref child_l_aabb,
+                    child_l_index,
+                    ref child_r_aabb,
+                    child_r_index,
+                    ..
+                } => {
+                    let mut active_l = [false; N];
+                    let mut active_r = [false; N];
+                    for lane in 0..N {
+                        if active[lane] {
+                            active_l[lane] = rays[lane].intersects_aabb(child_l_aabb);
+                            active_r[lane] = rays[lane].intersects_aabb(child_r_aabb);
+                        }
+                    }
+                    // Push the right child first, so the left subtree is visited first.
+                    if active_r.iter().any(|hit| *hit) {
+                        stack.push((child_r_index, active_r));
+                    }
+                    if active_l.iter().any(|hit| *hit) {
+                        stack.push((child_l_index, active_l));
+                    }
+                }
+                BVHNode::Leaf { .. } => {
+                    let leaf_shapes = self.leaf_shapes(node_index);
+                    for (lane, lane_indices) in indices.iter_mut().enumerate() {
+                        if !active[lane] {
+                            continue;
+                        }
+                        if leaf_shapes.len() == 1 && node_index != 0 {
+                            // The parent already tested the `AABB` of this shape.
+                            lane_indices.extend_from_slice(leaf_shapes);
+                        } else {
+                            let ray = &rays[lane];
+                            lane_indices.extend(
+                                leaf_shapes
+                                    .iter()
+                                    .filter(|index| ray.intersects_aabb(&shapes[**index].aabb())),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        indices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bvh::{BuildConfig, BVH};
+    use crate::ray::Ray;
+    use crate::testbase::{build_chain_bvh, create_n_cubes, default_bounds, next_Vec3};
+    use ultraviolet::Vec3;
+
+    #[test]
+    /// Tests whether every lane of a packet traversal returns the same shapes in the same
+    /// order as an independent `traverse` with its `Ray`, for coherent and incoherent
+    /// packets and leaves with several shapes.
+    fn test_traverse_packet_equals_traverse() {
+        let bounds = default_bounds();
+        let mut seed = 0;
+
+        for &max_leaf_size in &[1, 4] {
+            let mut triangles = create_n_cubes(1_000, &bounds);
+            let config = BuildConfig {
+                max_leaf_size,
+                ..Default::default()
+            };
+            let bvh = BVH::build_with_config(&mut triangles, &config);
+
+            for _ in 0..20 {
+                // A coherent 2x2 packet which shares its origin and a random packet.
+                let origin = next_Vec3(&mut seed, &bounds);
+                let target = next_Vec3(&mut seed, &bounds);
+                let coherent: [Ray; 4] = [
+                    Ray::new(origin, target - origin),
+                    Ray::new(origin, target + Vec3::new(500.0, 0.0, 0.0) - origin),
+                    Ray::new(origin, target + Vec3::new(0.0, 500.0, 0.0) - origin),
+                    Ray::new(origin, target + Vec3::new(500.0, 500.0, 0.0) - origin),
+                ];
+                let random: [Ray; 3] = [(); 3].map(|_| {
+                    let origin = next_Vec3(&mut seed, &bounds);
+                    Ray::new(origin, next_Vec3(&mut seed, &bounds) - origin)
+                });
+
+                let hits = bvh.traverse_packet_indices(&coherent, &triangles);
+                for (ray, lane) in coherent.iter().zip(hits.iter()) {
+                    assert_eq!(*lane, bvh.traverse_indices(ray, &triangles));
+                }
+                let hits = bvh.traverse_packet(&random, &triangles);
+                for (ray, lane) in random.iter().zip(hits.iter()) {
+                    let expected = bvh.traverse(ray, &triangles);
+                    assert_eq!(lane.len(), expected.len());
+                    assert!(lane.iter().zip(expected).all(|(a, b)| std::ptr::eq(*a, b)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether a packet traversal handles a `BVH` which is too deep to be traversed
+    /// recursively.
+    fn test_traverse_packet_deep_chain() {
+        let (boxes, bvh) = build_chain_bvh(300_000);
+        let rays = [
+            Ray::new(Vec3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0)),
+            Ray::new(Vec3::new(-10.0, 5.0, 0.0), Vec3::new(1.0, 0.0, 0.0)),
+        ];
+        let hits = bvh.traverse_packet_indices(&rays, &boxes);
+        assert_eq!(hits[0], bvh.traverse_indices(&rays[0], &boxes));
+        assert_eq!(hits[0].len(), boxes.len());
+        assert!(hits[1].is_empty());
+    }
+}
@@ -0,0 +1,1345 @@
+//! This module defines spatial queries on the [`BVH`] which, unlike [`traverse`],
+//! are not driven by a [`Ray`].
+//!
+//! [`BVH`]: struct.BVH.html
+//! [`Ray`]: ../ray/struct.Ray.html
+//! [`traverse`]: struct.BVH.html#method.traverse
+//!
+
+use crate::aabb::AABB;
+use crate::bounding_hierarchy::BHShape;
+use crate::bvh::bvh::TraversalStack;
+use crate::bvh::*;
+use crate::capsule::Capsule;
+use crate::cone::Cone;
+use crate::utils::Candidate;
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use ultraviolet::Vec3;
+
+impl BVH {
+    /// Traverses the [`BVH`] with a `predicate` on [`AABB`]s and returns the shapes
+    /// whose [`AABB`] satisfies it. Only nodes whose [`AABB`] satisfies `predicate` are
+    /// descended into. The nodes are kept on a `TraversalStack` instead of the call
+    /// stack, so arbitrarily deep trees can be traversed.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    ///
+    fn traverse_predicate<'a, Shape: BHShape, F: Fn(&AABB) -> bool>(
+        &self,
+        predicate: F,
+        shapes: &'a [Shape],
+    ) -> Vec<&'a Shape> {
+        let mut hits = Vec::new();
+        if self.nodes.is_empty() {
+            return hits;
+        }
+
+        let mut stack = TraversalStack::new();
+        stack.push(0);
+        while let Some(node_index) = stack.pop() {
+            match self.nodes[node_index] {
+                BVHNode::Node {
+                    ref child_l_aabb,
+                    child_l_index,
+                    ref child_r_aabb,
+                    child_r_index,
+                    ..
+                } => {
+                    // Push the right child first, so the left subtree is visited first.
+                    if predicate(child_r_aabb) {
+                        stack.push(child_r_index);
+                    }
+                    if predicate(child_l_aabb) {
+                        stack.push(child_l_index);
+                    }
+                }
+                BVHNode::Leaf { .. } => {
+                    let leaf_shapes = self.leaf_shapes(node_index).iter();
+                    hits.extend(
+                        leaf_shapes
+                            .map(|index| &shapes[*index])
+                            .filter(|shape| predicate(&shape.aabb())),
+                    );
+                }
+            }
+        }
+        hits
+    }
+
+    /// Returns the [`AABB`] of the subtree at `node_index`, which is stored in the parent,
+    /// or computed from the `shapes` for the root.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    fn subtree_aabb<Shape: BHShape>(&self, node_index: usize, shapes: &[Shape]) -> AABB {
+        if node_index == 0 {
+            self.nodes[0].get_node_aabb(&self.shape_indices, shapes)
+        } else {
+            self.child_aabb(node_index)
+        }
+    }
+
+    /// Traverses the [`BVH`] and returns all shapes whose [`AABB`] overlaps the `query`
+    /// [`AABB`]. Only nodes whose [`AABB`] [`intersects_aabb`] the `query` are visited.
+    /// Touching [`AABB`]s count as overlapping.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let query = AABB::with_bounds(Vec3::new(15.0, -1.0, -1.0), Vec3::new(35.0, 1.0, 1.0));
+    /// let mut hits: Vec<f32> = bvh
+    ///     .traverse_aabb(&query, &spheres)
+    ///     .iter()
+    ///     .map(|sphere| sphere.position.x)
+    ///     .collect();
+    /// hits.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    /// assert_eq!(hits, vec![20.0, 30.0]);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`intersects_aabb`]: ../aabb/struct.AABB.html#method.intersects_aabb
+    ///
+    pub fn traverse_aabb<'a, Shape: BHShape>(
+        &self,
+        query: &AABB,
+        shapes: &'a [Shape],
+    ) -> Vec<&'a Shape> {
+        self.traverse_predicate(|aabb| aabb.intersects_aabb(query), shapes)
+    }
+
+    /// Traverses the [`BVH`] and returns all shapes whose [`AABB`] overlaps the `start`
+    /// [`AABB`] moving along `velocity`. This is a [`traverse_aabb`] query with the
+    /// [`swept`] [`AABB`], so no shape is missed, however fast `start` moves. The result
+    /// is conservative: shapes which lie in a corner of the swept [`AABB`] which the
+    /// moving [`AABB`] never reaches are returned as well.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let start = AABB::with_bounds(Vec3::new(14.0, -1.0, -1.0), Vec3::new(16.0, 1.0, 1.0));
+    /// let hits = bvh.traverse_swept_aabb(&start, Vec3::new(20.0, 0.0, 0.0), &spheres);
+    /// assert_eq!(hits.len(), 2);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`swept`]: ../aabb/struct.AABB.html#method.swept
+    /// [`traverse_aabb`]: struct.BVH.html#method.traverse_aabb
+    ///
+    pub fn traverse_swept_aabb<'a, Shape: BHShape>(
+        &self,
+        start: &AABB,
+        velocity: Vec3,
+        shapes: &'a [Shape],
+    ) -> Vec<&'a Shape> {
+        self.traverse_aabb(&start.swept(velocity), shapes)
+    }
+
+    /// Traverses the [`BVH`] and returns all shapes whose [`AABB`] lies completely inside
+    /// the `region` [`AABB`], e.g. for a rubber-band selection. Shapes which merely overlap
+    /// the `region` are left out. Nodes are descended where their [`AABB`] overlaps the
+    /// `region`, like [`traverse_aabb`], and the shapes found there are tested with the
+    /// exact [`contains_aabb`], so shapes on the boundary of the `region` are contained.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// // Overlaps the spheres at 20, 30 and 40, but only contains the one at 30.
+    /// let region = AABB::with_bounds(Vec3::new(20.0, -5.0, -5.0), Vec3::new(40.0, 5.0, 5.0));
+    /// let selected = bvh.traverse_contained(&region, &spheres);
+    /// assert_eq!(selected.len(), 1);
+    /// assert_eq!(selected[0].position.x, 30.0);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`contains_aabb`]: ../aabb/struct.AABB.html#method.contains_aabb
+    /// [`traverse_aabb`]: struct.BVH.html#method.traverse_aabb
+    ///
+    pub fn traverse_contained<'a, Shape: BHShape>(
+        &self,
+        region: &AABB,
+        shapes: &'a [Shape],
+    ) -> Vec<&'a Shape> {
+        self.traverse_aabb(region, shapes)
+            .into_iter()
+            .filter(|shape| region.contains_aabb(&shape.aabb()))
+            .collect()
+    }
+
+    /// Traverses the [`BVH`] and returns all shapes whose [`AABB`] contains `point`.
+    /// Only nodes whose [`AABB`] [`contains`] the `point` are visited, so a `point`
+    /// outside of the [`BVH`] never enters any subtree of the root node.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let hits = bvh.traverse_point(Vec3::new(4.5, 0.0, 0.0), &spheres);
+    /// assert_eq!(hits.len(), 2);
+    ///
+    /// let no_hits = bvh.traverse_point(Vec3::new(4.5, 10.0, 0.0), &spheres);
+    /// assert!(no_hits.is_empty());
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`contains`]: ../aabb/struct.AABB.html#method.contains
+    ///
+    pub fn traverse_point<'a, Shape: BHShape>(
+        &self,
+        point: Vec3,
+        shapes: &'a [Shape],
+    ) -> Vec<&'a Shape> {
+        self.traverse_predicate(|aabb| aabb.contains(&point), shapes)
+    }
+
+    /// Traverses the [`BVH`] and returns all shapes whose [`AABB`] lies within `radius`
+    /// of `center`, i.e. whose [`distance_squared_to_point`] is at most `radius * radius`.
+    /// Only nodes whose [`AABB`] satisfies the same criterion are visited.
+    ///
+    /// The test is conservative: a shape is returned when its [`AABB`] touches the sphere,
+    /// even if the shape itself does not.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let mut hits: Vec<f32> = bvh
+    ///     .traverse_sphere(Vec3::new(45.0, 0.0, 0.0), 5.0, &spheres)
+    ///     .iter()
+    ///     .map(|sphere| sphere.position.x)
+    ///     .collect();
+    /// hits.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    /// assert_eq!(hits, vec![40.0, 50.0]);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`distance_squared_to_point`]: ../aabb/struct.AABB.html#method.distance_squared_to_point
+    ///
+    pub fn traverse_sphere<'a, Shape: BHShape>(
+        &self,
+        center: Vec3,
+        radius: f32,
+        shapes: &'a [Shape],
+    ) -> Vec<&'a Shape> {
+        let radius_squared = radius * radius;
+        self.traverse_predicate(
+            |aabb| aabb.distance_squared_to_point(&center) <= radius_squared,
+            shapes,
+        )
+    }
+
+    /// Traverses the [`BVH`] and returns the shapes whose [`AABB`] may intersect `cone`,
+    /// such as the shapes lit by a spotlight. Nodes and shapes are tested with
+    /// [`Cone::intersects_aabb`], which compares the bounding sphere of their [`AABB`]
+    /// with the cone.
+    ///
+    /// The test is conservative: every shape whose [`AABB`] intersects `cone` is returned,
+    /// but shapes which only come close to it may be returned as well.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::cone::Cone;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let spotlight = Cone::new(Vec3::new(50.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.3, 20.0);
+    /// let lit = bvh.traverse_cone(&spotlight, &spheres);
+    /// assert_eq!(lit.len(), 1);
+    /// assert_eq!(lit[0].position.x, 50.0);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`Cone::intersects_aabb`]: ../cone/struct.Cone.html#method.intersects_aabb
+    ///
+    pub fn traverse_cone<'a, Shape: BHShape>(
+        &self,
+        cone: &Cone,
+        shapes: &'a [Shape],
+    ) -> Vec<&'a Shape> {
+        self.traverse_predicate(|aabb| cone.intersects_aabb(aabb), shapes)
+    }
+
+    /// Traverses the [`BVH`] and returns the shapes whose [`AABB`] intersects `capsule`,
+    /// for example to find the shapes a character controller may collide with. Nodes and
+    /// shapes are tested with [`Capsule::intersects_aabb`], which compares the exact
+    /// distance between their [`AABB`] and the segment of the `capsule` with its radius.
+    ///
+    /// The test is conservative: a shape is returned when its [`AABB`] touches the
+    /// `capsule`, even if the shape itself does not.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::capsule::Capsule;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// // A capsule sloping down over the spheres at 20 and 30, which only reaches the
+    /// // box of the sphere at 30.
+    /// let capsule = Capsule::new(Vec3::new(15.0, 5.0, 0.0), Vec3::new(35.0, 2.0, 0.0), 2.0);
+    /// let mut hits: Vec<f32> = bvh
+    ///     .traverse_capsule(&capsule, &spheres)
+    ///     .iter()
+    ///     .map(|sphere| sphere.position.x)
+    ///     .collect();
+    /// hits.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    /// assert_eq!(hits, vec![30.0]);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`Capsule::intersects_aabb`]: ../capsule/struct.Capsule.html#method.intersects_aabb
+    ///
+    pub fn traverse_capsule<'a, Shape: BHShape>(
+        &self,
+        capsule: &Capsule,
+        shapes: &'a [Shape],
+    ) -> Vec<&'a Shape> {
+        self.traverse_predicate(|aabb| capsule.intersects_aabb(aabb), shapes)
+    }
+
+    /// Returns the `k` shapes whose [`AABB`]s are closest to `query`, sorted by
+    /// ascending distance. Shapes whose [`AABB`] contains `query` have a distance of `0.0`.
+    /// If fewer than `k` shapes exist, all of them are returned.
+    ///
+    /// The [`BVH`] is traversed closest node first, and subtrees which lie farther away
+    /// than the current `k`-th best shape are skipped.
+    /// Ties between equidistant shapes are broken by their index in `shapes`: the shapes
+    /// with the lower indices are preferred and returned first.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let nearest = bvh.k_nearest(Vec3::new(32.0, 0.0, 0.0), 3, &spheres);
+    /// assert_eq!(nearest.len(), 3);
+    /// assert_eq!(nearest[0].position.x, 30.0);
+    /// assert_eq!(nearest[1].position.x, 40.0);
+    /// assert_eq!(nearest[2].position.x, 20.0);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    ///
+    pub fn k_nearest<'a, Shape: BHShape>(
+        &self,
+        query: Vec3,
+        k: usize,
+        shapes: &'a [Shape],
+    ) -> Vec<&'a Shape> {
+        if k == 0 || self.nodes.is_empty() {
+            return Vec::new();
+        }
+
+        // Nodes which are yet to be visited, closest first.
+        let mut nodes_to_visit = BinaryHeap::new();
+        nodes_to_visit.push(Reverse(Candidate {
+            distance_squared: 0.0,
+            index: 0,
+        }));
+
+        // The best shapes found so far, worst first.
+        let mut best: BinaryHeap<Candidate> = BinaryHeap::with_capacity(k + 1);
+
+        while let Some(Reverse(node)) = nodes_to_visit.pop() {
+            // All remaining nodes are farther away than the `k`-th best shape.
+            // Equidistant nodes are still visited, as they may contain shapes with lower indices.
+            if let Some(worst) = best.peek() {
+                if best.len() == k && node.distance_squared > worst.distance_squared {
+                    break;
+                }
+            }
+
+            match self.nodes[node.index] {
+                BVHNode::Node {
+                    child_l_index,
+                    child_l_aabb,
+                    child_r_index,
+                    child_r_aabb,
+                    ..
+                } => {
+                    nodes_to_visit.push(Reverse(Candidate {
+                        distance_squared: child_l_aabb.distance_squared_to_point(&query),
+                        index: child_l_index,
+                    }));
+                    nodes_to_visit.push(Reverse(Candidate {
+                        distance_squared: child_r_aabb.distance_squared_to_point(&query),
+                        index: child_r_index,
+                    }));
+                }
+                BVHNode::Leaf { .. } => {
+                    for &index in self.leaf_shapes(node.index) {
+                        best.push(Candidate {
+                            distance_squared: shapes[index]
+                                .aabb()
+                                .distance_squared_to_point(&query),
+                            index,
+                        });
+                        if best.len() > k {
+                            best.pop();
+                        }
+                    }
+                }
+            }
+        }
+
+        best.into_sorted_vec()
+            .iter()
+            .map(|candidate| &shapes[candidate.index])
+            .collect()
+    }
+
+    /// Returns the indices of the two shapes with the closest [`AABB`]s and the distance
+    /// between those [`AABB`]s, which is `0.0` if they overlap. The lower index comes
+    /// first. Returns `None` if there are fewer than two shapes.
+    ///
+    /// Pairs of subtrees are descended closest first, and pairs whose [`AABB`]s lie
+    /// farther apart than the best pair found so far are skipped. For evenly distributed
+    /// shapes this takes about O(n log n) [`AABB`] tests instead of the O(n²) tests of
+    /// comparing all pairs, but it degrades towards O(n²) when many shapes are about
+    /// equally close to each other.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for &x in &[0.0, 10.0, 23.0, 30.0, 45.0] {
+    ///     let position = Vec3::new(x, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// assert_eq!(bvh.closest_pair(&spheres), Some((2, 3, 5.0)));
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    pub fn closest_pair<Shape: BHShape>(&self, shapes: &[Shape]) -> Option<(usize, usize, f32)> {
+        let mut best = None;
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        // Every pair of shapes either shares a leaf or is split up at exactly one inner
+        // node, where the two subtrees are compared with each other.
+        let mut stack = TraversalStack::new();
+        let mut pair_stack = TraversalStack::new();
+        stack.push(0);
+        while let Some(node_index) = stack.pop() {
+            match self.nodes[node_index] {
+                BVHNode::Node {
+                    child_l_index,
+                    child_r_index,
+                    ..
+                } => {
+                    stack.push(child_r_index);
+                    stack.push(child_l_index);
+                    self.closest_pair_between(
+                        child_l_index,
+                        child_r_index,
+                        shapes,
+                        &mut best,
+                        &mut pair_stack,
+                    );
+                }
+                BVHNode::Leaf { .. } => {
+                    let leaf_shapes = self.leaf_shapes(node_index);
+                    for (i, &a) in leaf_shapes.iter().enumerate() {
+                        for &b in &leaf_shapes[i + 1..] {
+                            update_closest_pair(a.min(b), a.max(b), shapes, &mut best);
+                        }
+                    }
+                }
+            }
+        }
+        best.map(|(a, b, distance_squared): (usize, usize, f32)| (a, b, distance_squared.sqrt()))
+    }
+
+    /// Updates `best` with the closest pair of one shape from each of the subtrees at the
+    /// node indices `a` and `b`. `best` holds the indices of the shapes and the squared
+    /// distance of their [`AABB`]s. The pairs of subtrees which are yet to be compared
+    /// are kept on `stack`, which is empty before and after.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    fn closest_pair_between<Shape: BHShape>(
+        &self,
+        a: usize,
+        b: usize,
+        shapes: &[Shape],
+        best: &mut Option<(usize, usize, f32)>,
+        stack: &mut TraversalStack,
+    ) {
+        // Each pair of subtrees takes two entries, the second one is popped first.
+        stack.push(a);
+        stack.push(b);
+        while let (Some(b), Some(a)) = (stack.pop(), stack.pop()) {
+            let (a_aabb, b_aabb) = (self.child_aabb(a), self.child_aabb(b));
+            if let Some((_, _, best_distance_squared)) = *best {
+                if aabb_distance_squared(&a_aabb, &b_aabb) >= best_distance_squared {
+                    continue;
+                }
+            }
+
+            // Descend into the larger inner node, so that both subtrees shrink evenly.
+            let (outer, other, other_aabb) = match (&self.nodes[a], &self.nodes[b]) {
+                (BVHNode::Leaf { .. }, BVHNode::Leaf { .. }) => {
+                    for &a in self.leaf_shapes(a) {
+                        for &b in self.leaf_shapes(b) {
+                            update_closest_pair(a.min(b), a.max(b), shapes, best);
+                        }
+                    }
+                    continue;
+                }
+                (BVHNode::Leaf { .. }, _) => (b, a, a_aabb),
+                (_, BVHNode::Leaf { .. }) => (a, b, b_aabb),
+                _ if a_aabb.surface_area() >= b_aabb.surface_area() => (a, b, b_aabb),
+                _ => (b, a, a_aabb),
+            };
+
+            if let BVHNode::Node {
+                child_l_index,
+                child_l_aabb,
+                child_r_index,
+                child_r_aabb,
+                ..
+            } = self.nodes[outer]
+            {
+                // Push the farther child first, so the closer one is compared first.
+                let mut children = [(child_l_index, child_l_aabb), (child_r_index, child_r_aabb)];
+                if aabb_distance_squared(&children[0].1, &other_aabb)
+                    <= aabb_distance_squared(&children[1].1, &other_aabb)
+                {
+                    children.swap(0, 1);
+                }
+                for (child_index, _) in &children {
+                    stack.push(*child_index);
+                    stack.push(other);
+                }
+            }
+        }
+    }
+
+    /// Returns all pairs of shapes, one from this [`BVH`] and one from `other`, whose
+    /// [`AABB`]s overlap. Each pair holds the index into `self_shapes` first and the index
+    /// into `other_shapes` second. Touching [`AABB`]s count as overlapping. The order of
+    /// the pairs is unspecified.
+    ///
+    /// Both trees are descended simultaneously, and a pair of subtrees is only entered if
+    /// their [`AABB`]s overlap, which makes this a broad phase for collisions between e.g.
+    /// a dynamic and a static scene.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let sphere = |x: f32| Sphere { position: Vec3::new(x, 0.0, 0.0), radius: 1.0, node_index: 0 };
+    /// let mut dynamic = vec![sphere(0.0), sphere(10.0), sphere(20.0)];
+    /// let mut fixed = vec![sphere(11.5), sphere(30.0), sphere(-1.0), sphere(100.0)];
+    /// let dynamic_bvh = BVH::build(&mut dynamic);
+    /// let fixed_bvh = BVH::build(&mut fixed);
+    ///
+    /// let mut pairs = dynamic_bvh.overlapping_pairs(&dynamic, &fixed_bvh, &fixed);
+    /// pairs.sort();
+    /// assert_eq!(pairs, vec![(0, 2), (1, 0)]);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    ///
+    pub fn overlapping_pairs<S1: BHShape, S2: BHShape>(
+        &self,
+        self_shapes: &[S1],
+        other: &BVH,
+        other_shapes: &[S2],
+    ) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        if !self.nodes.is_empty() && !other.nodes.is_empty() {
+            let self_aabb = self.nodes[0].get_node_aabb(&self.shape_indices, self_shapes);
+            let other_aabb = other.nodes[0].get_node_aabb(&other.shape_indices, other_shapes);
+            if self_aabb.intersects_aabb(&other_aabb) {
+                self.overlapping_pairs_between(
+                    0,
+                    self_shapes,
+                    other,
+                    0,
+                    other_shapes,
+                    &mut pairs,
+                    &mut TraversalStack::new(),
+                );
+            }
+        }
+        pairs
+    }
+
+    /// Returns all pairs of distinct shapes whose [`AABB`]s overlap, e.g. for the broad phase
+    /// of self-collisions of cloth or soft bodies. Every pair `(i, j)` holds indices into
+    /// `shapes` with `i < j`, so neither a shape paired with itself nor a mirrored duplicate
+    /// `(j, i)` is returned. Touching [`AABB`]s count as overlapping. The order of the pairs
+    /// is unspecified.
+    ///
+    /// Every inner node pairs its two subtrees with the simultaneous descent of
+    /// [`overlapping_pairs`] if their [`AABB`]s overlap, and then descends into each
+    /// subtree on its own, so no subtree is ever tested against itself.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for &x in &[0.0, 1.5, 3.0, 10.0, 30.0, 31.0] {
+    ///     let position = Vec3::new(x, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let mut pairs = bvh.self_overlapping_pairs(&spheres);
+    /// pairs.sort();
+    /// assert_eq!(pairs, vec![(0, 1), (1, 2), (4, 5)]);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`overlapping_pairs`]: struct.BVH.html#method.overlapping_pairs
+    ///
+    pub fn self_overlapping_pairs<Shape: BHShape>(&self, shapes: &[Shape]) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        if self.nodes.is_empty() {
+            return pairs;
+        }
+
+        // Every pair of shapes either shares a leaf or is split up at exactly one inner
+        // node, where the two subtrees are compared with each other.
+        let mut stack = TraversalStack::new();
+        let mut pair_stack = TraversalStack::new();
+        stack.push(0);
+        while let Some(node_index) = stack.pop() {
+            match self.nodes[node_index] {
+                BVHNode::Node {
+                    child_l_index,
+                    child_l_aabb,
+                    child_r_index,
+                    child_r_aabb,
+                    ..
+                } => {
+                    stack.push(child_r_index);
+                    stack.push(child_l_index);
+                    if child_l_aabb.intersects_aabb(&child_r_aabb) {
+                        let start = pairs.len();
+                        self.overlapping_pairs_between(
+                            child_l_index,
+                            shapes,
+                            self,
+                            child_r_index,
+                            shapes,
+                            &mut pairs,
+                            &mut pair_stack,
+                        );
+                        // The subtrees hold disjoint shapes, but not in ascending order.
+                        for pair in &mut pairs[start..] {
+                            *pair = (pair.0.min(pair.1), pair.0.max(pair.1));
+                        }
+                    }
+                }
+                BVHNode::Leaf { .. } => {
+                    let leaf_shapes = self.leaf_shapes(node_index);
+                    for (i, &a) in leaf_shapes.iter().enumerate() {
+                        let a_aabb = shapes[a].aabb();
+                        for &b in &leaf_shapes[i + 1..] {
+                            if a_aabb.intersects_aabb(&shapes[b].aabb()) {
+                                pairs.push((a.min(b), a.max(b)));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Pushes the overlapping pairs of shapes of the subtree at the node index `a` of this
+    /// [`BVH`] and the subtree at the node index `b` of `other`, whose [`AABB`]s must
+    /// overlap. The pairs of subtrees which are yet to be compared are kept on `stack`,
+    /// which is empty before and after.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    ///
+    #[allow(clippy::too_many_arguments)]
+    fn overlapping_pairs_between<S1: BHShape, S2: BHShape>(
+        &self,
+        a: usize,
+        self_shapes: &[S1],
+        other: &BVH,
+        b: usize,
+        other_shapes: &[S2],
+        pairs: &mut Vec<(usize, usize)>,
+        stack: &mut TraversalStack,
+    ) {
+        // Each pair of subtrees takes two entries, the second one is popped first.
+        stack.push(a);
+        stack.push(b);
+        while let (Some(b), Some(a)) = (stack.pop(), stack.pop()) {
+            // Descend into the larger inner node, so that both subtrees shrink evenly.
+            let descend_a = match (&self.nodes[a], &other.nodes[b]) {
+                (BVHNode::Leaf { .. }, BVHNode::Leaf { .. }) => {
+                    for &i in self.leaf_shapes(a) {
+                        let a_aabb = self_shapes[i].aabb();
+                        for &j in other.leaf_shapes(b) {
+                            if a_aabb.intersects_aabb(&other_shapes[j].aabb()) {
+                                pairs.push((i, j));
+                            }
+                        }
+                    }
+                    continue;
+                }
+                (BVHNode::Leaf { .. }, _) => false,
+                (_, BVHNode::Leaf { .. }) => true,
+                _ => {
+                    self.subtree_aabb(a, self_shapes).surface_area()
+                        >= other.subtree_aabb(b, other_shapes).surface_area()
+                }
+            };
+
+            let (nodes, outer, inner_aabb) = if descend_a {
+                (&self.nodes, a, other.subtree_aabb(b, other_shapes))
+            } else {
+                (&other.nodes, b, self.subtree_aabb(a, self_shapes))
+            };
+            if let BVHNode::Node {
+                child_l_index,
+                child_l_aabb,
+                child_r_index,
+                child_r_aabb,
+                ..
+            } = nodes[outer]
+            {
+                for (child_index, child_aabb) in
+                    &[(child_l_index, child_l_aabb), (child_r_index, child_r_aabb)]
+                {
+                    if !child_aabb.intersects_aabb(&inner_aabb) {
+                        continue;
+                    }
+                    if descend_a {
+                        stack.push(*child_index);
+                        stack.push(b);
+                    } else {
+                        stack.push(a);
+                        stack.push(*child_index);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the squared distance between the closest points of two [`AABB`]s, which is
+/// `0.0` if they overlap.
+///
+/// [`AABB`]: ../aabb/struct.AABB.html
+///
+fn aabb_distance_squared(a: &AABB, b: &AABB) -> f32 {
+    let gap = |a_min: f32, a_max: f32, b_min: f32, b_max: f32| {
+        (a_min - b_max).max(b_min - a_max).max(0.0)
+    };
+    let x = gap(a.min.x, a.max.x, b.min.x, b.max.x);
+    let y = gap(a.min.y, a.max.y, b.min.y, b.max.y);
+    let z = gap(a.min.z, a.max.z, b.min.z, b.max.z);
+    x * x + y * y + z * z
+}
+
+/// Replaces `best` with the shapes `a` and `b` if their [`AABB`]s are closer.
+///
+/// [`AABB`]: ../aabb/struct.AABB.html
+///
+fn update_closest_pair<Shape: BHShape>(
+    a: usize,
+    b: usize,
+    shapes: &[Shape],
+    best: &mut Option<(usize, usize, f32)>,
+) {
+    let distance_squared = aabb_distance_squared(&shapes[a].aabb(), &shapes[b].aabb());
+    let is_closer = match *best {
+        Some((_, _, best_distance_squared)) => distance_squared < best_distance_squared,
+        None => true,
+    };
+    if is_closer {
+        *best = Some((a, b, distance_squared));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aabb::{Bounded, AABB};
+    use crate::bvh::BVH;
+    use crate::capsule::Capsule;
+    use crate::cone::Cone;
+    use crate::testbase::{
+        build_chain_bvh, create_n_cubes, default_bounds, generate_aligned_boxes, next_Vec3,
+        tuple_to_point, Triangle, TupleVec,
+    };
+    use quickcheck::quickcheck;
+    use std::collections::HashSet;
+    use std::ptr;
+    use ultraviolet::Vec3;
+
+    /// Creates a dense scene of 100 cubes inside a box with edge length 100.
+    fn create_dense_scene() -> (Vec<Triangle>, BVH) {
+        let bounds = AABB::with_bounds(Vec3::new(-50.0, -50.0, -50.0), Vec3::new(50.0, 50.0, 50.0));
+        let mut triangles = create_n_cubes(100, &bounds);
+        let bvh = BVH::build(&mut triangles);
+        (triangles, bvh)
+    }
+
+    /// Collects the addresses of the `shapes`, to compare query results as sets.
+    fn to_set(shapes: Vec<&Triangle>) -> HashSet<*const Triangle> {
+        shapes
+            .into_iter()
+            .map(|shape| shape as *const Triangle)
+            .collect()
+    }
+
+    /// Compares the result of `traverse_point` to a linear scan over all shapes.
+    quickcheck! {
+        fn test_traverse_point_equals_linear_scan(p: TupleVec) -> bool {
+            let (triangles, bvh) = create_dense_scene();
+            let point = tuple_to_point(&p);
+
+            let expected = to_set(
+                triangles
+                    .iter()
+                    .filter(|triangle| triangle.aabb().contains(&point))
+                    .collect(),
+            );
+            to_set(bvh.traverse_point(point, &triangles)) == expected
+        }
+    }
+
+    #[test]
+    /// Tests whether points inside of the shapes find them, and points outside
+    /// of the scene find nothing.
+    fn test_traverse_point() {
+        let (triangles, bvh) = create_dense_scene();
+
+        for triangle in &triangles {
+            let hits = to_set(bvh.traverse_point(triangle.a, &triangles));
+            assert!(hits.contains(&(triangle as *const Triangle)));
+        }
+
+        let outside = Vec3::new(0.0, 1_000.0, 0.0);
+        assert!(bvh.traverse_point(outside, &triangles).is_empty());
+    }
+
+    /// Compares the result of `traverse_aabb` to a linear scan over all shapes.
+    quickcheck! {
+        fn test_traverse_aabb_equals_linear_scan(a: TupleVec, b: TupleVec) -> bool {
+            let (triangles, bvh) = create_dense_scene();
+            let query = AABB::empty()
+                .grow(&tuple_to_point(&a))
+                .grow(&tuple_to_point(&b));
+
+            let expected = to_set(
+                triangles
+                    .iter()
+                    .filter(|triangle| triangle.aabb().intersects_aabb(&query))
+                    .collect(),
+            );
+            to_set(bvh.traverse_aabb(&query, &triangles)) == expected
+        }
+    }
+
+    #[test]
+    /// Tests whether a box which moves so fast that it skips over a target between two
+    /// time steps still reports the target, while a box moving away from it does not.
+    fn test_traverse_swept_aabb_tunneling() {
+        let mut boxes = generate_aligned_boxes();
+        let bvh = BVH::build(&mut boxes);
+
+        // Starts left of all boxes and ends right of them within one step.
+        let start = AABB::with_bounds(Vec3::new(-20.0, 2.0, -0.1), Vec3::new(-19.8, 2.2, 0.1));
+        let velocity = Vec3::new(40.0, -2.1, 0.0);
+        let end = AABB::with_bounds(start.min + velocity, start.max + velocity);
+        assert!(bvh.traverse_aabb(&start, &boxes).is_empty());
+        assert!(bvh.traverse_aabb(&end, &boxes).is_empty());
+
+        let hits = bvh.traverse_swept_aabb(&start, velocity, &boxes);
+        let mut ids: Vec<i32> = hits.iter().map(|unit_box| unit_box.id).collect();
+        ids.sort();
+        assert_eq!(ids, (-10..11).collect::<Vec<i32>>());
+
+        let away = bvh.traverse_swept_aabb(&start, Vec3::new(-40.0, 0.0, 0.0), &boxes);
+        assert!(away.is_empty());
+    }
+
+    /// Collects the shapes whose `AABB` lies within `radius` of `center` by a linear scan.
+    fn sphere_linear_scan(
+        triangles: &[Triangle],
+        center: Vec3,
+        radius: f32,
+    ) -> HashSet<*const Triangle> {
+        to_set(
+            triangles
+                .iter()
+                .filter(|triangle| {
+                    triangle.aabb().distance_squared_to_point(&center) <= radius * radius
+                })
+                .collect(),
+        )
+    }
+
+    /// Compares the result of `traverse_sphere` to a linear scan over all shapes.
+    quickcheck! {
+        fn test_traverse_sphere_equals_linear_scan(c: TupleVec, r: f32) -> bool {
+            let (triangles, bvh) = create_dense_scene();
+            let center = tuple_to_point(&c);
+            let radius = r.abs() % 50.0;
+
+            let expected = sphere_linear_scan(&triangles, center, radius);
+            to_set(bvh.traverse_sphere(center, radius, &triangles)) == expected
+        }
+    }
+
+    #[test]
+    /// Tests `traverse_sphere` with spheres which lie entirely inside of a single
+    /// shape's `AABB`, and with a sphere outside of the scene.
+    fn test_traverse_sphere_inside_one_box() {
+        let mut boxes = generate_aligned_boxes();
+        let bvh = BVH::build(&mut boxes);
+
+        for unit_box in &boxes {
+            let hits = bvh.traverse_sphere(unit_box.pos, 0.25, &boxes);
+            assert_eq!(hits.len(), 1);
+            assert_eq!(hits[0].id, unit_box.id);
+        }
+
+        // The same criterion holds for the triangles of the dense scene, whose `AABB`s
+        // may be flat, so the sphere degenerates to their center point.
+        let (triangles, bvh) = create_dense_scene();
+        for triangle in &triangles {
+            let center = triangle.aabb().center();
+            let hits = to_set(bvh.traverse_sphere(center, 0.0, &triangles));
+            assert!(hits.contains(&(triangle as *const Triangle)));
+            assert_eq!(hits, sphere_linear_scan(&triangles, center, 0.0));
+        }
+
+        let outside = Vec3::new(0.0, 1_000.0, 0.0);
+        assert!(bvh.traverse_sphere(outside, 10.0, &triangles).is_empty());
+    }
+
+    /// Tests whether `traverse_cone` returns every shape with a point inside the `Cone`,
+    /// and only shapes whose `AABB` passes the conservative test of the `Cone`.
+    quickcheck! {
+        fn test_traverse_cone_is_conservative(a: TupleVec, t: TupleVec, h: f32, r: f32) -> bool {
+            // Aim the `Cone` at a point of the scene, so that it usually hits some shapes.
+            let apex = tuple_to_point(&a);
+            let direction = tuple_to_point(&t) * 0.5 - apex;
+            if direction.mag() < 0.01 {
+                return true;
+            }
+            let (triangles, bvh) = create_dense_scene();
+            let half_angle = h.abs() % std::f32::consts::FRAC_PI_2;
+            let cone = Cone::new(apex, direction, half_angle, r.abs() % 200.0);
+            let hits = to_set(bvh.traverse_cone(&cone, &triangles));
+
+            // Samples the vertices, the edge midpoints and the centroid of every triangle.
+            let touched = to_set(
+                triangles
+                    .iter()
+                    .filter(|triangle| {
+                        let (a, b, c) = (triangle.a, triangle.b, triangle.c);
+                        [a, b, c, (a + b) * 0.5, (b + c) * 0.5, (c + a) * 0.5, (a + b + c) / 3.0]
+                            .iter()
+                            .any(|point| cone.contains(point))
+                    })
+                    .collect(),
+            );
+            let candidates = to_set(
+                triangles
+                    .iter()
+                    .filter(|triangle| cone.intersects_aabb(&triangle.aabb()))
+                    .collect(),
+            );
+            touched.is_subset(&hits) && hits.is_subset(&candidates)
+        }
+    }
+
+    /// Tests whether `traverse_capsule` equals a linear scan with the exact distance of
+    /// every `AABB` to the segment, and returns every shape with a point inside the
+    /// `Capsule`.
+    quickcheck! {
+        fn test_traverse_capsule_equals_linear_scan(a: TupleVec, b: TupleVec, r: f32) -> bool {
+            let (triangles, bvh) = create_dense_scene();
+            let capsule = Capsule::new(tuple_to_point(&a), tuple_to_point(&b), r.abs() % 30.0);
+            let hits = to_set(bvh.traverse_capsule(&capsule, &triangles));
+
+            let expected = to_set(
+                triangles
+                    .iter()
+                    .filter(|triangle| {
+                        let aabb = triangle.aabb();
+                        aabb.distance_squared_to_segment(&capsule.a, &capsule.b)
+                            <= capsule.radius * capsule.radius
+                    })
+                    .collect(),
+            );
+
+            // The distance of a point to the segment of the `Capsule`.
+            let segment = capsule.b - capsule.a;
+            let distance_to_segment = |point: &Vec3| {
+                let t = if segment.mag_sq() > 0.0 {
+                    ((*point - capsule.a).dot(segment) / segment.mag_sq()).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                (*point - (capsule.a + segment * t)).mag()
+            };
+            let touched = to_set(
+                triangles
+                    .iter()
+                    .filter(|triangle| {
+                        [triangle.a, triangle.b, triangle.c]
+                            .iter()
+                            .any(|point| distance_to_segment(point) < capsule.radius * 0.999)
+                    })
+                    .collect(),
+            );
+            hits == expected && touched.is_subset(&hits)
+        }
+    }
+
+    #[test]
+    /// Compares the result of `k_nearest` to sorting all shapes by distance.
+    fn test_k_nearest_equals_brute_force() {
+        let bounds = default_bounds();
+        let mut seed = 0;
+
+        for &cube_count in &[1, 2, 10, 50] {
+            let mut triangles = create_n_cubes(cube_count, &bounds);
+            let bvh = BVH::build(&mut triangles);
+
+            for _ in 0..10 {
+                let query = next_Vec3(&mut seed, &bounds);
+
+                // Sort all indices by distance, breaking ties by index.
+                let mut expected: Vec<(f32, usize)> = triangles
+                    .iter()
+                    .enumerate()
+                    .map(|(i, triangle)| (triangle.aabb().distance_squared_to_point(&query), i))
+                    .collect();
+                expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+                for &k in &[0, 1, 5, 12, triangles.len() / 2, triangles.len() + 1] {
+                    let nearest: Vec<usize> = bvh
+                        .k_nearest(query, k, &triangles)
+                        .into_iter()
+                        .map(|triangle| {
+                            triangles.iter().position(|t| ptr::eq(t, triangle)).unwrap()
+                        })
+                        .collect();
+                    let expected_indices: Vec<usize> =
+                        expected.iter().take(k).map(|&(_, i)| i).collect();
+                    assert_eq!(nearest, expected_indices);
+                }
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether `traverse_contained` returns a box wholly inside the region, even if
+    /// it touches the boundary, but not a box which straddles the boundary, and compares
+    /// it to a linear scan.
+    fn test_traverse_contained() {
+        use crate::testbase::UnitBox;
+
+        let mut boxes = vec![
+            UnitBox::new(0, Vec3::new(0.0, 0.0, 0.0)),
+            UnitBox::new(1, Vec3::new(4.5, 0.0, 0.0)),
+            UnitBox::new(2, Vec3::new(5.0, 0.0, 0.0)),
+            UnitBox::new(3, Vec3::new(20.0, 0.0, 0.0)),
+        ];
+        let bvh = BVH::build(&mut boxes);
+        let region = AABB::with_bounds(Vec3::new(-5.0, -5.0, -5.0), Vec3::new(5.0, 5.0, 5.0));
+
+        let mut ids: Vec<i32> = bvh
+            .traverse_contained(&region, &boxes)
+            .iter()
+            .map(|unit_box| unit_box.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec![0, 1]);
+        assert_eq!(bvh.traverse_aabb(&region, &boxes).len(), 3);
+
+        let (triangles, bvh) = create_dense_scene();
+        let region = AABB::with_bounds(Vec3::new(-30.0, -20.0, -40.0), Vec3::new(10.0, 25.0, 5.0));
+        let expected = to_set(
+            triangles
+                .iter()
+                .filter(|triangle| region.contains_aabb(&triangle.aabb()))
+                .collect(),
+        );
+        assert!(!expected.is_empty());
+        assert_eq!(
+            to_set(bvh.traverse_contained(&region, &triangles)),
+            expected
+        );
+    }
+
+    #[test]
+    /// Compares the result of `overlapping_pairs` to testing all pairs of shapes of two
+    /// scenes, for `BVH`s with one and with several shapes per leaf.
+    fn test_overlapping_pairs_equals_brute_force() {
+        use crate::bvh::BuildConfig;
+        use crate::testbase::UnitBox;
+
+        let bounds = AABB::with_bounds(Vec3::new(-10.0, -10.0, -10.0), Vec3::new(10.0, 10.0, 10.0));
+        let mut seed = 0;
+
+        for &(a_count, b_count) in &[(0, 5), (1, 1), (3, 10), (50, 20), (200, 150)] {
+            for &max_leaf_size in &[1, 4] {
+                let config = BuildConfig {
+                    max_leaf_size,
+                    ..Default::default()
+                };
+                let mut a: Vec<UnitBox> = (0..a_count)
+                    .map(|id| UnitBox::new(id, next_Vec3(&mut seed, &bounds)))
+                    .collect();
+                let mut b: Vec<UnitBox> = (0..b_count)
+                    .map(|id| UnitBox::new(id, next_Vec3(&mut seed, &bounds)))
+                    .collect();
+                let a_bvh = BVH::build_with_config(&mut a, &config);
+                let b_bvh = BVH::build_with_config(&mut b, &config);
+
+                let mut expected = Vec::new();
+                for (i, a_box) in a.iter().enumerate() {
+                    for (j, b_box) in b.iter().enumerate() {
+                        if a_box.aabb().intersects_aabb(&b_box.aabb()) {
+                            expected.push((i, j));
+                        }
+                    }
+                }
+
+                let mut pairs = a_bvh.overlapping_pairs(&a, &b_bvh, &b);
+                pairs.sort();
+                assert_eq!(pairs, expected);
+
+                let mut mirrored: Vec<(usize, usize)> = b_bvh
+                    .overlapping_pairs(&b, &a_bvh, &a)
+                    .into_iter()
+                    .map(|(j, i)| (i, j))
+                    .collect();
+                mirrored.sort();
+                assert_eq!(mirrored, expected);
+            }
+        }
+    }
+
+    #[test]
+    /// Compares the result of `self_overlapping_pairs` to testing all unordered pairs of
+    /// shapes, for `BVH`s with one and with several shapes per leaf.
+    fn test_self_overlapping_pairs_equals_brute_force() {
+        use crate::bvh::BuildConfig;
+        use crate::testbase::UnitBox;
+
+        let bounds = AABB::with_bounds(Vec3::new(-10.0, -10.0, -10.0), Vec3::new(10.0, 10.0, 10.0));
+        let mut seed = 0;
+
+        for &box_count in &[0, 1, 2, 10, 50, 300] {
+            for &max_leaf_size in &[1, 4] {
+                let mut boxes: Vec<UnitBox> = (0..box_count)
+                    .map(|id| UnitBox::new(id, next_Vec3(&mut seed, &bounds)))
+                    .collect();
+                let config = BuildConfig {
+                    max_leaf_size,
+                    ..Default::default()
+                };
+                let bvh = BVH::build_with_config(&mut boxes, &config);
+
+                let mut expected = Vec::new();
+                for (i, a) in boxes.iter().enumerate() {
+                    for (j, b) in boxes.iter().enumerate().skip(i + 1) {
+                        if a.aabb().intersects_aabb(&b.aabb()) {
+                            expected.push((i, j));
+                        }
+                    }
+                }
+
+                let mut pairs = bvh.self_overlapping_pairs(&boxes);
+                assert!(pairs.iter().all(|&(i, j)| i < j));
+                pairs.sort();
+                assert_eq!(pairs, expected);
+            }
+        }
+    }
+
+    #[test]
+    /// Compares the result of `closest_pair` to testing all pairs of shapes, for
+    /// `BVH`s with one and with several shapes per leaf.
+    fn test_closest_pair_equals_brute_force() {
+        use crate::bvh::BuildConfig;
+        use crate::testbase::UnitBox;
+
+        let bounds = AABB::with_bounds(Vec3::new(-50.0, -50.0, -50.0), Vec3::new(50.0, 50.0, 50.0));
+        let mut seed = 0;
+
+        for &box_count in &[0, 1, 2, 3, 10, 50, 200] {
+            for &max_leaf_size in &[1, 4] {
+                let mut boxes: Vec<UnitBox> = (0..box_count)
+                    .map(|id| UnitBox::new(id, next_Vec3(&mut seed, &bounds)))
+                    .collect();
+                let config = BuildConfig {
+                    max_leaf_size,
+                    ..Default::default()
+                };
+                let bvh = BVH::build_with_config(&mut boxes, &config);
+
+                // The gap between two unit boxes on each axis is the distance of their
+                // centers minus one.
+                let distance = |a: usize, b: usize| {
+                    let offset = boxes[a].pos - boxes[b].pos;
+                    let gap = |offset: f32| (offset.abs() - 1.0).max(0.0);
+                    Vec3::new(gap(offset.x), gap(offset.y), gap(offset.z)).mag()
+                };
+                let mut expected: Option<f32> = None;
+                for a in 0..boxes.len() {
+                    for b in a + 1..boxes.len() {
+                        let d = distance(a, b);
+                        expected = Some(expected.map_or(d, |e: f32| e.min(d)));
+                    }
+                }
+
+                match (bvh.closest_pair(&boxes), expected) {
+                    (Some((a, b, d)), Some(expected)) => {
+                        assert!(a < b);
+                        assert!((d - distance(a, b)).abs() < 1e-3);
+                        assert!((d - expected).abs() < 1e-3);
+                    }
+                    (None, None) => assert!(boxes.len() < 2),
+                    (result, expected) => panic!("{:?} != {:?}", result, expected),
+                }
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether the queries handle a `BVH` which is too deep to be traversed
+    /// recursively.
+    fn test_queries_deep_chain() {
+        let n = 300_000;
+        let (boxes, bvh) = build_chain_bvh(n);
+        let ids = |hits: Vec<&crate::testbase::UnitBox>| {
+            let mut ids: Vec<i32> = hits.iter().map(|unit_box| unit_box.id).collect();
+            ids.sort_unstable();
+            ids
+        };
+
+        // The unit boxes are centered on the X axis at `0..n`, so the last ones are the
+        // deepest in the chain.
+        let last = (n - 1) as f32;
+        let point = Vec3::new(last - 1.0, 0.0, 0.0);
+        assert_eq!(ids(bvh.traverse_point(point, &boxes)), vec![n as i32 - 2]);
+        assert_eq!(ids(bvh.traverse_sphere(point, 0.75, &boxes)).len(), 3);
+        let query = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(last, 1.0, 1.0));
+        assert_eq!(bvh.traverse_aabb(&query, &boxes).len(), n);
+
+        // Neighbouring boxes touch, so they are the closest and the overlapping pairs.
+        let (a, b, distance) = bvh.closest_pair(&boxes).unwrap();
+        assert_eq!((b - a, distance), (1, 0.0));
+        let expected: Vec<(usize, usize)> = (1..n).map(|i| (i - 1, i)).collect();
+        let mut pairs = bvh.self_overlapping_pairs(&boxes);
+        pairs.sort_unstable();
+        assert_eq!(pairs, expected);
+        assert_eq!(
+            bvh.overlapping_pairs(&boxes, &bvh, &boxes).len(),
+            n + 2 * (n - 1)
+        );
+    }
+}
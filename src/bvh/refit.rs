@@ -0,0 +1,378 @@
+//! This module defines the refitting of a [`BVH`] after its shapes have moved.
+//! Unlike [`optimize`], refitting never changes the topology of the tree.
+//!
+//! [`BVH`]: struct.BVH.html
+//! [`optimize`]: struct.BVH.html#method.optimize
+//!
+
+use crate::aabb::AABB;
+use crate::bounding_hierarchy::BHShape;
+use crate::bvh::*;
+
+impl BVH {
+    /// Updates the [`AABB`]s of the [`BVH`] after some of the `shapes` moved, without
+    /// changing the topology of the [`BVH`].
+    ///
+    /// Every leaf whose shapes no longer match the [`AABB`] stored for it is refitted, and
+    /// so is every inner node on the path from such a leaf up to the root. A refitted
+    /// inner node gets the join of the [`AABB`]s stored for its two children, children
+    /// before parents and without recursion. Subtrees without moved shapes are not
+    /// visited and their stored [`AABB`]s are trusted, so the cost grows with the number
+    /// of leaves plus the number of nodes above moved shapes. Use [`tighten`] to recompute
+    /// every [`AABB`] from the shapes instead.
+    ///
+    /// Traversal stays correct after refitting, but the quality of the [`BVH`] degrades
+    /// the farther the shapes move from the positions they were built for. Call [`build`]
+    /// again from time to time when shapes move a lot, or use [`optimize`] to restructure
+    /// the affected nodes.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let mut bvh = BVH::build(&mut spheres);
+    ///
+    /// // Move every sphere up by 5 units.
+    /// for sphere in &mut spheres {
+    ///     sphere.position.y += 5.0;
+    /// }
+    /// bvh.refit(&spheres);
+    ///
+    /// let ray = Ray::new(Vec3::new(30.0, -10.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    /// let hits = bvh.traverse(&ray, &spheres);
+    /// assert_eq!(hits.len(), 1);
+    /// assert_eq!(hits[0].position, Vec3::new(30.0, 5.0, 0.0));
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`build`]: struct.BVH.html#method.build
+    /// [`optimize`]: struct.BVH.html#method.optimize
+    /// [`tighten`]: struct.BVH.html#method.tighten
+    ///
+    pub fn refit<Shape: BHShape>(&mut self, shapes: &[Shape]) {
+        // Refit the stale leaves and mark the inner nodes above them.
+        let mut dirty = vec![false; self.nodes.len()];
+        for leaf_index in 1..self.nodes.len() {
+            if self.nodes[leaf_index].shape_index().is_none() {
+                continue;
+            }
+            let leaf_aabb = self.nodes[leaf_index].get_node_aabb(&self.shape_indices, shapes);
+            if self.store_child_aabb(leaf_index, leaf_aabb) {
+                let mut node_index = self.nodes[leaf_index].parent();
+                while !dirty[node_index] {
+                    dirty[node_index] = true;
+                    if node_index == 0 {
+                        break;
+                    }
+                    node_index = self.nodes[node_index].parent();
+                }
+            }
+        }
+        if !dirty[0] {
+            return;
+        }
+
+        // Collect the marked nodes in pre-order, so that every child comes after its parent.
+        let mut order = Vec::new();
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            order.push(node_index);
+            if let BVHNode::Node {
+                child_l_index,
+                child_r_index,
+                ..
+            } = self.nodes[node_index]
+            {
+                stack.extend(
+                    [child_r_index, child_l_index]
+                        .iter()
+                        .filter(|child_index| dirty[**child_index]),
+                );
+            }
+        }
+
+        // Walk the pre-order backwards, so that the children are done before their parents.
+        for &node_index in order.iter().rev().filter(|node_index| **node_index != 0) {
+            let node = &self.nodes[node_index];
+            let node_aabb = node.child_l_aabb().join(&node.child_r_aabb());
+            self.store_child_aabb(node_index, node_aabb);
+        }
+    }
+
+    /// Stores `aabb` as the [`AABB`] of the node at `node_index` in its parent and returns
+    /// whether it differs from the stored one. Must not be called for the root.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    fn store_child_aabb(&mut self, node_index: usize, aabb: AABB) -> bool {
+        let parent_index = self.nodes[node_index].parent();
+        let parent = &mut self.nodes[parent_index];
+        let child_aabb = if parent.child_l() == node_index {
+            parent.child_l_aabb_mut()
+        } else {
+            parent.child_r_aabb_mut()
+        };
+        let is_stale = !child_aabb.relative_eq(&aabb, 0.0);
+        *child_aabb = aabb;
+        is_stale
+    }
+
+    /// Recomputes the child [`AABB`]s of all inner nodes as the exact joint [`AABB`] of
+    /// the `shapes` in their subtrees, and returns the number of child [`AABB`]s which
+    /// were stale and had to be replaced. A return value of `0` means the [`BVH`] was
+    /// already tight.
+    ///
+    /// Unlike [`refit`], which only follows the paths above moved shapes and trusts the
+    /// [`AABB`]s stored for all other subtrees, `tighten` visits every node and does not
+    /// trust any stored [`AABB`]. It also reports whether anything was out of date. Use it
+    /// as a final check before queries which rely on tight bounds, for example after
+    /// modifying the `nodes` by hand or forgetting to refit after moving shapes.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let mut bvh = BVH::build(&mut spheres);
+    /// assert_eq!(bvh.tighten(&spheres), 0);
+    ///
+    /// // Only the `AABB` of the leaf of the moved sphere and its ancestors are stale.
+    /// spheres[3].position.y += 5.0;
+    /// let depth = bvh.nodes[spheres[3].node_index].depth() as usize;
+    /// assert_eq!(bvh.tighten(&spheres), depth);
+    /// assert_eq!(bvh.tighten(&spheres), 0);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`refit`]: struct.BVH.html#method.refit
+    ///
+    pub fn tighten<Shape: BHShape>(&mut self, shapes: &[Shape]) -> usize {
+        if self.nodes.is_empty() {
+            return 0;
+        }
+
+        // Collect the nodes in pre-order, so that every child comes after its parent.
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            order.push(node_index);
+            if let BVHNode::Node {
+                child_l_index,
+                child_r_index,
+                ..
+            } = self.nodes[node_index]
+            {
+                stack.push(child_r_index);
+                stack.push(child_l_index);
+            }
+        }
+
+        // Walk the pre-order backwards, so that the children are done before their parents.
+        let mut subtree_aabbs = vec![AABB::empty(); self.nodes.len()];
+        let mut stale_count = 0;
+        for &node_index in order.iter().rev() {
+            let node = &mut self.nodes[node_index];
+            subtree_aabbs[node_index] = match *node {
+                BVHNode::Node {
+                    child_l_index,
+                    ref mut child_l_aabb,
+                    child_r_index,
+                    ref mut child_r_aabb,
+                    ..
+                } => {
+                    for (child_aabb, child_index) in
+                        [(child_l_aabb, child_l_index), (child_r_aabb, child_r_index)]
+                    {
+                        let tight_aabb = subtree_aabbs[child_index];
+                        if !child_aabb.relative_eq(&tight_aabb, 0.0) {
+                            *child_aabb = tight_aabb;
+                            stale_count += 1;
+                        }
+                    }
+                    subtree_aabbs[child_l_index].join(&subtree_aabbs[child_r_index])
+                }
+                BVHNode::Leaf { .. } => node.get_node_aabb(&self.shape_indices, shapes),
+            };
+        }
+        stale_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aabb::AABB;
+    use crate::bvh::{BVHNode, BuildConfig, BVH};
+    use crate::ray::Ray;
+    use crate::testbase::{
+        build_chain_bvh, create_n_cubes, default_bounds, generate_aligned_boxes,
+        randomly_transform_scene, Triangle,
+    };
+    use ultraviolet::Vec3;
+
+    #[test]
+    /// Moves all shapes by a fixed offset and checks whether every shape is still
+    /// found by a ray which points at it after refitting.
+    fn test_refit_after_offset() {
+        let mut boxes = generate_aligned_boxes();
+        let mut bvh = BVH::build(&mut boxes);
+
+        let offset = Vec3::new(3.0, 7.0, -2.0);
+        for unit_box in &mut boxes {
+            unit_box.pos += offset;
+        }
+        bvh.refit(&boxes);
+
+        bvh.assert_consistent(&boxes);
+        bvh.assert_tight(&boxes);
+        for unit_box in &boxes {
+            let origin = unit_box.pos - Vec3::new(0.0, 0.0, 100.0);
+            let ray = Ray::new(origin, Vec3::new(0.0, 0.0, 1.0));
+            let hits: Vec<i32> = bvh
+                .traverse(&ray, &boxes)
+                .iter()
+                .map(|hit| hit.id)
+                .collect();
+            assert_eq!(hits, vec![unit_box.id]);
+        }
+    }
+
+    /// Asserts that every child `AABB` below the node at `node_index` is exactly the joint
+    /// `AABB` of the shapes in its subtree, and returns the one of the whole subtree.
+    fn assert_exact_subtree(bvh: &BVH, node_index: usize, triangles: &[Triangle]) -> AABB {
+        match bvh.nodes[node_index] {
+            BVHNode::Node {
+                child_l_index,
+                child_l_aabb,
+                child_r_index,
+                child_r_aabb,
+                ..
+            } => {
+                let l_aabb = assert_exact_subtree(bvh, child_l_index, triangles);
+                let r_aabb = assert_exact_subtree(bvh, child_r_index, triangles);
+                assert!(child_l_aabb.relative_eq(&l_aabb, 0.0));
+                assert!(child_r_aabb.relative_eq(&r_aabb, 0.0));
+                l_aabb.join(&r_aabb)
+            }
+            BVHNode::Leaf { .. } => {
+                bvh.nodes[node_index].get_node_aabb(&bvh.shape_indices, triangles)
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether `tighten` makes every child `AABB` the exact joint `AABB` of its
+    /// subtree after randomly moving shapes and corrupting a node by hand, and whether
+    /// it reports the stale child `AABB`s.
+    fn test_tighten_after_randomly_transforming() {
+        let bounds = default_bounds();
+        for &max_leaf_size in &[1, 4] {
+            let mut triangles = create_n_cubes(1_000, &bounds);
+            let config = BuildConfig {
+                max_leaf_size,
+                ..Default::default()
+            };
+            let mut bvh = BVH::build_with_config(&mut triangles, &config);
+            assert_eq!(bvh.tighten(&triangles), 0);
+
+            let mut seed = 0;
+            randomly_transform_scene(&mut triangles, 3_000, &bounds, None, &mut seed);
+            *bvh.nodes[0].child_l_aabb_mut() = AABB::empty();
+            assert!(bvh.tighten(&triangles) > 0);
+
+            bvh.assert_consistent(&triangles);
+            assert_exact_subtree(&bvh, 0, &triangles);
+            assert_eq!(bvh.tighten(&triangles), 0);
+
+            // `refit` computes the same `AABB`s.
+            let tightened = bvh.nodes.clone();
+            bvh.refit(&triangles);
+            assert_eq!(bvh.nodes, tightened);
+        }
+    }
+
+    #[test]
+    /// Tests whether `refit` only refits the path above a moved shape and trusts the
+    /// `AABB`s stored for the other subtrees, which `tighten` does not.
+    fn test_refit_trusts_untouched_subtrees() {
+        use crate::aabb::Bounded;
+
+        let mut boxes = generate_aligned_boxes();
+        let mut bvh = BVH::build(&mut boxes);
+        let path = bvh.path_to_shape(0);
+
+        // Inflate the `AABB` of the root child which does not hold the moved shape.
+        let inflated = AABB::with_bounds(
+            Vec3::new(-100.0, -100.0, -100.0),
+            Vec3::new(100.0, 100.0, 100.0),
+        );
+        let untouched_is_left = bvh.nodes[0].child_l() != path[1];
+        if untouched_is_left {
+            *bvh.nodes[0].child_l_aabb_mut() = inflated;
+        } else {
+            *bvh.nodes[0].child_r_aabb_mut() = inflated;
+        }
+
+        boxes[0].pos += Vec3::new(0.0, 5.0, 0.0);
+        bvh.refit(&boxes);
+        let untouched_aabb = if untouched_is_left {
+            bvh.nodes[0].child_l_aabb()
+        } else {
+            bvh.nodes[0].child_r_aabb()
+        };
+        assert_eq!(untouched_aabb, inflated);
+        let leaf_index = path[path.len() - 1];
+        assert_eq!(bvh.child_aabb(leaf_index), boxes[0].aabb());
+        bvh.assert_consistent(&boxes);
+
+        assert_eq!(bvh.tighten(&boxes), 1);
+        bvh.assert_tight(&boxes);
+    }
+
+    #[test]
+    /// Tests whether `refit` handles a chain of nodes which is too deep for recursion.
+    fn test_refit_deep_chain() {
+        let (mut boxes, mut bvh) = build_chain_bvh(300_000);
+        let offset = Vec3::new(0.0, 5.0, 0.0);
+        for unit_box in &mut boxes {
+            unit_box.pos += offset;
+        }
+        bvh.refit(&boxes);
+
+        assert_eq!(bvh.tighten(&boxes), 0);
+        let expected = AABB::of_bounded(&boxes[1..]);
+        assert!(bvh.nodes[0].child_l_aabb().relative_eq(&expected, 0.0));
+        assert_eq!(bvh.bounds(), Some(AABB::of_bounded(&boxes)));
+    }
+
+    #[test]
+    /// Tests whether a refitted `BVH` is consistent and tight after randomly moving shapes.
+    fn test_refit_after_randomly_transforming() {
+        let bounds = default_bounds();
+        let mut triangles = create_n_cubes(1_000, &bounds);
+        let mut bvh = BVH::build(&mut triangles);
+
+        let mut seed = 0;
+        randomly_transform_scene(&mut triangles, 3_000, &bounds, None, &mut seed);
+        bvh.refit(&triangles);
+
+        bvh.assert_consistent(&triangles);
+        bvh.assert_tight(&triangles);
+    }
+}
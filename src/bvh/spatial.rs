@@ -0,0 +1,336 @@
+//! This module defines the SAH builder with spatial splits, which is used by
+//! [`BVH::build_with_config`] if [`BuildConfig::spatial_splits`] is set.
+//!
+//! Like in a split bounding volume hierarchy (SBVH), a node may be split by a plane
+//! instead of partitioning its shapes. Shapes which straddle the plane are referenced by
+//! both children, each bounded by [`Bounded::clipped_aabb`] on its side.
+//!
+//! [`Bounded::clipped_aabb`]: ../aabb/trait.Bounded.html#method.clipped_aabb
+//! [`BVH::build_with_config`]: struct.BVH.html#method.build_with_config
+//! [`BuildConfig::spatial_splits`]: struct.BuildConfig.html#structfield.spatial_splits
+//!
+
+use crate::aabb::AABB;
+use crate::axis::Axis;
+use crate::bounding_hierarchy::BHShape;
+use crate::bvh::*;
+use crate::utils::{concatenate_vectors, Bucket};
+
+/// Spatial splits are only considered for nodes whose best object split produces children
+/// which overlap by more than this fraction of the surface area of the root.
+const SPATIAL_SPLIT_OVERLAP: f32 = 1e-5;
+
+/// The number of references which spatial splits may add in total, relative to the number
+/// of shapes. With `1.0` a tree references at most twice as many shapes as it is built from.
+const SPATIAL_SPLIT_BUDGET: f32 = 1.0;
+
+/// A reference from a node under construction to a shape, bounded by the part of the
+/// shape's `AABB` which lies inside the node.
+#[derive(Debug, Copy, Clone)]
+struct Reference {
+    /// The index of the shape.
+    index: usize,
+
+    /// The `AABB` of the part of the shape this reference covers.
+    aabb: AABB,
+}
+
+/// The state of a build with spatial splits.
+struct SpatialBuilder<'a, Shape> {
+    /// The settings of the build.
+    config: &'a BuildConfig,
+
+    /// The shapes, which are told the index of their leaf.
+    shapes: &'a mut [Shape],
+
+    /// The nodes built so far.
+    nodes: &'a mut Vec<BVHNode>,
+
+    /// The shape indices of all leaves in the order the leaves were created, see
+    /// `BVH::shape_indices`.
+    shape_indices: &'a mut Vec<usize>,
+
+    /// The surface area of the root, which the overlap of an object split is compared with.
+    root_area: f32,
+
+    /// The number of references spatial splits may still add.
+    remaining_budget: usize,
+}
+
+/// Builds the nodes for the shapes at `indices`, whose `AABB`s are `aabbs`, using spatial
+/// splits where they are cheaper than partitioning the shapes. The nodes are appended to
+/// `nodes` starting with the root, and the shape indices of every leaf to `shape_indices`.
+///
+/// Shapes which are clipped by a spatial split appear in several leaves, and therefore
+/// several times in `shape_indices`. Each of them is told the index of the leaf created
+/// last.
+pub fn build_with_spatial_splits<Shape: BHShape>(
+    config: &BuildConfig,
+    shapes: &mut [Shape],
+    aabbs: &[AABB],
+    indices: &[usize],
+    nodes: &mut Vec<BVHNode>,
+    shape_indices: &mut Vec<usize>,
+) {
+    let references: Vec<Reference> = indices
+        .iter()
+        .map(|index| Reference {
+            index: *index,
+            aabb: aabbs[*index],
+        })
+        .collect();
+    let root_area = joint_aabb_of_references(&references).surface_area();
+    let mut builder = SpatialBuilder {
+        config,
+        shapes,
+        nodes,
+        shape_indices,
+        root_area,
+        remaining_budget: (indices.len() as f32 * SPATIAL_SPLIT_BUDGET) as usize,
+    };
+    builder.build_node(references, 0, 0);
+}
+
+/// Returns the joint `AABB` of the `references`.
+fn joint_aabb_of_references(references: &[Reference]) -> AABB {
+    references
+        .iter()
+        .fold(AABB::empty(), |aabb, reference| aabb.join(&reference.aabb))
+}
+
+impl<'a, Shape: BHShape> SpatialBuilder<'a, Shape> {
+    /// Builds the subtree for `references` and returns the index of its root node.
+    fn build_node(&mut self, references: Vec<Reference>, parent_index: usize, depth: u32) -> usize {
+        let node_index = self.nodes.len();
+        if references.len() <= self.config.max_leaf_size {
+            self.nodes.push(BVHNode::Leaf {
+                parent_index,
+                depth,
+                shape_index: self.shape_indices.len(),
+                shape_count: references.len(),
+            });
+            for reference in &references {
+                self.shapes[reference.index].set_bh_node_index(node_index);
+                self.shape_indices.push(reference.index);
+            }
+            return node_index;
+        }
+
+        // A dummy, which is replaced once the children know their indices.
+        self.nodes.push(BVHNode::Leaf {
+            parent_index: 0,
+            depth: 0,
+            shape_index: 0,
+            shape_count: 0,
+        });
+
+        let (child_l_references, child_r_references, split_axis, split_position) =
+            self.split(references);
+        let child_l_aabb = joint_aabb_of_references(&child_l_references);
+        let child_r_aabb = joint_aabb_of_references(&child_r_references);
+        let child_l_index = self.build_node(child_l_references, node_index, depth + 1);
+        let child_r_index = self.build_node(child_r_references, node_index, depth + 1);
+
+        self.nodes[node_index] = BVHNode::Node {
+            parent_index,
+            depth,
+            child_l_aabb,
+            child_l_index,
+            child_r_aabb,
+            child_r_index,
+            split_axis,
+            split_position,
+        };
+        node_index
+    }
+
+    /// Splits `references` into two non-empty halves, either by the best object split of
+    /// `BVHNode::build_with_config` or by the best spatial split, whichever is cheaper.
+    /// Returns the halves, followed by the axis and the position of the split plane.
+    fn split(&mut self, references: Vec<Reference>) -> (Vec<Reference>, Vec<Reference>, Axis, f32) {
+        let bounds = joint_aabb_of_references(&references);
+        let centroid_bounds = references.iter().fold(AABB::empty(), |aabb, reference| {
+            aabb.grow(&reference.aabb.center())
+        });
+        let split_axis = centroid_bounds.largest_axis();
+        let split_axis_size = centroid_bounds.max[split_axis] - centroid_bounds.min[split_axis];
+
+        if split_axis_size < self.config.epsilon {
+            // The centroids coincide, so just split the references in half.
+            let mut child_l_references = references;
+            let child_r_references = child_l_references.split_off(child_l_references.len() / 2);
+            let split_position = centroid_bounds.center()[split_axis];
+            return (
+                child_l_references,
+                child_r_references,
+                split_axis,
+                split_position,
+            );
+        }
+
+        // Find the best object split among the bucket borders along the centroid bounds.
+        let bucket_count = self.config.bucket_count;
+        let mut buckets = vec![Bucket::empty(); bucket_count];
+        let mut bucket_assignments: Vec<Vec<Reference>> = vec![Vec::new(); bucket_count];
+        for reference in &references {
+            let bucket_num_relative = (reference.aabb.center()[split_axis]
+                - centroid_bounds.min[split_axis])
+                / split_axis_size;
+            let bucket_num = (bucket_num_relative * (bucket_count as f32 - 0.01)) as usize;
+            buckets[bucket_num].add_aabb(&reference.aabb);
+            bucket_assignments[bucket_num].push(*reference);
+        }
+
+        let mut min_bucket = 0;
+        let mut min_cost = f32::INFINITY;
+        let mut child_l_aabb = AABB::empty();
+        let mut child_r_aabb = AABB::empty();
+        for i in 0..(bucket_count - 1) {
+            let (l_buckets, r_buckets) = buckets.split_at(i + 1);
+            let child_l = l_buckets.iter().fold(Bucket::empty(), Bucket::join_bucket);
+            let child_r = r_buckets.iter().fold(Bucket::empty(), Bucket::join_bucket);
+
+            let cost = (child_l.size as f32 * child_l.aabb.surface_area()
+                + child_r.size as f32 * child_r.aabb.surface_area())
+                / bounds.surface_area();
+            if cost < min_cost {
+                min_bucket = i;
+                min_cost = cost;
+                child_l_aabb = child_l.aabb;
+                child_r_aabb = child_r.aabb;
+            }
+        }
+
+        // Only try to clip the references if the children of the object split overlap.
+        let overlap = child_l_aabb.intersection(&child_r_aabb);
+        let overlap_area = if overlap.is_empty() {
+            0.0
+        } else {
+            overlap.surface_area()
+        };
+        if self.config.spatial_splits
+            && self.remaining_budget > 0
+            && self.root_area > 0.0
+            && overlap_area / self.root_area > SPATIAL_SPLIT_OVERLAP
+        {
+            if let Some(children) = self.spatial_split(&references, &bounds, min_cost) {
+                return children;
+            }
+        }
+
+        let (l_assignments, r_assignments) = bucket_assignments.split_at_mut(min_bucket + 1);
+        let split_position = centroid_bounds.min[split_axis]
+            + split_axis_size * (min_bucket + 1) as f32 / (bucket_count as f32 - 0.01);
+        (
+            concatenate_vectors(l_assignments),
+            concatenate_vectors(r_assignments),
+            split_axis,
+            split_position,
+        )
+    }
+
+    /// Finds the best split plane among the borders of `bucket_count` equally sized bins
+    /// along the largest axis of `bounds`. Returns the clipped references on both sides
+    /// and the plane, or `None` if no plane is cheaper than `object_cost` within the
+    /// remaining budget.
+    fn spatial_split(
+        &mut self,
+        references: &[Reference],
+        bounds: &AABB,
+        object_cost: f32,
+    ) -> Option<(Vec<Reference>, Vec<Reference>, Axis, f32)> {
+        let axis = bounds.largest_axis();
+        let extent = bounds.max[axis] - bounds.min[axis];
+        if extent < self.config.epsilon {
+            return None;
+        }
+        let bin_count = self.config.bucket_count;
+        let plane = |bin: usize| bounds.min[axis] + extent * bin as f32 / bin_count as f32;
+        let bin_of = |position: f32| {
+            let relative = (position - bounds.min[axis]) / extent;
+            ((relative * bin_count as f32) as usize).min(bin_count - 1)
+        };
+
+        // Every bin holds the parts of the references inside of it. The references are
+        // counted in the bin where they start and in the bin where they end.
+        let mut bin_aabbs = vec![AABB::empty(); bin_count];
+        let mut entries = vec![0; bin_count];
+        let mut exits = vec![0; bin_count];
+        for reference in references {
+            let entry = bin_of(reference.aabb.min[axis]);
+            let exit = bin_of(reference.aabb.max[axis]);
+            for (bin, bin_aabb) in bin_aabbs.iter_mut().enumerate().take(exit + 1).skip(entry) {
+                let (_, above) = reference.aabb.split(axis, plane(bin));
+                let (inside, _) = above.split(axis, plane(bin + 1));
+                bin_aabb.join_mut(&self.shapes[reference.index].clipped_aabb(&inside));
+            }
+            entries[entry] += 1;
+            exits[exit] += 1;
+        }
+
+        let mut best: Option<(usize, f32)> = None;
+        for i in 0..(bin_count - 1) {
+            let count_l: usize = entries[..=i].iter().sum();
+            let count_r: usize = exits[i + 1..].iter().sum();
+            let duplicates = (count_l + count_r).saturating_sub(references.len());
+            if count_l == 0 || count_r == 0 || duplicates > self.remaining_budget {
+                continue;
+            }
+            let aabb_l = bin_aabbs[..=i].iter().fold(AABB::empty(), |a, b| a.join(b));
+            let aabb_r = bin_aabbs[i + 1..]
+                .iter()
+                .fold(AABB::empty(), |a, b| a.join(b));
+            let cost = (count_l as f32 * aabb_l.surface_area()
+                + count_r as f32 * aabb_r.surface_area())
+                / bounds.surface_area();
+            if cost < best.map_or(object_cost, |(_, cost)| cost) {
+                best = Some((i + 1, cost));
+            }
+        }
+
+        let position = plane(best?.0);
+        let mut child_l_references = Vec::new();
+        let mut child_r_references = Vec::new();
+        for reference in references {
+            if reference.aabb.max[axis] <= position {
+                child_l_references.push(*reference);
+            } else if reference.aabb.min[axis] >= position {
+                child_r_references.push(*reference);
+            } else {
+                // Each side only keeps the part of the shape which actually lies on it.
+                let shape = &self.shapes[reference.index];
+                let (below, above) = reference.aabb.split(axis, position);
+                let (below, above) = (shape.clipped_aabb(&below), shape.clipped_aabb(&above));
+                match (below.is_empty(), above.is_empty()) {
+                    (false, false) => {
+                        child_l_references.push(Reference {
+                            aabb: below,
+                            ..*reference
+                        });
+                        child_r_references.push(Reference {
+                            aabb: above,
+                            ..*reference
+                        });
+                    }
+                    (false, true) => child_l_references.push(Reference {
+                        aabb: below,
+                        ..*reference
+                    }),
+                    (true, false) => child_r_references.push(Reference {
+                        aabb: above,
+                        ..*reference
+                    }),
+                    // Only possible by rounding, keep the reference as it is.
+                    (true, true) => child_l_references.push(*reference),
+                }
+            }
+        }
+        if child_l_references.is_empty() || child_r_references.is_empty() {
+            return None;
+        }
+
+        let duplicates = child_l_references.len() + child_r_references.len() - references.len();
+        self.remaining_budget = self.remaining_budget.saturating_sub(duplicates);
+        Some((child_l_references, child_r_references, axis, position))
+    }
+}
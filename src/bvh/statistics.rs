@@ -0,0 +1,490 @@
+//! This module defines [`BvhStats`], a summary of the structure and quality of a [`BVH`].
+//!
+//! [`BVH`]: struct.BVH.html
+//! [`BvhStats`]: struct.BvhStats.html
+//!
+
+use crate::aabb::AABB;
+use crate::bvh::bvh::TraversalStack;
+use crate::bvh::*;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Statistics about the structure and quality of a [`BVH`]. Created by [`BVH::statistics`].
+///
+/// [`BVH`]: struct.BVH.html
+/// [`BVH::statistics`]: struct.BVH.html#method.statistics
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BvhStats {
+    /// The total number of nodes.
+    pub node_count: usize,
+
+    /// The number of inner nodes.
+    pub inner_node_count: usize,
+
+    /// The number of leaf nodes.
+    pub leaf_count: usize,
+
+    /// The number of shapes referenced by the leaves.
+    pub shape_count: usize,
+
+    /// The depth of the deepest leaf. The root node has depth `0`.
+    pub max_depth: u32,
+
+    /// The average depth of the leaves.
+    pub average_depth: f32,
+
+    /// The average number of shapes per leaf.
+    pub average_leaf_size: f32,
+
+    /// The SAH cost of the tree: the sum of the surface areas of all inner nodes plus the
+    /// sum of the surface areas of all leaves weighted by their number of shapes, divided
+    /// by the surface area of the root. This is the expected number of node visits plus
+    /// shape tests of a random ray which hits the root, lower is better.
+    pub sah_cost: f32,
+}
+
+impl fmt::Display for BvhStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Nodes: {} ({} inner, {} leaves)",
+            self.node_count, self.inner_node_count, self.leaf_count
+        )?;
+        writeln!(
+            f,
+            "Shapes: {} ({:.2} per leaf)",
+            self.shape_count, self.average_leaf_size
+        )?;
+        writeln!(
+            f,
+            "Depth: {} max, {:.2} average",
+            self.max_depth, self.average_depth
+        )?;
+        write!(f, "SAH cost: {:.3}", self.sah_cost)
+    }
+}
+
+impl BVH {
+    /// Computes [`BvhStats`] for this [`BVH`], for example to compare the trees produced
+    /// by different build configurations.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let stats = bvh.statistics();
+    /// assert_eq!(stats.leaf_count, 10);
+    /// assert_eq!(stats.inner_node_count, 9);
+    /// println!("{}", stats);
+    /// ```
+    ///
+    /// [`BVH`]: struct.BVH.html
+    /// [`BvhStats`]: struct.BvhStats.html
+    ///
+    pub fn statistics(&self) -> BvhStats {
+        let mut stats = BvhStats {
+            node_count: self.nodes.len(),
+            inner_node_count: 0,
+            leaf_count: 0,
+            shape_count: 0,
+            max_depth: 0,
+            average_depth: 0.0,
+            average_leaf_size: 0.0,
+            sah_cost: 0.0,
+        };
+        if self.nodes.is_empty() {
+            return stats;
+        }
+
+        let root_aabb = match self.nodes[0] {
+            BVHNode::Node {
+                child_l_aabb,
+                child_r_aabb,
+                ..
+            } => Some(child_l_aabb.join(&child_r_aabb)),
+            BVHNode::Leaf { .. } => None,
+        };
+        let relative_area = |node_index: usize| match root_aabb {
+            Some(root_aabb) if root_aabb.surface_area() > 0.0 && node_index != 0 => {
+                self.child_aabb(node_index).surface_area() / root_aabb.surface_area()
+            }
+            _ => 1.0,
+        };
+
+        // The nodes are visited in pre-order, left subtree first.
+        let mut depth_sum = 0u64;
+        let mut stack = TraversalStack::new();
+        stack.push(0);
+        while let Some(node_index) = stack.pop() {
+            match self.nodes[node_index] {
+                BVHNode::Node {
+                    child_l_index,
+                    child_r_index,
+                    ..
+                } => {
+                    stats.inner_node_count += 1;
+                    stats.sah_cost += relative_area(node_index);
+                    stack.push(child_r_index);
+                    stack.push(child_l_index);
+                }
+                BVHNode::Leaf {
+                    depth, shape_count, ..
+                } => {
+                    stats.leaf_count += 1;
+                    stats.shape_count += shape_count;
+                    stats.max_depth = stats.max_depth.max(depth);
+                    depth_sum += u64::from(depth);
+                    stats.sah_cost += relative_area(node_index) * shape_count as f32;
+                }
+            }
+        }
+
+        stats.average_depth = depth_sum as f32 / stats.leaf_count as f32;
+        stats.average_leaf_size = stats.shape_count as f32 / stats.leaf_count as f32;
+        stats
+    }
+
+    /// Estimates the expected cost of traversing this [`BVH`] with a random [`Ray`] which
+    /// hits its root, using the surface area heuristic.
+    ///
+    /// The probability `P(node)` that such a [`Ray`] hits a node is the surface area of the
+    /// node's [`AABB`] divided by the one of the root. The cost is the sum of
+    /// `ct * P(node)` over all inner nodes plus the sum of `ci * shape_count * P(leaf)` over
+    /// all leaves, where `ct` is the cost of visiting an inner node and `ci` the cost of
+    /// testing a shape. Only the ratio of both matters for comparing two trees of the same
+    /// shapes, so `ct = 1.0` and `ci = 1.0` is a good default, which yields
+    /// [`BvhStats::sah_cost`]. For shapes which are much more expensive to intersect than
+    /// an [`AABB`], raise `ci` accordingly. No [`Ray`]s are traced, which makes this a
+    /// cheap way to compare builders and build configurations offline.
+    ///
+    /// An empty [`BVH`] costs `0.0`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::{BuildConfig, BVH};
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..100 {
+    ///     let position = Vec3::new((i % 10) as f32 * 10.0, (i / 10) as f32 * 10.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let fine = BVH::build(&mut spheres);
+    /// let coarse = BVH::build_with_config(
+    ///     &mut spheres,
+    ///     &BuildConfig { max_leaf_size: 16, ..Default::default() },
+    /// );
+    ///
+    /// // With expensive shapes, small leaves pay off.
+    /// assert!(fine.expected_traversal_cost(10.0, 1.0) < coarse.expected_traversal_cost(10.0, 1.0));
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`BvhStats::sah_cost`]: struct.BvhStats.html#structfield.sah_cost
+    /// [`Ray`]: ../ray/struct.Ray.html
+    ///
+    pub fn expected_traversal_cost(&self, ci: f32, ct: f32) -> f32 {
+        let node_cost = |node: &BVHNode| match *node {
+            BVHNode::Node { .. } => ct,
+            BVHNode::Leaf { shape_count, .. } => ci * shape_count as f32,
+        };
+        let root = match self.nodes.first() {
+            Some(root) => root,
+            None => return 0.0,
+        };
+
+        // Like in `statistics`, all nodes count fully if the root has no surface area.
+        let root_area = match *root {
+            BVHNode::Node {
+                child_l_aabb,
+                child_r_aabb,
+                ..
+            } => child_l_aabb.join(&child_r_aabb).surface_area(),
+            BVHNode::Leaf { .. } => 0.0,
+        };
+        let probability = |aabb: &AABB| {
+            if root_area > 0.0 {
+                aabb.surface_area() / root_area
+            } else {
+                1.0
+            }
+        };
+
+        // Every node but the root stores its `AABB` in its parent.
+        let mut cost = node_cost(root);
+        for node in &self.nodes {
+            if let BVHNode::Node {
+                child_l_index,
+                child_l_aabb,
+                child_r_index,
+                child_r_aabb,
+                ..
+            } = *node
+            {
+                cost += node_cost(&self.nodes[child_l_index]) * probability(&child_l_aabb);
+                cost += node_cost(&self.nodes[child_r_index]) * probability(&child_r_aabb);
+            }
+        }
+        cost
+    }
+
+    /// Returns the indices of the `n` inner nodes whose children overlap the most, together
+    /// with their overlap ratio, sorted by descending ratio. Fewer nodes are returned if the
+    /// [`BVH`] has less than `n` inner nodes.
+    ///
+    /// The overlap ratio of a node is the [`iou`] of the [`AABB`]s of its children: the
+    /// volume of their intersection divided by the volume of their union. It is `0.0` for
+    /// disjoint children and `1.0` for identical ones. A [`Ray`] which enters the overlap
+    /// has to visit both children, so nodes with a high ratio point at the regions of a
+    /// scene where [`BuildConfig::spatial_splits`] or a rebuild help the most. Children
+    /// without volume, such as the [`AABB`]s of a flat scene, have a ratio of `0.0`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// for (node_index, ratio) in bvh.worst_nodes(3) {
+    ///     println!("Node {} overlaps by {:.1}%", node_index, ratio * 100.0);
+    /// }
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`BuildConfig::spatial_splits`]: struct.BuildConfig.html#structfield.spatial_splits
+    /// [`Ray`]: ../ray/struct.Ray.html
+    /// [`iou`]: ../aabb/struct.AABB.html#method.iou
+    ///
+    pub fn worst_nodes(&self, n: usize) -> Vec<(usize, f32)> {
+        let mut ratios: Vec<(usize, f32)> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(node_index, node)| match *node {
+                BVHNode::Node {
+                    child_l_aabb,
+                    child_r_aabb,
+                    ..
+                } => Some((node_index, child_l_aabb.iou(&child_r_aabb))),
+                BVHNode::Leaf { .. } => None,
+            })
+            .collect();
+        ratios.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        ratios.truncate(n);
+        ratios
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aabb::AABB;
+    use crate::axis::Axis;
+    use crate::bvh::{BVHNode, BuildConfig, BVH};
+    use crate::testbase::{
+        build_chain_bvh, build_some_bh, create_n_cubes, default_bounds, UnitBox,
+    };
+    use ultraviolet::Vec3;
+
+    #[test]
+    /// Tests whether the counts of the statistics are consistent for several build
+    /// configurations.
+    fn test_statistics_counts_are_consistent() {
+        let bounds = default_bounds();
+        for &max_leaf_size in &[1, 4] {
+            let mut triangles = create_n_cubes(100, &bounds);
+            let config = BuildConfig {
+                max_leaf_size,
+                ..Default::default()
+            };
+            let bvh = BVH::build_with_config(&mut triangles, &config);
+            let stats = bvh.statistics();
+
+            assert_eq!(stats.node_count, bvh.nodes.len());
+            assert_eq!(stats.inner_node_count + stats.leaf_count, stats.node_count);
+            assert_eq!(stats.inner_node_count + 1, stats.leaf_count);
+            assert_eq!(stats.shape_count, triangles.len());
+            assert!(stats.average_leaf_size >= 1.0);
+            assert!(stats.average_leaf_size <= max_leaf_size as f32);
+            assert!(stats.average_depth <= stats.max_depth as f32);
+            assert!(stats.sah_cost > 1.0);
+        }
+    }
+
+    #[test]
+    /// Tests the statistics of a `BVH` with a single shape and of an empty `BVH`.
+    fn test_statistics_trivial_trees() {
+        let mut triangles = create_n_cubes(1, &default_bounds());
+        triangles.truncate(1);
+        let stats = BVH::build(&mut triangles).statistics();
+        assert_eq!(stats.node_count, 1);
+        assert_eq!(stats.leaf_count, 1);
+        assert_eq!(stats.max_depth, 0);
+        assert_eq!(stats.sah_cost, 1.0);
+
+        let empty = BVH {
+            nodes: Vec::new(),
+            shape_indices: Vec::new(),
+        }
+        .statistics();
+        assert_eq!(empty.node_count, 0);
+        assert_eq!(empty.sah_cost, 0.0);
+    }
+
+    #[test]
+    /// Tests the expected traversal cost of a hand-built tree with a unit cube leaf and a
+    /// 2x1x1 leaf with two shapes, and whether the unit costs match the SAH cost.
+    fn test_expected_traversal_cost() {
+        // The root spans [0, 4] x [0, 1] x [0, 1], with a surface area of 18.
+        let small = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        let large = AABB::with_bounds(Vec3::new(2.0, 0.0, 0.0), Vec3::new(4.0, 1.0, 1.0));
+        let bvh = BVH {
+            nodes: vec![
+                BVHNode::Node {
+                    parent_index: 0,
+                    depth: 0,
+                    child_l_aabb: small,
+                    child_l_index: 1,
+                    child_r_aabb: large,
+                    child_r_index: 2,
+                    split_axis: Axis::X,
+                    split_position: 1.5,
+                },
+                BVHNode::Leaf {
+                    parent_index: 0,
+                    depth: 1,
+                    shape_index: 0,
+                    shape_count: 1,
+                },
+                BVHNode::Leaf {
+                    parent_index: 0,
+                    depth: 1,
+                    shape_index: 1,
+                    shape_count: 2,
+                },
+            ],
+            shape_indices: vec![0, 1, 2],
+        };
+
+        // 0.5 * 1 + 2 * (1 * 6 / 18 + 2 * 10 / 18)
+        let expected = 0.5 + 2.0 * 26.0 / 18.0;
+        assert!((bvh.expected_traversal_cost(2.0, 0.5) - expected).abs() < 1e-5);
+        assert!((bvh.expected_traversal_cost(1.0, 1.0) - bvh.statistics().sah_cost).abs() < 1e-5);
+        assert_eq!(
+            BVH {
+                nodes: Vec::new(),
+                shape_indices: Vec::new()
+            }
+            .expected_traversal_cost(1.0, 1.0),
+            0.0
+        );
+
+        let mut triangles = create_n_cubes(100, &default_bounds());
+        let bvh = BVH::build(&mut triangles);
+        let sah_cost = bvh.statistics().sah_cost;
+        assert!((bvh.expected_traversal_cost(1.0, 1.0) - sah_cost).abs() < sah_cost * 1e-5);
+    }
+
+    #[test]
+    /// Tests whether `worst_nodes` reports no overlap for a row of disjoint boxes, the
+    /// overlap ratio of a hand-built node, and whether the ratios are sorted.
+    fn test_worst_nodes() {
+        let mut boxes: Vec<UnitBox> = (0..20)
+            .map(|i| UnitBox::new(i, Vec3::new(2.0 * i as f32, 0.0, 0.0)))
+            .collect();
+        let bvh = BVH::build(&mut boxes);
+        let worst = bvh.worst_nodes(5);
+        assert_eq!(worst.len(), 5);
+        assert!(worst.iter().all(|&(_, ratio)| ratio.abs() < 1e-6));
+        assert_eq!(bvh.worst_nodes(100).len(), 19);
+
+        // Children which share a third of their union.
+        let left = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0));
+        let right = AABB::with_bounds(Vec3::new(1.0, 0.0, 0.0), Vec3::new(3.0, 1.0, 1.0));
+        let bvh = BVH {
+            nodes: vec![
+                BVHNode::Node {
+                    parent_index: 0,
+                    depth: 0,
+                    child_l_aabb: left,
+                    child_l_index: 1,
+                    child_r_aabb: right,
+                    child_r_index: 2,
+                    split_axis: Axis::X,
+                    split_position: 1.5,
+                },
+                BVHNode::Leaf {
+                    parent_index: 0,
+                    depth: 1,
+                    shape_index: 0,
+                    shape_count: 1,
+                },
+                BVHNode::Leaf {
+                    parent_index: 0,
+                    depth: 1,
+                    shape_index: 1,
+                    shape_count: 1,
+                },
+            ],
+            shape_indices: vec![0, 1],
+        };
+        let worst = bvh.worst_nodes(2);
+        assert_eq!(worst.len(), 1);
+        assert_eq!(worst[0].0, 0);
+        assert!((worst[0].1 - 1.0 / 3.0).abs() < 1e-6);
+        assert!(BVH {
+            nodes: Vec::new(),
+            shape_indices: Vec::new()
+        }
+        .worst_nodes(1)
+        .is_empty());
+
+        let mut triangles = create_n_cubes(100, &default_bounds());
+        let bvh = BVH::build(&mut triangles);
+        let worst = bvh.worst_nodes(10);
+        assert_eq!(worst.len(), 10);
+        assert!(worst.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    }
+
+    #[test]
+    /// Tests whether the summary mentions all counts.
+    fn test_statistics_display() {
+        let (_, bvh) = build_some_bh::<BVH>();
+        let summary = bvh.statistics().to_string();
+        assert!(summary.contains("Nodes: 41 (20 inner, 21 leaves)"));
+        assert!(summary.contains("Shapes: 21"));
+    }
+
+    #[test]
+    /// Tests the statistics of a `BVH` which is too deep to be traversed recursively.
+    fn test_statistics_deep_chain() {
+        let n = 300_000;
+        let (_, bvh) = build_chain_bvh(n);
+        let stats = bvh.statistics();
+        assert_eq!(stats.inner_node_count, n - 1);
+        assert_eq!(stats.leaf_count, n);
+        assert_eq!(stats.shape_count, n);
+        assert_eq!(stats.max_depth, n as u32 - 1);
+        assert!(stats.sah_cost > 1.0);
+    }
+}
@@ -0,0 +1,355 @@
+//! This module defines the validation of the structural invariants of a [`BVH`].
+//!
+//! [`BVH`]: struct.BVH.html
+//!
+
+use crate::aabb::AABB;
+use crate::bounding_hierarchy::BHShape;
+use crate::bvh::*;
+use crate::utils::has_valid_aabb;
+use crate::EPSILON;
+
+impl BVH {
+    /// Checks the structural invariants of the [`BVH`] and returns the first violation
+    /// found as a [`BvhError`]. A valid [`BVH`] satisfies the following conditions:
+    ///
+    /// - All child indices are in bounds and every node is reachable from the root on
+    ///   exactly one path.
+    /// - Every node stores the index of its parent (`0` for the root) and its depth.
+    /// - The [`AABB`] an inner node stores for a child contains the [`AABB`]s of the
+    ///   child's subtree, up to [`EPSILON`]. See [`validate_eps`] for other tolerances.
+    /// - Every leaf references a range of [`shape_indices`], and every shape is referenced
+    ///   by exactly one leaf and knows the index of that leaf. Shapes with an empty or
+    ///   non-finite [`AABB`] may be left out, like [`build`] does.
+    ///
+    /// Unlike [`assert_consistent`], this never panics, which makes it suitable for
+    /// checking the output of custom builders.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::{BvhError, BVH};
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    /// assert_eq!(bvh.validate(&spheres), Ok(()));
+    ///
+    /// // Moving a shape without refitting breaks the bounds of its leaf.
+    /// spheres[3].position.y += 5.0;
+    /// match bvh.validate(&spheres) {
+    ///     Err(BvhError::ShapeOutsideLeaf { shape_index, .. }) => assert_eq!(shape_index, 3),
+    ///     other => panic!("unexpected result {:?}", other),
+    /// }
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`BvhError`]: enum.BvhError.html
+    /// [`EPSILON`]: ../constant.EPSILON.html
+    /// [`assert_consistent`]: struct.BVH.html#method.assert_consistent
+    /// [`build`]: struct.BVH.html#method.build
+    /// [`shape_indices`]: struct.BVH.html#structfield.shape_indices
+    /// [`validate_eps`]: struct.BVH.html#method.validate_eps
+    ///
+    pub fn validate<Shape: BHShape>(&self, shapes: &[Shape]) -> Result<(), BvhError> {
+        self.validate_eps(shapes, EPSILON)
+    }
+
+    /// Checks the structural invariants of the [`BVH`] like [`validate`], but allows the
+    /// [`AABB`]s of children and shapes to exceed the [`AABB`]s stored for them by up to
+    /// `epsilon` instead of [`EPSILON`]. Scenes with large coordinates need a larger
+    /// `epsilon`, as the rounding errors of refitting grow with the magnitude of the bounds.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`EPSILON`]: ../constant.EPSILON.html
+    /// [`validate`]: struct.BVH.html#method.validate
+    ///
+    pub fn validate_eps<Shape: BHShape>(
+        &self,
+        shapes: &[Shape],
+        epsilon: f32,
+    ) -> Result<(), BvhError> {
+        let mut visited = vec![false; self.nodes.len()];
+        let mut shape_leaves: Vec<Option<usize>> = vec![None; shapes.len()];
+
+        // Each entry holds a node index, the index of its parent, its expected depth, and
+        // the `AABB` the parent stores for it, which is `None` for the root.
+        let mut stack: Vec<(usize, usize, u32, Option<AABB>)> = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push((0, 0, 0, None));
+        }
+
+        while let Some((node_index, parent_index, depth, outer_aabb)) = stack.pop() {
+            if visited[node_index] {
+                return Err(BvhError::NodeReachedTwice { node_index });
+            }
+            visited[node_index] = true;
+
+            let node = &self.nodes[node_index];
+            if node.parent() != parent_index {
+                return Err(BvhError::WrongParent {
+                    node_index,
+                    expected: parent_index,
+                    actual: node.parent(),
+                });
+            }
+            if node.depth() != depth {
+                return Err(BvhError::WrongDepth {
+                    node_index,
+                    expected: depth,
+                    actual: node.depth(),
+                });
+            }
+
+            match *node {
+                BVHNode::Node {
+                    child_l_index,
+                    child_l_aabb,
+                    child_r_index,
+                    child_r_aabb,
+                    ..
+                } => {
+                    for &(child_index, child_aabb) in
+                        &[(child_l_index, child_l_aabb), (child_r_index, child_r_aabb)]
+                    {
+                        if child_index >= self.nodes.len() {
+                            return Err(BvhError::ChildIndexOutOfBounds {
+                                node_index,
+                                child_index,
+                            });
+                        }
+                        if let Some(outer_aabb) = outer_aabb {
+                            if !outer_aabb.approx_contains_aabb_eps(&child_aabb, epsilon) {
+                                return Err(BvhError::ChildOutsideParent {
+                                    node_index: parent_index,
+                                    child_index: node_index,
+                                });
+                            }
+                        }
+                    }
+                    // Push the right child first, so that the left subtree is checked first.
+                    stack.push((child_r_index, node_index, depth + 1, Some(child_r_aabb)));
+                    stack.push((child_l_index, node_index, depth + 1, Some(child_l_aabb)));
+                }
+                BVHNode::Leaf {
+                    shape_index,
+                    shape_count,
+                    ..
+                } => {
+                    let leaf_shapes = self
+                        .shape_indices
+                        .get(shape_index..shape_index + shape_count)
+                        .ok_or(BvhError::LeafOutOfBounds { node_index })?;
+                    for &shape_index in leaf_shapes {
+                        if shape_index >= shapes.len() {
+                            return Err(BvhError::ShapeIndexOutOfBounds {
+                                node_index,
+                                shape_index,
+                            });
+                        }
+                        if shape_leaves[shape_index].is_some() {
+                            return Err(BvhError::DuplicateShape { shape_index });
+                        }
+                        shape_leaves[shape_index] = Some(node_index);
+
+                        if let Some(outer_aabb) = outer_aabb {
+                            let shape_aabb = shapes[shape_index].aabb();
+                            if !outer_aabb.approx_contains_aabb_eps(&shape_aabb, epsilon) {
+                                return Err(BvhError::ShapeOutsideLeaf {
+                                    node_index,
+                                    shape_index,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(node_index) = visited.iter().position(|visited| !visited) {
+            return Err(BvhError::DetachedNode { node_index });
+        }
+        for (shape_index, leaf) in shape_leaves.into_iter().enumerate() {
+            match leaf {
+                None if !has_valid_aabb(&shapes[shape_index]) => {}
+                None => return Err(BvhError::MissingShape { shape_index }),
+                Some(leaf_index) => {
+                    let actual = shapes[shape_index].bh_node_index();
+                    if actual != leaf_index {
+                        return Err(BvhError::WrongShapeNodeIndex {
+                            shape_index,
+                            expected: leaf_index,
+                            actual,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bounding_hierarchy::BHShape;
+    use crate::bvh::{BVHNode, BuildConfig, BvhError, BVH};
+    use crate::testbase::{build_some_bh, create_n_cubes, default_bounds};
+    use ultraviolet::Vec3;
+
+    #[test]
+    /// Tests whether `BVH`s built in different ways are valid.
+    fn test_validate_built_bvhs() {
+        let bounds = default_bounds();
+        let mut triangles = create_n_cubes(100, &bounds);
+        assert_eq!(BVH::build(&mut triangles).validate(&triangles), Ok(()));
+        assert_eq!(BVH::build_lbvh(&mut triangles).validate(&triangles), Ok(()));
+
+        let config = BuildConfig {
+            max_leaf_size: 4,
+            ..Default::default()
+        };
+        let bvh = BVH::build_with_config(&mut triangles, &config);
+        assert_eq!(bvh.validate(&triangles), Ok(()));
+
+        let empty: Vec<crate::testbase::Triangle> = Vec::new();
+        assert_eq!(
+            BVH {
+                nodes: Vec::new(),
+                shape_indices: Vec::new(),
+            }
+            .validate(&empty),
+            Ok(())
+        );
+    }
+
+    #[test]
+    /// Tests whether broken links between nodes are reported.
+    fn test_validate_broken_links() {
+        let (shapes, bvh) = build_some_bh::<BVH>();
+
+        let mut broken = BVH {
+            nodes: bvh.nodes.clone(),
+            shape_indices: bvh.shape_indices.clone(),
+        };
+        *broken.nodes[1].parent_mut() = 7;
+        assert_eq!(
+            broken.validate(&shapes),
+            Err(BvhError::WrongParent {
+                node_index: 1,
+                expected: 0,
+                actual: 7,
+            })
+        );
+
+        let mut broken = BVH {
+            nodes: bvh.nodes.clone(),
+            shape_indices: bvh.shape_indices.clone(),
+        };
+        if let BVHNode::Node {
+            ref mut child_r_index,
+            ..
+        } = broken.nodes[0]
+        {
+            *child_r_index = 1_000;
+        }
+        assert_eq!(
+            broken.validate(&shapes),
+            Err(BvhError::ChildIndexOutOfBounds {
+                node_index: 0,
+                child_index: 1_000,
+            })
+        );
+
+        let mut broken = BVH {
+            nodes: bvh.nodes.clone(),
+            shape_indices: bvh.shape_indices.clone(),
+        };
+        broken.nodes.push(BVHNode::Leaf {
+            parent_index: 0,
+            depth: 1,
+            shape_index: 0,
+            shape_count: 1,
+        });
+        let node_index = broken.nodes.len() - 1;
+        assert_eq!(
+            broken.validate(&shapes),
+            Err(BvhError::DetachedNode { node_index })
+        );
+    }
+
+    #[test]
+    /// Tests whether wrong shape references and bounds are reported.
+    fn test_validate_broken_shapes() {
+        let (mut shapes, bvh) = build_some_bh::<BVH>();
+        let leaf_index = shapes[5].bh_node_index();
+
+        let mut broken = BVH {
+            nodes: bvh.nodes.clone(),
+            shape_indices: bvh.shape_indices.clone(),
+        };
+        let entry = broken.nodes[leaf_index].shape_index().unwrap();
+        broken.shape_indices[entry] = 4;
+        match broken.validate(&shapes) {
+            Err(BvhError::DuplicateShape { shape_index: 4 })
+            | Err(BvhError::ShapeOutsideLeaf { shape_index: 4, .. }) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        if let BVHNode::Leaf {
+            ref mut shape_count,
+            ..
+        } = broken.nodes[leaf_index]
+        {
+            *shape_count = shapes.len() + 1;
+        }
+        assert_eq!(
+            broken.validate(&shapes),
+            Err(BvhError::LeafOutOfBounds {
+                node_index: leaf_index
+            })
+        );
+
+        shapes[5].set_bh_node_index(0);
+        assert_eq!(
+            bvh.validate(&shapes),
+            Err(BvhError::WrongShapeNodeIndex {
+                shape_index: 5,
+                expected: leaf_index,
+                actual: 0,
+            })
+        );
+        shapes[5].set_bh_node_index(leaf_index);
+
+        shapes[5].pos += Vec3::new(0.0, 10.0, 0.0);
+        assert_eq!(
+            bvh.validate(&shapes),
+            Err(BvhError::ShapeOutsideLeaf {
+                node_index: leaf_index,
+                shape_index: 5,
+            })
+        );
+    }
+
+    #[test]
+    /// Tests whether `validate_eps` tolerates shapes which exceed their leaf by less than
+    /// `epsilon`, and reports them otherwise.
+    fn test_validate_eps() {
+        let (mut shapes, bvh) = build_some_bh::<BVH>();
+        let leaf_index = shapes[5].bh_node_index();
+
+        shapes[5].pos += Vec3::new(0.0, 0.01, 0.0);
+        let outside = Err(BvhError::ShapeOutsideLeaf {
+            node_index: leaf_index,
+            shape_index: 5,
+        });
+        assert_eq!(bvh.validate(&shapes), outside);
+        assert_eq!(bvh.validate_eps(&shapes, 0.001), outside);
+        assert_eq!(bvh.validate_eps(&shapes, 0.1), Ok(()));
+    }
+}
@@ -0,0 +1,574 @@
+//! This module defines a traversal of a [`BVH`] with a [`Ray`] which reports every
+//! entered node to a callback instead of collecting the hit shapes, and the queries
+//! built on top of it.
+//!
+//! [`BVH`]: struct.BVH.html
+//! [`Ray`]: ../ray/struct.Ray.html
+//!
+
+use crate::aabb::AABB;
+use crate::bounding_hierarchy::BHShape;
+use crate::bvh::bvh::TraversalStack;
+use crate::bvh::*;
+use crate::ray::Ray;
+
+/// A node of a [`BVH`] whose [`AABB`] is hit by the [`Ray`] of a [`BVH::traverse_visit`].
+///
+/// [`AABB`]: ../aabb/struct.AABB.html
+/// [`BVH`]: struct.BVH.html
+/// [`BVH::traverse_visit`]: struct.BVH.html#method.traverse_visit
+/// [`Ray`]: ../ray/struct.Ray.html
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum VisitEvent {
+    /// An interior node is entered. Its children follow if the callback descends.
+    Node {
+        /// The index of the node in [`BVH::nodes`].
+        ///
+        /// [`BVH::nodes`]: struct.BVH.html#structfield.nodes
+        ///
+        node_index: usize,
+
+        /// The [`AABB`] of the node, which the [`Ray`] hits.
+        ///
+        /// [`AABB`]: ../aabb/struct.AABB.html
+        /// [`Ray`]: ../ray/struct.Ray.html
+        ///
+        aabb: AABB,
+
+        /// The depth of the node. The root has depth `0`.
+        depth: u32,
+    },
+
+    /// A leaf is entered. The shapes of the leaf are not tested against the [`Ray`].
+    ///
+    /// [`Ray`]: ../ray/struct.Ray.html
+    ///
+    Leaf {
+        /// The index of the node in [`BVH::nodes`].
+        ///
+        /// [`BVH::nodes`]: struct.BVH.html#structfield.nodes
+        ///
+        node_index: usize,
+
+        /// The [`AABB`] of the leaf, which the [`Ray`] hits.
+        ///
+        /// [`AABB`]: ../aabb/struct.AABB.html
+        /// [`Ray`]: ../ray/struct.Ray.html
+        ///
+        aabb: AABB,
+
+        /// The depth of the leaf. The root has depth `0`.
+        depth: u32,
+
+        /// The index of the first entry of the leaf in [`BVH::shape_indices`].
+        ///
+        /// [`BVH::shape_indices`]: struct.BVH.html#structfield.shape_indices
+        ///
+        shape_index: usize,
+
+        /// The number of shapes in the leaf.
+        shape_count: usize,
+    },
+}
+
+impl VisitEvent {
+    /// Returns the index of the entered node in [`BVH::nodes`].
+    ///
+    /// [`BVH::nodes`]: struct.BVH.html#structfield.nodes
+    ///
+    pub fn node_index(&self) -> usize {
+        match *self {
+            VisitEvent::Node { node_index, .. } | VisitEvent::Leaf { node_index, .. } => node_index,
+        }
+    }
+
+    /// Returns the [`AABB`] of the entered node.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    pub fn aabb(&self) -> AABB {
+        match *self {
+            VisitEvent::Node { aabb, .. } | VisitEvent::Leaf { aabb, .. } => aabb,
+        }
+    }
+
+    /// Returns the depth of the entered node. The root has depth `0`.
+    pub fn depth(&self) -> u32 {
+        match *self {
+            VisitEvent::Node { depth, .. } | VisitEvent::Leaf { depth, .. } => depth,
+        }
+    }
+}
+
+/// The answer of the callback of [`BVH::traverse_visit`] to a [`VisitEvent`].
+///
+/// [`BVH::traverse_visit`]: struct.BVH.html#method.traverse_visit
+/// [`VisitEvent`]: enum.VisitEvent.html
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Descend {
+    /// Enter the children of the node which are hit. Same as `Skip` for leaves.
+    Children,
+
+    /// Skip the subtree of the node and continue with its next sibling.
+    Skip,
+
+    /// End the whole traversal.
+    Stop,
+}
+
+impl BVH {
+    /// Traverses the [`BVH`] with a [`Ray`] and calls `visit` for every node whose
+    /// [`AABB`] is hit, without collecting any results.
+    ///
+    /// The nodes are reported in pre-order: a node is reported before its children, and
+    /// the whole subtree of the left child before the right child. The [`Descend`]
+    /// returned by `visit` decides whether the children of a node are entered, or
+    /// whether the traversal stops altogether. Unlike [`traverse`], the root is tested
+    /// against the [`Ray`] as well, and the shapes of leaves are left to the callback.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::{Descend, VisitEvent, BVH};
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// // Count the touched nodes per depth and the leaves reached.
+    /// let ray = Ray::new(Vec3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// let mut per_depth = Vec::new();
+    /// let mut leaves = 0;
+    /// bvh.traverse_visit(&ray, &spheres, |event| {
+    ///     let depth = event.depth() as usize;
+    ///     if per_depth.len() <= depth {
+    ///         per_depth.resize(depth + 1, 0);
+    ///     }
+    ///     per_depth[depth] += 1;
+    ///     if let VisitEvent::Leaf { .. } = event {
+    ///         leaves += 1;
+    ///     }
+    ///     Descend::Children
+    /// });
+    ///
+    /// assert_eq!(per_depth[0], 1);
+    /// assert_eq!(leaves, 10);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`Descend`]: enum.Descend.html
+    /// [`Ray`]: ../ray/struct.Ray.html
+    /// [`traverse`]: struct.BVH.html#method.traverse
+    ///
+    pub fn traverse_visit<Shape: BHShape, V: FnMut(VisitEvent) -> Descend>(
+        &self,
+        ray: &Ray,
+        shapes: &[Shape],
+        mut visit: V,
+    ) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let root_aabb = self.nodes[0].get_node_aabb(&self.shape_indices, shapes);
+        if !ray.intersects_aabb(&root_aabb) {
+            return;
+        }
+
+        // Only nodes whose `AABB` is hit are pushed, so the stack always holds the right
+        // children which are left to visit, with the next one on top.
+        let mut stack = TraversalStack::new();
+        stack.push(0);
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            let aabb = if node_index == 0 {
+                root_aabb
+            } else {
+                self.child_aabb(node_index)
+            };
+            let depth = node.depth();
+            match *node {
+                BVHNode::Node {
+                    ref child_l_aabb,
+                    child_l_index,
+                    ref child_r_aabb,
+                    child_r_index,
+                    ..
+                } => {
+                    let event = VisitEvent::Node {
+                        node_index,
+                        aabb,
+                        depth,
+                    };
+                    match visit(event) {
+                        Descend::Children => {}
+                        Descend::Skip => continue,
+                        Descend::Stop => return,
+                    }
+                    if ray.intersects_aabb(child_r_aabb) {
+                        stack.push(child_r_index);
+                    }
+                    if ray.intersects_aabb(child_l_aabb) {
+                        stack.push(child_l_index);
+                    }
+                }
+                BVHNode::Leaf {
+                    shape_index,
+                    shape_count,
+                    ..
+                } => {
+                    let event = VisitEvent::Leaf {
+                        node_index,
+                        aabb,
+                        depth,
+                        shape_index,
+                        shape_count,
+                    };
+                    if visit(event) == Descend::Stop {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if `intersect` reports a hit for any shape whose [`AABB`] is hit by
+    /// `ray`, such as a blocker between a point and a light.
+    ///
+    /// The traversal stops at the first reported hit, so, unlike [`traverse_nearest`],
+    /// no hits have to be compared and the nearest one may never be tested at all. Restrict
+    /// the `ray` to the segment which has to be free, e.g. with [`Ray::with_bounds`] up to
+    /// the distance of the light.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let hits_sphere = |ray: &Ray, sphere: &Sphere| {
+    ///     let to_center = sphere.position - ray.origin;
+    ///     let closest = to_center - ray.direction * to_center.dot(ray.direction);
+    ///     closest.mag() <= sphere.radius
+    /// };
+    ///
+    /// let light = Vec3::new(50.0, 10.0, 0.0);
+    /// let blocked = Vec3::new(50.0, -10.0, 0.0);
+    /// let free = Vec3::new(55.0, -10.0, 0.0);
+    /// for &(point, occluded) in &[(blocked, true), (free, false)] {
+    ///     let to_light = light - point;
+    ///     let ray = Ray::with_bounds(point, to_light, 0.0, to_light.mag());
+    ///     assert_eq!(bvh.is_occluded(&ray, &spheres, hits_sphere), occluded);
+    /// }
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`Ray::with_bounds`]: ../ray/struct.Ray.html#method.with_bounds
+    /// [`traverse_nearest`]: struct.BVH.html#method.traverse_nearest
+    ///
+    pub fn is_occluded<Shape, F>(&self, ray: &Ray, shapes: &[Shape], intersect: F) -> bool
+    where
+        Shape: BHShape,
+        F: Fn(&Ray, &Shape) -> bool,
+    {
+        let mut occluded = false;
+        self.traverse_visit(ray, shapes, |event| match event {
+            VisitEvent::Node { .. } => Descend::Children,
+            VisitEvent::Leaf {
+                shape_index,
+                shape_count,
+                ..
+            } => {
+                // The `AABB` of a single shape was already tested as the one of its leaf.
+                occluded = self.shape_indices[shape_index..shape_index + shape_count]
+                    .iter()
+                    .map(|index| &shapes[*index])
+                    .filter(|shape| shape_count == 1 || ray.intersects_aabb(&shape.aabb()))
+                    .any(|shape| intersect(ray, shape));
+                if occluded {
+                    Descend::Stop
+                } else {
+                    Descend::Skip
+                }
+            }
+        });
+        occluded
+    }
+
+    /// Traverses the [`BVH`] like [`traverse`], but returns every hit shape together with
+    /// the [`AABB`] the [`BVH`] stores for its leaf, which is the [`AABB`] the traversal
+    /// actually tested. This is useful to draw the boxes a [`Ray`] passed through, e.g. in
+    /// debug overlays. For leaves with several shapes, all of them share the joint [`AABB`]
+    /// of the leaf.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::Bounded;
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let bvh = BVH::build(&mut spheres);
+    ///
+    /// let ray = Ray::new(Vec3::new(30.0, 0.0, -10.0), Vec3::new(0.0, 0.0, 1.0));
+    /// let hits = bvh.traverse_with_aabbs(&ray, &spheres);
+    ///
+    /// assert_eq!(hits.len(), 1);
+    /// let (sphere, leaf_aabb) = hits[0];
+    /// assert_eq!(sphere.position.x, 30.0);
+    /// assert_eq!(leaf_aabb, sphere.aabb());
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`BVH`]: struct.BVH.html
+    /// [`Ray`]: ../ray/struct.Ray.html
+    /// [`traverse`]: struct.BVH.html#method.traverse
+    ///
+    pub fn traverse_with_aabbs<'a, Shape: BHShape>(
+        &self,
+        ray: &Ray,
+        shapes: &'a [Shape],
+    ) -> Vec<(&'a Shape, AABB)> {
+        let mut hits = Vec::new();
+        self.traverse_visit(ray, shapes, |event| {
+            if let VisitEvent::Leaf {
+                aabb,
+                shape_index,
+                shape_count,
+                ..
+            } = event
+            {
+                // The `AABB` of a single shape was already tested as the one of its leaf.
+                let leaf_shapes = &self.shape_indices[shape_index..shape_index + shape_count];
+                hits.extend(
+                    leaf_shapes
+                        .iter()
+                        .map(|index| &shapes[*index])
+                        .filter(|shape| shape_count == 1 || ray.intersects_aabb(&shape.aabb()))
+                        .map(|shape| (shape, aabb)),
+                );
+            }
+            Descend::Children
+        });
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aabb::Bounded;
+    use crate::bvh::{BuildConfig, Descend, VisitEvent, BVH};
+    use crate::ray::Ray;
+    use crate::testbase::{
+        build_chain_bvh, create_n_cubes, default_bounds, generate_aligned_boxes, next_Vec3,
+    };
+    use crate::testbase::{Triangle, UnitBox};
+    use ultraviolet::Vec3;
+
+    #[test]
+    /// Tests the number and the order of the nodes visited in a tree of aligned boxes,
+    /// for a ray through all boxes, a ray missing them, skipped subtrees and a stop.
+    fn test_traverse_visit_counts_nodes() {
+        let mut boxes = generate_aligned_boxes();
+        let bvh = BVH::build(&mut boxes);
+        let through_all = Ray::new(Vec3::new(-20.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        // Every node is hit, and parents precede their children.
+        let mut visited = Vec::new();
+        bvh.traverse_visit(&through_all, &boxes, |event| {
+            visited.push(event);
+            Descend::Children
+        });
+        assert_eq!(visited.len(), bvh.nodes.len());
+        assert_eq!(visited.len(), 2 * boxes.len() - 1);
+        assert_eq!(visited[0].node_index(), 0);
+        assert_eq!(visited[0].depth(), 0);
+        for (position, event) in visited.iter().enumerate().skip(1) {
+            let parent = bvh.nodes[event.node_index()].parent();
+            let parent_position = visited
+                .iter()
+                .position(|other| other.node_index() == parent)
+                .unwrap();
+            assert!(parent_position < position);
+            assert_eq!(event.depth(), visited[parent_position].depth() + 1);
+            assert!(visited[parent_position].aabb().contains_aabb(&event.aabb()));
+        }
+        let leaves = visited
+            .iter()
+            .filter(|event| matches!(event, VisitEvent::Leaf { .. }))
+            .count();
+        assert_eq!(leaves, boxes.len());
+
+        // A ray which misses the root visits nothing.
+        let missing = Ray::new(Vec3::new(-20.0, 5.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let mut count = 0;
+        bvh.traverse_visit(&missing, &boxes, |_| {
+            count += 1;
+            Descend::Children
+        });
+        assert_eq!(count, 0);
+
+        // Skipping below depth 1 visits the root and its two children.
+        let mut count = 0;
+        bvh.traverse_visit(&through_all, &boxes, |event| {
+            count += 1;
+            if event.depth() < 1 {
+                Descend::Children
+            } else {
+                Descend::Skip
+            }
+        });
+        assert_eq!(count, 3);
+
+        // Stopping at the first leaf ends the traversal there.
+        let mut count = 0;
+        let mut leaves = 0;
+        bvh.traverse_visit(&through_all, &boxes, |event| {
+            count += 1;
+            match event {
+                VisitEvent::Leaf { .. } => {
+                    leaves += 1;
+                    Descend::Stop
+                }
+                VisitEvent::Node { .. } => Descend::Children,
+            }
+        });
+        assert_eq!(leaves, 1);
+        assert!(count < bvh.nodes.len());
+    }
+
+    #[test]
+    /// Tests whether a box between a point and a light occludes it, while the light is
+    /// visible from a point beside the box and from a point in front of the box.
+    fn test_is_occluded_blocker() {
+        let mut boxes = generate_aligned_boxes();
+        let bvh = BVH::build(&mut boxes);
+        // The unit boxes are solid, so a hit of their `AABB` is a hit of the box.
+        let intersect = |ray: &Ray, unit_box: &UnitBox| ray.intersects_aabb(&unit_box.aabb());
+        let light = Vec3::new(0.0, 10.0, 0.0);
+
+        let shadow_ray = |point: Vec3| {
+            let to_light = light - point;
+            Ray::with_bounds(point, to_light, 0.0, to_light.mag())
+        };
+        assert!(bvh.is_occluded(&shadow_ray(Vec3::new(0.0, -10.0, 0.0)), &boxes, intersect));
+        assert!(!bvh.is_occluded(&shadow_ray(Vec3::new(0.0, -10.0, 5.0)), &boxes, intersect));
+        assert!(!bvh.is_occluded(&shadow_ray(Vec3::new(0.0, 5.0, 0.0)), &boxes, intersect));
+    }
+
+    #[test]
+    /// Tests whether `is_occluded` agrees with testing all triangles, for leaves with one and
+    /// with several triangles, and whether it tests no triangle after the first hit.
+    fn test_is_occluded_equals_brute_force() {
+        let intersect = |ray: &Ray, triangle: &Triangle| {
+            ray.intersects_triangle(&triangle.a, &triangle.b, &triangle.c)
+                .distance
+                < ray.t_max
+        };
+
+        let bounds = default_bounds();
+        let mut seed = 0;
+        for &max_leaf_size in &[1, 4] {
+            let mut triangles = create_n_cubes(1_000, &bounds);
+            let config = BuildConfig {
+                max_leaf_size,
+                ..Default::default()
+            };
+            let bvh = BVH::build_with_config(&mut triangles, &config);
+
+            for _ in 0..100 {
+                let point = next_Vec3(&mut seed, &bounds);
+                let to_light = next_Vec3(&mut seed, &bounds) - point;
+                let ray = Ray::with_bounds(point, to_light, 0.0, to_light.mag());
+
+                let expected = triangles.iter().any(|triangle| intersect(&ray, triangle));
+                let hit_count = std::cell::Cell::new(0);
+                let occluded = bvh.is_occluded(&ray, &triangles, |ray, triangle| {
+                    let hit = intersect(ray, triangle);
+                    hit_count.set(hit_count.get() + hit as usize);
+                    hit
+                });
+                assert_eq!(occluded, expected);
+                assert_eq!(hit_count.get(), occluded as usize);
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether `traverse_with_aabbs` returns the same shapes in the same order as
+    /// `traverse`, each with the `AABB` of its leaf, which equals the `AABB` of the shape
+    /// in a freshly built `BVH` with one shape per leaf.
+    fn test_traverse_with_aabbs() {
+        let bounds = default_bounds();
+        let mut seed = 0;
+        for &max_leaf_size in &[1, 4] {
+            let mut triangles = create_n_cubes(1_000, &bounds);
+            let config = BuildConfig {
+                max_leaf_size,
+                ..Default::default()
+            };
+            let bvh = BVH::build_with_config(&mut triangles, &config);
+
+            for _ in 0..100 {
+                let origin = next_Vec3(&mut seed, &bounds);
+                let ray = Ray::new(origin, next_Vec3(&mut seed, &bounds) - origin);
+                let hits = bvh.traverse_with_aabbs(&ray, &triangles);
+                let expected = bvh.traverse(&ray, &triangles);
+
+                assert_eq!(hits.len(), expected.len());
+                for ((shape, leaf_aabb), expected) in hits.iter().zip(expected) {
+                    assert!(std::ptr::eq(*shape, expected));
+                    if max_leaf_size == 1 {
+                        assert_eq!(*leaf_aabb, shape.aabb());
+                    } else {
+                        assert!(leaf_aabb.contains_aabb(&shape.aabb()));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether `traverse_visit`, `is_occluded` and `traverse_with_aabbs` handle a
+    /// `BVH` which is too deep to be traversed recursively.
+    fn test_traverse_visit_deep_chain() {
+        let n = 300_000;
+        let (boxes, bvh) = build_chain_bvh(n);
+        let ray = Ray::new(Vec3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        // The left children descend the chain, so the deepest leaf is reported first.
+        let mut events = Vec::new();
+        bvh.traverse_visit(&ray, &boxes, |event| {
+            events.push(event);
+            Descend::Children
+        });
+        assert_eq!(events.len(), bvh.nodes.len());
+        assert_eq!(events[n - 1].node_index(), bvh.nodes.len() - 1);
+
+        // The only blocker is the first box, which is reached last.
+        assert!(bvh.is_occluded(&ray, &boxes, |_, unit_box| unit_box.id == 0));
+        assert!(!bvh.is_occluded(&ray, &boxes, |_, _| false));
+        assert_eq!(bvh.traverse_with_aabbs(&ray, &boxes).len(), n);
+    }
+}
@@ -0,0 +1,449 @@
+//! Two-dimensional Axis Aligned Bounding Boxes.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Index;
+
+use ultraviolet::Vec2;
+
+use crate::axis::Axis2;
+use crate::bvh2d::Ray2;
+
+/// Two-dimensional version of [`AABB`].
+///
+/// [`AABB`]: ../aabb/struct.AABB.html
+///
+#[derive(Debug, Copy, Clone)]
+pub struct AABB2 {
+    /// Minimum coordinates
+    pub min: Vec2,
+
+    /// Maximum coordinates
+    pub max: Vec2,
+}
+
+impl fmt::Display for AABB2 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Min bound: {:?}; Max bound: {:?}", self.min, self.max)
+    }
+}
+
+/// Two-dimensional version of [`Bounded`], implemented by things which can be bounded
+/// by an [`AABB2`].
+///
+/// [`AABB2`]: struct.AABB2.html
+/// [`Bounded`]: ../aabb/trait.Bounded.html
+///
+pub trait Bounded2 {
+    /// Returns the geometric bounds of this object in the form of an [`AABB2`].
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    fn aabb(&self) -> AABB2;
+}
+
+impl AABB2 {
+    /// Creates a new [`AABB2`] with the given bounds.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh2d::AABB2;
+    /// use bvh_ultraviolet::ultraviolet::Vec2;
+    ///
+    /// let aabb = AABB2::with_bounds(Vec2::new(-1.0, -1.0), Vec2::new(1.0, 1.0));
+    /// assert_eq!(aabb.min.x, -1.0);
+    /// assert_eq!(aabb.max.y, 1.0);
+    /// ```
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn with_bounds(min: Vec2, max: Vec2) -> AABB2 {
+        AABB2 { min, max }
+    }
+
+    /// Creates a new empty [`AABB2`], which contains nothing and is the neutral element
+    /// of [`join`] and [`grow`].
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    /// [`grow`]: struct.AABB2.html#method.grow
+    /// [`join`]: struct.AABB2.html#method.join
+    ///
+    pub fn empty() -> AABB2 {
+        AABB2 {
+            min: Vec2::new(f32::INFINITY, f32::INFINITY),
+            max: Vec2::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    /// Returns true if the point `p` is inside the [`AABB2`].
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn contains(&self, p: &Vec2) -> bool {
+        p.x >= self.min.x && p.x <= self.max.x && p.y >= self.min.y && p.y <= self.max.y
+    }
+
+    /// Returns true if the point `p` is approximately inside the [`AABB2`]
+    /// with respect to some `epsilon`.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn approx_contains_eps(&self, p: &Vec2, epsilon: f32) -> bool {
+        (p.x - self.min.x) > -epsilon
+            && (p.x - self.max.x) < epsilon
+            && (p.y - self.min.y) > -epsilon
+            && (p.y - self.max.y) < epsilon
+    }
+
+    /// Returns true if the `other` [`AABB2`] is approximately inside this [`AABB2`]
+    /// with respect to some `epsilon`.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn approx_contains_aabb_eps(&self, other: &AABB2, epsilon: f32) -> bool {
+        self.approx_contains_eps(&other.min, epsilon)
+            && self.approx_contains_eps(&other.max, epsilon)
+    }
+
+    /// Returns a new minimal [`AABB2`] which contains both this [`AABB2`] and `other`.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn join(&self, other: &AABB2) -> AABB2 {
+        AABB2::with_bounds(
+            Vec2::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            Vec2::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        )
+    }
+
+    /// Mutable version of [`AABB2::join`].
+    ///
+    /// [`AABB2::join`]: struct.AABB2.html#method.join
+    ///
+    pub fn join_mut(&mut self, other: &AABB2) {
+        *self = self.join(other);
+    }
+
+    /// Returns a new minimal [`AABB2`] which contains both this [`AABB2`] and the point
+    /// `other`.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn grow(&self, other: &Vec2) -> AABB2 {
+        AABB2::with_bounds(
+            Vec2::new(self.min.x.min(other.x), self.min.y.min(other.y)),
+            Vec2::new(self.max.x.max(other.x), self.max.y.max(other.y)),
+        )
+    }
+
+    /// Mutable version of [`AABB2::grow`].
+    ///
+    /// [`AABB2::grow`]: struct.AABB2.html#method.grow
+    ///
+    pub fn grow_mut(&mut self, other: &Vec2) {
+        *self = self.grow(other);
+    }
+
+    /// Returns a new minimal [`AABB2`] which contains both this [`AABB2`] and the
+    /// [`Bounded2`] `other`.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    /// [`Bounded2`]: trait.Bounded2.html
+    ///
+    pub fn join_bounded<T: Bounded2>(&self, other: &T) -> AABB2 {
+        self.join(&other.aabb())
+    }
+
+    /// Returns true if this [`AABB2`] and `other` overlap or touch.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn intersects_aabb(&self, other: &AABB2) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+    }
+
+    /// Returns the distance along `ray` at which it enters this [`AABB2`], or `None`
+    /// if it misses the [`AABB2`]. See [`AABB::intersect_ray_distance`].
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    /// [`AABB::intersect_ray_distance`]: ../aabb/struct.AABB.html#method.intersect_ray_distance
+    ///
+    pub fn intersect_ray_distance(&self, ray: &Ray2) -> Option<f32> {
+        let tx1 = (self.min.x - ray.origin.x) * ray.inv_direction.x;
+        let tx2 = (self.max.x - ray.origin.x) * ray.inv_direction.x;
+
+        let mut t_near = tx1.min(tx2);
+        let mut t_far = tx1.max(tx2);
+
+        let ty1 = (self.min.y - ray.origin.y) * ray.inv_direction.y;
+        let ty2 = (self.max.y - ray.origin.y) * ray.inv_direction.y;
+
+        t_near = t_near.max(ty1.min(ty2));
+        t_far = t_far.min(ty1.max(ty2));
+
+        let t_near = t_near.max(ray.t_min);
+        if t_far >= t_near && t_near <= ray.t_max {
+            Some(t_near)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the size of this [`AABB2`] in both dimensions.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    /// Returns the center point of the [`AABB2`].
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn center(&self) -> Vec2 {
+        self.min + (self.size() / 2.0)
+    }
+
+    /// Returns true if this [`AABB2`] is empty, i.e. its min bound is greater than its
+    /// max bound on any axis.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y
+    }
+
+    /// Returns the perimeter of this [`AABB2`]. This is the two-dimensional counterpart of
+    /// [`AABB::surface_area`] and used as such by the SAH build of the [`BVH2`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh2d::AABB2;
+    /// use bvh_ultraviolet::ultraviolet::Vec2;
+    ///
+    /// let aabb = AABB2::with_bounds(Vec2::new(0.0, 0.0), Vec2::new(3.0, 1.0));
+    /// assert_eq!(aabb.perimeter(), 8.0);
+    /// ```
+    ///
+    /// [`AABB::surface_area`]: ../aabb/struct.AABB.html#method.surface_area
+    /// [`AABB2`]: struct.AABB2.html
+    /// [`BVH2`]: struct.BVH2.html
+    ///
+    pub fn perimeter(&self) -> f32 {
+        let size = self.size();
+        2.0 * (size.x + size.y)
+    }
+
+    /// Returns the area of this [`AABB2`].
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn area(&self) -> f32 {
+        let size = self.size();
+        size.x * size.y
+    }
+
+    /// Returns the axis along which the [`AABB2`] is stretched the most.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn largest_axis(&self) -> Axis2 {
+        let size = self.size();
+        if size.x > size.y {
+            Axis2::X
+        } else {
+            Axis2::Y
+        }
+    }
+
+    /// Returns the point inside of this [`AABB2`] which is closest to `p`.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn closest_point(&self, p: &Vec2) -> Vec2 {
+        Vec2::new(
+            p.x.max(self.min.x).min(self.max.x),
+            p.y.max(self.min.y).min(self.max.y),
+        )
+    }
+
+    /// Returns the squared distance from `p` to this [`AABB2`], `0.0` if `p` is inside.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub fn distance_squared_to_point(&self, p: &Vec2) -> f32 {
+        (*p - self.closest_point(p)).mag_sq()
+    }
+}
+
+/// Default instance for [`AABB2`]s. Returns an [`AABB2`] which is [`empty()`].
+///
+/// [`AABB2`]: struct.AABB2.html
+/// [`empty()`]: #method.empty
+///
+impl Default for AABB2 {
+    fn default() -> AABB2 {
+        AABB2::empty()
+    }
+}
+
+/// Collects points into the smallest [`AABB2`] which contains all of them.
+///
+/// [`AABB2`]: struct.AABB2.html
+///
+impl FromIterator<Vec2> for AABB2 {
+    fn from_iter<I: IntoIterator<Item = Vec2>>(iter: I) -> AABB2 {
+        iter.into_iter()
+            .fold(AABB2::empty(), |aabb, point| aabb.grow(&point))
+    }
+}
+
+/// Collects [`AABB2`]s into the smallest [`AABB2`] which contains all of them.
+///
+/// [`AABB2`]: struct.AABB2.html
+///
+impl FromIterator<AABB2> for AABB2 {
+    fn from_iter<I: IntoIterator<Item = AABB2>>(iter: I) -> AABB2 {
+        iter.into_iter()
+            .fold(AABB2::empty(), |joint, aabb| joint.join(&aabb))
+    }
+}
+
+/// Make [`AABB2`]s indexable. `aabb[0]` gives a reference to the minimum bound.
+/// All other indices return a reference to the maximum bound.
+///
+/// [`AABB2`]: struct.AABB2.html
+///
+impl Index<usize> for AABB2 {
+    type Output = Vec2;
+
+    fn index(&self, index: usize) -> &Vec2 {
+        if index == 0 {
+            &self.min
+        } else {
+            &self.max
+        }
+    }
+}
+
+/// Implementation of [`Bounded2`] for [`AABB2`].
+///
+/// [`Bounded2`]: trait.Bounded2.html
+/// [`AABB2`]: struct.AABB2.html
+///
+impl Bounded2 for AABB2 {
+    fn aabb(&self) -> AABB2 {
+        *self
+    }
+}
+
+/// Implementation of [`Bounded2`] for `Vec2`.
+///
+/// [`Bounded2`]: trait.Bounded2.html
+///
+impl Bounded2 for Vec2 {
+    fn aabb(&self) -> AABB2 {
+        AABB2::with_bounds(*self, *self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::axis::Axis2;
+    use crate::bvh2d::{Bounded2, AABB2};
+    use crate::testbase::{tuple_to_point2, TupleVec2};
+    use crate::EPSILON;
+
+    use quickcheck::quickcheck;
+    use ultraviolet::Vec2;
+
+    /// Test whether an empty `AABB2` does not contains anything.
+    quickcheck! {
+        fn test_empty_contains_nothing(tpl: TupleVec2) -> bool {
+            let p = tuple_to_point2(&tpl);
+            let aabb: AABB2 = Default::default();
+            !AABB2::empty().contains(&p) && !aabb.contains(&p) && aabb.is_empty()
+        }
+    }
+
+    /// Test whether an `AABB2` always contains its center.
+    quickcheck! {
+        fn test_aabb_contains_center(a: TupleVec2, b: TupleVec2) -> bool {
+            let aabb = AABB2::empty()
+                .grow(&tuple_to_point2(&a))
+                .join_bounded(&tuple_to_point2(&b));
+            aabb.contains(&aabb.center())
+        }
+    }
+
+    /// Test whether the joint of two point-sets contains all the points.
+    quickcheck! {
+        fn test_join_two_aabbs(a: Vec<TupleVec2>, b: Vec<TupleVec2>) -> bool {
+            let a: Vec<Vec2> = a.iter().map(tuple_to_point2).collect();
+            let b: Vec<Vec2> = b.iter().map(tuple_to_point2).collect();
+            let aabb1: AABB2 = a.iter().copied().collect();
+            let aabb2: AABB2 = b.iter().copied().collect();
+            let joint = aabb1.join(&aabb2);
+            let mut joint_mut = aabb1;
+            joint_mut.join_mut(&aabb2);
+
+            a.iter().all(|point| aabb1.contains(point))
+                && b.iter().all(|point| aabb2.contains(point))
+                && a.iter().chain(b.iter()).all(|point| joint.contains(point))
+                && joint.min == joint_mut.min
+                && joint.max == joint_mut.max
+        }
+    }
+
+    /// Test whether two `AABB2`s intersect their joint, and whether the closest point of
+    /// an `AABB2` is contained in it.
+    quickcheck! {
+        fn test_intersects_joint_and_closest_point(a: TupleVec2, b: TupleVec2, c: TupleVec2,
+                                                   d: TupleVec2, p: TupleVec2) -> bool {
+            let aabb1 = AABB2::empty()
+                .grow(&tuple_to_point2(&a))
+                .grow(&tuple_to_point2(&b));
+            let aabb2 = AABB2::empty()
+                .grow(&tuple_to_point2(&c))
+                .grow(&tuple_to_point2(&d));
+            let joint = aabb1.join(&aabb2);
+            let point = tuple_to_point2(&p);
+            let closest = aabb1.closest_point(&point);
+
+            joint.intersects_aabb(&aabb1)
+                && joint.intersects_aabb(&aabb2)
+                && joint.approx_contains_aabb_eps(&aabb1, EPSILON)
+                && aabb1.contains(&closest)
+                && aabb1.contains(&point) == (aabb1.distance_squared_to_point(&point) == 0.0)
+        }
+    }
+
+    /// Compute and compare the perimeter and the area of a square by hand.
+    quickcheck! {
+        fn test_perimeter_and_area_square(pos: TupleVec2, size: f32) -> bool {
+            let pos = tuple_to_point2(&pos);
+            let size = size.abs() + 1.0;
+            let aabb = AABB2::with_bounds(pos, pos + Vec2::new(size, size));
+
+            let epsilon = EPSILON * (1.0 + pos.mag());
+            (1.0 - aabb.perimeter() / (4.0 * size)).abs() < epsilon
+                && (1.0 - aabb.area() / (size * size)).abs() < epsilon
+        }
+    }
+
+    #[test]
+    /// Tests the largest axis and the index access of an `AABB2`.
+    fn test_largest_axis_and_index() {
+        let wide = AABB2::with_bounds(Vec2::new(0.0, 0.0), Vec2::new(2.0, 1.0));
+        let tall = AABB2::with_bounds(Vec2::new(0.0, 0.0), Vec2::new(1.0, 2.0));
+        assert_eq!(wide.largest_axis(), Axis2::X);
+        assert_eq!(tall.largest_axis(), Axis2::Y);
+        assert_eq!(wide[0], wide.min);
+        assert_eq!(wide[1], wide.max);
+        assert_eq!(wide.aabb().max, Vec2::new(2.0, 1.0));
+    }
+}
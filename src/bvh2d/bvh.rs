@@ -0,0 +1,838 @@
+//! Two-dimensional version of the [`BVH`], built with the same SAH partitioning.
+//!
+//! [`BVH`]: ../bvh/struct.BVH.html
+//!
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+use ultraviolet::Vec2;
+
+use crate::bvh::{BuildConfig, TraversalStack};
+use crate::bvh2d::{Bounded2, Ray2, AABB2};
+use crate::utils::{concatenate_vectors, Candidate};
+
+/// Two-dimensional version of [`BHShape`], implemented by shapes which can be stored
+/// in a [`BVH2`].
+///
+/// [`BHShape`]: ../bounding_hierarchy/trait.BHShape.html
+/// [`BVH2`]: struct.BVH2.html
+///
+pub trait BHShape2: Bounded2 {
+    /// Sets the index of the referenced [`BVHNode2`].
+    ///
+    /// [`BVHNode2`]: enum.BVHNode2.html
+    ///
+    fn set_bh_node_index(&mut self, _: usize);
+
+    /// Gets the index of the referenced [`BVHNode2`].
+    ///
+    /// [`BVHNode2`]: enum.BVHNode2.html
+    ///
+    fn bh_node_index(&self) -> usize;
+}
+
+/// A shape counter and the joint [`AABB2`] of the shapes, used by the SAH build of the
+/// [`BVH2`] like `Bucket` is by the [`BVH`].
+///
+/// [`AABB2`]: struct.AABB2.html
+/// [`BVH`]: ../bvh/struct.BVH.html
+/// [`BVH2`]: struct.BVH2.html
+///
+#[derive(Copy, Clone)]
+struct Bucket2 {
+    /// The number of shapes in this `Bucket2`.
+    size: usize,
+
+    /// The joint `AABB2` of the shapes in this `Bucket2`.
+    aabb: AABB2,
+}
+
+impl Bucket2 {
+    /// Returns an empty bucket.
+    fn empty() -> Bucket2 {
+        Bucket2 {
+            size: 0,
+            aabb: AABB2::empty(),
+        }
+    }
+
+    /// Extend this `Bucket2` by a shape with the given `AABB2`.
+    fn add_aabb(&mut self, aabb: &AABB2) {
+        self.size += 1;
+        self.aabb = self.aabb.join(aabb);
+    }
+
+    /// Join the contents of two `Bucket2`s.
+    fn join_bucket(a: Bucket2, b: &Bucket2) -> Bucket2 {
+        Bucket2 {
+            size: a.size + b.size,
+            aabb: a.aabb.join(&b.aabb),
+        }
+    }
+}
+
+/// Two-dimensional version of [`BVHNode`]. The leaves and inner nodes have the same
+/// layout, only the [`AABB2`]s of the children have two dimensions.
+///
+/// [`AABB2`]: struct.AABB2.html
+/// [`BVHNode`]: ../bvh/enum.BVHNode.html
+///
+#[derive(Debug, Copy, Clone)]
+pub enum BVHNode2 {
+    /// Leaf node.
+    Leaf {
+        /// The node's parent.
+        parent_index: usize,
+
+        /// The node's depth.
+        depth: u32,
+
+        /// The index of the first shape referenced by this leaf.
+        shape_index: usize,
+
+        /// The number of consecutive shapes, starting at `shape_index`, referenced by this
+        /// leaf.
+        shape_count: usize,
+    },
+
+    /// Inner node.
+    Node {
+        /// The node's parent.
+        parent_index: usize,
+
+        /// The node's depth.
+        depth: u32,
+
+        /// Index of the left subtree's root node.
+        child_l_index: usize,
+
+        /// The convex hull of the shapes' `AABB2`s in child_l.
+        child_l_aabb: AABB2,
+
+        /// Index of the right subtree's root node.
+        child_r_index: usize,
+
+        /// The convex hull of the shapes' `AABB2`s in child_r.
+        child_r_aabb: AABB2,
+    },
+}
+
+impl BVHNode2 {
+    /// Returns the index of the parent node.
+    pub fn parent(&self) -> usize {
+        match *self {
+            BVHNode2::Node { parent_index, .. } | BVHNode2::Leaf { parent_index, .. } => {
+                parent_index
+            }
+        }
+    }
+
+    /// Returns the depth of the node. The root node has depth `0`.
+    pub fn depth(&self) -> u32 {
+        match *self {
+            BVHNode2::Node { depth, .. } | BVHNode2::Leaf { depth, .. } => depth,
+        }
+    }
+
+    /// Returns the range of shape indices referenced by this node, if it is a leaf.
+    pub fn shape_range(&self) -> Option<Range<usize>> {
+        match *self {
+            BVHNode2::Leaf {
+                shape_index,
+                shape_count,
+                ..
+            } => Some(shape_index..shape_index + shape_count),
+            _ => None,
+        }
+    }
+
+    /// The build function sometimes needs to add nodes while their data is not available yet.
+    /// A dummy created by this function serves the purpose of being changed later on.
+    fn create_dummy() -> BVHNode2 {
+        BVHNode2::Leaf {
+            parent_index: 0,
+            depth: 0,
+            shape_index: 0,
+            shape_count: 0,
+        }
+    }
+
+    /// Builds a [`BVHNode2`] recursively using SAH partitioning with the settings
+    /// from `config`. Returns the index of the new node in the nodes vector.
+    /// The shape indices of every new leaf are appended to `leaf_order`.
+    ///
+    /// [`BVHNode2`]: enum.BVHNode2.html
+    ///
+    fn build<T: BHShape2>(
+        config: &BuildConfig,
+        shapes: &mut [T],
+        indices: &[usize],
+        nodes: &mut Vec<BVHNode2>,
+        leaf_order: &mut Vec<usize>,
+        parent_index: usize,
+        depth: u32,
+    ) -> usize {
+        let mut aabb_bounds = AABB2::empty();
+        let mut centroid_bounds = AABB2::empty();
+        for index in indices {
+            let shape_aabb = shapes[*index].aabb();
+            aabb_bounds.join_mut(&shape_aabb);
+            centroid_bounds.grow_mut(&shape_aabb.center());
+        }
+
+        // If there are few enough elements left, don't split anymore
+        if !indices.is_empty() && indices.len() <= config.max_leaf_size {
+            let node_index = nodes.len();
+            nodes.push(BVHNode2::Leaf {
+                parent_index,
+                depth,
+                shape_index: indices[0],
+                shape_count: indices.len(),
+            });
+            // Let the shapes know the index of the node that represents them.
+            for index in indices {
+                shapes[*index].set_bh_node_index(node_index);
+            }
+            leaf_order.extend_from_slice(indices);
+            return node_index;
+        }
+
+        // This dummy is replaced once the children are built, because they must know
+        // their parent.
+        let node_index = nodes.len();
+        nodes.push(BVHNode2::create_dummy());
+
+        // Find the axis along which the shapes are spread the most.
+        let split_axis = centroid_bounds.largest_axis();
+        let split_axis_size = centroid_bounds.max[split_axis] - centroid_bounds.min[split_axis];
+
+        let (child_l_indices, child_r_indices, child_l_aabb, child_r_aabb) = if split_axis_size
+            < config.epsilon
+        {
+            // The shapes lie too close together to split them in a sensible way.
+            // Instead we just split the list of shapes in half.
+            let (child_l_indices, child_r_indices) = indices.split_at(indices.len() / 2);
+            let joint_aabb = |indices: &[usize]| -> AABB2 {
+                indices.iter().map(|index| shapes[*index].aabb()).collect()
+            };
+            (
+                child_l_indices.to_vec(),
+                child_r_indices.to_vec(),
+                joint_aabb(child_l_indices),
+                joint_aabb(child_r_indices),
+            )
+        } else {
+            // Assign the shapes to `config.bucket_count` `Bucket2`s along the split axis.
+            let bucket_count = config.bucket_count;
+            let mut buckets = vec![Bucket2::empty(); bucket_count];
+            let mut bucket_assignments: Vec<Vec<usize>> = vec![Vec::new(); bucket_count];
+            for index in indices {
+                let shape_aabb = shapes[*index].aabb();
+                let bucket_num_relative = (shape_aabb.center()[split_axis]
+                    - centroid_bounds.min[split_axis])
+                    / split_axis_size;
+                let bucket_num = (bucket_num_relative * (bucket_count as f32 - 0.01)) as usize;
+
+                buckets[bucket_num].add_aabb(&shape_aabb);
+                bucket_assignments[bucket_num].push(*index);
+            }
+
+            // Compute the costs for each configuration and select the best configuration.
+            // The perimeter takes the place of the surface area.
+            let mut min_bucket = 0;
+            let mut min_cost = f32::INFINITY;
+            let mut child_l_aabb = AABB2::empty();
+            let mut child_r_aabb = AABB2::empty();
+            for i in 0..(bucket_count - 1) {
+                let (l_buckets, r_buckets) = buckets.split_at(i + 1);
+                let child_l = l_buckets
+                    .iter()
+                    .fold(Bucket2::empty(), Bucket2::join_bucket);
+                let child_r = r_buckets
+                    .iter()
+                    .fold(Bucket2::empty(), Bucket2::join_bucket);
+
+                let cost = (child_l.size as f32 * child_l.aabb.perimeter()
+                    + child_r.size as f32 * child_r.aabb.perimeter())
+                    / aabb_bounds.perimeter();
+                if cost < min_cost {
+                    min_bucket = i;
+                    min_cost = cost;
+                    child_l_aabb = child_l.aabb;
+                    child_r_aabb = child_r.aabb;
+                }
+            }
+
+            let (l_assignments, r_assignments) = bucket_assignments.split_at_mut(min_bucket + 1);
+            (
+                concatenate_vectors(l_assignments),
+                concatenate_vectors(r_assignments),
+                child_l_aabb,
+                child_r_aabb,
+            )
+        };
+
+        // Proceed recursively.
+        let child_l_index = BVHNode2::build(
+            config,
+            shapes,
+            &child_l_indices,
+            nodes,
+            leaf_order,
+            node_index,
+            depth + 1,
+        );
+        let child_r_index = BVHNode2::build(
+            config,
+            shapes,
+            &child_r_indices,
+            nodes,
+            leaf_order,
+            node_index,
+            depth + 1,
+        );
+
+        // Construct the actual data structure and replace the dummy node.
+        assert!(!child_l_aabb.is_empty());
+        assert!(!child_r_aabb.is_empty());
+        nodes[node_index] = BVHNode2::Node {
+            parent_index,
+            depth,
+            child_l_aabb,
+            child_l_index,
+            child_r_aabb,
+            child_r_index,
+        };
+
+        node_index
+    }
+
+    /// Traverses the [`BVH2`], descending only into nodes whose [`AABB2`] satisfies
+    /// `predicate`. Pushes the indices of all shapes whose [`AABB2`] satisfies `predicate`
+    /// as well. Single shape leaves below the root are pushed without testing them again.
+    /// The nodes are kept on a `TraversalStack` instead of the call stack, like in the
+    /// [`BVH`], so arbitrarily deep trees can be traversed.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    /// [`BVH`]: ../bvh/struct.BVH.html
+    /// [`BVH2`]: struct.BVH2.html
+    ///
+    fn traverse_iterative<Shape: Bounded2, F: Fn(&AABB2) -> bool>(
+        nodes: &[BVHNode2],
+        predicate: &F,
+        shapes: &[Shape],
+        indices: &mut Vec<usize>,
+    ) {
+        let mut stack = TraversalStack::new();
+        let mut node_index = 0;
+        loop {
+            match nodes[node_index] {
+                BVHNode2::Node {
+                    ref child_l_aabb,
+                    child_l_index,
+                    ref child_r_aabb,
+                    child_r_index,
+                    ..
+                } => {
+                    let hit_l = predicate(child_l_aabb);
+                    let hit_r = predicate(child_r_aabb);
+                    if hit_l && hit_r {
+                        // Visit the left subtree first.
+                        stack.push(child_r_index);
+                        node_index = child_l_index;
+                        continue;
+                    } else if hit_l {
+                        node_index = child_l_index;
+                        continue;
+                    } else if hit_r {
+                        node_index = child_r_index;
+                        continue;
+                    }
+                }
+                BVHNode2::Leaf {
+                    shape_index,
+                    shape_count: 1,
+                    ..
+                } if node_index != 0 => {
+                    // The parent already tested the `AABB2` of this shape.
+                    indices.push(shape_index);
+                }
+                BVHNode2::Leaf {
+                    shape_index,
+                    shape_count,
+                    ..
+                } => {
+                    let range = shape_index..shape_index + shape_count;
+                    indices.extend(range.filter(|index| predicate(&shapes[*index].aabb())));
+                }
+            }
+
+            node_index = match stack.pop() {
+                Some(index) => index,
+                None => break,
+            };
+        }
+    }
+}
+
+/// Two-dimensional version of the [`BVH`].
+///
+/// [`BVH`]: ../bvh/struct.BVH.html
+///
+pub struct BVH2 {
+    /// The list of nodes of the [`BVH2`].
+    ///
+    /// [`BVH2`]: struct.BVH2.html
+    ///
+    pub nodes: Vec<BVHNode2>,
+}
+
+impl BVH2 {
+    /// Creates a new [`BVH2`] from the `shapes` slice with the default [`BuildConfig`].
+    ///
+    /// [`BVH2`]: struct.BVH2.html
+    /// [`BuildConfig`]: ../bvh/struct.BuildConfig.html
+    ///
+    pub fn build<Shape: BHShape2>(shapes: &mut [Shape]) -> BVH2 {
+        BVH2::build_with_config(shapes, &BuildConfig::default())
+    }
+
+    /// Creates a new [`BVH2`] from the `shapes` slice with the settings from `config`.
+    /// See [`BVH::build_with_config`]; with a `max_leaf_size` above 1 the `shapes` slice
+    /// is reordered in the same way.
+    ///
+    /// [`BVH2`]: struct.BVH2.html
+    /// [`BVH::build_with_config`]: ../bvh/struct.BVH.html#method.build_with_config
+    ///
+    pub fn build_with_config<Shape: BHShape2>(shapes: &mut [Shape], config: &BuildConfig) -> BVH2 {
+        assert!(
+            config.bucket_count >= 2,
+            "BuildConfig::bucket_count must be at least 2"
+        );
+        assert!(
+            config.max_leaf_size >= 1,
+            "BuildConfig::max_leaf_size must be at least 1"
+        );
+        if shapes.is_empty() {
+            return BVH2 { nodes: Vec::new() };
+        }
+        let indices = (0..shapes.len()).collect::<Vec<usize>>();
+        let mut nodes = Vec::with_capacity(shapes.len() * 2);
+        let mut leaf_order = Vec::with_capacity(shapes.len());
+        BVHNode2::build(config, shapes, &indices, &mut nodes, &mut leaf_order, 0, 0);
+
+        if config.max_leaf_size > 1 {
+            // Move the shapes of each leaf next to each other.
+            let mut new_positions = vec![0; shapes.len()];
+            for (new_position, old_position) in leaf_order.iter().enumerate() {
+                new_positions[*old_position] = new_position;
+            }
+            for node in &mut nodes {
+                if let BVHNode2::Leaf {
+                    ref mut shape_index,
+                    ..
+                } = *node
+                {
+                    *shape_index = new_positions[*shape_index];
+                }
+            }
+            for position in 0..shapes.len() {
+                while new_positions[position] != position {
+                    let target = new_positions[position];
+                    shapes.swap(position, target);
+                    new_positions.swap(position, target);
+                }
+            }
+        }
+
+        BVH2 { nodes }
+    }
+
+    /// Traverses the [`BVH2`] with a `predicate` on [`AABB2`]s and returns the indices of
+    /// the shapes whose [`AABB2`] satisfies it.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    /// [`BVH2`]: struct.BVH2.html
+    ///
+    fn traverse_predicate<Shape: Bounded2, F: Fn(&AABB2) -> bool>(
+        &self,
+        predicate: F,
+        shapes: &[Shape],
+    ) -> Vec<usize> {
+        let mut indices = Vec::new();
+        if !self.nodes.is_empty() {
+            BVHNode2::traverse_iterative(&self.nodes, &predicate, shapes, &mut indices);
+        }
+        indices
+    }
+
+    /// Traverses the [`BVH2`].
+    /// Returns a subset of `shapes`, in which the [`AABB2`]s of the elements were hit by
+    /// `ray`.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    /// [`BVH2`]: struct.BVH2.html
+    ///
+    pub fn traverse<'a, Shape: Bounded2>(&self, ray: &Ray2, shapes: &'a [Shape]) -> Vec<&'a Shape> {
+        self.traverse_indices(ray, shapes)
+            .iter()
+            .map(|index| &shapes[*index])
+            .collect()
+    }
+
+    /// Traverses the [`BVH2`].
+    /// Returns the indices into `shapes` of the elements whose [`AABB2`]s were hit by
+    /// `ray`, in the same order in which [`traverse`] returns the shapes.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    /// [`BVH2`]: struct.BVH2.html
+    /// [`traverse`]: struct.BVH2.html#method.traverse
+    ///
+    pub fn traverse_indices<Shape: Bounded2>(&self, ray: &Ray2, shapes: &[Shape]) -> Vec<usize> {
+        self.traverse_predicate(|aabb| ray.intersects_aabb(aabb), shapes)
+    }
+
+    /// Traverses the [`BVH2`] and returns all shapes whose [`AABB2`] overlaps the `query`
+    /// [`AABB2`]. Touching [`AABB2`]s count as overlapping.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    /// [`BVH2`]: struct.BVH2.html
+    ///
+    pub fn traverse_aabb<'a, Shape: Bounded2>(
+        &self,
+        query: &AABB2,
+        shapes: &'a [Shape],
+    ) -> Vec<&'a Shape> {
+        self.traverse_predicate(|aabb| aabb.intersects_aabb(query), shapes)
+            .iter()
+            .map(|index| &shapes[*index])
+            .collect()
+    }
+
+    /// Returns the `k` shapes whose [`AABB2`]s are closest to `query`, sorted by
+    /// ascending distance, like [`BVH::k_nearest`]. Shapes whose [`AABB2`] contains
+    /// `query` have a distance of `0.0`, and ties are broken by the index in `shapes`.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    /// [`BVH::k_nearest`]: ../bvh/struct.BVH.html#method.k_nearest
+    ///
+    pub fn k_nearest<'a, Shape: Bounded2>(
+        &self,
+        query: Vec2,
+        k: usize,
+        shapes: &'a [Shape],
+    ) -> Vec<&'a Shape> {
+        if k == 0 || self.nodes.is_empty() {
+            return Vec::new();
+        }
+
+        // Nodes which are yet to be visited, closest first.
+        let mut nodes_to_visit = BinaryHeap::new();
+        nodes_to_visit.push(Reverse(Candidate {
+            distance_squared: 0.0,
+            index: 0,
+        }));
+
+        // The best shapes found so far, worst first.
+        let mut best: BinaryHeap<Candidate> = BinaryHeap::with_capacity(k + 1);
+
+        while let Some(Reverse(node)) = nodes_to_visit.pop() {
+            // All remaining nodes are farther away than the `k`-th best shape.
+            if let Some(worst) = best.peek() {
+                if best.len() == k && node.distance_squared > worst.distance_squared {
+                    break;
+                }
+            }
+
+            match self.nodes[node.index] {
+                BVHNode2::Node {
+                    child_l_index,
+                    child_l_aabb,
+                    child_r_index,
+                    child_r_aabb,
+                    ..
+                } => {
+                    nodes_to_visit.push(Reverse(Candidate {
+                        distance_squared: child_l_aabb.distance_squared_to_point(&query),
+                        index: child_l_index,
+                    }));
+                    nodes_to_visit.push(Reverse(Candidate {
+                        distance_squared: child_r_aabb.distance_squared_to_point(&query),
+                        index: child_r_index,
+                    }));
+                }
+                BVHNode2::Leaf {
+                    shape_index,
+                    shape_count,
+                    ..
+                } => {
+                    let leaf_shapes = shapes.iter().enumerate().skip(shape_index);
+                    for (index, shape) in leaf_shapes.take(shape_count) {
+                        best.push(Candidate {
+                            distance_squared: shape.aabb().distance_squared_to_point(&query),
+                            index,
+                        });
+                        if best.len() > k {
+                            best.pop();
+                        }
+                    }
+                }
+            }
+        }
+
+        best.into_sorted_vec()
+            .iter()
+            .map(|candidate| &shapes[candidate.index])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use quickcheck::quickcheck;
+    use ultraviolet::Vec2;
+
+    use crate::bvh::BuildConfig;
+    use crate::bvh2d::{BHShape2, BVHNode2, Bounded2, Ray2, AABB2, BVH2};
+    use crate::testbase::{tuple_to_point2, TupleVec2};
+    use crate::EPSILON;
+
+    /// A square with an id, placed on a pseudo-random position.
+    struct Square {
+        id: usize,
+        pos: Vec2,
+        size: f32,
+        node_index: usize,
+    }
+
+    impl Bounded2 for Square {
+        fn aabb(&self) -> AABB2 {
+            let half_size = Vec2::new(self.size, self.size) * 0.5;
+            AABB2::with_bounds(self.pos - half_size, self.pos + half_size)
+        }
+    }
+
+    impl BHShape2 for Square {
+        fn set_bh_node_index(&mut self, index: usize) {
+            self.node_index = index;
+        }
+
+        fn bh_node_index(&self) -> usize {
+            self.node_index
+        }
+    }
+
+    /// Creates `n` squares of varying sizes, scattered inside of `[-50, 50]^2`.
+    fn create_squares(n: usize) -> Vec<Square> {
+        let mut seed = 0x5eed_u64;
+        let mut next = || {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 40) as f32 / (1u64 << 24) as f32
+        };
+        (0..n)
+            .map(|id| Square {
+                id,
+                pos: Vec2::new(next() * 100.0 - 50.0, next() * 100.0 - 50.0),
+                size: 0.5 + next() * 4.0,
+                node_index: 0,
+            })
+            .collect()
+    }
+
+    /// Builds a `BVH2` over 200 squares with the given maximum leaf size.
+    fn build_scene(max_leaf_size: usize) -> (Vec<Square>, BVH2) {
+        let mut squares = create_squares(200);
+        let config = BuildConfig {
+            max_leaf_size,
+            ..Default::default()
+        };
+        let bvh = BVH2::build_with_config(&mut squares, &config);
+        (squares, bvh)
+    }
+
+    /// Collects the ids of the `squares`, to compare query results as sets.
+    fn ids(squares: Vec<&Square>) -> HashSet<usize> {
+        squares.into_iter().map(|square| square.id).collect()
+    }
+
+    #[test]
+    /// Tests whether every shape is referenced by the leaf it points to, and whether the
+    /// inner nodes contain the bounds of their subtrees.
+    fn test_build_is_consistent() {
+        for &max_leaf_size in &[1, 4] {
+            let (squares, bvh) = build_scene(max_leaf_size);
+            let mut seen = vec![false; squares.len()];
+            for (node_index, node) in bvh.nodes.iter().enumerate() {
+                match *node {
+                    BVHNode2::Leaf { .. } => {
+                        for shape_index in node.shape_range().unwrap() {
+                            assert!(!seen[shape_index]);
+                            seen[shape_index] = true;
+                            assert_eq!(squares[shape_index].bh_node_index(), node_index);
+                        }
+                    }
+                    BVHNode2::Node {
+                        child_l_index,
+                        child_l_aabb,
+                        child_r_index,
+                        child_r_aabb,
+                        ..
+                    } => {
+                        for &(child_index, child_aabb) in
+                            &[(child_l_index, child_l_aabb), (child_r_index, child_r_aabb)]
+                        {
+                            let child = &bvh.nodes[child_index];
+                            assert_eq!(child.parent(), node_index);
+                            assert_eq!(child.depth(), node.depth() + 1);
+                            if let Some(range) = child.shape_range() {
+                                for shape_index in range {
+                                    let shape_aabb = squares[shape_index].aabb();
+                                    assert!(
+                                        child_aabb.approx_contains_aabb_eps(&shape_aabb, EPSILON)
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            assert!(seen.iter().all(|seen| *seen));
+        }
+    }
+
+    /// Compares the result of `traverse` to testing all shapes.
+    quickcheck! {
+        fn test_traverse_equals_linear_scan(origin: TupleVec2, direction: TupleVec2) -> bool {
+            let ray = Ray2::new(tuple_to_point2(&origin), tuple_to_point2(&direction));
+            [1, 4].iter().all(|max_leaf_size| {
+                let (squares, bvh) = build_scene(*max_leaf_size);
+                let expected = ids(
+                    squares
+                        .iter()
+                        .filter(|square| ray.intersects_aabb(&square.aabb()))
+                        .collect(),
+                );
+                ids(bvh.traverse(&ray, &squares)) == expected
+            })
+        }
+    }
+
+    /// Compares the result of `traverse_aabb` to a linear scan over all shapes.
+    quickcheck! {
+        fn test_traverse_aabb_equals_linear_scan(a: TupleVec2, b: TupleVec2) -> bool {
+            let query = AABB2::empty()
+                .grow(&tuple_to_point2(&a))
+                .grow(&tuple_to_point2(&b));
+            [1, 4].iter().all(|max_leaf_size| {
+                let (squares, bvh) = build_scene(*max_leaf_size);
+                let expected = ids(
+                    squares
+                        .iter()
+                        .filter(|square| square.aabb().intersects_aabb(&query))
+                        .collect(),
+                );
+                ids(bvh.traverse_aabb(&query, &squares)) == expected
+            })
+        }
+    }
+
+    /// Compares the result of `k_nearest` to sorting all shapes by their distance.
+    quickcheck! {
+        fn test_k_nearest_equals_brute_force(p: TupleVec2, k: u8) -> bool {
+            let query = tuple_to_point2(&p);
+            let k = k as usize % 20;
+            [1, 4].iter().all(|max_leaf_size| {
+                let (squares, bvh) = build_scene(*max_leaf_size);
+                let mut expected: Vec<(f32, usize)> = squares
+                    .iter()
+                    .map(|square| (square.aabb().distance_squared_to_point(&query), square.id))
+                    .collect();
+                expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+                let nearest = bvh.k_nearest(query, k, &squares);
+                nearest.len() == k.min(squares.len())
+                    && nearest.iter().zip(expected.iter()).all(|(square, (distance, _))| {
+                        square.aabb().distance_squared_to_point(&query) == *distance
+                    })
+            })
+        }
+    }
+
+    #[test]
+    /// Tests whether an empty `BVH2` can be built and queried.
+    fn test_empty_bvh() {
+        let mut squares: Vec<Square> = Vec::new();
+        let bvh = BVH2::build(&mut squares);
+        let ray = Ray2::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0));
+        assert!(bvh.traverse(&ray, &squares).is_empty());
+        assert!(bvh.k_nearest(Vec2::new(0.0, 0.0), 3, &squares).is_empty());
+    }
+
+    /// Creates `n` squares along the X axis and a chain of inner nodes over them which
+    /// descends to the left and has a single leaf on each right side, so the tree is
+    /// `n - 1` levels deep.
+    fn build_chain(n: usize) -> (Vec<Square>, BVH2) {
+        let squares: Vec<Square> = (0..n)
+            .map(|id| Square {
+                id,
+                pos: Vec2::new(id as f32, 0.0),
+                size: 0.5,
+                node_index: 0,
+            })
+            .collect();
+
+        // `suffix_aabbs[i]` is the joint `AABB2` of the squares `i..n`.
+        let mut suffix_aabbs = vec![AABB2::empty(); n + 1];
+        for i in (0..n).rev() {
+            suffix_aabbs[i] = suffix_aabbs[i + 1].join(&squares[i].aabb());
+        }
+
+        let leaf_index = |shape_index: usize| n - 1 + shape_index;
+        let mut nodes = Vec::with_capacity(2 * n - 1);
+        for i in 0..n - 1 {
+            let child_l_index = if i < n - 2 { i + 1 } else { leaf_index(n - 1) };
+            nodes.push(BVHNode2::Node {
+                parent_index: i.saturating_sub(1),
+                depth: i as u32,
+                child_l_index,
+                child_l_aabb: suffix_aabbs[i + 1],
+                child_r_index: leaf_index(i),
+                child_r_aabb: squares[i].aabb(),
+            });
+        }
+        for i in 0..n {
+            nodes.push(BVHNode2::Leaf {
+                parent_index: i.min(n - 2),
+                depth: i.min(n - 2) as u32 + 1,
+                shape_index: i,
+                shape_count: 1,
+            });
+        }
+        (squares, BVH2 { nodes })
+    }
+
+    #[test]
+    /// Tests whether a `BVH2` which is too deep for recursion can be traversed, and
+    /// whether the shape of a root leaf is tested against the query.
+    fn test_traverse_deep_chain_and_root_leaf() {
+        let n = 300_000;
+        let (squares, bvh) = build_chain(n);
+        let ray = Ray2::new(Vec2::new(-10.0, 0.0), Vec2::new(1.0, 0.0));
+        assert_eq!(bvh.traverse_indices(&ray, &squares).len(), n);
+        let query = AABB2::with_bounds(Vec2::new(-1.0, -1.0), Vec2::new(2.2, 1.0));
+        assert_eq!(bvh.traverse_aabb(&query, &squares).len(), 3);
+
+        let mut single = create_squares(1);
+        let bvh = BVH2::build(&mut single);
+        let miss = Ray2::new(Vec2::new(-100.0, 60.0), Vec2::new(1.0, 0.0));
+        assert!(bvh.traverse(&miss, &single).is_empty());
+        let hit = Ray2::new(Vec2::new(-100.0, single[0].pos.y), Vec2::new(1.0, 0.0));
+        assert_eq!(bvh.traverse(&hit, &single).len(), 1);
+    }
+}
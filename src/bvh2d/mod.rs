@@ -0,0 +1,68 @@
+//! A two-dimensional version of the [`BVH`] over `Vec2`, for example for 2D broad phases.
+//!
+//! [`AABB2`], [`Ray2`] and [`BVH2`] mirror their three-dimensional counterparts, but
+//! store one coordinate less per bound. The [`BVH2`] is built with the same SAH bucket
+//! approach restricted to the two axes of [`Axis2`], using the perimeter of an
+//! [`AABB2`] in place of the surface area.
+//!
+//! # Examples
+//! ```
+//! use bvh_ultraviolet::bvh2d::{Bounded2, BHShape2, AABB2, BVH2, Ray2};
+//! use bvh_ultraviolet::ultraviolet::Vec2;
+//!
+//! struct Circle {
+//!     position: Vec2,
+//!     radius: f32,
+//!     node_index: usize,
+//! }
+//!
+//! impl Bounded2 for Circle {
+//!     fn aabb(&self) -> AABB2 {
+//!         let half_size = Vec2::new(self.radius, self.radius);
+//!         AABB2::with_bounds(self.position - half_size, self.position + half_size)
+//!     }
+//! }
+//!
+//! impl BHShape2 for Circle {
+//!     fn set_bh_node_index(&mut self, index: usize) {
+//!         self.node_index = index;
+//!     }
+//!
+//!     fn bh_node_index(&self) -> usize {
+//!         self.node_index
+//!     }
+//! }
+//!
+//! let mut circles = Vec::new();
+//! for i in 0..10 {
+//!     let position = Vec2::new(i as f32 * 10.0, 0.0);
+//!     circles.push(Circle { position, radius: 1.0, node_index: 0 });
+//! }
+//! let bvh = BVH2::build(&mut circles);
+//!
+//! let ray = Ray2::new(Vec2::new(30.0, -10.0), Vec2::new(0.0, 1.0));
+//! let hits = bvh.traverse(&ray, &circles);
+//! assert_eq!(hits.len(), 1);
+//! assert_eq!(hits[0].position.x, 30.0);
+//!
+//! let query = AABB2::with_bounds(Vec2::new(15.0, -1.0), Vec2::new(35.0, 1.0));
+//! assert_eq!(bvh.traverse_aabb(&query, &circles).len(), 2);
+//!
+//! let nearest = bvh.k_nearest(Vec2::new(62.0, 5.0), 1, &circles);
+//! assert_eq!(nearest[0].position.x, 60.0);
+//! ```
+//!
+//! [`AABB2`]: struct.AABB2.html
+//! [`Axis2`]: ../axis/enum.Axis2.html
+//! [`BVH`]: ../bvh/struct.BVH.html
+//! [`BVH2`]: struct.BVH2.html
+//! [`Ray2`]: struct.Ray2.html
+//!
+
+mod aabb;
+mod bvh;
+mod ray;
+
+pub use self::aabb::*;
+pub use self::bvh::*;
+pub use self::ray::*;
@@ -0,0 +1,164 @@
+//! Two-dimensional rays and their intersection algorithm for axis aligned bounding boxes.
+
+use ultraviolet::Vec2;
+
+use crate::bvh2d::AABB2;
+
+/// Two-dimensional version of [`Ray`].
+///
+/// [`Ray`]: ../ray/struct.Ray.html
+///
+#[derive(Debug)]
+pub struct Ray2 {
+    /// The ray origin.
+    pub origin: Vec2,
+
+    /// The ray direction.
+    pub direction: Vec2,
+
+    /// The start of the interval along the ray in which intersections are reported.
+    /// Defaults to `0.0`.
+    pub t_min: f32,
+
+    /// The end of the interval along the ray in which intersections are reported.
+    /// Defaults to `f32::INFINITY`.
+    pub t_max: f32,
+
+    /// Inverse (1/x) ray direction. Cached for use in [`AABB2`] intersections.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    ///
+    pub(crate) inv_direction: Vec2,
+
+    /// Sign of the direction per axis. 0 means positive, 1 means negative.
+    pub(crate) sign: [usize; 2],
+}
+
+impl Ray2 {
+    /// Creates a new [`Ray2`] from an `origin` and a `direction`.
+    /// `direction` will be normalized.
+    ///
+    /// [`Ray2`]: struct.Ray2.html
+    ///
+    pub fn new(origin: Vec2, direction: Vec2) -> Ray2 {
+        Ray2::with_bounds(origin, direction, 0.0, f32::INFINITY)
+    }
+
+    /// Creates a new [`Ray2`] from an `origin` and a `direction`, which only reports
+    /// intersections between the distances `t_min` and `t_max` along the ray.
+    /// `direction` will be normalized, so the distances are measured in world units.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh2d::{AABB2, Ray2};
+    /// use bvh_ultraviolet::ultraviolet::Vec2;
+    ///
+    /// let ray = Ray2::with_bounds(Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), 0.0, 50.0);
+    ///
+    /// let near_box = AABB2::with_bounds(Vec2::new(9.0, -1.0), Vec2::new(11.0, 1.0));
+    /// let far_box = AABB2::with_bounds(Vec2::new(99.0, -1.0), Vec2::new(101.0, 1.0));
+    ///
+    /// assert!(ray.intersects_aabb(&near_box));
+    /// assert!(!ray.intersects_aabb(&far_box));
+    /// ```
+    ///
+    /// [`Ray2`]: struct.Ray2.html
+    ///
+    pub fn with_bounds(origin: Vec2, direction: Vec2, t_min: f32, t_max: f32) -> Ray2 {
+        let direction = direction.normalized();
+        Ray2 {
+            origin,
+            direction,
+            t_min,
+            t_max,
+            inv_direction: Vec2::new(1.0 / direction.x, 1.0 / direction.y),
+            // Sign of the direction. 0 means positive, 1 means negative.
+            sign: [(direction.x < 0.0) as usize, (direction.y < 0.0) as usize],
+        }
+    }
+
+    /// Tests the intersection of a [`Ray2`] with an [`AABB2`] using the same algorithm
+    /// as [`Ray::intersects_aabb`], restricted to two axes.
+    /// Only intersections within `[t_min, t_max]` of the [`Ray2`] are reported.
+    ///
+    /// [`AABB2`]: struct.AABB2.html
+    /// [`Ray2`]: struct.Ray2.html
+    /// [`Ray::intersects_aabb`]: ../ray/struct.Ray.html#method.intersects_aabb
+    ///
+    pub fn intersects_aabb(&self, aabb: &AABB2) -> bool {
+        let mut ray_min = (aabb[self.sign[0]].x - self.origin.x) * self.inv_direction.x;
+        let mut ray_max = (aabb[1 - self.sign[0]].x - self.origin.x) * self.inv_direction.x;
+
+        let y_min = (aabb[self.sign[1]].y - self.origin.y) * self.inv_direction.y;
+        let y_max = (aabb[1 - self.sign[1]].y - self.origin.y) * self.inv_direction.y;
+
+        if (ray_min > y_max) || (y_min > ray_max) {
+            return false;
+        }
+
+        // `max` and `min` ignore the `NaN` bound of an origin on a slab plane.
+        ray_min = ray_min.max(y_min);
+        ray_max = ray_max.min(y_max);
+
+        ray_min.max(self.t_min) <= ray_max.min(self.t_max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bvh2d::{Ray2, AABB2};
+    use crate::testbase::{tuple_to_point2, TupleVec2};
+
+    use quickcheck::quickcheck;
+
+    /// Generates a random `Ray2` which points at at a random `AABB2`.
+    fn gen_ray_to_aabb(data: (TupleVec2, TupleVec2, TupleVec2)) -> (Ray2, AABB2) {
+        let aabb = AABB2::empty()
+            .grow(&tuple_to_point2(&data.0))
+            .grow(&tuple_to_point2(&data.1));
+        let pos = tuple_to_point2(&data.2);
+        let ray = Ray2::new(pos, aabb.center() - pos);
+        (ray, aabb)
+    }
+
+    /// Test whether a `Ray2` which points at the center of an `AABB2` intersects it.
+    quickcheck! {
+        fn test_ray_points_at_aabb_center(data: (TupleVec2, TupleVec2, TupleVec2)) -> bool {
+            let (ray, aabb) = gen_ray_to_aabb(data);
+            ray.intersects_aabb(&aabb)
+        }
+    }
+
+    /// Test whether a `Ray2` which points away from the center of an `AABB2`
+    /// does not intersect it, unless its origin is inside the `AABB2`.
+    quickcheck! {
+        fn test_ray_points_from_aabb_center(data: (TupleVec2, TupleVec2, TupleVec2)) -> bool {
+            let (mut ray, aabb) = gen_ray_to_aabb(data);
+
+            // Invert the direction of the ray
+            ray.direction = -ray.direction;
+            ray.inv_direction = -ray.inv_direction;
+            !ray.intersects_aabb(&aabb) || aabb.contains(&ray.origin)
+        }
+    }
+
+    /// Test whether a `Ray2` which points at the center of an `AABB2`, but ends before
+    /// reaching it, does not intersect it.
+    quickcheck! {
+        fn test_ray_ends_before_aabb(data: (TupleVec2, TupleVec2, TupleVec2)) -> bool {
+            let (ray, aabb) = gen_ray_to_aabb(data);
+            let entry_distance = match aabb.intersect_ray_distance(&ray) {
+                Some(distance) if distance > 0.0 => distance,
+                // The origin is inside of the `AABB2`.
+                _ => return true,
+            };
+
+            let short_ray = Ray2::with_bounds(ray.origin, ray.direction, 0.0, entry_distance * 0.5);
+            let late_ray =
+                Ray2::with_bounds(ray.origin, ray.direction, entry_distance * 0.5, f32::INFINITY);
+            !short_ray.intersects_aabb(&aabb)
+                && aabb.intersect_ray_distance(&short_ray).is_none()
+                && late_ray.intersects_aabb(&aabb)
+        }
+    }
+}
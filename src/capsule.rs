@@ -0,0 +1,71 @@
+//! This module defines a [`Capsule`], such as the collision shape of a character
+//! controller, which can be used to query a [`BVH`].
+//!
+//! [`BVH`]: ../bvh/struct.BVH.html
+//! [`Capsule`]: struct.Capsule.html
+//!
+
+use crate::aabb::{Bounded, AABB};
+use ultraviolet::Vec3;
+
+/// A capsule, which consists of all points within `radius` of the line segment from
+/// `a` to `b`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Capsule {
+    /// The start of the segment.
+    pub a: Vec3,
+
+    /// The end of the segment.
+    pub b: Vec3,
+
+    /// The radius around the segment.
+    pub radius: f32,
+}
+
+impl Capsule {
+    /// Creates a new [`Capsule`] around the segment from `a` to `b`.
+    ///
+    /// [`Capsule`]: struct.Capsule.html
+    ///
+    pub fn new(a: Vec3, b: Vec3, radius: f32) -> Capsule {
+        Capsule { a, b, radius }
+    }
+
+    /// Returns true if this [`Capsule`] intersects `aabb`, by comparing the
+    /// [`distance_squared_to_segment`] of the [`AABB`] with the `radius`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::capsule::Capsule;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let capsule = Capsule::new(Vec3::new(-5.0, 3.0, 0.0), Vec3::new(5.0, 3.0, 0.0), 2.0);
+    /// let below = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    /// let lower = AABB::with_bounds(Vec3::new(-1.0, -2.0, -1.0), Vec3::new(1.0, 0.5, 1.0));
+    ///
+    /// assert!(capsule.intersects_aabb(&below));
+    /// assert!(!capsule.intersects_aabb(&lower));
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`Capsule`]: struct.Capsule.html
+    /// [`distance_squared_to_segment`]: ../aabb/struct.AABB.html#method.distance_squared_to_segment
+    ///
+    pub fn intersects_aabb(&self, aabb: &AABB) -> bool {
+        aabb.distance_squared_to_segment(&self.a, &self.b) <= self.radius * self.radius
+    }
+}
+
+impl Bounded for Capsule {
+    /// Returns the [`AABB`] of the segment, expanded by the `radius`.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    fn aabb(&self) -> AABB {
+        AABB::empty()
+            .grow(&self.a)
+            .grow(&self.b)
+            .expand(self.radius)
+    }
+}
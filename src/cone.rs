@@ -0,0 +1,164 @@
+//! This module defines a [`Cone`], such as the lit volume of a spotlight, which can be
+//! used to query a [`BVH`].
+//!
+//! [`BVH`]: ../bvh/struct.BVH.html
+//! [`Cone`]: struct.Cone.html
+//!
+
+use crate::aabb::AABB;
+use ultraviolet::Vec3;
+
+/// A circular cone, which starts at its `apex`, opens around its `direction` by
+/// `half_angle` and is cut off by a sphere of radius `range` around the `apex`.
+///
+/// The `direction` is expected to have unit length and the `half_angle` to lie between
+/// `0` and `π / 2`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Cone {
+    /// The tip of the cone.
+    pub apex: Vec3,
+
+    /// The unit axis of the cone, pointing from the `apex` into the cone.
+    pub direction: Vec3,
+
+    /// The angle between the axis and the surface of the cone in radians.
+    pub half_angle: f32,
+
+    /// The maximum distance of the points of the cone from the `apex`.
+    pub range: f32,
+}
+
+impl Cone {
+    /// Creates a new [`Cone`]. `direction` will be normalized.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::cone::Cone;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let cone = Cone::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 2.0), 0.5, 10.0);
+    /// assert_eq!(cone.direction, Vec3::new(0.0, 0.0, 1.0));
+    /// ```
+    ///
+    /// [`Cone`]: struct.Cone.html
+    ///
+    pub fn new(apex: Vec3, direction: Vec3, half_angle: f32, range: f32) -> Cone {
+        Cone {
+            apex,
+            direction: direction.normalized(),
+            half_angle,
+            range,
+        }
+    }
+
+    /// Returns true if `point` lies inside this [`Cone`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::cone::Cone;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let cone = Cone::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0), 0.5, 10.0);
+    ///
+    /// assert!(cone.contains(&Vec3::new(0.0, 1.0, 5.0)));
+    /// assert!(!cone.contains(&Vec3::new(0.0, 5.0, 5.0)));
+    /// assert!(!cone.contains(&Vec3::new(0.0, 0.0, 11.0)));
+    /// assert!(!cone.contains(&Vec3::new(0.0, 0.0, -1.0)));
+    /// ```
+    ///
+    /// [`Cone`]: struct.Cone.html
+    ///
+    pub fn contains(&self, point: &Vec3) -> bool {
+        let offset = *point - self.apex;
+        let distance = offset.mag();
+        distance <= self.range && offset.dot(self.direction) >= distance * self.half_angle.cos()
+    }
+
+    /// Returns true if the sphere around `center` with `radius` may intersect this
+    /// [`Cone`]. The test is conservative: it never rejects an intersecting sphere, but
+    /// may accept spheres which only come close to the cone near its `apex` or its
+    /// spherical cap.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::cone::Cone;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let cone = Cone::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0), 0.5, 10.0);
+    ///
+    /// assert!(cone.intersects_sphere(Vec3::new(0.0, 4.0, 5.0), 2.0));
+    /// assert!(!cone.intersects_sphere(Vec3::new(0.0, 6.0, 5.0), 2.0));
+    /// assert!(!cone.intersects_sphere(Vec3::new(0.0, 0.0, -3.0), 2.0));
+    /// ```
+    ///
+    /// [`Cone`]: struct.Cone.html
+    ///
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        let offset = center - self.apex;
+        let distance_squared = offset.mag_sq();
+        let along_axis = offset.dot(self.direction);
+        let from_axis = (distance_squared - along_axis * along_axis).max(0.0).sqrt();
+
+        // The distance of the center from the surface of the infinite cone, which
+        // underestimates the distance of centers behind the apex.
+        let (sin, cos) = self.half_angle.sin_cos();
+        let from_surface = cos * from_axis - sin * along_axis;
+
+        let reach = self.range + radius;
+        from_surface <= radius && along_axis >= -radius && distance_squared <= reach * reach
+    }
+
+    /// Returns true if `aabb` may intersect this [`Cone`], by testing its
+    /// [`bounding_sphere`] with [`intersects_sphere`]. The test is conservative: it
+    /// never rejects an intersecting [`AABB`], but may accept [`AABB`]s close to the cone.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::cone::Cone;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let cone = Cone::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0), 0.5, 10.0);
+    /// let inside = AABB::with_bounds(Vec3::new(-1.0, -1.0, 4.0), Vec3::new(1.0, 1.0, 6.0));
+    /// let beside = AABB::with_bounds(Vec3::new(8.0, -1.0, 4.0), Vec3::new(10.0, 1.0, 6.0));
+    ///
+    /// assert!(cone.intersects_aabb(&inside));
+    /// assert!(!cone.intersects_aabb(&beside));
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`Cone`]: struct.Cone.html
+    /// [`bounding_sphere`]: ../aabb/struct.AABB.html#method.bounding_sphere
+    /// [`intersects_sphere`]: struct.Cone.html#method.intersects_sphere
+    ///
+    pub fn intersects_aabb(&self, aabb: &AABB) -> bool {
+        let (center, radius) = aabb.bounding_sphere();
+        self.intersects_sphere(center, radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cone::Cone;
+    use crate::testbase::{tuple_to_point, tuple_to_vector, TupleVec};
+
+    use quickcheck::quickcheck;
+
+    /// Test whether a sphere around a point inside a `Cone` is never rejected, and
+    /// whether points on the axis lie inside the `Cone`.
+    quickcheck! {
+        fn test_cone_never_rejects_contained_points(a: TupleVec, d: TupleVec, p: TupleVec) -> bool {
+            let direction = tuple_to_vector(&d);
+            if direction.mag() < 0.01 {
+                return true;
+            }
+            let apex = tuple_to_point(&a);
+            let point = tuple_to_point(&p);
+            let cone = Cone::new(apex, direction, 0.6, (point - apex).mag() * 1.5 + 1.0);
+
+            let on_axis = apex + cone.direction * cone.range * 0.5;
+            (!cone.contains(&point) || cone.intersects_sphere(point, 1e-3 * (1.0 + point.mag())))
+                && cone.contains(&on_axis)
+        }
+    }
+}
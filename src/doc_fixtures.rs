@@ -0,0 +1,34 @@
+//! Shapes shared by the documentation examples. Not part of the public API.
+
+use crate::aabb::{Bounded, AABB};
+use crate::bounding_hierarchy::BHShape;
+use ultraviolet::Vec3;
+
+/// A sphere which stores the index of its [`BVH`] node. Its [`AABB`] is the cube
+/// around it.
+///
+/// [`AABB`]: ../aabb/struct.AABB.html
+/// [`BVH`]: ../bvh/struct.BVH.html
+///
+pub struct Sphere {
+    pub position: Vec3,
+    pub radius: f32,
+    pub node_index: usize,
+}
+
+impl Bounded for Sphere {
+    fn aabb(&self) -> AABB {
+        let half_size = Vec3::new(self.radius, self.radius, self.radius);
+        AABB::with_bounds(self.position - half_size, self.position + half_size)
+    }
+}
+
+impl BHShape for Sphere {
+    fn set_bh_node_index(&mut self, index: usize) {
+        self.node_index = index;
+    }
+
+    fn bh_node_index(&self) -> usize {
+        self.node_index
+    }
+}
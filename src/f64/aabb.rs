@@ -0,0 +1,893 @@
+//! Double precision Axis Aligned Bounding Boxes.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Index;
+
+use ultraviolet::DVec3;
+
+use crate::axis::Axis;
+use crate::f64::Ray64;
+
+/// Double precision version of [`AABB`].
+///
+/// [`AABB`]: ../aabb/struct.AABB.html
+///
+#[derive(Debug, Copy, Clone)]
+pub struct AABB64 {
+    /// Minimum coordinates
+    pub min: DVec3,
+
+    /// Maximum coordinates
+    pub max: DVec3,
+}
+
+impl fmt::Display for AABB64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Min bound: {:?}; Max bound: {:?}", self.min, self.max)
+    }
+}
+
+/// Double precision version of [`Bounded`], implemented by things which can be bounded
+/// by an [`AABB64`].
+///
+/// [`AABB64`]: struct.AABB64.html
+/// [`Bounded`]: ../aabb/trait.Bounded.html
+///
+pub trait Bounded64 {
+    /// Returns the geometric bounds of this object in the form of an [`AABB64`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    ///
+    fn aabb(&self) -> AABB64;
+}
+
+impl AABB64 {
+    /// Creates a new [`AABB64`] with the given bounds.
+    /// See [`AABB::with_bounds`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::with_bounds`]: ../aabb/struct.AABB.html#method.with_bounds
+    ///
+    pub fn with_bounds(min: DVec3, max: DVec3) -> AABB64 {
+        AABB64 { min, max }
+    }
+
+    /// Creates a new empty [`AABB64`].
+    /// See [`AABB::empty`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::empty`]: ../aabb/struct.AABB.html#method.empty
+    ///
+    pub fn empty() -> AABB64 {
+        AABB64 {
+            min: DVec3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: DVec3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    /// Returns true if the [`DVec3`] is inside the [`AABB64`].
+    /// See [`AABB::contains`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::contains`]: ../aabb/struct.AABB.html#method.contains
+    /// [`DVec3`]: ../../ultraviolet/vec/struct.DVec3.html
+    ///
+    pub fn contains(&self, p: &DVec3) -> bool {
+        p.x >= self.min.x
+            && p.x <= self.max.x
+            && p.y >= self.min.y
+            && p.y <= self.max.y
+            && p.z >= self.min.z
+            && p.z <= self.max.z
+    }
+
+    /// Returns true if the [`DVec3`] is approximately inside the [`AABB64`]
+    /// with respect to some `epsilon`.
+    /// See [`AABB::approx_contains_eps`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::approx_contains_eps`]: ../aabb/struct.AABB.html#method.approx_contains_eps
+    /// [`DVec3`]: ../../ultraviolet/vec/struct.DVec3.html
+    ///
+    pub fn approx_contains_eps(&self, p: &DVec3, epsilon: f64) -> bool {
+        (p.x - self.min.x) > -epsilon
+            && (p.x - self.max.x) < epsilon
+            && (p.y - self.min.y) > -epsilon
+            && (p.y - self.max.y) < epsilon
+            && (p.z - self.min.z) > -epsilon
+            && (p.z - self.max.z) < epsilon
+    }
+
+    /// Returns true if the `other` [`AABB64`] is approximately inside this [`AABB64`]
+    /// with respect to some `epsilon`.
+    /// See [`AABB::approx_contains_aabb_eps`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::approx_contains_aabb_eps`]: ../aabb/struct.AABB.html#method.approx_contains_aabb_eps
+    ///
+    pub fn approx_contains_aabb_eps(&self, other: &AABB64, epsilon: f64) -> bool {
+        self.approx_contains_eps(&other.min, epsilon)
+            && self.approx_contains_eps(&other.max, epsilon)
+    }
+
+    /// Returns true if the `other` [`AABB64`] is approximately equal to this [`AABB64`]
+    /// with respect to some `epsilon`.
+    /// See [`AABB::relative_eq`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::relative_eq`]: ../aabb/struct.AABB.html#method.relative_eq
+    ///
+    pub fn relative_eq(&self, other: &AABB64, epsilon: f64) -> bool {
+        fn relative_equal(a: DVec3, b: DVec3, epsilon: f64) -> bool {
+            (a.x - b.x).abs() <= epsilon
+                && (a.y - b.y).abs() <= epsilon
+                && (a.z - b.z).abs() <= epsilon
+        }
+
+        relative_equal(self.min, other.min, epsilon) && relative_equal(self.max, other.max, epsilon)
+    }
+
+    /// Returns a new minimal [`AABB64`] which contains both this [`AABB64`] and `other`.
+    /// See [`AABB::join`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::join`]: ../aabb/struct.AABB.html#method.join
+    ///
+    pub fn join(&self, other: &AABB64) -> AABB64 {
+        AABB64::with_bounds(
+            DVec3::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            DVec3::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        )
+    }
+
+    /// Mutable version of [`AABB64::join`].
+    ///
+    /// [`AABB64::join`]: struct.AABB64.html#method.join
+    ///
+    pub fn join_mut(&mut self, other: &AABB64) {
+        *self = self.join(other);
+    }
+
+    /// Returns the [`AABB64`] in which this [`AABB64`] and `other` overlap.
+    /// See [`AABB::intersection`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::intersection`]: ../aabb/struct.AABB.html#method.intersection
+    ///
+    pub fn intersection(&self, other: &AABB64) -> AABB64 {
+        AABB64::with_bounds(
+            DVec3::new(
+                self.min.x.max(other.min.x),
+                self.min.y.max(other.min.y),
+                self.min.z.max(other.min.z),
+            ),
+            DVec3::new(
+                self.max.x.min(other.max.x),
+                self.max.y.min(other.max.y),
+                self.max.z.min(other.max.z),
+            ),
+        )
+    }
+
+    /// Mutable version of [`AABB64::intersection`].
+    ///
+    /// [`AABB64::intersection`]: struct.AABB64.html#method.intersection
+    ///
+    pub fn intersection_mut(&mut self, other: &AABB64) {
+        *self = self.intersection(other);
+    }
+
+    /// Returns true if this [`AABB64`] and `other` overlap or touch.
+    /// See [`AABB::intersects_aabb`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::intersects_aabb`]: ../aabb/struct.AABB.html#method.intersects_aabb
+    ///
+    pub fn intersects_aabb(&self, other: &AABB64) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+            && self.min.z <= other.max.z
+            && self.max.z >= other.min.z
+    }
+
+    /// Returns true if this [`AABB64`] and `other` overlap up to some `epsilon`.
+    /// See [`AABB::intersects_aabb_eps`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::intersects_aabb_eps`]: ../aabb/struct.AABB.html#method.intersects_aabb_eps
+    ///
+    pub fn intersects_aabb_eps(&self, other: &AABB64, epsilon: f64) -> bool {
+        (self.min.x - other.max.x) < epsilon
+            && (self.max.x - other.min.x) > -epsilon
+            && (self.min.y - other.max.y) < epsilon
+            && (self.max.y - other.min.y) > -epsilon
+            && (self.min.z - other.max.z) < epsilon
+            && (self.max.z - other.min.z) > -epsilon
+    }
+
+    /// Returns true if `ray` intersects this [`AABB64`].
+    /// See [`Ray64::intersects_aabb`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`Ray64::intersects_aabb`]: struct.Ray64.html#method.intersects_aabb
+    ///
+    pub fn intersects_ray(&self, ray: &Ray64) -> bool {
+        ray.intersects_aabb(self)
+    }
+
+    /// Returns the distance along `ray` at which it enters this [`AABB64`], or `None`
+    /// if it misses the [`AABB64`]. See [`AABB::intersect_ray_distance`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::intersect_ray_distance`]: ../aabb/struct.AABB.html#method.intersect_ray_distance
+    ///
+    pub fn intersect_ray_distance(&self, ray: &Ray64) -> Option<f64> {
+        let tx1 = (self.min.x - ray.origin.x) * ray.inv_direction.x;
+        let tx2 = (self.max.x - ray.origin.x) * ray.inv_direction.x;
+
+        let mut t_near = tx1.min(tx2);
+        let mut t_far = tx1.max(tx2);
+
+        let ty1 = (self.min.y - ray.origin.y) * ray.inv_direction.y;
+        let ty2 = (self.max.y - ray.origin.y) * ray.inv_direction.y;
+
+        t_near = t_near.max(ty1.min(ty2));
+        t_far = t_far.min(ty1.max(ty2));
+
+        let tz1 = (self.min.z - ray.origin.z) * ray.inv_direction.z;
+        let tz2 = (self.max.z - ray.origin.z) * ray.inv_direction.z;
+
+        t_near = t_near.max(tz1.min(tz2));
+        t_far = t_far.min(tz1.max(tz2));
+
+        let t_near = t_near.max(ray.t_min);
+        if t_far >= t_near && t_near <= ray.t_max {
+            Some(t_near)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a new minimal [`AABB64`] which contains both this [`AABB64`] and the point
+    /// `other`. See [`AABB::grow`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::grow`]: ../aabb/struct.AABB.html#method.grow
+    ///
+    pub fn grow(&self, other: &DVec3) -> AABB64 {
+        AABB64::with_bounds(
+            DVec3::new(
+                self.min.x.min(other.x),
+                self.min.y.min(other.y),
+                self.min.z.min(other.z),
+            ),
+            DVec3::new(
+                self.max.x.max(other.x),
+                self.max.y.max(other.y),
+                self.max.z.max(other.z),
+            ),
+        )
+    }
+
+    /// Mutable version of [`AABB64::grow`].
+    ///
+    /// [`AABB64::grow`]: struct.AABB64.html#method.grow
+    ///
+    pub fn grow_mut(&mut self, other: &DVec3) {
+        *self = self.grow(other);
+    }
+
+    /// Returns a new [`AABB64`] which is enlarged by `margin` on every side.
+    /// See [`AABB::expand`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::expand`]: ../aabb/struct.AABB.html#method.expand
+    ///
+    pub fn expand(&self, margin: f64) -> AABB64 {
+        self.expand_by(DVec3::new(margin, margin, margin))
+    }
+
+    /// Mutable version of [`AABB64::expand`].
+    ///
+    /// [`AABB64::expand`]: struct.AABB64.html#method.expand
+    ///
+    pub fn expand_mut(&mut self, margin: f64) {
+        *self = self.expand(margin);
+    }
+
+    /// Returns a new [`AABB64`] which is enlarged by the per axis `margins` on every side.
+    /// See [`AABB::expand_by`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::expand_by`]: ../aabb/struct.AABB.html#method.expand_by
+    ///
+    pub fn expand_by(&self, margins: DVec3) -> AABB64 {
+        AABB64::with_bounds(self.min - margins, self.max + margins)
+    }
+
+    /// Returns a new minimal [`AABB64`] which contains both this [`AABB64`] and the
+    /// [`Bounded64`] `other`. See [`AABB::join_bounded`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::join_bounded`]: ../aabb/struct.AABB.html#method.join_bounded
+    /// [`Bounded64`]: trait.Bounded64.html
+    ///
+    pub fn join_bounded<T: Bounded64>(&self, other: &T) -> AABB64 {
+        self.join(&other.aabb())
+    }
+
+    /// Returns the size of this [`AABB64`] in all three dimensions.
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    ///
+    pub fn size(&self) -> DVec3 {
+        self.max - self.min
+    }
+
+    /// Returns the center point of the [`AABB64`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    ///
+    pub fn center(&self) -> DVec3 {
+        self.min + (self.size() / 2.0)
+    }
+
+    /// Returns half of the size of this [`AABB64`] in all three dimensions.
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    ///
+    pub fn half_extents(&self) -> DVec3 {
+        self.size() * 0.5
+    }
+
+    /// Returns the center and radius of the smallest sphere which contains this
+    /// [`AABB64`]. See [`AABB::bounding_sphere`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::bounding_sphere`]: ../aabb/struct.AABB.html#method.bounding_sphere
+    ///
+    pub fn bounding_sphere(&self) -> (DVec3, f64) {
+        (self.center(), self.half_extents().mag())
+    }
+
+    /// Returns the length of the diagonal of this [`AABB64`], or `-1.0` if it is empty.
+    /// See [`AABB::diagonal`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::diagonal`]: ../aabb/struct.AABB.html#method.diagonal
+    ///
+    pub fn diagonal(&self) -> f64 {
+        if self.is_empty() {
+            -1.0
+        } else {
+            self.size().mag()
+        }
+    }
+
+    /// Returns the squared length of the diagonal of this [`AABB64`], or `-1.0` if it is
+    /// empty. See [`AABB::diagonal_squared`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::diagonal_squared`]: ../aabb/struct.AABB.html#method.diagonal_squared
+    ///
+    pub fn diagonal_squared(&self) -> f64 {
+        if self.is_empty() {
+            -1.0
+        } else {
+            self.size().mag_sq()
+        }
+    }
+
+    /// Returns true if this [`AABB64`] is empty, i.e. its min bound is greater than its
+    /// max bound on any axis.
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    /// Returns the total surface area of this [`AABB64`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    ///
+    pub fn surface_area(&self) -> f64 {
+        let size = self.size();
+        2.0 * (size.x * size.y + size.x * size.z + size.y * size.z)
+    }
+
+    /// Returns the volume of this [`AABB64`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    ///
+    pub fn volume(&self) -> f64 {
+        let size = self.size();
+        size.x * size.y * size.z
+    }
+
+    /// Returns the axis along which the [`AABB64`] is stretched the most.
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    ///
+    pub fn largest_axis(&self) -> Axis {
+        let size = self.size();
+        if size.x > size.y && size.x > size.z {
+            Axis::X
+        } else if size.y > size.z {
+            Axis::Y
+        } else {
+            Axis::Z
+        }
+    }
+
+    /// Returns the point inside of this [`AABB64`] which is closest to `p`.
+    /// See [`AABB::closest_point`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::closest_point`]: ../aabb/struct.AABB.html#method.closest_point
+    ///
+    pub fn closest_point(&self, p: &DVec3) -> DVec3 {
+        DVec3::new(
+            p.x.max(self.min.x).min(self.max.x),
+            p.y.max(self.min.y).min(self.max.y),
+            p.z.max(self.min.z).min(self.max.z),
+        )
+    }
+
+    /// Returns the squared distance from `p` to this [`AABB64`], `0.0` if `p` is inside.
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    ///
+    pub fn distance_squared_to_point(&self, p: &DVec3) -> f64 {
+        (*p - self.closest_point(p)).mag_sq()
+    }
+
+    /// Returns the distance from `p` to this [`AABB64`], `0.0` if `p` is inside.
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    ///
+    pub fn distance_to_point(&self, p: &DVec3) -> f64 {
+        self.distance_squared_to_point(p).sqrt()
+    }
+
+    /// Returns the eight corners of this [`AABB64`], in the same order as
+    /// [`AABB::corners`].
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`AABB::corners`]: ../aabb/struct.AABB.html#method.corners
+    ///
+    pub fn corners(&self) -> [DVec3; 8] {
+        let mut corners = [self.min; 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            *corner = DVec3::new(self[i & 1].x, self[(i >> 1) & 1].y, self[(i >> 2) & 1].z);
+        }
+        corners
+    }
+}
+
+/// Default instance for [`AABB64`]s. Returns an [`AABB64`] which is [`empty()`].
+///
+/// [`AABB64`]: struct.AABB64.html
+/// [`empty()`]: #method.empty
+///
+impl Default for AABB64 {
+    fn default() -> AABB64 {
+        AABB64::empty()
+    }
+}
+
+/// Collects points into the smallest [`AABB64`] which contains all of them.
+///
+/// [`AABB64`]: struct.AABB64.html
+///
+impl FromIterator<DVec3> for AABB64 {
+    fn from_iter<I: IntoIterator<Item = DVec3>>(iter: I) -> AABB64 {
+        let mut aabb = AABB64::empty();
+        aabb.extend(iter);
+        aabb
+    }
+}
+
+/// Collects [`AABB64`]s into the smallest [`AABB64`] which contains all of them.
+///
+/// [`AABB64`]: struct.AABB64.html
+///
+impl FromIterator<AABB64> for AABB64 {
+    fn from_iter<I: IntoIterator<Item = AABB64>>(iter: I) -> AABB64 {
+        let mut aabb = AABB64::empty();
+        aabb.extend(iter);
+        aabb
+    }
+}
+
+/// Grows the [`AABB64`] to contain all points.
+///
+/// [`AABB64`]: struct.AABB64.html
+///
+impl Extend<DVec3> for AABB64 {
+    fn extend<I: IntoIterator<Item = DVec3>>(&mut self, iter: I) {
+        for point in iter {
+            self.grow_mut(&point);
+        }
+    }
+}
+
+/// Joins the [`AABB64`] with all other [`AABB64`]s.
+///
+/// [`AABB64`]: struct.AABB64.html
+///
+impl Extend<AABB64> for AABB64 {
+    fn extend<I: IntoIterator<Item = AABB64>>(&mut self, iter: I) {
+        for aabb in iter {
+            self.join_mut(&aabb);
+        }
+    }
+}
+
+/// Make [`AABB64`]s indexable. `aabb[0]` gives a reference to the minimum bound.
+/// All other indices return a reference to the maximum bound.
+///
+/// [`AABB64`]: struct.AABB64.html
+///
+impl Index<usize> for AABB64 {
+    type Output = DVec3;
+
+    fn index(&self, index: usize) -> &DVec3 {
+        if index == 0 {
+            &self.min
+        } else {
+            &self.max
+        }
+    }
+}
+
+/// Implementation of [`Bounded64`] for [`AABB64`].
+///
+/// [`Bounded64`]: trait.Bounded64.html
+/// [`AABB64`]: struct.AABB64.html
+///
+impl Bounded64 for AABB64 {
+    fn aabb(&self) -> AABB64 {
+        *self
+    }
+}
+
+/// Implementation of [`Bounded64`] for [`DVec3`].
+///
+/// [`Bounded64`]: trait.Bounded64.html
+/// [`DVec3`]: ../../ultraviolet/vec/struct.DVec3.html
+///
+impl Bounded64 for DVec3 {
+    fn aabb(&self) -> AABB64 {
+        AABB64::with_bounds(*self, *self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::f64::{Bounded64, Ray64, AABB64, EPSILON};
+    use crate::testbase::{tuple_to_point64, TupleVec64};
+
+    use quickcheck::quickcheck;
+    use ultraviolet::DVec3;
+
+    /// Test whether an empty `AABB64` does not contains anything.
+    quickcheck! {
+        fn test_empty_contains_nothing(tpl: TupleVec64) -> bool {
+            let p = tuple_to_point64(&tpl);
+            !AABB64::empty().contains(&p)
+        }
+    }
+
+    /// Test whether a default `AABB64` is empty.
+    quickcheck! {
+        fn test_default_is_empty(tpl: TupleVec64) -> bool {
+            let p = tuple_to_point64(&tpl);
+            let aabb: AABB64 = Default::default();
+            !aabb.contains(&p)
+        }
+    }
+
+    /// Test whether an `AABB64` always contains its center.
+    quickcheck! {
+        fn test_aabb_contains_center(a: TupleVec64, b: TupleVec64) -> bool {
+            let p1 = tuple_to_point64(&a);
+            let p2 = tuple_to_point64(&b);
+            let aabb = AABB64::empty().grow(&p1).join_bounded(&p2);
+            aabb.contains(&aabb.center())
+        }
+    }
+
+    /// Test whether the joint of two point-sets contains all the points.
+    quickcheck! {
+        fn test_join_two_aabbs(a: (TupleVec64, TupleVec64, TupleVec64, TupleVec64, TupleVec64),
+                               b: (TupleVec64, TupleVec64, TupleVec64, TupleVec64, TupleVec64))
+                               -> bool {
+            let points = [a.0, a.1, a.2, a.3, a.4, b.0, b.1, b.2, b.3, b.4];
+            let points = points.iter().map(tuple_to_point64).collect::<Vec<DVec3>>();
+
+            let aabb1 = points.iter().take(5).fold(AABB64::empty(), |aabb, point| aabb.grow(point));
+            let aabb2 = points.iter().skip(5).fold(AABB64::empty(), |aabb, point| aabb.grow(point));
+            let aabbu = aabb1.join(&aabb2);
+
+            points.iter().take(5).all(|point| aabb1.contains(point))
+                && points.iter().skip(5).all(|point| aabb2.contains(point))
+                && points.iter().all(|point| aabbu.contains(point))
+        }
+    }
+
+    /// Test whether the intersection of two `AABB64`s is contained in both of them.
+    quickcheck! {
+        fn test_intersection_contained_in_both(a: TupleVec64, b: TupleVec64,
+                                               c: TupleVec64, d: TupleVec64) -> bool {
+            let aabb1 = AABB64::empty()
+                .grow(&tuple_to_point64(&a))
+                .grow(&tuple_to_point64(&b));
+            let aabb2 = AABB64::empty()
+                .grow(&tuple_to_point64(&c))
+                .grow(&tuple_to_point64(&d));
+
+            let intersection = aabb1.intersection(&aabb2);
+            let mut intersection_mut = aabb1;
+            intersection_mut.intersection_mut(&aabb2);
+
+            // A disjoint pair yields an empty `AABB64`, which lies in anything.
+            intersection.min == intersection_mut.min
+                && intersection.max == intersection_mut.max
+                && (intersection.is_empty()
+                    || (aabb1.approx_contains_aabb_eps(&intersection, EPSILON)
+                        && aabb2.approx_contains_aabb_eps(&intersection, EPSILON)))
+        }
+    }
+
+    /// Test whether two `AABB64`s always intersect their own joint and their non-empty
+    /// intersection.
+    quickcheck! {
+        fn test_intersects_aabb_joint(a: TupleVec64, b: TupleVec64,
+                                      c: TupleVec64, d: TupleVec64) -> bool {
+            let aabb1 = AABB64::empty()
+                .grow(&tuple_to_point64(&a))
+                .grow(&tuple_to_point64(&b));
+            let aabb2 = AABB64::empty()
+                .grow(&tuple_to_point64(&c))
+                .grow(&tuple_to_point64(&d));
+
+            let joint = aabb1.join(&aabb2);
+            let overlap = aabb1.intersection(&aabb2);
+
+            joint.intersects_aabb(&aabb1)
+                && joint.intersects_aabb(&aabb2)
+                && joint.intersects_aabb_eps(&aabb1, EPSILON)
+                && aabb1.intersects_aabb(&aabb2) != overlap.is_empty()
+        }
+    }
+
+    /// Test whether a `Ray64` pointing at the center of an `AABB64` enters it at a point
+    /// on its boundary, or at its origin if the origin is inside.
+    quickcheck! {
+        fn test_intersect_ray_distance_to_center(a: TupleVec64, b: TupleVec64,
+                                                 o: TupleVec64) -> bool {
+            let aabb = AABB64::empty()
+                .grow(&tuple_to_point64(&a))
+                .grow(&tuple_to_point64(&b));
+            let origin = tuple_to_point64(&o);
+            let ray = Ray64::new(origin, aabb.center() - origin);
+
+            match aabb.intersect_ray_distance(&ray) {
+                Some(distance) => {
+                    let epsilon = EPSILON * (1.0 + origin.mag() + aabb.size().mag());
+                    let entry = ray.origin + ray.direction * distance;
+                    aabb.intersects_ray(&ray)
+                        && distance >= 0.0
+                        && aabb.approx_contains_eps(&entry, epsilon)
+                        && (distance > 0.0 || aabb.approx_contains_eps(&origin, epsilon))
+                }
+                // Degenerate rays with the origin in the center have no direction.
+                None => aabb.center() == origin,
+            }
+        }
+    }
+
+    /// Test whether some points relative to the center of an `AABB64` are classified
+    /// correctly.
+    quickcheck! {
+        fn test_points_relative_to_center_and_size(a: TupleVec64, b: TupleVec64) -> bool {
+            let aabb = AABB64::empty()
+                .grow(&tuple_to_point64(&a))
+                .grow(&tuple_to_point64(&b));
+
+            let size_half = aabb.size() / 2.0;
+            let center = aabb.center();
+            let inside_ppp = center + size_half;
+            let inside_mmm = center - size_half;
+            let outside_ppp = inside_ppp + DVec3::new(0.1, 0.1, 0.1);
+            let outside_mmm = inside_mmm - DVec3::new(0.1, 0.1, 0.1);
+
+            aabb.approx_contains_eps(&inside_ppp, EPSILON)
+                && aabb.approx_contains_eps(&inside_mmm, EPSILON)
+                && !aabb.contains(&outside_ppp)
+                && !aabb.contains(&outside_mmm)
+        }
+    }
+
+    /// Test whether all corners of an `AABB64` are inside its bounding sphere and can be
+    /// reproduced from its center and half extents.
+    quickcheck! {
+        fn test_half_extents_and_bounding_sphere(a: TupleVec64, b: TupleVec64) -> bool {
+            let aabb = AABB64::empty()
+                .grow(&tuple_to_point64(&a))
+                .grow(&tuple_to_point64(&b));
+            let center = aabb.center();
+            let half_extents = aabb.half_extents();
+            let (sphere_center, radius) = aabb.bounding_sphere();
+
+            let epsilon = EPSILON * (1.0 + aabb.min.mag() + aabb.max.mag());
+            let reproduced = AABB64::with_bounds(center - half_extents, center + half_extents);
+            sphere_center == center
+                && reproduced.relative_eq(&aabb, epsilon)
+                && aabb
+                    .corners()
+                    .iter()
+                    .all(|corner| (*corner - sphere_center).mag() <= radius + epsilon)
+        }
+    }
+
+    /// Test whether the diagonal of an `AABB64` is the distance between its min and max
+    /// corner, and whether empty `AABB64`s yield the sentinel value.
+    quickcheck! {
+        fn test_diagonal(a: TupleVec64, b: TupleVec64) -> bool {
+            let min = tuple_to_point64(&a);
+            let max = tuple_to_point64(&b);
+            let aabb = AABB64::with_bounds(min, max);
+
+            if aabb.is_empty() {
+                aabb.diagonal() == -1.0 && aabb.diagonal_squared() == -1.0
+            } else {
+                let distance = (max - min).mag();
+                let epsilon = EPSILON * (1.0 + distance);
+                (aabb.diagonal() - distance).abs() < epsilon
+                    && (aabb.diagonal_squared().sqrt() - distance).abs() < epsilon
+            }
+        }
+    }
+
+    /// Test whether the surface area and the volume of a nonempty `AABB64` are always
+    /// positive.
+    quickcheck! {
+        fn test_surface_and_volume_always_positive(a: TupleVec64, b: TupleVec64) -> bool {
+            let aabb = AABB64::empty()
+                .grow(&tuple_to_point64(&a))
+                .grow(&tuple_to_point64(&b));
+            aabb.surface_area() >= 0.0 && aabb.volume() >= 0.0
+        }
+    }
+
+    /// Compute and compare the surface area and the volume of a cube by hand.
+    quickcheck! {
+        fn test_surface_area_and_volume_cube(pos: TupleVec64, size: f64) -> bool {
+            let pos = tuple_to_point64(&pos);
+            let size = size.abs() + 1.0;
+            let aabb = AABB64::with_bounds(pos, pos + DVec3::new(size, size, size));
+
+            let area = 6.0 * size * size;
+            let volume = size * size * size;
+            (1.0 - aabb.surface_area() / area).abs() < EPSILON
+                && (1.0 - aabb.volume() / volume).abs() < EPSILON
+        }
+    }
+
+    /// Test whether the closest point of an `AABB64` is inside of it, and equal to
+    /// the query point if that is inside as well.
+    quickcheck! {
+        fn test_closest_point_contained(a: TupleVec64, b: TupleVec64, p: TupleVec64) -> bool {
+            let aabb = AABB64::empty()
+                .grow(&tuple_to_point64(&a))
+                .grow(&tuple_to_point64(&b));
+            let point = tuple_to_point64(&p);
+            let closest = aabb.closest_point(&point);
+            let distance = aabb.distance_to_point(&point);
+
+            aabb.contains(&closest)
+                && (!aabb.contains(&point) || closest == point)
+                && aabb.contains(&point) == (aabb.distance_squared_to_point(&point) == 0.0)
+                && distance <= (point - aabb.min).mag()
+                && distance <= (point - aabb.max).mag()
+        }
+    }
+
+    /// Test whether all corners of an `AABB64` are contained in it, span it and
+    /// follow the documented order.
+    quickcheck! {
+        fn test_corners(a: TupleVec64, b: TupleVec64) -> bool {
+            let aabb = AABB64::empty()
+                .grow(&tuple_to_point64(&a))
+                .grow(&tuple_to_point64(&b));
+            let corners = aabb.corners();
+
+            let ordered = corners.iter().enumerate().all(|(i, corner)| {
+                let x = if i & 1 == 0 { aabb.min.x } else { aabb.max.x };
+                let y = if i & 2 == 0 { aabb.min.y } else { aabb.max.y };
+                let z = if i & 4 == 0 { aabb.min.z } else { aabb.max.z };
+                *corner == DVec3::new(x, y, z)
+            });
+            let joint: AABB64 = corners.iter().copied().collect();
+
+            ordered
+                && corners.iter().all(|corner| aabb.contains(corner))
+                && joint.min == aabb.min
+                && joint.max == aabb.max
+        }
+    }
+
+    /// Compare the surface area and volume of an expanded cube to those computed by hand.
+    quickcheck! {
+        fn test_expand_cube(pos: TupleVec64, size: f64, margin: f64) -> bool {
+            let pos = tuple_to_point64(&pos);
+            let size = size.abs() + 1.0;
+            let margin = margin % (size / 2.0);
+            let aabb = AABB64::with_bounds(pos, pos + DVec3::new(size, size, size));
+
+            let expanded = aabb.expand(margin);
+            let mut expanded_mut = aabb;
+            expanded_mut.expand_mut(margin);
+
+            let new_size = size + 2.0 * margin;
+            let area = 6.0 * new_size * new_size;
+            let volume = new_size * new_size * new_size;
+            let epsilon = EPSILON * (1.0 + pos.mag() + size);
+            expanded.relative_eq(&expanded_mut, epsilon)
+                && !expanded.is_empty()
+                && (1.0 - expanded.surface_area() / area).abs() < epsilon
+                && (1.0 - expanded.volume() / volume).abs() < epsilon
+        }
+    }
+
+    /// Test whether collecting points and `AABB64`s is equivalent to folding with `grow`
+    /// and `join`.
+    quickcheck! {
+        fn test_from_iterator_equals_fold(points: Vec<TupleVec64>) -> bool {
+            let points: Vec<DVec3> = points.iter().map(tuple_to_point64).collect();
+            let folded = points
+                .iter()
+                .fold(AABB64::empty(), |aabb, point| aabb.grow(point));
+
+            let collected: AABB64 = points.iter().copied().collect();
+            let joined: AABB64 = points.iter().map(|point| point.aabb()).collect();
+
+            [collected, joined]
+                .iter()
+                .all(|aabb| aabb.min == folded.min && aabb.max == folded.max)
+        }
+    }
+
+    #[test]
+    /// Test whether boxes a billion units away from the origin, which `f32` cannot tell
+    /// apart, stay separated in double precision.
+    fn test_far_from_origin_boxes_are_disjoint() {
+        let offset = 1.0e9;
+        let aabb1 = AABB64::with_bounds(
+            DVec3::new(offset, 0.0, 0.0),
+            DVec3::new(offset + 0.25, 1.0, 1.0),
+        );
+        let aabb2 = AABB64::with_bounds(
+            DVec3::new(offset + 0.5, 0.0, 0.0),
+            DVec3::new(offset + 0.75, 1.0, 1.0),
+        );
+        assert_eq!((offset as f32 + 0.25) as f64, (offset as f32 + 0.5) as f64);
+
+        assert!(!aabb1.intersects_aabb(&aabb2));
+        assert!(aabb1.intersection(&aabb2).is_empty());
+        assert_eq!(aabb1.join(&aabb2).size().x, 0.75);
+    }
+}
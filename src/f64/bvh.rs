@@ -0,0 +1,709 @@
+//! Double precision version of the [`BVH`], built with the same SAH partitioning.
+//!
+//! [`BVH`]: ../bvh/struct.BVH.html
+//!
+
+use std::ops::Range;
+
+use crate::bvh::{BuildConfig, TraversalStack};
+use crate::f64::{Bounded64, Ray64, AABB64};
+
+/// Double precision version of [`BHShape`], implemented by shapes which can be stored
+/// in a [`BVH64`].
+///
+/// [`BHShape`]: ../bounding_hierarchy/trait.BHShape.html
+/// [`BVH64`]: struct.BVH64.html
+///
+pub trait BHShape64: Bounded64 {
+    /// Sets the index of the referenced [`BVHNode64`].
+    ///
+    /// [`BVHNode64`]: enum.BVHNode64.html
+    ///
+    fn set_bh_node_index(&mut self, _: usize);
+
+    /// Gets the index of the referenced [`BVHNode64`].
+    ///
+    /// [`BVHNode64`]: enum.BVHNode64.html
+    ///
+    fn bh_node_index(&self) -> usize;
+}
+
+/// Double precision version of [`BVHNode`]. The leaves and inner nodes have the same
+/// layout, only the [`AABB64`]s of the children are stored in double precision.
+///
+/// [`AABB64`]: struct.AABB64.html
+/// [`BVHNode`]: ../bvh/enum.BVHNode.html
+///
+#[derive(Debug, Copy, Clone)]
+pub enum BVHNode64 {
+    /// Leaf node.
+    Leaf {
+        /// The node's parent.
+        parent_index: usize,
+
+        /// The node's depth.
+        depth: u32,
+
+        /// The index of the first shape referenced by this leaf.
+        shape_index: usize,
+
+        /// The number of consecutive shapes, starting at `shape_index`, referenced by this
+        /// leaf.
+        shape_count: usize,
+    },
+
+    /// Inner node.
+    Node {
+        /// The node's parent.
+        parent_index: usize,
+
+        /// The node's depth.
+        depth: u32,
+
+        /// Index of the left subtree's root node.
+        child_l_index: usize,
+
+        /// The convex hull of the shapes' `AABB64`s in child_l.
+        child_l_aabb: AABB64,
+
+        /// Index of the right subtree's root node.
+        child_r_index: usize,
+
+        /// The convex hull of the shapes' `AABB64`s in child_r.
+        child_r_aabb: AABB64,
+    },
+}
+
+impl BVHNode64 {
+    /// Returns the index of the parent node.
+    pub fn parent(&self) -> usize {
+        match *self {
+            BVHNode64::Node { parent_index, .. } | BVHNode64::Leaf { parent_index, .. } => {
+                parent_index
+            }
+        }
+    }
+
+    /// Returns the depth of the node. The root node has depth `0`.
+    pub fn depth(&self) -> u32 {
+        match *self {
+            BVHNode64::Node { depth, .. } | BVHNode64::Leaf { depth, .. } => depth,
+        }
+    }
+
+    /// Returns the range of shape indices referenced by this node, if it is a leaf.
+    pub fn shape_range(&self) -> Option<Range<usize>> {
+        match *self {
+            BVHNode64::Leaf {
+                shape_index,
+                shape_count,
+                ..
+            } => Some(shape_index..shape_index + shape_count),
+            _ => None,
+        }
+    }
+
+    /// The build function sometimes needs to add nodes while their data is not available yet.
+    /// A dummy created by this function serves the purpose of being changed later on.
+    fn create_dummy() -> BVHNode64 {
+        BVHNode64::Leaf {
+            parent_index: 0,
+            depth: 0,
+            shape_index: 0,
+            shape_count: 0,
+        }
+    }
+
+    /// Builds a [`BVHNode64`] recursively using SAH partitioning with the settings
+    /// from `config`, like the single precision builder. Returns the index of the new
+    /// node in the nodes vector. The shape indices of every new leaf are appended to
+    /// `leaf_order`.
+    ///
+    /// [`BVHNode64`]: enum.BVHNode64.html
+    ///
+    fn build<T: BHShape64>(
+        config: &BuildConfig,
+        shapes: &mut [T],
+        indices: &[usize],
+        nodes: &mut Vec<BVHNode64>,
+        leaf_order: &mut Vec<usize>,
+        parent_index: usize,
+        depth: u32,
+    ) -> usize {
+        let mut aabb_bounds = AABB64::empty();
+        let mut centroid_bounds = AABB64::empty();
+        for index in indices {
+            let shape_aabb = shapes[*index].aabb();
+            aabb_bounds.join_mut(&shape_aabb);
+            centroid_bounds.grow_mut(&shape_aabb.center());
+        }
+
+        // If there are few enough elements left, don't split anymore
+        if !indices.is_empty() && indices.len() <= config.max_leaf_size {
+            let node_index = nodes.len();
+            nodes.push(BVHNode64::Leaf {
+                parent_index,
+                depth,
+                shape_index: indices[0],
+                shape_count: indices.len(),
+            });
+            // Let the shapes know the index of the node that represents them.
+            for index in indices {
+                shapes[*index].set_bh_node_index(node_index);
+            }
+            leaf_order.extend_from_slice(indices);
+            return node_index;
+        }
+
+        // This dummy is replaced once the children are built, because they must know
+        // their parent.
+        let node_index = nodes.len();
+        nodes.push(BVHNode64::create_dummy());
+
+        // Find the axis along which the shapes are spread the most.
+        let split_axis = centroid_bounds.largest_axis();
+        let split_axis_size = centroid_bounds.max[split_axis] - centroid_bounds.min[split_axis];
+
+        let (child_l_indices, child_r_indices) = if split_axis_size < f64::from(config.epsilon) {
+            // The shapes lie too close together to split them in a sensible way.
+            // Instead we just split the list of shapes in half.
+            let (child_l_indices, child_r_indices) = indices.split_at(indices.len() / 2);
+            (child_l_indices.to_vec(), child_r_indices.to_vec())
+        } else {
+            // Assign the shapes to `config.bucket_count` buckets along the split axis.
+            let bucket_count = config.bucket_count;
+            let mut bucket_sizes = vec![0; bucket_count];
+            let mut bucket_aabbs = vec![AABB64::empty(); bucket_count];
+            let mut bucket_assignments: Vec<Vec<usize>> = vec![Vec::new(); bucket_count];
+            for index in indices {
+                let shape_aabb = shapes[*index].aabb();
+                let bucket_num_relative = (shape_aabb.center()[split_axis]
+                    - centroid_bounds.min[split_axis])
+                    / split_axis_size;
+                let bucket_num = (bucket_num_relative * (bucket_count as f64 - 0.01)) as usize;
+
+                bucket_sizes[bucket_num] += 1;
+                bucket_aabbs[bucket_num].join_mut(&shape_aabb);
+                bucket_assignments[bucket_num].push(*index);
+            }
+
+            // Compute the costs for each configuration and select the best configuration.
+            let mut min_bucket = 0;
+            let mut min_cost = f64::INFINITY;
+            for i in 0..(bucket_count - 1) {
+                let child_l_size: usize = bucket_sizes[..=i].iter().sum();
+                let child_r_size: usize = bucket_sizes[i + 1..].iter().sum();
+                let child_l_aabb: AABB64 = bucket_aabbs[..=i].iter().copied().collect();
+                let child_r_aabb: AABB64 = bucket_aabbs[i + 1..].iter().copied().collect();
+
+                let cost = (child_l_size as f64 * child_l_aabb.surface_area()
+                    + child_r_size as f64 * child_r_aabb.surface_area())
+                    / aabb_bounds.surface_area();
+                if cost < min_cost {
+                    min_bucket = i;
+                    min_cost = cost;
+                }
+            }
+
+            let (l_assignments, r_assignments) = bucket_assignments.split_at(min_bucket + 1);
+            (l_assignments.concat(), r_assignments.concat())
+        };
+
+        let joint_aabb = |indices: &[usize], shapes: &[T]| -> AABB64 {
+            indices.iter().map(|index| shapes[*index].aabb()).collect()
+        };
+        let child_l_aabb = joint_aabb(&child_l_indices, shapes);
+        let child_r_aabb = joint_aabb(&child_r_indices, shapes);
+
+        // Proceed recursively.
+        let child_l_index = BVHNode64::build(
+            config,
+            shapes,
+            &child_l_indices,
+            nodes,
+            leaf_order,
+            node_index,
+            depth + 1,
+        );
+        let child_r_index = BVHNode64::build(
+            config,
+            shapes,
+            &child_r_indices,
+            nodes,
+            leaf_order,
+            node_index,
+            depth + 1,
+        );
+
+        // Construct the actual data structure and replace the dummy node.
+        assert!(!child_l_aabb.is_empty());
+        assert!(!child_r_aabb.is_empty());
+        nodes[node_index] = BVHNode64::Node {
+            parent_index,
+            depth,
+            child_l_aabb,
+            child_l_index,
+            child_r_aabb,
+            child_r_index,
+        };
+
+        node_index
+    }
+
+    /// Traverses the [`BVH64`] recursively and appends the indices of all shapes whose
+    /// [`AABB64`] is intersected by the given [`Ray64`] to `indices`.
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`BVH64`]: struct.BVH64.html
+    /// [`Ray64`]: struct.Ray64.html
+    ///
+    pub fn traverse_recursive<Shape: Bounded64>(
+        nodes: &[BVHNode64],
+        node_index: usize,
+        ray: &Ray64,
+        shapes: &[Shape],
+        indices: &mut Vec<usize>,
+    ) {
+        match nodes[node_index] {
+            BVHNode64::Node {
+                ref child_l_aabb,
+                child_l_index,
+                ref child_r_aabb,
+                child_r_index,
+                ..
+            } => {
+                if ray.intersects_aabb(child_l_aabb) {
+                    BVHNode64::traverse_recursive(nodes, child_l_index, ray, shapes, indices);
+                }
+                if ray.intersects_aabb(child_r_aabb) {
+                    BVHNode64::traverse_recursive(nodes, child_r_index, ray, shapes, indices);
+                }
+            }
+            BVHNode64::Leaf {
+                shape_index,
+                shape_count: 1,
+                ..
+            } => {
+                // The parent already tested the `AABB64` of this shape.
+                indices.push(shape_index);
+            }
+            BVHNode64::Leaf {
+                shape_index,
+                shape_count,
+                ..
+            } => {
+                let range = shape_index..shape_index + shape_count;
+                indices.extend(range.filter(|index| ray.intersects_aabb(&shapes[*index].aabb())));
+            }
+        }
+    }
+
+    /// Traverses the [`BVH64`] like [`traverse_recursive`], but iteratively with an
+    /// explicit stack of node indices, so it never recurses. The shape indices are pushed
+    /// in the same order. The stack is the `TraversalStack` of the [`BVH`], which keeps
+    /// the node indices of shallow trees in a fixed-size array.
+    ///
+    /// [`BVH`]: ../bvh/struct.BVH.html
+    /// [`BVH64`]: struct.BVH64.html
+    /// [`traverse_recursive`]: enum.BVHNode64.html#method.traverse_recursive
+    ///
+    pub fn traverse_iterative<Shape: Bounded64>(
+        nodes: &[BVHNode64],
+        ray: &Ray64,
+        shapes: &[Shape],
+        indices: &mut Vec<usize>,
+    ) {
+        let mut stack = TraversalStack::new();
+        let mut node_index = 0;
+        loop {
+            match nodes[node_index] {
+                BVHNode64::Node {
+                    ref child_l_aabb,
+                    child_l_index,
+                    ref child_r_aabb,
+                    child_r_index,
+                    ..
+                } => {
+                    let hit_l = ray.intersects_aabb(child_l_aabb);
+                    let hit_r = ray.intersects_aabb(child_r_aabb);
+                    if hit_l && hit_r {
+                        // Visit the left subtree first, like `traverse_recursive`.
+                        stack.push(child_r_index);
+                        node_index = child_l_index;
+                        continue;
+                    } else if hit_l {
+                        node_index = child_l_index;
+                        continue;
+                    } else if hit_r {
+                        node_index = child_r_index;
+                        continue;
+                    }
+                }
+                BVHNode64::Leaf {
+                    shape_index,
+                    shape_count: 1,
+                    ..
+                } => {
+                    // The parent already tested the `AABB64` of this shape.
+                    indices.push(shape_index);
+                }
+                BVHNode64::Leaf {
+                    shape_index,
+                    shape_count,
+                    ..
+                } => {
+                    let range = shape_index..shape_index + shape_count;
+                    indices
+                        .extend(range.filter(|index| ray.intersects_aabb(&shapes[*index].aabb())));
+                }
+            }
+
+            node_index = match stack.pop() {
+                Some(index) => index,
+                None => break,
+            };
+        }
+    }
+}
+
+/// Double precision version of the [`BVH`].
+///
+/// [`BVH`]: ../bvh/struct.BVH.html
+///
+pub struct BVH64 {
+    /// The list of nodes of the [`BVH64`].
+    ///
+    /// [`BVH64`]: struct.BVH64.html
+    ///
+    pub nodes: Vec<BVHNode64>,
+}
+
+impl BVH64 {
+    /// Creates a new [`BVH64`] from the `shapes` slice with the default [`BuildConfig`].
+    ///
+    /// [`BVH64`]: struct.BVH64.html
+    /// [`BuildConfig`]: ../bvh/struct.BuildConfig.html
+    ///
+    pub fn build<Shape: BHShape64>(shapes: &mut [Shape]) -> BVH64 {
+        BVH64::build_with_config(shapes, &BuildConfig::default())
+    }
+
+    /// Creates a new [`BVH64`] from the `shapes` slice with the settings from `config`.
+    /// See [`BVH::build_with_config`]; with a `max_leaf_size` above 1 the `shapes` slice
+    /// is reordered in the same way.
+    ///
+    /// [`BVH64`]: struct.BVH64.html
+    /// [`BVH::build_with_config`]: ../bvh/struct.BVH.html#method.build_with_config
+    ///
+    pub fn build_with_config<Shape: BHShape64>(
+        shapes: &mut [Shape],
+        config: &BuildConfig,
+    ) -> BVH64 {
+        assert!(
+            config.bucket_count >= 2,
+            "BuildConfig::bucket_count must be at least 2"
+        );
+        assert!(
+            config.max_leaf_size >= 1,
+            "BuildConfig::max_leaf_size must be at least 1"
+        );
+        if shapes.is_empty() {
+            return BVH64 { nodes: Vec::new() };
+        }
+        let indices = (0..shapes.len()).collect::<Vec<usize>>();
+        let mut nodes = Vec::with_capacity(shapes.len() * 2);
+        let mut leaf_order = Vec::with_capacity(shapes.len());
+        BVHNode64::build(config, shapes, &indices, &mut nodes, &mut leaf_order, 0, 0);
+
+        if config.max_leaf_size > 1 {
+            // Move the shapes of each leaf next to each other.
+            let mut new_positions = vec![0; shapes.len()];
+            for (new_position, old_position) in leaf_order.iter().enumerate() {
+                new_positions[*old_position] = new_position;
+            }
+            for node in &mut nodes {
+                if let BVHNode64::Leaf {
+                    ref mut shape_index,
+                    ..
+                } = *node
+                {
+                    *shape_index = new_positions[*shape_index];
+                }
+            }
+            for position in 0..shapes.len() {
+                while new_positions[position] != position {
+                    let target = new_positions[position];
+                    shapes.swap(position, target);
+                    new_positions.swap(position, target);
+                }
+            }
+        }
+
+        BVH64 { nodes }
+    }
+
+    /// Traverses the [`BVH64`].
+    /// Returns a subset of `shapes`, in which the [`AABB64`]s of the elements were hit by
+    /// `ray`.
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`BVH64`]: struct.BVH64.html
+    ///
+    pub fn traverse<'a, Shape: Bounded64>(
+        &'a self,
+        ray: &Ray64,
+        shapes: &'a [Shape],
+    ) -> Vec<&'a Shape> {
+        self.traverse_indices(ray, shapes)
+            .iter()
+            .map(|index| &shapes[*index])
+            .collect::<Vec<_>>()
+    }
+
+    /// Traverses the [`BVH64`].
+    /// Returns the indices into `shapes` of the elements whose [`AABB64`]s were hit by
+    /// `ray`, in the same order in which [`traverse`] returns the shapes.
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`BVH64`]: struct.BVH64.html
+    /// [`traverse`]: struct.BVH64.html#method.traverse
+    ///
+    pub fn traverse_indices<Shape: Bounded64>(&self, ray: &Ray64, shapes: &[Shape]) -> Vec<usize> {
+        let mut indices = Vec::new();
+        match self.nodes.first() {
+            None => {}
+            // No parent has tested the `AABB64` of a root leaf, so all its shapes are tested.
+            Some(&BVHNode64::Leaf {
+                shape_index,
+                shape_count,
+                ..
+            }) => {
+                let range = shape_index..shape_index + shape_count;
+                indices.extend(range.filter(|index| ray.intersects_aabb(&shapes[*index].aabb())));
+            }
+            Some(&BVHNode64::Node { .. }) => {
+                BVHNode64::traverse_iterative(&self.nodes, ray, shapes, &mut indices);
+            }
+        }
+        indices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use ultraviolet::DVec3;
+
+    use crate::bvh::BuildConfig;
+    use crate::f64::{BHShape64, BVHNode64, Bounded64, Ray64, AABB64, BVH64};
+
+    /// A cube with double precision coordinates.
+    struct Cube64 {
+        pos: DVec3,
+        size: f64,
+        node_index: usize,
+    }
+
+    impl Bounded64 for Cube64 {
+        fn aabb(&self) -> AABB64 {
+            let half_size = DVec3::new(self.size, self.size, self.size) * 0.5;
+            AABB64::with_bounds(self.pos - half_size, self.pos + half_size)
+        }
+    }
+
+    impl BHShape64 for Cube64 {
+        fn set_bh_node_index(&mut self, index: usize) {
+            self.node_index = index;
+        }
+
+        fn bh_node_index(&self) -> usize {
+            self.node_index
+        }
+    }
+
+    /// Creates a 10x10x10 grid of small cubes with a spacing of `1.0`, shifted by `offset`.
+    fn create_cube_grid(offset: DVec3) -> Vec<Cube64> {
+        let mut cubes = Vec::new();
+        for x in 0..10 {
+            for y in 0..10 {
+                for z in 0..10 {
+                    cubes.push(Cube64 {
+                        pos: offset + DVec3::new(x as f64, y as f64, z as f64),
+                        size: 0.5,
+                        node_index: 0,
+                    });
+                }
+            }
+        }
+        cubes
+    }
+
+    /// Returns the set of indices of the cubes whose `AABB64`s are hit by `ray`.
+    fn brute_force(ray: &Ray64, cubes: &[Cube64]) -> HashSet<usize> {
+        (0..cubes.len())
+            .filter(|index| ray.intersects_aabb(&cubes[*index].aabb()))
+            .collect()
+    }
+
+    /// Asserts that every shape is referenced by the leaf it points to, and that all inner
+    /// nodes contain the bounds of their children.
+    fn assert_consistent(bvh: &BVH64, cubes: &[Cube64]) {
+        let mut seen = vec![false; cubes.len()];
+        for (node_index, node) in bvh.nodes.iter().enumerate() {
+            match *node {
+                BVHNode64::Leaf { .. } => {
+                    for shape_index in node.shape_range().unwrap() {
+                        assert!(!seen[shape_index]);
+                        seen[shape_index] = true;
+                        assert_eq!(cubes[shape_index].bh_node_index(), node_index);
+                    }
+                }
+                BVHNode64::Node {
+                    child_l_index,
+                    child_r_index,
+                    ..
+                } => {
+                    assert_eq!(bvh.nodes[child_l_index].parent(), node_index);
+                    assert_eq!(bvh.nodes[child_r_index].parent(), node_index);
+                    assert_eq!(bvh.nodes[child_l_index].depth(), node.depth() + 1);
+                }
+            }
+        }
+        assert!(seen.iter().all(|seen| *seen));
+    }
+
+    #[test]
+    /// Tests whether the traversal of a `BVH64` returns the same shapes as testing all
+    /// shapes, for grids near and far away from the origin and several leaf sizes.
+    fn test_traverse_equals_brute_force() {
+        for &offset in &[0.0, 1.0e9] {
+            for &max_leaf_size in &[1, 4] {
+                let origin = DVec3::new(offset, offset, offset);
+                let mut cubes = create_cube_grid(origin);
+                let config = BuildConfig {
+                    max_leaf_size,
+                    ..Default::default()
+                };
+                let bvh = BVH64::build_with_config(&mut cubes, &config);
+                assert_consistent(&bvh, &cubes);
+
+                let rays = [
+                    Ray64::new(
+                        origin - DVec3::new(5.0, 0.0, 0.0),
+                        DVec3::new(1.0, 0.0, 0.0),
+                    ),
+                    Ray64::new(
+                        origin + DVec3::new(4.0, -5.0, 4.0),
+                        DVec3::new(0.0, 1.0, 0.0),
+                    ),
+                    Ray64::new(
+                        origin - DVec3::new(1.0, 1.0, 1.0),
+                        DVec3::new(1.0, 1.0, 1.0),
+                    ),
+                    Ray64::new(
+                        origin + DVec3::new(2.5, 2.5, 2.5),
+                        DVec3::new(0.3, -0.2, 1.0),
+                    ),
+                ];
+                for ray in &rays {
+                    let hits: HashSet<usize> =
+                        bvh.traverse_indices(ray, &cubes).into_iter().collect();
+                    assert_eq!(hits, brute_force(ray, &cubes));
+                    assert!(!hits.is_empty());
+                }
+            }
+        }
+    }
+
+    #[test]
+    /// Tests whether a ray along a row of cubes a billion units away from the origin only
+    /// hits that row, which `f32` coordinates cannot resolve.
+    fn test_traverse_far_from_origin() {
+        let origin = DVec3::new(1.0e9, 1.0e9, 1.0e9);
+        let mut cubes = create_cube_grid(origin);
+        let bvh = BVH64::build(&mut cubes);
+
+        let ray = Ray64::new(
+            origin + DVec3::new(-5.0, 3.0, 7.0),
+            DVec3::new(1.0, 0.0, 0.0),
+        );
+        let hits = bvh.traverse(&ray, &cubes);
+        assert_eq!(hits.len(), 10);
+        for cube in hits {
+            assert_eq!(cube.pos.y, origin.y + 3.0);
+            assert_eq!(cube.pos.z, origin.z + 7.0);
+        }
+    }
+
+    #[test]
+    /// Tests whether an empty `BVH64` can be built and traversed.
+    fn test_empty_bvh() {
+        let mut cubes: Vec<Cube64> = Vec::new();
+        let bvh = BVH64::build(&mut cubes);
+        let ray = Ray64::new(DVec3::new(0.0, 0.0, 0.0), DVec3::new(1.0, 0.0, 0.0));
+        assert!(bvh.traverse(&ray, &cubes).is_empty());
+    }
+
+    /// Creates `n` cubes along the X axis and a chain of inner nodes over them which
+    /// descends to the left and has a single leaf on each right side, so the tree is
+    /// `n - 1` levels deep.
+    fn build_chain(n: usize) -> (Vec<Cube64>, BVH64) {
+        let cubes: Vec<Cube64> = (0..n)
+            .map(|i| Cube64 {
+                pos: DVec3::new(i as f64, 0.0, 0.0),
+                size: 0.5,
+                node_index: 0,
+            })
+            .collect();
+
+        // `suffix_aabbs[i]` is the joint `AABB64` of the cubes `i..n`.
+        let mut suffix_aabbs = vec![AABB64::empty(); n + 1];
+        for i in (0..n).rev() {
+            suffix_aabbs[i] = suffix_aabbs[i + 1].join(&cubes[i].aabb());
+        }
+
+        let leaf_index = |shape_index: usize| n - 1 + shape_index;
+        let mut nodes = Vec::with_capacity(2 * n - 1);
+        for i in 0..n - 1 {
+            let child_l_index = if i < n - 2 { i + 1 } else { leaf_index(n - 1) };
+            nodes.push(BVHNode64::Node {
+                parent_index: i.saturating_sub(1),
+                depth: i as u32,
+                child_l_index,
+                child_l_aabb: suffix_aabbs[i + 1],
+                child_r_index: leaf_index(i),
+                child_r_aabb: cubes[i].aabb(),
+            });
+        }
+        for i in 0..n {
+            nodes.push(BVHNode64::Leaf {
+                parent_index: i.min(n - 2),
+                depth: i.min(n - 2) as u32 + 1,
+                shape_index: i,
+                shape_count: 1,
+            });
+        }
+        (cubes, BVH64 { nodes })
+    }
+
+    #[test]
+    /// Tests whether a `BVH64` which is too deep for recursion can be traversed, and
+    /// whether the shape of a root leaf is tested against the ray.
+    fn test_traverse_deep_chain_and_root_leaf() {
+        let n = 300_000;
+        let (cubes, bvh) = build_chain(n);
+        let ray = Ray64::new(DVec3::new(-10.0, 0.0, 0.0), DVec3::new(1.0, 0.0, 0.0));
+        assert_eq!(bvh.traverse_indices(&ray, &cubes).len(), n);
+
+        let mut single = vec![Cube64 {
+            pos: DVec3::new(0.0, 0.0, 0.0),
+            size: 0.5,
+            node_index: 0,
+        }];
+        let bvh = BVH64::build(&mut single);
+        assert_eq!(bvh.traverse(&ray, &single).len(), 1);
+        let miss = Ray64::new(DVec3::new(-10.0, 5.0, 0.0), DVec3::new(1.0, 0.0, 0.0));
+        assert!(bvh.traverse(&miss, &single).is_empty());
+    }
+}
@@ -0,0 +1,69 @@
+//! Double precision versions of [`AABB`], [`Ray`] and [`BVH`], built on ultraviolet's `DVec3`.
+//!
+//! With `f32` coordinates, boxes far away from the origin lose their relative precision,
+//! which shows as cracks between neighbouring shapes in large coordinate spaces.
+//! The types in this module mirror the single precision API one to one, with an
+//! additional `64` suffix, and use the same ray intersection algorithms and the same
+//! SAH build.
+//!
+//! # Examples
+//! ```
+//! use bvh_ultraviolet::f64::{Bounded64, BHShape64, AABB64, BVH64, Ray64};
+//! use bvh_ultraviolet::ultraviolet::DVec3;
+//!
+//! struct Sphere {
+//!     position: DVec3,
+//!     radius: f64,
+//!     node_index: usize,
+//! }
+//!
+//! impl Bounded64 for Sphere {
+//!     fn aabb(&self) -> AABB64 {
+//!         let half_size = DVec3::new(self.radius, self.radius, self.radius);
+//!         AABB64::with_bounds(self.position - half_size, self.position + half_size)
+//!     }
+//! }
+//!
+//! impl BHShape64 for Sphere {
+//!     fn set_bh_node_index(&mut self, index: usize) {
+//!         self.node_index = index;
+//!     }
+//!
+//!     fn bh_node_index(&self) -> usize {
+//!         self.node_index
+//!     }
+//! }
+//!
+//! // Spheres which are one unit apart, a billion units away from the origin.
+//! let mut spheres = Vec::new();
+//! for i in 0..10 {
+//!     let position = DVec3::new(1.0e9 + i as f64, 0.0, 0.0);
+//!     spheres.push(Sphere { position, radius: 0.25, node_index: 0 });
+//! }
+//! let bvh = BVH64::build(&mut spheres);
+//!
+//! let ray = Ray64::new(DVec3::new(1.0e9 + 3.0, -10.0, 0.0), DVec3::new(0.0, 1.0, 0.0));
+//! let hits = bvh.traverse(&ray, &spheres);
+//! assert_eq!(hits.len(), 1);
+//! assert_eq!(hits[0].position.x, 1.0e9 + 3.0);
+//! ```
+//!
+//! [`AABB`]: ../aabb/struct.AABB.html
+//! [`BVH`]: ../bvh/struct.BVH.html
+//! [`Ray`]: ../ray/struct.Ray.html
+//!
+
+mod aabb;
+mod bvh;
+mod ray;
+
+pub use self::aabb::*;
+pub use self::bvh::*;
+pub use self::ray::*;
+
+/// A minimal floating value used as a lower bound, the double precision version of
+/// [`EPSILON`].
+///
+/// [`EPSILON`]: ../constant.EPSILON.html
+///
+pub const EPSILON: f64 = 0.00001;
@@ -0,0 +1,299 @@
+//! Double precision rays and their intersection algorithms for axis aligned bounding
+//! boxes and triangles.
+
+use ultraviolet::DVec3;
+
+use crate::f64::{AABB64, EPSILON};
+
+/// Double precision version of [`Ray`].
+///
+/// [`Ray`]: ../ray/struct.Ray.html
+///
+#[derive(Debug)]
+pub struct Ray64 {
+    /// The ray origin.
+    pub origin: DVec3,
+
+    /// The ray direction.
+    pub direction: DVec3,
+
+    /// The start of the interval along the ray in which intersections are reported.
+    /// Defaults to `0.0`.
+    pub t_min: f64,
+
+    /// The end of the interval along the ray in which intersections are reported.
+    /// Defaults to `f64::INFINITY`.
+    pub t_max: f64,
+
+    /// Inverse (1/x) ray direction. Cached for use in [`AABB64`] intersections.
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    ///
+    pub(crate) inv_direction: DVec3,
+
+    /// Sign of the direction per axis. 0 means positive, 1 means negative.
+    pub(crate) sign: [usize; 3],
+}
+
+/// Double precision version of [`Intersection`], returned by
+/// [`Ray64::intersects_triangle`].
+///
+/// [`Intersection`]: ../ray/struct.Intersection.html
+/// [`Ray64::intersects_triangle`]: struct.Ray64.html#method.intersects_triangle
+///
+pub struct Intersection64 {
+    /// Distance from the ray origin to the intersection point.
+    pub distance: f64,
+
+    /// U coordinate of the intersection.
+    pub u: f64,
+
+    /// V coordinate of the intersection.
+    pub v: f64,
+}
+
+impl Intersection64 {
+    /// Constructs an `Intersection64`. `distance` should be set to positive infinity,
+    /// if the intersection does not occur.
+    pub fn new(distance: f64, u: f64, v: f64) -> Intersection64 {
+        Intersection64 { distance, u, v }
+    }
+}
+
+impl Ray64 {
+    /// Creates a new [`Ray64`] from an `origin` and a `direction`.
+    /// `direction` will be normalized.
+    ///
+    /// [`Ray64`]: struct.Ray64.html
+    ///
+    pub fn new(origin: DVec3, direction: DVec3) -> Ray64 {
+        Ray64::with_bounds(origin, direction, 0.0, f64::INFINITY)
+    }
+
+    /// Creates a new [`Ray64`] from an `origin` and a `direction`, which only reports
+    /// intersections between the distances `t_min` and `t_max` along the ray.
+    /// See [`Ray::with_bounds`].
+    ///
+    /// [`Ray64`]: struct.Ray64.html
+    /// [`Ray::with_bounds`]: ../ray/struct.Ray.html#method.with_bounds
+    ///
+    pub fn with_bounds(origin: DVec3, direction: DVec3, t_min: f64, t_max: f64) -> Ray64 {
+        let direction = direction.normalized();
+        Ray64 {
+            origin,
+            direction,
+            t_min,
+            t_max,
+            inv_direction: DVec3::new(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z),
+            // Sign of the direction. 0 means positive, 1 means negative.
+            sign: [
+                (direction.x < 0.0) as usize,
+                (direction.y < 0.0) as usize,
+                (direction.z < 0.0) as usize,
+            ],
+        }
+    }
+
+    /// Tests the intersection of a [`Ray64`] with an [`AABB64`] using the same algorithm
+    /// as [`Ray::intersects_aabb`].
+    /// Only intersections within `[t_min, t_max]` of the [`Ray64`] are reported.
+    ///
+    /// [`AABB64`]: struct.AABB64.html
+    /// [`Ray64`]: struct.Ray64.html
+    /// [`Ray::intersects_aabb`]: ../ray/struct.Ray.html#method.intersects_aabb
+    ///
+    pub fn intersects_aabb(&self, aabb: &AABB64) -> bool {
+        let mut ray_min = (aabb[self.sign[0]].x - self.origin.x) * self.inv_direction.x;
+        let mut ray_max = (aabb[1 - self.sign[0]].x - self.origin.x) * self.inv_direction.x;
+
+        let y_min = (aabb[self.sign[1]].y - self.origin.y) * self.inv_direction.y;
+        let y_max = (aabb[1 - self.sign[1]].y - self.origin.y) * self.inv_direction.y;
+
+        if (ray_min > y_max) || (y_min > ray_max) {
+            return false;
+        }
+
+        // `max` and `min` ignore the `NaN` bound of an origin on a slab plane.
+        ray_min = ray_min.max(y_min);
+        ray_max = ray_max.min(y_max);
+
+        let z_min = (aabb[self.sign[2]].z - self.origin.z) * self.inv_direction.z;
+        let z_max = (aabb[1 - self.sign[2]].z - self.origin.z) * self.inv_direction.z;
+
+        if (ray_min > z_max) || (z_min > ray_max) {
+            return false;
+        }
+
+        ray_min = ray_min.max(z_min);
+        ray_max = ray_max.min(z_max);
+
+        ray_min.max(self.t_min) <= ray_max.min(self.t_max)
+    }
+
+    /// Double precision version of [`Ray::intersects_triangle`], using the
+    /// Möller-Trumbore algorithm with backface culling.
+    /// The distance is set to +INFINITY if the ray does not intersect the triangle, or hits
+    /// it from behind.
+    ///
+    /// [`Ray::intersects_triangle`]: ../ray/struct.Ray.html#method.intersects_triangle
+    ///
+    pub fn intersects_triangle(&self, a: &DVec3, b: &DVec3, c: &DVec3) -> Intersection64 {
+        let a_to_b = *b - *a;
+        let a_to_c = *c - *a;
+
+        // Begin calculating determinant - also used to calculate u parameter
+        let u_vec = self.direction.cross(a_to_c);
+
+        // If determinant is near zero, ray lies in plane of triangle
+        let det = a_to_b.dot(u_vec);
+
+        // Only testing positive bound, thus enabling backface culling
+        if det < EPSILON {
+            return Intersection64::new(f64::INFINITY, 0.0, 0.0);
+        }
+
+        let inv_det = 1.0 / det;
+
+        // Vector from point a to ray origin
+        let a_to_origin = self.origin - *a;
+
+        // Calculate u parameter
+        let u = a_to_origin.dot(u_vec) * inv_det;
+
+        // Test bounds: u < 0 || u > 1 => outside of triangle
+        if !(0.0..=1.0).contains(&u) {
+            return Intersection64::new(f64::INFINITY, u, 0.0);
+        }
+
+        // Prepare to test v parameter
+        let v_vec = a_to_origin.cross(a_to_b);
+
+        // Calculate v parameter and test bound
+        let v = self.direction.dot(v_vec) * inv_det;
+        // The intersection lies outside of the triangle
+        if v < 0.0 || u + v > 1.0 {
+            return Intersection64::new(f64::INFINITY, u, v);
+        }
+
+        let dist = a_to_c.dot(v_vec) * inv_det;
+
+        if dist > EPSILON {
+            Intersection64::new(dist, u, v)
+        } else {
+            Intersection64::new(f64::INFINITY, u, v)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp;
+
+    use crate::f64::{Ray64, AABB64, EPSILON};
+    use crate::testbase::{tuple_to_point64, TupleVec64};
+
+    use quickcheck::quickcheck;
+
+    /// Generates a random `Ray64` which points at at a random `AABB64`.
+    fn gen_ray_to_aabb(data: (TupleVec64, TupleVec64, TupleVec64)) -> (Ray64, AABB64) {
+        let aabb = AABB64::empty()
+            .grow(&tuple_to_point64(&data.0))
+            .grow(&tuple_to_point64(&data.1));
+        let center = aabb.center();
+        let pos = tuple_to_point64(&data.2);
+        let ray = Ray64::new(pos, center - pos);
+        (ray, aabb)
+    }
+
+    /// Test whether a `Ray64` which points at the center of an `AABB64` intersects it.
+    quickcheck! {
+        fn test_ray_points_at_aabb_center(data: (TupleVec64, TupleVec64, TupleVec64)) -> bool {
+            let (ray, aabb) = gen_ray_to_aabb(data);
+            ray.intersects_aabb(&aabb)
+        }
+    }
+
+    /// Test whether a `Ray64` which points at the center of an `AABB64`, but ends before
+    /// reaching it, does not intersect it.
+    quickcheck! {
+        fn test_ray_ends_before_aabb(data: (TupleVec64, TupleVec64, TupleVec64)) -> bool {
+            let (ray, aabb) = gen_ray_to_aabb(data);
+            let entry_distance = match aabb.intersect_ray_distance(&ray) {
+                Some(distance) if distance > 0.0 => distance,
+                // The origin is inside of the `AABB64`.
+                _ => return true,
+            };
+
+            let short_ray =
+                Ray64::with_bounds(ray.origin, ray.direction, 0.0, entry_distance * 0.5);
+            let late_ray =
+                Ray64::with_bounds(ray.origin, ray.direction, entry_distance * 0.5, f64::INFINITY);
+            !short_ray.intersects_aabb(&aabb)
+                && aabb.intersect_ray_distance(&short_ray).is_none()
+                && late_ray.intersects_aabb(&aabb)
+        }
+    }
+
+    /// Test whether a `Ray64` which points away from the center of an `AABB64`
+    /// does not intersect it, unless its origin is inside the `AABB64`.
+    quickcheck! {
+        fn test_ray_points_from_aabb_center(data: (TupleVec64, TupleVec64, TupleVec64)) -> bool {
+            let (mut ray, aabb) = gen_ray_to_aabb(data);
+
+            // Invert the direction of the ray
+            ray.direction = -ray.direction;
+            ray.inv_direction = -ray.inv_direction;
+            !ray.intersects_aabb(&aabb) || aabb.contains(&ray.origin)
+        }
+    }
+
+    /// Test whether a `Ray64` which points at the center of a triangle
+    /// intersects it, unless it sees the back face, which is culled.
+    quickcheck! {
+        fn test_ray_hits_triangle(a: TupleVec64,
+                                  b: TupleVec64,
+                                  c: TupleVec64,
+                                  origin: TupleVec64,
+                                  u: u16,
+                                  v: u16)
+                                  -> bool {
+            // Define a triangle, u/v vectors and its normal
+            let triangle = (tuple_to_point64(&a), tuple_to_point64(&b), tuple_to_point64(&c));
+            let u_vec = triangle.1 - triangle.0;
+            let v_vec = triangle.2 - triangle.0;
+            let normal = u_vec.cross(v_vec);
+
+            // Get some u and v coordinates such that u+v <= 1
+            let u = u % 101;
+            let v = cmp::min(100 - u, v % 101);
+            let u = u as f64 / 100.0;
+            let v = v as f64 / 100.0;
+
+            // Define some point on the triangle
+            let point_on_triangle = triangle.0 + u * u_vec + v * v_vec;
+
+            // Define a ray which points at the triangle
+            let origin = tuple_to_point64(&origin);
+            let ray = Ray64::new(origin, point_on_triangle - origin);
+            let on_back_side = normal.dot(ray.origin - triangle.0) <= 0.0;
+
+            // Perform the intersection test
+            let intersects = ray.intersects_triangle(&triangle.0, &triangle.1, &triangle.2);
+            let uv_sum = intersects.u + intersects.v;
+
+            if on_back_side {
+                intersects.distance == f64::INFINITY
+            } else {
+                let intersection_inside = (0.0..=1.0).contains(&uv_sum)
+                    && intersects.distance < f64::INFINITY;
+
+                // Or the input data was close to the border
+                let close_to_border =
+                    u.abs() < EPSILON || (u - 1.0).abs() < EPSILON || v.abs() < EPSILON ||
+                    (v - 1.0).abs() < EPSILON || (u + v - 1.0).abs() < EPSILON;
+
+                intersection_inside || close_to_border
+            }
+        }
+    }
+}
@@ -5,11 +5,15 @@ use crate::bounding_hierarchy::{BHShape, BoundingHierarchy};
 use crate::bvh::{BVHNode, BVH};
 use crate::ray::Ray;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// A structure of a node of a flat [`BVH`]. The structure of the nodes allows for an
 /// iterative traversal approach without the necessity to maintain a stack or queue.
 ///
 /// [`BVH`]: ../bvh/struct.BVH.html
 ///
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FlatNode {
     /// The [`AABB`] of the [`BVH`] node. Prior to testing the [`AABB`] bounds,
     /// the `entry_index` must be checked. In case the entry_index is [`u32::max_value()`],
@@ -48,6 +52,7 @@ impl BVHNode {
     fn create_flat_branch<F, FNodeType>(
         &self,
         nodes: &[BVHNode],
+        shape_indices: &[usize],
         this_aabb: &AABB,
         vec: &mut Vec<FNodeType>,
         next_free: usize,
@@ -62,7 +67,8 @@ impl BVHNode {
         assert_eq!(vec.len() - 1, next_free);
 
         // Create subtree.
-        let index_after_subtree = self.flatten_custom(nodes, vec, next_free + 1, constructor);
+        let index_after_subtree =
+            self.flatten_custom(nodes, shape_indices, vec, next_free + 1, constructor);
 
         // Replace dummy node by actual node with the entry index pointing to the subtree
         // and the exit index pointing to the next node after the subtree.
@@ -78,12 +84,14 @@ impl BVHNode {
 
     /// Flattens the [`BVH`], so that it can be traversed in an iterative manner.
     /// This method constructs custom flat nodes using the `constructor`.
+    /// `nodes` and `shape_indices` are the fields of the [`BVH`].
     ///
     /// [`BVH`]: ../bvh/struct.BVH.html
     ///
     pub fn flatten_custom<F, FNodeType>(
         &self,
         nodes: &[BVHNode],
+        shape_indices: &[usize],
         vec: &mut Vec<FNodeType>,
         next_free: usize,
         constructor: &F,
@@ -101,6 +109,7 @@ impl BVHNode {
             } => {
                 let index_after_child_l = nodes[child_l_index].create_flat_branch(
                     nodes,
+                    shape_indices,
                     child_l_aabb,
                     vec,
                     next_free,
@@ -108,22 +117,30 @@ impl BVHNode {
                 );
                 nodes[child_r_index].create_flat_branch(
                     nodes,
+                    shape_indices,
                     child_r_aabb,
                     vec,
                     index_after_child_l,
                     constructor,
                 )
             }
-            BVHNode::Leaf { shape_index, .. } => {
+            BVHNode::Leaf {
+                shape_index,
+                shape_count,
+                ..
+            } => {
+                // Leaves with several shapes become a chain of flat leaves, one per shape.
                 let mut next_shape = next_free;
-                next_shape += 1;
-                let leaf_node = constructor(
-                    &AABB::empty(),
-                    u32::max_value(),
-                    next_shape as u32,
-                    shape_index as u32,
-                );
-                vec.push(leaf_node);
+                for index in &shape_indices[shape_index..shape_index + shape_count] {
+                    next_shape += 1;
+                    let leaf_node = constructor(
+                        &AABB::empty(),
+                        u32::max_value(),
+                        next_shape as u32,
+                        *index as u32,
+                    );
+                    vec.push(leaf_node);
+                }
 
                 next_shape
             }
@@ -140,6 +157,127 @@ impl BVHNode {
 ///
 pub type FlatBVH = Vec<FlatNode>;
 
+/// A node of a [`FlatBVH`] with a fixed `#[repr(C)]` layout of nine 4-byte values, which
+/// can be uploaded to the GPU as it is. Created by [`ToGpuNodes::to_gpu_nodes`].
+///
+/// The nodes support the same stackless traversal as the [`FlatBVH`]. Starting at index
+/// `0`, each node either skips ahead or descends, until the index runs past the end:
+///
+/// ```text
+/// index = 0
+/// while index < node_count:
+///     node = nodes[index]
+///     if node.shape_index != 0xFFFFFFFF:
+///         // Leaf: test the shape and continue after it.
+///         intersect(ray, shapes[node.shape_index])
+///         index = node.exit_index
+///     else if intersects(ray, node.aabb_min, node.aabb_max):
+///         // Hit inner node: descend into its subtree.
+///         index = node.entry_index
+///     else:
+///         // Missed inner node: skip its subtree.
+///         index = node.exit_index
+/// ```
+///
+/// Note that `[f32; 3]` has an alignment of 4 bytes, so shader languages which align
+/// three-component vectors to 16 bytes have to declare the bounds as separate floats.
+/// With the `bytemuck` feature, [`GpuBvhNode`] implements `Pod`, so the nodes can be
+/// written to a buffer with `bytemuck::cast_slice`.
+///
+/// [`FlatBVH`]: type.FlatBVH.html
+/// [`GpuBvhNode`]: struct.GpuBvhNode.html
+/// [`ToGpuNodes::to_gpu_nodes`]: trait.ToGpuNodes.html#tymethod.to_gpu_nodes
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(C)]
+pub struct GpuBvhNode {
+    /// The minimum corner of the node's [`AABB`]. Undefined for leaves.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    pub aabb_min: [f32; 3],
+
+    /// The maximum corner of the node's [`AABB`]. Undefined for leaves.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    pub aabb_max: [f32; 3],
+
+    /// The index of the node to continue with if the [`AABB`] is hit.
+    /// [`u32::max_value()`] for leaves.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`u32::max_value()`]: https://doc.rust-lang.org/std/u32/constant.MAX.html
+    ///
+    pub entry_index: u32,
+
+    /// The index of the node to continue with if the [`AABB`] is missed or after a leaf.
+    /// Equal to the number of nodes when the traversal is finished.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    pub exit_index: u32,
+
+    /// The index of the shape for leaves, [`u32::max_value()`] for inner nodes.
+    ///
+    /// [`u32::max_value()`]: https://doc.rust-lang.org/std/u32/constant.MAX.html
+    ///
+    pub shape_index: u32,
+}
+
+// Safety: `GpuBvhNode` is `#[repr(C)]` and consists of nine 4-byte values, so it has no
+// padding and every bit pattern is valid.
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for GpuBvhNode {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for GpuBvhNode {}
+
+/// Converts a [`FlatBVH`] into [`GpuBvhNode`]s.
+///
+/// [`FlatBVH`]: type.FlatBVH.html
+/// [`GpuBvhNode`]: struct.GpuBvhNode.html
+///
+pub trait ToGpuNodes {
+    /// Returns the nodes with a fixed layout suitable for uploading to the GPU.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::flat_bvh::ToGpuNodes;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    /// # use bvh_ultraviolet::doc_fixtures::Sphere;
+    ///
+    /// let mut spheres = Vec::new();
+    /// for i in 0..10 {
+    ///     let position = Vec3::new(i as f32 * 10.0, 0.0, 0.0);
+    ///     spheres.push(Sphere { position, radius: 1.0, node_index: 0 });
+    /// }
+    /// let gpu_nodes = BVH::build(&mut spheres).flatten().to_gpu_nodes();
+    ///
+    /// let leaf_count = gpu_nodes
+    ///     .iter()
+    ///     .filter(|node| node.shape_index != u32::MAX)
+    ///     .count();
+    /// assert_eq!(leaf_count, 10);
+    /// assert_eq!(std::mem::size_of_val(&gpu_nodes[0]), 36);
+    /// ```
+    fn to_gpu_nodes(&self) -> Vec<GpuBvhNode>;
+}
+
+impl ToGpuNodes for FlatBVH {
+    fn to_gpu_nodes(&self) -> Vec<GpuBvhNode> {
+        self.iter()
+            .map(|node| GpuBvhNode {
+                aabb_min: [node.aabb.min.x, node.aabb.min.y, node.aabb.min.z],
+                aabb_max: [node.aabb.max.x, node.aabb.max.y, node.aabb.max.z],
+                entry_index: node.entry_index,
+                exit_index: node.exit_index,
+                shape_index: node.shape_index,
+            })
+            .collect()
+    }
+}
+
 impl BVH {
     /// Flattens the [`BVH`] so that it can be traversed iteratively.
     /// Constructs the flat nodes using the supplied function.
@@ -230,13 +368,20 @@ impl BVH {
         F: Fn(&AABB, u32, u32, u32) -> FNodeType,
     {
         let mut vec = Vec::new();
-        self.nodes[0].flatten_custom(&self.nodes, &mut vec, 0, constructor);
+        if self.nodes.is_empty() {
+            return vec;
+        }
+        self.nodes[0].flatten_custom(&self.nodes, &self.shape_indices, &mut vec, 0, constructor);
         vec
     }
 
     /// Flattens the [`BVH`] so that it can be traversed iteratively.
     ///
+    /// Leaves holding several shapes are stored as one consecutive leaf node per shape.
+    /// A [`BVH`] without nodes is flattened into an empty [`FlatBVH`].
+    ///
     /// [`BVH`]: ../bvh/struct.BVH.html
+    /// [`FlatBVH`]: type.FlatBVH.html
     ///
     /// # Example
     ///
@@ -411,20 +556,46 @@ impl BoundingHierarchy for FlatBVH {
         hit_shapes
     }
 
-    /// Prints a textual representation of a [`FlatBVH`].
+    /// Prints a textual representation of a [`FlatBVH`], one line per [`FlatNode`] in the
+    /// order of the flat array. Inner nodes show their entry and exit indices and their
+    /// [`AABB`], leaves show their shape index and exit index.
     ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
     /// [`FlatBVH`]: struct.FlatBVH.html
+    /// [`FlatNode`]: struct.FlatNode.html
     ///
     fn pretty_print(&self) {
-        for (i, node) in self.iter().enumerate() {
-            println!(
-                "{}\tentry {}\texit {}\tshape {}",
-                i, node.entry_index, node.exit_index, node.shape_index
-            );
-        }
+        print!("{}", pretty_string(self));
     }
 }
 
+/// Renders the [`FlatNode`]s of a [`FlatBVH`] for [`pretty_print`].
+///
+/// [`FlatBVH`]: type.FlatBVH.html
+/// [`FlatNode`]: struct.FlatNode.html
+/// [`pretty_print`]: ../bounding_hierarchy/trait.BoundingHierarchy.html#method.pretty_print
+///
+fn pretty_string(flat_bvh: &[FlatNode]) -> String {
+    let mut output = String::new();
+    for (i, node) in flat_bvh.iter().enumerate() {
+        let line = if node.entry_index == u32::MAX {
+            format!(
+                "{}: leaf, shape {}, exit {}",
+                i, node.shape_index, node.exit_index
+            )
+        } else {
+            let (min, max) = (node.aabb.min, node.aabb.max);
+            format!(
+                "{}: node, entry {}, exit {}, min [{}, {}, {}], max [{}, {}, {}]",
+                i, node.entry_index, node.exit_index, min.x, min.y, min.z, max.x, max.y, max.z
+            )
+        };
+        output.push_str(&line);
+        output.push('\n');
+    }
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use crate::flat_bvh::FlatBVH;
@@ -442,6 +613,113 @@ mod tests {
     fn test_traverse_flat_bvh() {
         traverse_some_bh::<FlatBVH>();
     }
+
+    #[test]
+    /// Tests whether the textual representation of a `FlatBVH` has one line per node,
+    /// which names the node type and its jump targets.
+    fn test_pretty_string_flat_bvh() {
+        use crate::flat_bvh::pretty_string;
+
+        let (shapes, flat_bvh) = build_some_bh::<FlatBVH>();
+        let output = pretty_string(&flat_bvh);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), flat_bvh.len());
+
+        for (i, (line, node)) in lines.iter().zip(flat_bvh.iter()).enumerate() {
+            assert!(line.starts_with(&format!("{}: ", i)));
+            assert!(line.contains(&format!("exit {}", node.exit_index)));
+            if node.entry_index == u32::MAX {
+                assert!(line.contains(&format!("leaf, shape {}", node.shape_index)));
+            } else {
+                assert!(line.contains(&format!("node, entry {}", node.entry_index)));
+            }
+        }
+        let leaf_count = lines.iter().filter(|line| line.contains("leaf")).count();
+        assert_eq!(leaf_count, shapes.len());
+    }
+
+    #[test]
+    /// Walks the GPU nodes like a shader would and compares the hits to `BVH::traverse`.
+    fn test_gpu_nodes_traversal_equals_bvh() {
+        use crate::aabb::{Bounded, AABB};
+        use crate::bvh::BVH;
+        use crate::flat_bvh::ToGpuNodes;
+        use crate::ray::Ray;
+        use crate::testbase::{create_n_cubes, default_bounds, next_Vec3};
+        use ultraviolet::Vec3;
+
+        let bounds = default_bounds();
+        let mut triangles = create_n_cubes(100, &bounds);
+        let bvh = BVH::build(&mut triangles);
+        let gpu_nodes = bvh.flatten().to_gpu_nodes();
+
+        let mut seed = 0;
+        for _ in 0..100 {
+            let ray = Ray::new(next_Vec3(&mut seed, &bounds), next_Vec3(&mut seed, &bounds));
+
+            let mut hits = Vec::new();
+            let mut index = 0;
+            while index < gpu_nodes.len() {
+                let node = &gpu_nodes[index];
+                if node.shape_index != u32::MAX {
+                    let shape_index = node.shape_index as usize;
+                    if ray.intersects_aabb(&triangles[shape_index].aabb()) {
+                        hits.push(shape_index);
+                    }
+                    index = node.exit_index as usize;
+                } else {
+                    let aabb = AABB::with_bounds(
+                        Vec3::new(node.aabb_min[0], node.aabb_min[1], node.aabb_min[2]),
+                        Vec3::new(node.aabb_max[0], node.aabb_max[1], node.aabb_max[2]),
+                    );
+                    index = if ray.intersects_aabb(&aabb) {
+                        node.entry_index as usize
+                    } else {
+                        node.exit_index as usize
+                    };
+                }
+            }
+
+            let mut expected = bvh.traverse_indices(&ray, &triangles);
+            hits.sort();
+            expected.sort();
+            assert_eq!(hits, expected);
+        }
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    /// Tests whether GPU nodes can be cast to bytes of the documented size and back.
+    fn test_gpu_nodes_cast_to_bytes() {
+        use crate::bvh::BVH;
+        use crate::flat_bvh::{GpuBvhNode, ToGpuNodes};
+
+        let (_, bvh) = build_some_bh::<BVH>();
+        let gpu_nodes = bvh.flatten().to_gpu_nodes();
+
+        let bytes: &[u8] = bytemuck::cast_slice(&gpu_nodes);
+        assert_eq!(bytes.len(), gpu_nodes.len() * 9 * 4);
+        let nodes: &[GpuBvhNode] = bytemuck::cast_slice(bytes);
+        assert_eq!(nodes, &gpu_nodes[..]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    /// Tests whether a deserialized `FlatBVH` traverses exactly like the original one.
+    fn test_serde_round_trip_flat_bvh() {
+        use crate::bvh::BVH;
+        use crate::testbase::{assert_identical_traversal, create_n_cubes, default_bounds};
+
+        let bounds = default_bounds();
+        let mut triangles = create_n_cubes(100, &bounds);
+        let flat_bvh = BVH::build(&mut triangles).flatten();
+
+        let bytes = bincode::serialize(&flat_bvh).expect("serializing the FlatBVH");
+        let deserialized: FlatBVH =
+            bincode::deserialize(&bytes).expect("deserializing the FlatBVH");
+
+        assert_identical_traversal(&flat_bvh, &deserialized, &triangles, &bounds);
+    }
 }
 
 #[cfg(all(feature = "bench", test))]
@@ -65,6 +65,42 @@
 //! let hit_sphere_aabbs = bvh.traverse(&ray, &spheres);
 //! ```
 //!
+//! ## Precision
+//!
+//! Most operations are exact and compare floats directly: [`AABB::contains`],
+//! [`AABB::contains_aabb`], [`AABB::intersects_aabb`], the [`Ray`]-[`AABB`] slab tests and
+//! with them all traversals of a [`BVH`]. Touching bounds count as overlapping.
+//!
+//! The tolerance-based operations take the tolerance from the caller, so it can be fitted
+//! to the scale of the scene: [`AABB::approx_contains_eps`],
+//! [`AABB::approx_contains_aabb_eps`], [`AABB::intersects_aabb_eps`],
+//! [`AABB::relative_eq`], [`BVH::validate_eps`], [`BVH::assert_tight_eps`] and
+//! [`BuildConfig::epsilon`], which decides when the builder treats the centroids of shapes
+//! as coincident. The triangle and plane intersections of [`Ray`] treat it as parallel, or
+//! a hit as too close to its origin, below the tolerance passed to
+//! [`Ray::intersect_triangle_eps`], [`Ray::intersects_triangle_eps`] and
+//! [`Ray::intersects_plane_eps`].
+//!
+//! The global [`EPSILON`] is only the default of these, which is used by the variants
+//! without the `_eps` suffix.
+//!
+//! [`AABB`]: aabb/struct.AABB.html
+//! [`AABB::approx_contains_aabb_eps`]: aabb/struct.AABB.html#method.approx_contains_aabb_eps
+//! [`AABB::approx_contains_eps`]: aabb/struct.AABB.html#method.approx_contains_eps
+//! [`AABB::contains`]: aabb/struct.AABB.html#method.contains
+//! [`AABB::contains_aabb`]: aabb/struct.AABB.html#method.contains_aabb
+//! [`AABB::intersects_aabb`]: aabb/struct.AABB.html#method.intersects_aabb
+//! [`AABB::intersects_aabb_eps`]: aabb/struct.AABB.html#method.intersects_aabb_eps
+//! [`AABB::relative_eq`]: aabb/struct.AABB.html#method.relative_eq
+//! [`BVH`]: bvh/struct.BVH.html
+//! [`BVH::assert_tight_eps`]: bvh/struct.BVH.html#method.assert_tight_eps
+//! [`BVH::validate_eps`]: bvh/struct.BVH.html#method.validate_eps
+//! [`BuildConfig::epsilon`]: bvh/struct.BuildConfig.html#structfield.epsilon
+//! [`EPSILON`]: constant.EPSILON.html
+//! [`Ray`]: ray/struct.Ray.html
+//! [`Ray::intersect_triangle_eps`]: ray/struct.Ray.html#method.intersect_triangle_eps
+//! [`Ray::intersects_plane_eps`]: ray/struct.Ray.html#method.intersects_plane_eps
+//! [`Ray::intersects_triangle_eps`]: ray/struct.Ray.html#method.intersects_triangle_eps
 //! [`nalgebra`]: http://nalgebra.org/doc/nalgebra/
 //!
 
@@ -78,16 +114,24 @@ extern crate test;
 
 pub use ultraviolet;
 
-/// A minimal floating value used as a lower bound.
-/// TODO: replace by/add ULPS/relative float comparison methods.
+/// A minimal floating value used as a lower bound. It is the default tolerance of the
+/// approximate operations, see the [crate documentation](index.html#precision).
 pub const EPSILON: f32 = 0.00001;
 
 pub mod aabb;
 pub mod axis;
 pub mod bounding_hierarchy;
 pub mod bvh;
+pub mod bvh2d;
+pub mod capsule;
+pub mod cone;
+#[doc(hidden)]
+pub mod doc_fixtures;
+pub mod f64;
 pub mod flat_bvh;
+pub mod plane;
 pub mod ray;
+pub mod triangle;
 mod utils;
 
 #[cfg(test)]
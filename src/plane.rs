@@ -0,0 +1,97 @@
+//! This module defines a [`Plane`], which can be intersected by a [`Ray`].
+//!
+//! [`Plane`]: struct.Plane.html
+//! [`Ray`]: ../ray/struct.Ray.html
+//!
+
+use ultraviolet::Vec3;
+
+/// A plane in Hessian normal form. The plane consists of all points `p` for which
+/// `normal.dot(p) == distance`.
+///
+/// The `normal` points to the front side of the plane, where the signed distance of
+/// points is positive. It is expected to have unit length, so that `distance` is the
+/// signed distance of the plane from the origin.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Plane {
+    /// The unit normal, pointing to the front side of the plane.
+    pub normal: Vec3,
+
+    /// The signed distance of the plane from the origin along `normal`.
+    pub distance: f32,
+}
+
+impl Plane {
+    /// Creates a new [`Plane`] from a unit `normal` and its signed `distance` from the
+    /// origin.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::plane::Plane;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let ground = Plane::new(Vec3::new(0.0, 1.0, 0.0), -2.0);
+    /// assert_eq!(ground.signed_distance(&Vec3::new(5.0, 0.0, 5.0)), 2.0);
+    /// ```
+    ///
+    /// [`Plane`]: struct.Plane.html
+    ///
+    pub fn new(normal: Vec3, distance: f32) -> Plane {
+        Plane { normal, distance }
+    }
+
+    /// Creates a new [`Plane`] through `point`, whose front side faces `normal`.
+    /// `normal` will be normalized.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::plane::Plane;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let plane = Plane::from_point_normal(Vec3::new(0.0, 0.0, 3.0), Vec3::new(0.0, 0.0, -4.0));
+    /// assert_eq!(plane.normal, Vec3::new(0.0, 0.0, -1.0));
+    /// assert_eq!(plane.distance, -3.0);
+    /// ```
+    ///
+    /// [`Plane`]: struct.Plane.html
+    ///
+    pub fn from_point_normal(point: Vec3, normal: Vec3) -> Plane {
+        let normal = normal.normalized();
+        Plane::new(normal, normal.dot(point))
+    }
+
+    /// Returns the signed distance of `point` to this [`Plane`]. It is positive on the
+    /// front side, which `normal` points to, and negative on the back side.
+    ///
+    /// [`Plane`]: struct.Plane.html
+    ///
+    pub fn signed_distance(&self, point: &Vec3) -> f32 {
+        self.normal.dot(*point) - self.distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plane::Plane;
+    use crate::testbase::{tuple_to_point, tuple_to_vector, TupleVec};
+
+    use quickcheck::quickcheck;
+
+    /// Test whether the point a `Plane` is created from lies on it, and whether
+    /// moving along the normal moves to the front side.
+    quickcheck! {
+        fn test_plane_from_point_normal(p: TupleVec, n: TupleVec) -> bool {
+            let point = tuple_to_point(&p);
+            let normal = tuple_to_vector(&n);
+            if normal.mag() < 0.01 {
+                return true;
+            }
+            let plane = Plane::from_point_normal(point, normal);
+
+            let tolerance = 1e-5 * (1.0 + point.mag());
+            plane.signed_distance(&point).abs() < tolerance
+                && plane.signed_distance(&(point + plane.normal)) > 0.0
+                && plane.signed_distance(&(point - plane.normal)) < 0.0
+        }
+    }
+}
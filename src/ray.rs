@@ -1,10 +1,20 @@
 //! This module defines a Ray structure and intersection algorithms
-//! for axis aligned bounding boxes and triangles.
+//! for axis aligned bounding boxes, triangles and planes.
 
 use crate::aabb::AABB;
+use crate::plane::Plane;
 use crate::EPSILON;
 use std::f32::INFINITY;
-use ultraviolet::Vec3;
+use ultraviolet::{Mat4, Vec3};
+
+#[cfg(feature = "glam")]
+use crate::utils::{vec3_from_glam, vec3_to_glam};
+#[cfg(feature = "mint")]
+use crate::utils::{vec3_from_mint, vec3_to_mint};
+#[cfg(feature = "simd")]
+use ultraviolet::{f32x4, Vec3x4};
+#[cfg(feature = "simd")]
+use wide::{CmpGt, CmpLe};
 
 /// A struct which defines a ray and some of its cached values.
 #[derive(Debug)]
@@ -12,24 +22,37 @@ pub struct Ray {
     /// The ray origin.
     pub origin: Vec3,
 
-    /// The ray direction.
+    /// The ray direction. The inverse direction and signs are cached from it when the
+    /// [`Ray`] is created, so a different direction needs a new [`Ray`].
+    ///
+    /// [`Ray`]: struct.Ray.html
+    ///
     pub direction: Vec3,
 
+    /// The start of the interval along the ray in which intersections are reported.
+    /// Defaults to `0.0`.
+    pub t_min: f32,
+
+    /// The end of the interval along the ray in which intersections are reported.
+    /// Defaults to `f32::INFINITY`.
+    pub t_max: f32,
+
     /// Inverse (1/x) ray direction. Cached for use in [`AABB`] intersections.
     ///
-    /// [`AABB`]: struct.AABB.html
+    /// [`AABB`]: ../aabb/struct.AABB.html
     ///
-    inv_direction: Vec3,
+    pub(crate) inv_direction: Vec3,
 
-    /// Sign of the direction. 0 means positive, 1 means negative.
+    /// Sign of the direction per axis. 0 means positive, 1 means negative.
     /// Cached for use in [`AABB`] intersections.
     ///
-    /// [`AABB`]: struct.AABB.html
+    /// [`AABB`]: ../aabb/struct.AABB.html
     ///
-    sign: Vec3,
+    pub(crate) sign: [usize; 3],
 }
 
 /// A struct which is returned by the `intersects_triangle` method.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Intersection {
     /// Distance from the ray origin to the intersection point.
     pub distance: f32,
@@ -49,6 +72,35 @@ impl Intersection {
     }
 }
 
+/// A trait implemented by the results of exact intersection tests, which exposes the
+/// distance of the hit along the [`Ray`]. It lets [`BVH::traverse_nearest`] return the
+/// full hit record of the closest shape, e.g. with barycentric coordinates or a normal,
+/// instead of just its distance.
+///
+/// [`BVH::traverse_nearest`]: ../bvh/struct.BVH.html#method.traverse_nearest
+/// [`Ray`]: struct.Ray.html
+///
+pub trait HitDistance {
+    /// Returns the distance from the [`Ray`] origin to the hit, in units of the
+    /// [`Ray`] direction.
+    ///
+    /// [`Ray`]: struct.Ray.html
+    ///
+    fn t(&self) -> f32;
+}
+
+impl HitDistance for f32 {
+    fn t(&self) -> f32 {
+        *self
+    }
+}
+
+impl HitDistance for Intersection {
+    fn t(&self) -> f32 {
+        self.distance
+    }
+}
+
 impl Ray {
     /// Creates a new [`Ray`] from an `origin` and a `direction`.
     /// `direction` will be normalized.
@@ -66,30 +118,210 @@ impl Ray {
     /// assert_eq!(ray.origin[i], origin[i]);
     /// assert_eq!(ray.direction[i], direction[i]);
     /// # }
+    /// assert_eq!(ray.inv_direction().x, 1.0);
+    /// assert_eq!(ray.sign(), [0, 0, 0]);
     /// ```
     ///
     /// [`Ray`]: struct.Ray.html
     ///
     pub fn new(origin: Vec3, direction: Vec3) -> Ray {
-        let direction = direction.normalized();
+        Ray::with_bounds(origin, direction, 0.0, f32::INFINITY)
+    }
+
+    /// Creates a new [`Ray`] from an `origin` and a `direction` given as `glam::Vec3`s,
+    /// as used by bevy. `direction` will be normalized.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::ray::Ray;
+    ///
+    /// let ray = Ray::from_glam(glam::Vec3::ZERO, glam::Vec3::new(0.0, 0.0, 2.0));
+    /// assert_eq!(ray.to_glam(), (glam::Vec3::ZERO, glam::Vec3::Z));
+    /// ```
+    ///
+    /// [`Ray`]: struct.Ray.html
+    ///
+    #[cfg(feature = "glam")]
+    pub fn from_glam(origin: glam::Vec3, direction: glam::Vec3) -> Ray {
+        Ray::new(vec3_from_glam(origin), vec3_from_glam(direction))
+    }
+
+    /// Returns the origin and the normalized direction of this [`Ray`] as `glam::Vec3`s,
+    /// in the order `(origin, direction)`.
+    ///
+    /// [`Ray`]: struct.Ray.html
+    ///
+    #[cfg(feature = "glam")]
+    pub fn to_glam(&self) -> (glam::Vec3, glam::Vec3) {
+        (vec3_to_glam(self.origin), vec3_to_glam(self.direction))
+    }
+
+    /// Creates a new [`Ray`] from an `origin` and a `direction` given as `mint` vectors.
+    /// `direction` will be normalized.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::ray::Ray;
+    ///
+    /// let origin = mint::Vector3 { x: 0.0, y: 0.0, z: 0.0 };
+    /// let direction = mint::Vector3 { x: 0.0, y: 0.0, z: 2.0 };
+    /// let ray = Ray::from_mint(origin, direction);
+    /// assert_eq!(ray.to_mint().1, mint::Vector3 { x: 0.0, y: 0.0, z: 1.0 });
+    /// ```
+    ///
+    /// [`Ray`]: struct.Ray.html
+    ///
+    #[cfg(feature = "mint")]
+    pub fn from_mint(origin: mint::Vector3<f32>, direction: mint::Vector3<f32>) -> Ray {
+        Ray::new(vec3_from_mint(origin), vec3_from_mint(direction))
+    }
+
+    /// Returns the origin and the normalized direction of this [`Ray`] as `mint` vectors,
+    /// in the order `(origin, direction)`.
+    ///
+    /// [`Ray`]: struct.Ray.html
+    ///
+    #[cfg(feature = "mint")]
+    pub fn to_mint(&self) -> (mint::Vector3<f32>, mint::Vector3<f32>) {
+        (vec3_to_mint(self.origin), vec3_to_mint(self.direction))
+    }
+
+    /// Creates a new [`Ray`] from an `origin` and a `direction`, which only reports
+    /// intersections between the distances `t_min` and `t_max` along the ray.
+    /// `direction` will be normalized, so the distances are measured in world units.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let origin = Vec3::new(0.0,0.0,0.0);
+    /// let direction = Vec3::new(1.0,0.0,0.0);
+    /// let shadow_ray = Ray::with_bounds(origin, direction, 0.0, 50.0);
+    ///
+    /// let near_box = AABB::with_bounds(Vec3::new(9.0,-1.0,-1.0), Vec3::new(11.0,1.0,1.0));
+    /// let far_box = AABB::with_bounds(Vec3::new(99.0,-1.0,-1.0), Vec3::new(101.0,1.0,1.0));
+    ///
+    /// assert!(shadow_ray.intersects_aabb(&near_box));
+    /// assert!(!shadow_ray.intersects_aabb(&far_box));
+    /// ```
+    ///
+    /// [`Ray`]: struct.Ray.html
+    ///
+    pub fn with_bounds(origin: Vec3, direction: Vec3, t_min: f32, t_max: f32) -> Ray {
+        Ray::with_unnormalized_direction(origin, direction.normalized(), t_min, t_max)
+    }
+
+    /// Creates a new [`Ray`] like [`with_bounds`], but keeps the length of `direction`.
+    ///
+    /// [`Ray`]: struct.Ray.html
+    /// [`with_bounds`]: struct.Ray.html#method.with_bounds
+    ///
+    fn with_unnormalized_direction(origin: Vec3, direction: Vec3, t_min: f32, t_max: f32) -> Ray {
         Ray {
             origin,
             direction,
+            t_min,
+            t_max,
             inv_direction: Vec3::new(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z),
             // Sign of the direction. 0 means positive, 1 means negative.
-            sign: Vec3::new(
-                // (direction.x < 0.0) as usize,
-                // (direction.y < 0.0) as usize,
-                // (direction.z < 0.0) as usize,
-                if direction.x < 0.0 { 1.0 } else { 0.0 },
-                if direction.y < 0.0 { 1.0 } else { 0.0 },
-                if direction.z < 0.0 { 1.0 } else { 0.0 },
-            ),
+            sign: [
+                (direction.x < 0.0) as usize,
+                (direction.y < 0.0) as usize,
+                (direction.z < 0.0) as usize,
+            ],
         }
     }
 
+    /// Returns the inverse (1/x) direction of this [`Ray`], which is cached when it is
+    /// created. Custom traversal code can reuse it instead of dividing by the direction
+    /// for every tested node.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+    /// assert_eq!(ray.inv_direction().y, -1.0);
+    /// ```
+    ///
+    /// [`Ray`]: struct.Ray.html
+    ///
+    pub fn inv_direction(&self) -> Vec3 {
+        self.inv_direction
+    }
+
+    /// Returns the sign of the direction of this [`Ray`] per axis, which is cached when
+    /// it is created. 0 means positive, 1 means negative, so it can be used directly to
+    /// index the near and far bounds of an [`AABB`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    /// let ray = Ray::new(Vec3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+    ///
+    /// let [_, sign_y, _] = ray.sign();
+    /// assert_eq!(aabb[sign_y].y, 1.0);
+    /// assert_eq!(aabb[1 - sign_y].y, -1.0);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    pub fn sign(&self) -> [usize; 3] {
+        self.sign
+    }
+
+    /// Transforms this [`Ray`] by the affine transformation `m`, e.g. from world space into
+    /// the object space of an instance, so that a single [`BVH`] in object space can be
+    /// traversed for every instance. The origin is transformed as a point and the direction
+    /// as a vector, and the cached inverse direction and signs are recomputed.
+    ///
+    /// The direction is not normalized, so a point at distance `t` along this [`Ray`] is
+    /// transformed to the point at the same `t` along the transformed [`Ray`]. Distances
+    /// reported in object space, including `t_min` and `t_max`, thus stay comparable with
+    /// distances in world space even if `m` scales, but are no longer measured in object
+    /// space units.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::{Mat4, Vec3};
+    ///
+    /// // An instance of the unit box, scaled by 2 and moved to x = 10.
+    /// let object_aabb = AABB::with_bounds(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    /// let object_to_world = Mat4::from_translation(Vec3::new(10.0, 0.0, 0.0)) * Mat4::from_scale(2.0);
+    /// let world_to_object = object_to_world.inversed();
+    ///
+    /// let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// let object_ray = ray.transform(&world_to_object);
+    ///
+    /// assert_eq!(object_ray.direction, Vec3::new(0.5, 0.0, 0.0));
+    /// // The box is entered at x = 8 in world space.
+    /// assert_eq!(object_aabb.intersect_ray_distance(&object_ray), Some(8.0));
+    /// ```
+    ///
+    /// [`BVH`]: ../bvh/struct.BVH.html
+    /// [`Ray`]: struct.Ray.html
+    ///
+    pub fn transform(&self, m: &Mat4) -> Ray {
+        Ray::with_unnormalized_direction(
+            m.transform_point3(self.origin),
+            m.transform_vec3(self.direction),
+            self.t_min,
+            self.t_max,
+        )
+    }
+
     /// Tests the intersection of a [`Ray`] with an [`AABB`] using the optimized algorithm
     /// from [this paper](http://www.cs.utah.edu/~awilliam/box/box.pdf).
+    /// Only intersections within `[t_min, t_max]` of the [`Ray`] are reported.
     ///
     /// # Examples
     /// ```
@@ -112,47 +344,97 @@ impl Ray {
     /// [`AABB`]: struct.AABB.html
     ///
     pub fn intersects_aabb(&self, aabb: &AABB) -> bool {
-        let mut ray_min = (aabb[self.sign.x as usize].x - self.origin.x) * self.inv_direction.x;
-        let mut ray_max = (aabb[1 - self.sign.x as usize].x - self.origin.x) * self.inv_direction.x;
+        let mut ray_min = (aabb[self.sign[0]].x - self.origin.x) * self.inv_direction.x;
+        let mut ray_max = (aabb[1 - self.sign[0]].x - self.origin.x) * self.inv_direction.x;
 
-        let y_min = (aabb[self.sign.y as usize].y - self.origin.y) * self.inv_direction.y;
-        let y_max = (aabb[1 - self.sign.y as usize].y - self.origin.y) * self.inv_direction.y;
+        let y_min = (aabb[self.sign[1]].y - self.origin.y) * self.inv_direction.y;
+        let y_max = (aabb[1 - self.sign[1]].y - self.origin.y) * self.inv_direction.y;
 
         if (ray_min > y_max) || (y_min > ray_max) {
             return false;
         }
 
-        if y_min > ray_min {
-            ray_min = y_min;
-        }
-        // Using the following solution significantly decreases the performance
-        // ray_min = ray_min.max(y_min);
-
-        if y_max < ray_max {
-            ray_max = y_max;
-        }
-        // Using the following solution significantly decreases the performance
-        // ray_max = ray_max.min(y_max);
+        // An origin on a slab plane turns `0 * inf` into a `NaN` bound. Unlike a comparison,
+        // `max` and `min` ignore it, so the other axes and `[t_min, t_max]` decide.
+        ray_min = ray_min.max(y_min);
+        ray_max = ray_max.min(y_max);
 
-        let z_min = (aabb[self.sign.z as usize].z - self.origin.z) * self.inv_direction.z;
-        let z_max = (aabb[1 - self.sign.z as usize].z - self.origin.z) * self.inv_direction.z;
+        let z_min = (aabb[self.sign[2]].z - self.origin.z) * self.inv_direction.z;
+        let z_max = (aabb[1 - self.sign[2]].z - self.origin.z) * self.inv_direction.z;
 
         if (ray_min > z_max) || (z_min > ray_max) {
             return false;
         }
 
-        // Only required for bounded intersection intervals.
-        // if z_min > ray_min {
-        // ray_min = z_min;
-        // }
+        ray_min = ray_min.max(z_min);
+        ray_max = ray_max.min(z_max);
 
-        if z_max < ray_max {
-            ray_max = z_max;
-        }
-        // Using the following solution significantly decreases the performance
-        // ray_max = ray_max.min(y_max);
+        ray_min.max(self.t_min) <= ray_max.min(self.t_max)
+    }
 
-        ray_max > 0.0
+    /// Tests the intersection of a [`Ray`] with four [`AABB`]s at once, using the wide
+    /// `Vec3x4` type of `ultraviolet` to run the slab test in SIMD lanes.
+    /// Lane `i` of the result is identical to `intersects_aabb(aabbs[i])`, including
+    /// the handling of `NaN`s, since every comparison of [`intersects_aabb`] is mirrored
+    /// lane by lane.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::AABB;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// let aabbs: Vec<AABB> = (0..4)
+    ///     .map(|i| {
+    ///         let center = Vec3::new(10.0, i as f32 * 0.75, 0.0);
+    ///         AABB::with_bounds(center - Vec3::one(), center + Vec3::one())
+    ///     })
+    ///     .collect();
+    ///
+    /// let hits = ray.intersects_aabb_x4([&aabbs[0], &aabbs[1], &aabbs[2], &aabbs[3]]);
+    /// assert_eq!(hits, [true, true, false, false]);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`Ray`]: struct.Ray.html
+    /// [`intersects_aabb`]: struct.Ray.html#method.intersects_aabb
+    ///
+    #[cfg(feature = "simd")]
+    pub fn intersects_aabb_x4(&self, aabbs: [&AABB; 4]) -> [bool; 4] {
+        let [sign_x, sign_y, sign_z] = self.sign;
+        let near = Vec3x4::from(
+            aabbs.map(|aabb| Vec3::new(aabb[sign_x].x, aabb[sign_y].y, aabb[sign_z].z)),
+        );
+        let far = Vec3x4::from(
+            aabbs.map(|aabb| Vec3::new(aabb[1 - sign_x].x, aabb[1 - sign_y].y, aabb[1 - sign_z].z)),
+        );
+        let origin = Vec3x4::splat(self.origin);
+        let inv_direction = Vec3x4::splat(self.inv_direction);
+        let t_near = (near - origin) * inv_direction;
+        let t_far = (far - origin) * inv_direction;
+
+        // Like `f32::max` and `f32::min`, these ignore a `NaN` in either operand. The SSE
+        // versions of `f32x4::max` and `f32x4::min` return `b` if either lane is `NaN`.
+        let max = |a: f32x4, b: f32x4| b.is_nan().blend(a, a.max(b));
+        let min = |a: f32x4, b: f32x4| b.is_nan().blend(a, a.min(b));
+
+        let mut ray_min = t_near.x;
+        let mut ray_max = t_far.x;
+
+        let miss_y = ray_min.cmp_gt(t_far.y) | t_near.y.cmp_gt(ray_max);
+        ray_min = max(ray_min, t_near.y);
+        ray_max = min(ray_max, t_far.y);
+
+        let miss_z = ray_min.cmp_gt(t_far.z) | t_near.z.cmp_gt(ray_max);
+        ray_min = max(ray_min, t_near.z);
+        ray_max = min(ray_max, t_far.z);
+
+        ray_min = max(ray_min, f32x4::splat(self.t_min));
+        ray_max = min(ray_max, f32x4::splat(self.t_max));
+        let in_bounds = ray_min.cmp_le(ray_max);
+        let mask = in_bounds.move_mask() & !(miss_y | miss_z).move_mask();
+        [mask & 1 != 0, mask & 2 != 0, mask & 4 != 0, mask & 8 != 0]
     }
 
     /// Naive implementation of a [`Ray`]/[`AABB`] intersection algorithm.
@@ -197,7 +479,7 @@ impl Ray {
         let latest_entry = x_entry.max(y_entry).max(z_entry);
         let earliest_exit = x_exit.min(y_exit).min(z_exit);
 
-        latest_entry < earliest_exit && earliest_exit > 0.0
+        latest_entry < earliest_exit && latest_entry < self.t_max && earliest_exit > self.t_min
     }
 
     /// Implementation of the algorithm described [here]
@@ -242,7 +524,7 @@ impl Ray {
         tmin = tmin.max(tz1.min(tz2));
         tmax = tmax.min(tz1.max(tz2));
 
-        tmax >= tmin && tmax >= 0.0
+        tmax >= tmin && tmin <= self.t_max && tmax >= self.t_min
     }
 
     /// Implementation of the [Möller-Trumbore triangle/ray intersection algorithm]
@@ -252,6 +534,23 @@ impl Ray {
     /// The distance is set to +INFINITY if the ray does not intersect the triangle, or hits
     /// it from behind.
     pub fn intersects_triangle(&self, a: &Vec3, b: &Vec3, c: &Vec3) -> Intersection {
+        self.intersects_triangle_eps(a, b, c, EPSILON)
+    }
+
+    /// Version of [`intersects_triangle`] which treats the ray as parallel to the triangle,
+    /// and discards hits as too close to its origin, below `epsilon` instead of [`EPSILON`].
+    /// Scenes at much larger or smaller scales than `1.0` should scale `epsilon` accordingly.
+    ///
+    /// [`EPSILON`]: ../constant.EPSILON.html
+    /// [`intersects_triangle`]: struct.Ray.html#method.intersects_triangle
+    ///
+    pub fn intersects_triangle_eps(
+        &self,
+        a: &Vec3,
+        b: &Vec3,
+        c: &Vec3,
+        epsilon: f32,
+    ) -> Intersection {
         let a_to_b = *b - *a;
         let a_to_c = *c - *a;
 
@@ -267,8 +566,8 @@ impl Ray {
 
         // Only testing positive bound, thus enabling backface culling
         // If backface culling is not desired write:
-        // det < EPSILON && det > -EPSILON
-        if det < EPSILON {
+        // det < epsilon && det > -epsilon
+        if det < epsilon {
             return Intersection::new(INFINITY, 0.0, 0.0);
         }
 
@@ -297,12 +596,175 @@ impl Ray {
 
         let dist = a_to_c.dot(v_vec) * inv_det;
 
-        if dist > EPSILON {
+        if dist > epsilon {
             Intersection::new(dist, u, v)
         } else {
             Intersection::new(INFINITY, u, v)
         }
     }
+
+    /// Intersects this [`Ray`] with the triangle `a`, `b`, `c` using the
+    /// [Möller-Trumbore algorithm](https://en.wikipedia.org/wiki/M%C3%B6ller%E2%80%93Trumbore_intersection_algorithm).
+    /// Unlike [`intersects_triangle`], both sides of the triangle are hit.
+    ///
+    /// Returns the [`Intersection`] with the distance `t` along the [`Ray`] and the
+    /// barycentric coordinates `u` and `v`, such that the hit point is
+    /// `a + u * (b - a) + v * (c - a)`. Returns `None` if the [`Ray`] misses the triangle,
+    /// runs parallel to its plane, or hits it outside of `[t_min, t_max]` or
+    /// closer than [`EPSILON`]. Use [`intersect_triangle_eps`] to choose that tolerance.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let a = Vec3::new(0.0, 0.0, 0.0);
+    /// let b = Vec3::new(1.0, 0.0, 0.0);
+    /// let c = Vec3::new(0.0, 1.0, 0.0);
+    ///
+    /// let ray = Ray::new(Vec3::new(0.25, 0.5, 2.0), Vec3::new(0.0, 0.0, -1.0));
+    /// let intersection = ray.intersect_triangle(a, b, c).unwrap();
+    /// assert_eq!(intersection.distance, 2.0);
+    /// assert_eq!((intersection.u, intersection.v), (0.25, 0.5));
+    ///
+    /// let ray_miss = Ray::new(Vec3::new(1.0, 1.0, 2.0), Vec3::new(0.0, 0.0, -1.0));
+    /// assert!(ray_miss.intersect_triangle(a, b, c).is_none());
+    /// ```
+    ///
+    /// [`EPSILON`]: ../constant.EPSILON.html
+    /// [`Intersection`]: struct.Intersection.html
+    /// [`Ray`]: struct.Ray.html
+    /// [`intersect_triangle_eps`]: struct.Ray.html#method.intersect_triangle_eps
+    /// [`intersects_triangle`]: struct.Ray.html#method.intersects_triangle
+    ///
+    pub fn intersect_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<Intersection> {
+        self.intersect_triangle_eps(a, b, c, EPSILON)
+    }
+
+    /// Intersects this [`Ray`] with the triangle `a`, `b`, `c` like [`intersect_triangle`],
+    /// but treats the [`Ray`] as parallel to the triangle, and discards hits as too close to
+    /// its origin, below `epsilon` instead of [`EPSILON`]. Scenes at much larger or smaller
+    /// scales than `1.0` should scale `epsilon` accordingly.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let a = Vec3::new(0.0, 0.0, 0.0);
+    /// let b = Vec3::new(1.0, 0.0, 0.0);
+    /// let c = Vec3::new(0.0, 1.0, 0.0);
+    ///
+    /// // A hit right in front of the origin is only reported with a smaller tolerance.
+    /// let ray = Ray::new(Vec3::new(0.25, 0.5, 0.000_001), Vec3::new(0.0, 0.0, -1.0));
+    /// assert!(ray.intersect_triangle(a, b, c).is_none());
+    /// assert!(ray.intersect_triangle_eps(a, b, c, 0.000_000_1).is_some());
+    /// ```
+    ///
+    /// [`EPSILON`]: ../constant.EPSILON.html
+    /// [`Ray`]: struct.Ray.html
+    /// [`intersect_triangle`]: struct.Ray.html#method.intersect_triangle
+    ///
+    pub fn intersect_triangle_eps(
+        &self,
+        a: Vec3,
+        b: Vec3,
+        c: Vec3,
+        epsilon: f32,
+    ) -> Option<Intersection> {
+        let a_to_b = b - a;
+        let a_to_c = c - a;
+        let u_vec = self.direction.cross(a_to_c);
+
+        // A determinant near zero means the ray lies parallel to the plane of the triangle.
+        let det = a_to_b.dot(u_vec);
+        if det.abs() < epsilon {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+
+        let a_to_origin = self.origin - a;
+        let u = a_to_origin.dot(u_vec) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let v_vec = a_to_origin.cross(a_to_b);
+        let v = self.direction.dot(v_vec) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let distance = a_to_c.dot(v_vec) * inv_det;
+        if distance > epsilon && distance >= self.t_min && distance <= self.t_max {
+            Some(Intersection::new(distance, u, v))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the signed distance `t` along this [`Ray`] at which it crosses the `plane`,
+    /// so that `origin + t * direction` lies on the `plane`. `t` is negative if the crossing
+    /// lies behind the origin, and `[t_min, t_max]` is not taken into account.
+    ///
+    /// The [`Ray`] crosses from either side of the `plane`. Returns `None` if it runs
+    /// parallel to the `plane`, i.e. if the dot product of `direction` and the normal
+    /// of the `plane` is within [`EPSILON`] of zero. Use [`intersects_plane_eps`] to choose
+    /// that tolerance.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::plane::Plane;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let ground = Plane::new(Vec3::new(0.0, 1.0, 0.0), 0.0);
+    ///
+    /// let ray = Ray::new(Vec3::new(1.0, 10.0, 1.0), Vec3::new(0.0, -1.0, 0.0));
+    /// assert_eq!(ray.intersects_plane(&ground), Some(10.0));
+    ///
+    /// let horizontal_ray = Ray::new(Vec3::new(1.0, 10.0, 1.0), Vec3::new(1.0, 0.0, 0.0));
+    /// assert_eq!(horizontal_ray.intersects_plane(&ground), None);
+    /// ```
+    ///
+    /// [`EPSILON`]: ../constant.EPSILON.html
+    /// [`Ray`]: struct.Ray.html
+    /// [`intersects_plane_eps`]: struct.Ray.html#method.intersects_plane_eps
+    ///
+    pub fn intersects_plane(&self, plane: &Plane) -> Option<f32> {
+        self.intersects_plane_eps(plane, EPSILON)
+    }
+
+    /// Returns the signed distance along this [`Ray`] at which it crosses the `plane` like
+    /// [`intersects_plane`], but treats the [`Ray`] as parallel to the `plane` if the dot
+    /// product of `direction` and the normal of the `plane` is within `epsilon` of zero,
+    /// instead of [`EPSILON`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::plane::Plane;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let ground = Plane::new(Vec3::new(0.0, 1.0, 0.0), 0.0);
+    ///
+    /// // A ray which descends very slowly only counts as crossing with a smaller tolerance.
+    /// let ray = Ray::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, -0.000_001, 0.0));
+    /// assert_eq!(ray.intersects_plane(&ground), None);
+    /// assert!(ray.intersects_plane_eps(&ground, 0.000_000_1).is_some());
+    /// ```
+    ///
+    /// [`EPSILON`]: ../constant.EPSILON.html
+    /// [`Ray`]: struct.Ray.html
+    /// [`intersects_plane`]: struct.Ray.html#method.intersects_plane
+    ///
+    pub fn intersects_plane_eps(&self, plane: &Plane, epsilon: f32) -> Option<f32> {
+        let denominator = plane.normal.dot(self.direction);
+        if denominator.abs() < epsilon {
+            return None;
+        }
+        Some(-plane.signed_distance(&self.origin) / denominator)
+    }
 }
 
 #[cfg(test)]
@@ -311,11 +773,13 @@ mod tests {
     use std::f32::INFINITY;
 
     use crate::aabb::AABB;
-    use crate::ray::Ray;
+    use crate::plane::Plane;
+    use crate::ray::{Intersection, Ray};
     use crate::testbase::{tuple_to_point, TupleVec};
     use crate::EPSILON;
 
     use quickcheck::quickcheck;
+    use ultraviolet::Vec3;
 
     /// Generates a random `Ray` which points at at a random `AABB`.
     fn gen_ray_to_aabb(data: (TupleVec, TupleVec, TupleVec)) -> (Ray, AABB) {
@@ -360,6 +824,30 @@ mod tests {
         }
     }
 
+    /// Test whether a `Ray` which points at the center of an `AABB`, but ends before
+    /// reaching it, does not intersect it with any of the algorithms.
+    quickcheck! {
+        fn test_ray_ends_before_aabb(data: (TupleVec, TupleVec, TupleVec)) -> bool {
+            let (ray, aabb) = gen_ray_to_aabb(data);
+            let entry_distance = match aabb.intersect_ray_distance(&ray) {
+                Some(distance) if distance > 0.0 => distance,
+                // The origin is inside of the `AABB`.
+                _ => return true,
+            };
+
+            let short_ray = Ray::with_bounds(ray.origin, ray.direction, 0.0, entry_distance * 0.5);
+            let late_ray =
+                Ray::with_bounds(ray.origin, ray.direction, entry_distance * 0.5, f32::INFINITY);
+            !short_ray.intersects_aabb(&aabb)
+                && !short_ray.intersects_aabb_naive(&aabb)
+                && !short_ray.intersects_aabb_branchless(&aabb)
+                && aabb.intersect_ray_distance(&short_ray).is_none()
+                && late_ray.intersects_aabb(&aabb)
+                && late_ray.intersects_aabb_naive(&aabb)
+                && late_ray.intersects_aabb_branchless(&aabb)
+        }
+    }
+
     /// Test whether a `Ray` which points away from the center of an `AABB`
     /// does not intersect it, unless its origin is inside the `AABB`.
     /// Uses the optimized algorithm.
@@ -465,6 +953,278 @@ mod tests {
             }
         }
     }
+
+    /// Test whether the SIMD intersection of four `AABB`s agrees with `intersects_aabb`
+    /// for each of them.
+    #[cfg(feature = "simd")]
+    quickcheck! {
+        fn test_intersects_aabb_x4_equals_scalar(data: (TupleVec, TupleVec, TupleVec),
+                                                 boxes: (TupleVec, TupleVec, TupleVec, TupleVec))
+                                                 -> bool {
+            let (ray, aabb) = gen_ray_to_aabb(data);
+            let aabbs = [
+                aabb,
+                AABB::empty().grow(&tuple_to_point(&boxes.0)).grow(&tuple_to_point(&boxes.1)),
+                AABB::empty().grow(&tuple_to_point(&boxes.2)).grow(&tuple_to_point(&boxes.3)),
+                AABB::empty().grow(&ray.origin).grow(&tuple_to_point(&boxes.0)),
+            ];
+            let hits = ray.intersects_aabb_x4([&aabbs[0], &aabbs[1], &aabbs[2], &aabbs[3]]);
+            aabbs.iter().zip(hits.iter()).all(|(aabb, hit)| ray.intersects_aabb(aabb) == *hit)
+        }
+    }
+
+    #[test]
+    /// Test whether `intersect_triangle` hits both the front and the back face of a
+    /// triangle at the same distance and barycentric coordinates.
+    fn test_intersect_triangle_front_and_back() {
+        let a = Vec3::new(0.0, 0.0, 0.0);
+        let b = Vec3::new(1.0, 0.0, 0.0);
+        let c = Vec3::new(0.0, 1.0, 0.0);
+
+        let front = Ray::new(Vec3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let back = Ray::new(Vec3::new(0.25, 0.25, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let expected = Some(Intersection::new(1.0, 0.25, 0.25));
+        assert_eq!(front.intersect_triangle(a, b, c), expected);
+        assert_eq!(back.intersect_triangle(a, b, c), expected);
+
+        // The back face is culled by `intersects_triangle`.
+        assert_eq!(front.intersects_triangle(&a, &b, &c).distance, 1.0);
+        assert_eq!(back.intersects_triangle(&a, &b, &c).distance, f32::INFINITY);
+
+        // Hits behind the origin or outside of `[t_min, t_max]` are not reported.
+        let away = Ray::new(Vec3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, 1.0));
+        let short = Ray::with_bounds(front.origin, front.direction, 0.0, 0.5);
+        assert_eq!(away.intersect_triangle(a, b, c), None);
+        assert_eq!(short.intersect_triangle(a, b, c), None);
+    }
+
+    #[test]
+    /// Test whether a `Ray` parallel to the plane of a triangle misses it, even if it
+    /// runs through the triangle.
+    fn test_intersect_triangle_parallel_miss() {
+        let a = Vec3::new(0.0, 0.0, 0.0);
+        let b = Vec3::new(1.0, 0.0, 0.0);
+        let c = Vec3::new(0.0, 1.0, 0.0);
+
+        let above = Ray::new(Vec3::new(-1.0, 0.25, 1.0), Vec3::new(1.0, 0.0, 0.0));
+        let in_plane = Ray::new(Vec3::new(-1.0, 0.25, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(above.intersect_triangle(a, b, c), None);
+        assert_eq!(in_plane.intersect_triangle(a, b, c), None);
+    }
+
+    #[test]
+    /// Test whether `Ray`s cross a `Plane` from its front and back side, and whether
+    /// `Ray`s parallel to the `Plane` miss it.
+    fn test_intersects_plane() {
+        let plane = Plane::new(Vec3::new(0.0, 0.0, 1.0), 2.0);
+
+        let from_front = Ray::new(Vec3::new(1.0, 2.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let from_back = Ray::new(Vec3::new(1.0, 2.0, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(from_front.intersects_plane(&plane), Some(3.0));
+        assert_eq!(from_back.intersects_plane(&plane), Some(3.0));
+
+        // Crossings behind the origin yield a negative distance.
+        let away = Ray::new(Vec3::new(1.0, 2.0, 5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(away.intersects_plane(&plane), Some(-3.0));
+
+        let oblique = Ray::new(Vec3::new(0.0, 0.0, 3.0), Vec3::new(1.0, 0.0, -1.0));
+        let t = oblique.intersects_plane(&plane).unwrap();
+        let point = oblique.origin + oblique.direction * t;
+        assert!(plane.signed_distance(&point).abs() < EPSILON);
+
+        let parallel = Ray::new(Vec3::new(1.0, 2.0, 5.0), Vec3::new(1.0, 1.0, 0.0));
+        let in_plane = Ray::new(Vec3::new(1.0, 2.0, 2.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(parallel.intersects_plane(&plane), None);
+        assert_eq!(in_plane.intersects_plane(&plane), None);
+    }
+
+    #[test]
+    /// Test `Ray`s whose origin lies on a face plane of an `AABB`, where the slab test
+    /// computes `0 * inf`, and whether `intersects_aabb` agrees with `ray_interval`.
+    fn test_intersects_aabb_origin_on_face_plane() {
+        let aabb = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+
+        // On the plane `x = 0` and inside the slabs of `z`, and on the planes `x = 0` and
+        // `z = 1` at once.
+        let hitting = [
+            Ray::new(Vec3::new(0.0, -1.0, 0.5), Vec3::new(0.0, 1.0, 0.0)),
+            Ray::new(Vec3::new(0.0, -1.0, 1.0), Vec3::new(0.0, 1.0, 0.0)),
+            Ray::new(Vec3::new(0.5, 2.0, 0.0), Vec3::new(0.0, -1.0, 0.0)),
+        ];
+        for ray in &hitting {
+            assert!(ray.intersects_aabb(&aabb));
+            assert!(aabb.ray_interval(ray).is_some());
+            #[cfg(feature = "simd")]
+            assert_eq!(ray.intersects_aabb_x4([&aabb; 4]), [true; 4]);
+        }
+        assert_eq!(aabb.ray_interval(&hitting[0]), Some((1.0, 2.0)));
+
+        // On the plane `x = 0`, but pointing away or ending before the `AABB`.
+        let missing = [
+            Ray::new(Vec3::new(0.0, -1.0, 0.5), Vec3::new(0.0, -1.0, 0.0)),
+            Ray::with_bounds(
+                Vec3::new(0.0, -1.0, 0.5),
+                Vec3::new(0.0, 1.0, 0.0),
+                0.0,
+                0.5,
+            ),
+        ];
+        for ray in &missing {
+            assert!(!ray.intersects_aabb(&aabb));
+            assert!(aabb.ray_interval(ray).is_none());
+            #[cfg(feature = "simd")]
+            assert_eq!(ray.intersects_aabb_x4([&aabb; 4]), [false; 4]);
+        }
+    }
+
+    #[test]
+    /// Test `Ray`s which graze the hypotenuse of a triangle just inside, exactly on, and
+    /// just outside of it.
+    fn test_intersect_triangle_near_edge() {
+        let a = Vec3::new(0.0, 0.0, 0.0);
+        let b = Vec3::new(1.0, 0.0, 0.0);
+        let c = Vec3::new(0.0, 1.0, 0.0);
+        let direction = Vec3::new(0.0, 0.0, -1.0);
+
+        let inside = Ray::new(Vec3::new(0.5, 0.5 - 10.0 * EPSILON, 1.0), direction);
+        let on_edge = Ray::new(Vec3::new(0.5, 0.5, 1.0), direction);
+        let outside = Ray::new(Vec3::new(0.5, 0.5 + 10.0 * EPSILON, 1.0), direction);
+
+        assert!(inside.intersect_triangle(a, b, c).is_some());
+        let intersection = on_edge.intersect_triangle(a, b, c).unwrap();
+        assert_eq!(intersection.u + intersection.v, 1.0);
+        assert!(outside.intersect_triangle(a, b, c).is_none());
+    }
+
+    #[test]
+    /// Test whether the `_eps` variants of the triangle and plane intersections hit a tiny
+    /// triangle and cross a `Plane` at a shallow angle, which the default `EPSILON` treats
+    /// as parallel.
+    fn test_intersections_eps() {
+        let a = Vec3::new(0.0, 0.0, 0.0);
+        let b = Vec3::new(0.001, 0.0, 0.0);
+        let c = Vec3::new(0.0, 0.001, 0.0);
+        let ray = Ray::new(
+            Vec3::new(0.000_25, 0.000_25, 0.001),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        assert_eq!(ray.intersect_triangle(a, b, c), None);
+        assert_eq!(ray.intersects_triangle(&a, &b, &c).distance, f32::INFINITY);
+
+        let epsilon = EPSILON * 0.000_001;
+        let intersection = ray.intersect_triangle_eps(a, b, c, epsilon).unwrap();
+        assert!((intersection.distance - 0.001).abs() < EPSILON);
+        assert!((intersection.u - 0.25).abs() < EPSILON);
+        assert!((intersection.v - 0.25).abs() < EPSILON);
+        let intersection = ray.intersects_triangle_eps(&a, &b, &c, epsilon);
+        assert!((intersection.distance - 0.001).abs() < EPSILON);
+
+        let plane = Plane::new(Vec3::new(0.0, 1.0, 0.0), 0.0);
+        let shallow = Ray::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, -0.000_001, 0.0));
+        assert_eq!(shallow.intersects_plane(&plane), None);
+        let t = shallow
+            .intersects_plane_eps(&plane, EPSILON * 0.01)
+            .unwrap();
+        let point = shallow.origin + shallow.direction * t;
+        assert!(point.y.abs() < EPSILON);
+    }
+
+    #[cfg(feature = "glam")]
+    /// Test whether converting a `Ray` to `glam` vectors and back yields the same origin
+    /// and direction.
+    quickcheck! {
+        fn test_glam_round_trip(o: TupleVec, d: TupleVec) -> bool {
+            let direction = tuple_to_point(&d);
+            if direction.mag() < EPSILON {
+                return true;
+            }
+            let ray = Ray::new(tuple_to_point(&o), direction);
+            let (origin, direction) = ray.to_glam();
+            let converted = Ray::from_glam(origin, direction);
+            let difference = converted.direction - ray.direction;
+            converted.origin == ray.origin && difference.mag() < EPSILON
+        }
+    }
+
+    #[test]
+    /// Test whether a `Ray` transformed into the object space of an instance, which is
+    /// rotated, scaled and translated, hits the object space `AABB` at the same distances
+    /// as the original `Ray` hits the `AABB` of the instance in world space.
+    fn test_transform_matches_transformed_aabb() {
+        use std::f32::consts::FRAC_PI_2;
+        use ultraviolet::Mat4;
+
+        let object_aabb = AABB::with_bounds(Vec3::new(-1.0, -2.0, -0.5), Vec3::new(3.0, 2.0, 0.5));
+        let object_to_world = Mat4::from_translation(Vec3::new(5.0, 0.0, -3.0))
+            * Mat4::from_rotation_y(FRAC_PI_2)
+            * Mat4::from_scale(2.0);
+        let world_to_object = object_to_world.inversed();
+        let world_aabb = object_aabb
+            .corners()
+            .iter()
+            .fold(AABB::empty(), |aabb, corner| {
+                aabb.grow(&object_to_world.transform_point3(*corner))
+            });
+        let world_center = world_aabb.center();
+
+        let origins = [
+            Vec3::new(40.0, 1.0, 7.0),
+            Vec3::new(-20.0, -3.0, 0.0),
+            Vec3::new(0.0, 30.0, 0.5),
+            Vec3::new(5.0, -1.0, -40.0),
+            world_center,
+        ];
+        let targets = [
+            world_center,
+            world_center + Vec3::new(0.5, 1.5, -1.0),
+            world_center + Vec3::new(0.0, 10.0, 0.0),
+            world_center + Vec3::new(20.0, 0.0, 20.0),
+        ];
+        let mut hit_count = 0;
+        for origin in &origins {
+            for target in &targets {
+                if *origin == *target {
+                    continue;
+                }
+                let ray = Ray::new(*origin, *target - *origin);
+                let object_ray = ray.transform(&world_to_object);
+
+                let world_distance = world_aabb.intersect_ray_distance(&ray);
+                let object_distance = object_aabb.intersect_ray_distance(&object_ray);
+                assert_eq!(
+                    ray.intersects_aabb(&world_aabb),
+                    object_ray.intersects_aabb(&object_aabb)
+                );
+                match (world_distance, object_distance) {
+                    (Some(expected), Some(distance)) => {
+                        assert!((distance - expected).abs() < 1e-4 * (1.0 + expected));
+                        hit_count += 1;
+                    }
+                    (expected, distance) => assert!(expected.is_none() && distance.is_none()),
+                }
+            }
+        }
+        assert!(hit_count > 0 && hit_count < origins.len() * targets.len());
+    }
+
+    #[cfg(feature = "mint")]
+    /// Test whether converting `mint` vectors to a `Ray` and back yields the same origin
+    /// and, for unit length directions, the same direction.
+    quickcheck! {
+        fn test_mint_round_trip(o: TupleVec, d: TupleVec) -> bool {
+            let direction = tuple_to_point(&d);
+            if direction.mag() < EPSILON {
+                return true;
+            }
+            let direction = direction.normalized();
+            let origin = mint::Vector3::from([o.0, o.1, o.2]);
+            let mint_direction = mint::Vector3::from([direction.x, direction.y, direction.z]);
+            let (converted_origin, converted_direction) =
+                Ray::from_mint(origin, mint_direction).to_mint();
+            let difference = crate::utils::vec3_from_mint(converted_direction) - direction;
+            converted_origin == origin && difference.mag() < EPSILON
+        }
+    }
 }
 
 #[cfg(all(feature = "bench", test))]
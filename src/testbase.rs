@@ -6,15 +6,17 @@ use std::f32;
 use std::mem::transmute;
 
 use num::{FromPrimitive, Integer};
-use ultraviolet::Vec3;
 use obj::raw::object::Polygon;
 use obj::*;
 use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
 use rand::SeedableRng;
+use ultraviolet::{DVec3, Vec2, Vec3};
 
 use crate::aabb::{Bounded, AABB};
+use crate::axis::Axis;
 use crate::bounding_hierarchy::{BHShape, BoundingHierarchy};
+use crate::bvh::{BVHNode, BVH};
 use crate::ray::Ray;
 
 /// A vector represented as a tuple
@@ -30,6 +32,22 @@ pub fn tuple_to_vector(tpl: &TupleVec) -> Vec3 {
     Vec3::new(tpl.0, tpl.1, tpl.2)
 }
 
+/// A two-dimensional vector represented as a tuple
+pub type TupleVec2 = (f32, f32);
+
+/// Convert a `TupleVec2` to a `Vec2`.
+pub fn tuple_to_point2(tpl: &TupleVec2) -> Vec2 {
+    Vec2::new(tpl.0, tpl.1)
+}
+
+/// A double precision vector represented as a tuple
+pub type TupleVec64 = (f64, f64, f64);
+
+/// Convert a `TupleVec64` to a `DVec3`.
+pub fn tuple_to_point64(tpl: &TupleVec64) -> DVec3 {
+    DVec3::new(tpl.0, tpl.1, tpl.2)
+}
+
 /// Define some `Bounded` structure.
 pub struct UnitBox {
     pub id: i32,
@@ -84,6 +102,54 @@ pub fn build_some_bh<BH: BoundingHierarchy>() -> (Vec<UnitBox>, BH) {
     (boxes, bh)
 }
 
+/// Creates `n` collinear `UnitBox`s along the X axis and a deliberately unbalanced `BVH`
+/// over them: a chain of inner nodes which descends to the left and has a single leaf on
+/// each right side, so the tree is `n - 1` levels deep. Used to test whether queries
+/// handle trees which are too deep for recursion.
+pub fn build_chain_bvh(n: usize) -> (Vec<UnitBox>, BVH) {
+    let shapes: Vec<UnitBox> = (0..n)
+        .map(|i| UnitBox::new(i as i32, Vec3::new(i as f32, 0.0, 0.0)))
+        .collect();
+
+    // `suffix_aabbs[i]` is the joint `AABB` of the shapes `i..n`.
+    let mut suffix_aabbs = vec![AABB::empty(); n + 1];
+    for i in (0..n).rev() {
+        suffix_aabbs[i] = suffix_aabbs[i + 1].join(&shapes[i].aabb());
+    }
+
+    let leaf_index = |shape_index: usize| n - 1 + shape_index;
+    let mut nodes = Vec::with_capacity(2 * n - 1);
+    for i in 0..n - 1 {
+        let child_l_index = if i < n - 2 { i + 1 } else { leaf_index(n - 1) };
+        nodes.push(BVHNode::Node {
+            parent_index: i.saturating_sub(1),
+            depth: i as u32,
+            child_l_index,
+            child_l_aabb: suffix_aabbs[i + 1],
+            child_r_index: leaf_index(i),
+            child_r_aabb: shapes[i].aabb(),
+            split_axis: Axis::X,
+            split_position: i as f32 + 0.5,
+        });
+    }
+    for i in 0..n {
+        nodes.push(BVHNode::Leaf {
+            parent_index: i.min(n - 2),
+            depth: i.min(n - 2) as u32 + 1,
+            shape_index: i,
+            shape_count: 1,
+        });
+    }
+    let shape_indices = (0..n).collect();
+    (
+        shapes,
+        BVH {
+            nodes,
+            shape_indices,
+        },
+    )
+}
+
 /// Given a ray, a bounding hierarchy, the complete list of shapes in the scene and a list of
 /// expected hits, verifies, whether the ray hits only the expected shapes.
 fn traverse_and_verify<BH: BoundingHierarchy>(
@@ -144,6 +210,32 @@ pub fn traverse_some_bh<BH: BoundingHierarchy>() {
     }
 }
 
+/// Traverses both `BoundingHierarchy`s with random rays and asserts that they
+/// return the same shapes for every ray.
+#[cfg(feature = "serde")]
+pub fn assert_identical_traversal<A: BoundingHierarchy, B: BoundingHierarchy>(
+    a: &A,
+    b: &B,
+    shapes: &[Triangle],
+    bounds: &AABB,
+) {
+    let mut seed = 0;
+    for _ in 0..100 {
+        let ray = Ray::new(next_Vec3(&mut seed, bounds), next_Vec3(&mut seed, bounds));
+        let hits_a: HashSet<_> = a
+            .traverse(&ray, shapes)
+            .into_iter()
+            .map(|shape| shape as *const Triangle)
+            .collect();
+        let hits_b: HashSet<_> = b
+            .traverse(&ray, shapes)
+            .into_iter()
+            .map(|shape| shape as *const Triangle)
+            .collect();
+        assert_eq!(hits_a, hits_b);
+    }
+}
+
 /// A triangle struct. Instance of a more complex `Bounded` primitive.
 #[derive(Debug)]
 pub struct Triangle {
@@ -170,6 +262,10 @@ impl Bounded for Triangle {
     fn aabb(&self) -> AABB {
         self.aabb
     }
+
+    fn clipped_aabb(&self, bounds: &AABB) -> AABB {
+        crate::triangle::Triangle::new(self.a, self.b, self.c).clipped_aabb(bounds)
+    }
 }
 
 impl BHShape for Triangle {
@@ -355,6 +451,47 @@ pub fn create_n_cubes(n: usize, bounds: &AABB) -> Vec<Triangle> {
     vec
 }
 
+/// Creates `n` deterministic random long and thin triangles inside of `bounds`. Each
+/// triangle connects two random points and is only one unit wide, so the `AABB`s of the
+/// triangles are huge and overlap a lot, while the triangles themselves rarely do.
+pub fn create_thin_triangles(n: usize, bounds: &AABB) -> Vec<Triangle> {
+    let mut triangles = Vec::new();
+    let mut seed = 0;
+    for _ in 0..n {
+        let a = next_Vec3(&mut seed, bounds);
+        let b = next_Vec3(&mut seed, bounds);
+        triangles.push(Triangle::new(a, b, b + Vec3::new(0.0, 1.0, 0.0)));
+    }
+    triangles
+}
+
+/// Creates a scene of `n` deterministic random unit cubes, like [`create_n_cubes`],
+/// plus one huge triangle per axis which spans `bounds`. The huge triangles cover
+/// the whole scene, while their centroids lie at its center.
+///
+/// [`create_n_cubes`]: fn.create_n_cubes.html
+///
+pub fn create_mixed_scale_scene(n: usize, bounds: &AABB) -> Vec<Triangle> {
+    let mut triangles = create_n_cubes(n, bounds);
+    let (min, max) = (bounds.min, bounds.max);
+    triangles.push(Triangle::new(
+        min,
+        Vec3::new(max.x, min.y, max.z),
+        Vec3::new(max.x, max.y, min.z),
+    ));
+    triangles.push(Triangle::new(
+        Vec3::new(max.x, min.y, min.z),
+        Vec3::new(min.x, max.y, min.z),
+        Vec3::new(min.x, min.y, max.z),
+    ));
+    triangles.push(Triangle::new(
+        max,
+        Vec3::new(min.x, max.y, min.z),
+        Vec3::new(max.x, min.y, min.z),
+    ));
+    triangles
+}
+
 /// Loads the sponza model.
 #[cfg(feature = "bench")]
 pub fn load_sponza_scene() -> (Vec<Triangle>, AABB) {
@@ -438,6 +575,107 @@ pub fn create_ray(seed: &mut u64, bounds: &AABB) -> Ray {
     Ray::new(origin, direction)
 }
 
+/// Sweep 1,000 rays over the `AABB`s of 1,000 random triangles, i.e. one million
+/// `Ray`/`AABB` tests per iteration. With `precomputed` set, the tests use the cached
+/// inverse direction and sign of the `Ray`, otherwise they are recomputed for every test.
+#[cfg(feature = "bench")]
+fn intersect_aabb_sweep(precomputed: bool, b: &mut ::test::Bencher) {
+    let bounds = default_bounds();
+    let aabbs: Vec<AABB> = create_n_cubes(84, &bounds)
+        .iter()
+        .take(1_000)
+        .map(|triangle| triangle.aabb())
+        .collect();
+    let mut seed = 0;
+    let rays: Vec<Ray> = (0..1_000).map(|_| create_ray(&mut seed, &bounds)).collect();
+
+    b.iter(|| {
+        let mut hits = 0;
+        for ray in &rays {
+            for aabb in &aabbs {
+                let hit = if precomputed {
+                    ray.intersects_aabb(aabb)
+                } else {
+                    // Rebuild the ray to recompute its cached values for every test.
+                    Ray::new(ray.origin, ray.direction).intersects_aabb(aabb)
+                };
+                if hit {
+                    hits += 1;
+                }
+            }
+        }
+        ::test::black_box(hits)
+    });
+}
+
+#[cfg(feature = "bench")]
+#[bench]
+/// Benchmark one million `Ray`/`AABB` tests using the cached inverse direction and sign.
+fn bench_intersect_aabb_sweep_1m_precomputed(b: &mut ::test::Bencher) {
+    intersect_aabb_sweep(true, b);
+}
+
+#[cfg(feature = "bench")]
+#[bench]
+/// Benchmark one million `Ray`/`AABB` tests recomputing the inverse direction and sign.
+fn bench_intersect_aabb_sweep_1m_recomputed(b: &mut ::test::Bencher) {
+    intersect_aabb_sweep(false, b);
+}
+
+/// Traverse a `BVH` over 100,000 random unit boxes with 1,000 random rays per iteration,
+/// with the scalar or the SIMD iterative traversal.
+#[cfg(all(feature = "bench", feature = "simd"))]
+fn traverse_100k_boxes(simd: bool, b: &mut ::test::Bencher) {
+    use crate::bvh::{BVHNode, BVH};
+
+    let bounds = default_bounds();
+    let mut seed = 0;
+    let mut boxes: Vec<UnitBox> = (0..100_000)
+        .map(|i| UnitBox::new(i, next_Vec3(&mut seed, &bounds)))
+        .collect();
+    let bvh = BVH::build(&mut boxes);
+    let rays: Vec<Ray> = (0..1_000).map(|_| create_ray(&mut seed, &bounds)).collect();
+
+    let mut indices = Vec::new();
+    b.iter(|| {
+        for ray in &rays {
+            indices.clear();
+            if simd {
+                BVHNode::traverse_iterative_x4(
+                    &bvh.nodes,
+                    &bvh.shape_indices,
+                    ray,
+                    &boxes,
+                    &mut indices,
+                );
+            } else {
+                BVHNode::traverse_iterative(
+                    &bvh.nodes,
+                    &bvh.shape_indices,
+                    ray,
+                    &boxes,
+                    &mut indices,
+                );
+            }
+            ::test::black_box(&indices);
+        }
+    });
+}
+
+#[cfg(all(feature = "bench", feature = "simd"))]
+#[bench]
+/// Benchmark the scalar traversal of a `BVH` over 100,000 boxes.
+fn bench_traverse_100k_boxes_scalar(b: &mut ::test::Bencher) {
+    traverse_100k_boxes(false, b);
+}
+
+#[cfg(all(feature = "bench", feature = "simd"))]
+#[bench]
+/// Benchmark the SIMD traversal of a `BVH` over 100,000 boxes.
+fn bench_traverse_100k_boxes_simd(b: &mut ::test::Bencher) {
+    traverse_100k_boxes(true, b);
+}
+
 /// Benchmark the construction of a `BoundingHierarchy` with `n` triangles.
 #[cfg(feature = "bench")]
 fn build_n_triangles_bh<T: BoundingHierarchy>(n: usize, b: &mut ::test::Bencher) {
@@ -0,0 +1,172 @@
+//! This module defines a [`Triangle`] primitive, which can be intersected by a [`Ray`]
+//! after traversing a [`BVH`].
+//!
+//! [`BVH`]: ../bvh/struct.BVH.html
+//! [`Ray`]: ../ray/struct.Ray.html
+//! [`Triangle`]: struct.Triangle.html
+//!
+
+use crate::aabb::{Bounded, AABB};
+use crate::axis::Axis;
+use crate::ray::{Intersection, Ray};
+use ultraviolet::Vec3;
+
+/// A triangle defined by its three vertices.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Triangle {
+    /// The first vertex.
+    pub a: Vec3,
+
+    /// The second vertex.
+    pub b: Vec3,
+
+    /// The third vertex.
+    pub c: Vec3,
+}
+
+impl Triangle {
+    /// Creates a new [`Triangle`] from its three vertices.
+    ///
+    /// [`Triangle`]: struct.Triangle.html
+    ///
+    pub fn new(a: Vec3, b: Vec3, c: Vec3) -> Triangle {
+        Triangle { a, b, c }
+    }
+
+    /// Intersects the `ray` with this [`Triangle`] from either side.
+    /// See [`Ray::intersect_triangle`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::{AABB, Bounded};
+    /// use bvh_ultraviolet::bvh::BVH;
+    /// use bvh_ultraviolet::ray::Ray;
+    /// use bvh_ultraviolet::triangle::Triangle;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let bvh = BVH { nodes: Vec::new(), shape_indices: Vec::new() };
+    /// let triangles: Vec<Triangle> = Vec::new();
+    /// let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+    ///
+    /// let nearest = bvh.traverse_nearest(&ray, &triangles, |ray, triangle| {
+    ///     triangle.intersect(ray).map(|intersection| intersection.distance)
+    /// });
+    /// assert_eq!(nearest, None);
+    ///
+    /// let triangle = Triangle::new(
+    ///     Vec3::new(-1.0, -1.0, 5.0),
+    ///     Vec3::new(1.0, -1.0, 5.0),
+    ///     Vec3::new(0.0, 1.0, 5.0),
+    /// );
+    /// assert_eq!(triangle.intersect(&ray).unwrap().distance, 5.0);
+    /// ```
+    ///
+    /// [`Ray::intersect_triangle`]: ../ray/struct.Ray.html#method.intersect_triangle
+    /// [`Triangle`]: struct.Triangle.html
+    ///
+    pub fn intersect(&self, ray: &Ray) -> Option<Intersection> {
+        ray.intersect_triangle(self.a, self.b, self.c)
+    }
+}
+
+impl Bounded for Triangle {
+    /// Returns the [`AABB`] of the three vertices.
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    ///
+    fn aabb(&self) -> AABB {
+        AABB::empty().grow(&self.a).grow(&self.b).grow(&self.c)
+    }
+
+    /// Clips this [`Triangle`] against the six planes of `bounds` and returns the [`AABB`]
+    /// of the remaining polygon, which is much tighter than the intersection of [`aabb`]
+    /// and `bounds` for long, diagonal triangles.
+    ///
+    /// # Examples
+    /// ```
+    /// use bvh_ultraviolet::aabb::{AABB, Bounded};
+    /// use bvh_ultraviolet::triangle::Triangle;
+    /// use bvh_ultraviolet::ultraviolet::Vec3;
+    ///
+    /// let diagonal = Triangle::new(
+    ///     Vec3::new(0.0, 0.0, 0.0),
+    ///     Vec3::new(10.0, 10.0, 0.0),
+    ///     Vec3::new(10.0, 10.0, 1.0),
+    /// );
+    /// let left_half = AABB::with_bounds(Vec3::new(0.0, 0.0, 0.0), Vec3::new(5.0, 10.0, 1.0));
+    ///
+    /// let clipped = diagonal.clipped_aabb(&left_half);
+    /// assert_eq!(clipped.max.x, 5.0);
+    /// assert!(clipped.max.y <= 5.0 + 1e-5);
+    /// ```
+    ///
+    /// [`AABB`]: ../aabb/struct.AABB.html
+    /// [`Triangle`]: struct.Triangle.html
+    /// [`aabb`]: ../aabb/trait.Bounded.html#tymethod.aabb
+    ///
+    fn clipped_aabb(&self, bounds: &AABB) -> AABB {
+        // Sutherland-Hodgman clipping against one slab after the other.
+        let mut polygon = vec![self.a, self.b, self.c];
+        for axis in Axis::iter() {
+            for &(plane, keep_above) in &[(bounds.min[axis], true), (bounds.max[axis], false)] {
+                let inside = |p: &Vec3| {
+                    if keep_above {
+                        p[axis] >= plane
+                    } else {
+                        p[axis] <= plane
+                    }
+                };
+                let mut clipped = Vec::with_capacity(polygon.len() + 1);
+                for (i, p) in polygon.iter().enumerate() {
+                    let q = &polygon[(i + 1) % polygon.len()];
+                    if inside(p) {
+                        clipped.push(*p);
+                    }
+                    if inside(p) != inside(q) {
+                        let t = (plane - p[axis]) / (q[axis] - p[axis]);
+                        let mut crossing = *p + (*q - *p) * t;
+                        crossing[axis] = plane;
+                        clipped.push(crossing);
+                    }
+                }
+                polygon = clipped;
+                if polygon.is_empty() {
+                    return AABB::empty();
+                }
+            }
+        }
+        polygon
+            .iter()
+            .fold(AABB::empty(), |aabb, p| aabb.grow(p))
+            .intersection(bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aabb::Bounded;
+    use crate::ray::Ray;
+    use crate::testbase::{tuple_to_point, TupleVec};
+    use crate::triangle::Triangle;
+
+    use quickcheck::quickcheck;
+
+    /// Test whether the `AABB` of a `Triangle` contains its vertices and its centroid,
+    /// and whether a `Ray` through the centroid hits the `Triangle`.
+    quickcheck! {
+        fn test_triangle_aabb_and_centroid(a: TupleVec, b: TupleVec, c: TupleVec) -> bool {
+            let triangle = Triangle::new(tuple_to_point(&a), tuple_to_point(&b), tuple_to_point(&c));
+            let aabb = triangle.aabb();
+            let centroid = (triangle.a + triangle.b + triangle.c) / 3.0;
+
+            let normal = (triangle.b - triangle.a).cross(triangle.c - triangle.a);
+            let ray = Ray::new(centroid + normal, -normal);
+            let degenerate = normal.mag() < 1.0;
+
+            aabb.contains(&triangle.a)
+                && aabb.contains(&triangle.b)
+                && aabb.contains(&triangle.c)
+                && (degenerate || triangle.intersect(&ray).is_some())
+        }
+    }
+}
@@ -1,7 +1,12 @@
 //! Utilities module.
 
-use crate::aabb::AABB;
-use crate::bounding_hierarchy::BHShape;
+use crate::aabb::{Bounded, AABB};
+use std::cmp::Ordering;
+#[cfg(any(feature = "glam", feature = "mint"))]
+use ultraviolet::Vec3;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 /// Concatenates the list of vectors into a single vector.
 /// Drains the elements from the source `vectors`.
@@ -13,6 +18,34 @@ pub fn concatenate_vectors<T: Sized>(vectors: &mut [Vec<T>]) -> Vec<T> {
     result
 }
 
+/// Converts a `glam::Vec3` to an ultraviolet `Vec3`.
+#[cfg(feature = "glam")]
+pub fn vec3_from_glam(v: glam::Vec3) -> Vec3 {
+    Vec3::new(v.x, v.y, v.z)
+}
+
+/// Converts an ultraviolet `Vec3` to a `glam::Vec3`.
+#[cfg(feature = "glam")]
+pub fn vec3_to_glam(v: Vec3) -> glam::Vec3 {
+    glam::Vec3::new(v.x, v.y, v.z)
+}
+
+/// Converts a `mint::Vector3<f32>` to an ultraviolet `Vec3`.
+#[cfg(feature = "mint")]
+pub fn vec3_from_mint(v: mint::Vector3<f32>) -> Vec3 {
+    Vec3::new(v.x, v.y, v.z)
+}
+
+/// Converts an ultraviolet `Vec3` to a `mint::Vector3<f32>`.
+#[cfg(feature = "mint")]
+pub fn vec3_to_mint(v: Vec3) -> mint::Vector3<f32> {
+    mint::Vector3 {
+        x: v.x,
+        y: v.y,
+        z: v.z,
+    }
+}
+
 /// Defines a Bucket utility object. Used to store the properties of shape-partitions
 /// in the BVH build procedure using SAH.
 #[derive(Copy, Clone)]
@@ -22,6 +55,9 @@ pub struct Bucket {
 
     /// The joint `AABB` of the shapes in this `Bucket`.
     pub aabb: AABB,
+
+    /// The summed intersection cost of the shapes in this `Bucket`.
+    pub cost: f32,
 }
 
 impl Bucket {
@@ -30,13 +66,20 @@ impl Bucket {
         Bucket {
             size: 0,
             aabb: AABB::empty(),
+            cost: 0.0,
         }
     }
 
-    /// Extend this `Bucket` by a shape with the given `AABB`.
+    /// Extend this `Bucket` by a shape with the given `AABB` and an intersection cost of `1.0`.
     pub fn add_aabb(&mut self, aabb: &AABB) {
+        self.add_aabb_with_cost(aabb, 1.0);
+    }
+
+    /// Extend this `Bucket` by a shape with the given `AABB` and intersection `cost`.
+    pub fn add_aabb_with_cost(&mut self, aabb: &AABB, cost: f32) {
         self.size += 1;
         self.aabb = self.aabb.join(aabb);
+        self.cost += cost;
     }
 
     /// Join the contents of two `Bucket`s.
@@ -44,11 +87,40 @@ impl Bucket {
         Bucket {
             size: a.size + b.size,
             aabb: a.aabb.join(&b.aabb),
+            cost: a.cost + b.cost,
         }
     }
 }
 
-pub fn joint_aabb_of_shapes<Shape: BHShape>(indices: &[usize], shapes: &[Shape]) -> AABB {
+/// Returns true if the `AABB` of `shape` is finite and not empty. Builders skip all other
+/// shapes, because their `AABB`s cannot be hit and would spoil the SAH comparisons.
+pub fn has_valid_aabb<Shape: Bounded>(shape: &Shape) -> bool {
+    let aabb = shape.aabb();
+    aabb.is_finite() && !aabb.is_empty()
+}
+
+/// Returns the joint `AABB` of the shapes at `indices`. An empty `indices` slice yields
+/// `AABB::empty()`, so callers can check `is_empty()` instead of handling a panic.
+///
+/// # Examples
+/// ```
+/// use bvh_ultraviolet::aabb::{joint_aabb_of_shapes, AABB};
+/// use bvh_ultraviolet::ultraviolet::Vec3;
+///
+/// let boxes: Vec<AABB> = (0..10)
+///     .map(|i| {
+///         let min = Vec3::new(i as f32, 0.0, 0.0);
+///         AABB::with_bounds(min, min + Vec3::one())
+///     })
+///     .collect();
+///
+/// let joint = joint_aabb_of_shapes(&[2, 5], &boxes);
+/// assert_eq!(joint.min, Vec3::new(2.0, 0.0, 0.0));
+/// assert_eq!(joint.max, Vec3::new(6.0, 1.0, 1.0));
+/// assert!(joint_aabb_of_shapes::<AABB>(&[], &boxes).is_empty());
+/// ```
+///
+pub fn joint_aabb_of_shapes<Shape: Bounded>(indices: &[usize], shapes: &[Shape]) -> AABB {
     let mut aabb = AABB::empty();
     for index in indices {
         let shape = &shapes[*index];
@@ -57,9 +129,92 @@ pub fn joint_aabb_of_shapes<Shape: BHShape>(indices: &[usize], shapes: &[Shape])
     aabb
 }
 
+/// Below this number of indices [`par_joint_aabb_of_shapes`] folds serially, because
+/// splitting the work across threads costs more than it saves.
+///
+/// [`par_joint_aabb_of_shapes`]: fn.par_joint_aabb_of_shapes.html
+///
+#[cfg(feature = "rayon")]
+pub const PARALLEL_JOINT_AABB_THRESHOLD: usize = 10_000;
+
+/// Parallel version of [`joint_aabb_of_shapes`] using `rayon`. Falls back to the serial
+/// fold when there are fewer than [`PARALLEL_JOINT_AABB_THRESHOLD`] `indices`.
+///
+/// The result is identical to the one of [`joint_aabb_of_shapes`], independent of how
+/// `rayon` splits the work: joining `AABB`s only takes the component-wise minimum and
+/// maximum, which are exact, so the order of the joins does not matter.
+///
+/// # Examples
+/// ```
+/// use bvh_ultraviolet::aabb::{joint_aabb_of_shapes, par_joint_aabb_of_shapes, AABB};
+/// use bvh_ultraviolet::ultraviolet::Vec3;
+///
+/// let boxes: Vec<AABB> = (0..100_000)
+///     .map(|i| {
+///         let position = Vec3::new((i % 97) as f32, (i % 89) as f32 * 0.5, i as f32 * 0.1);
+///         AABB::with_bounds(position, position + Vec3::one())
+///     })
+///     .collect();
+/// let indices: Vec<usize> = (0..boxes.len()).collect();
+///
+/// let serial = joint_aabb_of_shapes(&indices, &boxes);
+/// let parallel = par_joint_aabb_of_shapes(&indices, &boxes);
+/// assert_eq!(serial.min, parallel.min);
+/// assert_eq!(serial.max, parallel.max);
+/// ```
+///
+/// [`joint_aabb_of_shapes`]: fn.joint_aabb_of_shapes.html
+/// [`PARALLEL_JOINT_AABB_THRESHOLD`]: constant.PARALLEL_JOINT_AABB_THRESHOLD.html
+///
+#[cfg(feature = "rayon")]
+pub fn par_joint_aabb_of_shapes<Shape: Bounded + Sync>(
+    indices: &[usize],
+    shapes: &[Shape],
+) -> AABB {
+    if indices.len() < PARALLEL_JOINT_AABB_THRESHOLD {
+        return joint_aabb_of_shapes(indices, shapes);
+    }
+
+    indices
+        .par_iter()
+        .fold(AABB::empty, |aabb, index| aabb.join(&shapes[*index].aabb()))
+        .reduce(AABB::empty, |a, b| a.join(&b))
+}
+
+/// A node or shape index together with the squared distance of its `AABB` to a query point.
+/// Ordered by distance first and index second, which makes the order of equidistant
+/// candidates deterministic.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Candidate {
+    /// The squared distance to the query point.
+    pub distance_squared: f32,
+
+    /// The index of the node or shape.
+    pub index: usize,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        self.distance_squared
+            .partial_cmp(&other.distance_squared)
+            .unwrap_or(Ordering::Equal)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::utils::concatenate_vectors;
+    use crate::aabb::Bounded;
+    use crate::testbase::generate_aligned_boxes;
+    use crate::utils::{concatenate_vectors, joint_aabb_of_shapes};
 
     #[test]
     /// Test if concatenating no `Vec`s yields an empty `Vec`.
@@ -80,4 +235,104 @@ mod tests {
         assert_eq!(result, expected);
         assert_eq!(vectors, vec![vec![], vec![], vec![], vec![], vec![]]);
     }
+
+    #[test]
+    /// Test if the joint `AABB` of no shapes is empty.
+    fn test_joint_aabb_of_no_shapes_is_empty() {
+        let shapes = generate_aligned_boxes();
+        assert!(joint_aabb_of_shapes(&[], &shapes).is_empty());
+
+        let joint = joint_aabb_of_shapes(&[0, 20], &shapes);
+        assert_eq!(joint.min, shapes[0].aabb().min);
+        assert_eq!(joint.max, shapes[20].aabb().max);
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    /// Test if the parallel joint `AABB` is identical to the serial one, both below and
+    /// above the parallel threshold.
+    fn test_par_joint_aabb_of_shapes_equals_serial() {
+        use crate::testbase::{create_n_cubes, default_bounds};
+        use crate::utils::par_joint_aabb_of_shapes;
+
+        let triangles = create_n_cubes(2_000, &default_bounds());
+        for &count in &[0, 100, triangles.len()] {
+            let indices: Vec<usize> = (0..count).collect();
+            let serial = joint_aabb_of_shapes(&indices, &triangles);
+            let parallel = par_joint_aabb_of_shapes(&indices, &triangles);
+            assert_eq!(serial.min, parallel.min);
+            assert_eq!(serial.max, parallel.max);
+        }
+    }
+}
+
+#[cfg(all(feature = "bench", test))]
+mod bench {
+    use crate::aabb::AABB;
+    use crate::testbase::{default_bounds, next_Vec3};
+    use crate::utils::joint_aabb_of_shapes;
+    use ultraviolet::Vec3;
+
+    /// Creates 5,000,000 deterministic random unit boxes and the indices of all of them.
+    fn create_5m_boxes() -> (Vec<AABB>, Vec<usize>) {
+        let bounds = default_bounds();
+        let mut seed = 0;
+        let boxes: Vec<AABB> = (0..5_000_000)
+            .map(|_| {
+                let position = next_Vec3(&mut seed, &bounds);
+                AABB::with_bounds(position, position + Vec3::one())
+            })
+            .collect();
+        let indices = (0..boxes.len()).collect();
+        (boxes, indices)
+    }
+
+    #[bench]
+    /// Benchmark the serial joint `AABB` of 5,000,000 boxes.
+    fn bench_joint_aabb_of_5m_boxes(b: &mut ::test::Bencher) {
+        let (boxes, indices) = create_5m_boxes();
+        b.iter(|| joint_aabb_of_shapes(&indices, &boxes));
+    }
+
+    #[bench]
+    #[cfg(feature = "rayon")]
+    /// Benchmark the parallel joint `AABB` of 5,000,000 boxes.
+    fn bench_par_joint_aabb_of_5m_boxes(b: &mut ::test::Bencher) {
+        use crate::utils::par_joint_aabb_of_shapes;
+
+        let (boxes, indices) = create_5m_boxes();
+        b.iter(|| par_joint_aabb_of_shapes(&indices, &boxes));
+    }
+
+    #[bench]
+    /// Benchmark the serial enclosing `AABB` of 5,000,000 boxes.
+    fn bench_enclosing_5m_boxes(b: &mut ::test::Bencher) {
+        let (boxes, _) = create_5m_boxes();
+        b.iter(|| AABB::enclosing(&boxes));
+    }
+
+    #[bench]
+    #[cfg(feature = "rayon")]
+    /// Benchmark the parallel enclosing `AABB` of 5,000,000 boxes.
+    fn bench_enclosing_par_5m_boxes(b: &mut ::test::Bencher) {
+        let (boxes, _) = create_5m_boxes();
+        b.iter(|| AABB::enclosing_par(&boxes));
+    }
+}
+
+/// (De)serializes a `Vec3` as `[f32; 3]`.
+/// Used with `#[serde(with = "crate::utils::serde_vec3")]` on `Vec3` fields.
+#[cfg(feature = "serde")]
+pub mod serde_vec3 {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use ultraviolet::Vec3;
+
+    pub fn serialize<S: Serializer>(vec: &Vec3, serializer: S) -> Result<S::Ok, S::Error> {
+        [vec.x, vec.y, vec.z].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec3, D::Error> {
+        let [x, y, z] = <[f32; 3]>::deserialize(deserializer)?;
+        Ok(Vec3::new(x, y, z))
+    }
 }